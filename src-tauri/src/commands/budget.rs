@@ -0,0 +1,100 @@
+use crate::commands::config::AppSettingsState;
+use crate::services::budget::{self, BudgetStatus};
+use crate::services::database::SpendingBudget;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingBudgetInput {
+    pub scope: String,      // "workspace" | "provider"
+    pub scope_ref: String,  // Workspace path or provider config name
+    pub period: String,     // "daily" | "monthly"
+    pub soft_limit_usd: Option<f64>,
+    pub hard_limit_usd: Option<f64>,
+    pub enabled: bool,
+}
+
+/// List all spending budgets
+#[tauri::command]
+pub fn list_spending_budgets(state: State<AppSettingsState>) -> Result<Vec<SpendingBudget>, String> {
+    let db = state.app_db.lock().map_err(|e| e.to_string())?;
+    db.list_spending_budgets()
+        .map_err(|e| format!("Failed to list budgets: {}", e))
+}
+
+/// Create or update a budget (one budget per scope and period)
+#[tauri::command]
+pub fn save_spending_budget(
+    input: SpendingBudgetInput,
+    state: State<AppSettingsState>,
+) -> Result<(), String> {
+    if input.scope != "workspace" && input.scope != "provider" {
+        return Err(format!("Invalid budget scope: {}. Must be 'workspace' or 'provider'", input.scope));
+    }
+
+    // Validates the period
+    budget::period_bounds(&input.period)?;
+
+    if input.scope_ref.trim().is_empty() {
+        return Err("Budget scope reference is required".to_string());
+    }
+
+    if input.soft_limit_usd.is_none() && input.hard_limit_usd.is_none() {
+        return Err("At least one of soft_limit_usd or hard_limit_usd is required".to_string());
+    }
+
+    let limits = [input.soft_limit_usd, input.hard_limit_usd];
+    if limits.iter().flatten().any(|l| *l < 0.0 || !l.is_finite()) {
+        return Err("Budget limits must be non-negative numbers".to_string());
+    }
+
+    let budget = SpendingBudget {
+        id: Uuid::new_v4().to_string(),
+        scope: input.scope,
+        scope_ref: input.scope_ref,
+        period: input.period,
+        soft_limit_usd: input.soft_limit_usd,
+        hard_limit_usd: input.hard_limit_usd,
+        enabled: input.enabled,
+    };
+
+    let db = state.app_db.lock().map_err(|e| e.to_string())?;
+    db.save_spending_budget(&budget)
+        .map_err(|e| format!("Failed to save budget: {}", e))
+}
+
+/// Delete a budget
+#[tauri::command]
+pub fn delete_spending_budget(
+    budget_id: String,
+    state: State<AppSettingsState>,
+) -> Result<(), String> {
+    let db = state.app_db.lock().map_err(|e| e.to_string())?;
+    db.delete_spending_budget(&budget_id)
+        .map_err(|e| format!("Failed to delete budget: {}", e))
+}
+
+/// Report spending and remaining budget for the current period
+/// Without filters, reports every enabled budget
+#[tauri::command]
+pub fn get_budget_status(
+    workspace_path: Option<String>,
+    provider_name: Option<String>,
+    state: State<AppSettingsState>,
+) -> Result<Vec<BudgetStatus>, String> {
+    let db = state.app_db.lock().map_err(|e| e.to_string())?;
+
+    if workspace_path.is_some() || provider_name.is_some() {
+        return budget::applicable_budgets(&db, workspace_path.as_deref(), provider_name.as_deref());
+    }
+
+    let budgets = db.list_spending_budgets()
+        .map_err(|e| format!("Failed to list budgets: {}", e))?;
+
+    budgets
+        .iter()
+        .filter(|b| b.enabled)
+        .map(|b| budget::budget_status(&db, b))
+        .collect()
+}
@@ -0,0 +1,249 @@
+use crate::commands::prompt::load_prompt_runtime;
+use crate::services::converters::{self, PromptFormat};
+use crate::services::database::AppDatabase;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize)]
+pub struct ImportedPrompt {
+    pub source: String,
+    pub target: String,
+    /// What could not be carried over
+    pub lossy: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SkippedFile {
+    pub source: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub imported: Vec<ImportedPrompt>,
+    pub skipped: Vec<SkippedFile>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportResult {
+    pub output_path: String,
+    pub lossy: Vec<String>,
+}
+
+/// Files of a folder, recursively (hidden entries skipped)
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(&path, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+/// File name without its format suffix: `support.openai.json` → `support`
+fn base_name(path: &Path) -> String {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("prompt");
+    let name = name.split('.').next().filter(|n| !n.is_empty()).unwrap_or(name);
+    name.to_string()
+}
+
+fn sanitize(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '-' })
+        .collect();
+    let name = name.trim_matches('-');
+    if name.is_empty() { "prompt".to_string() } else { name.to_string() }
+}
+
+/// `dir/name.extension`, or `dir/name-2.extension`, ... when taken (existing files are never overwritten)
+fn unique_path(dir: &Path, name: &str, extension: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}.{}", name, extension));
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.{}", name, n, extension));
+        n += 1;
+    }
+    path
+}
+
+/// Convert every prompt file of a folder to .vibe.md files in the workspace
+/// The format is detected per file unless given; unrecognized files are skipped
+#[tauri::command]
+pub fn import_prompt_folder(
+    workspace_path: String,
+    source_path: String,
+    format: Option<PromptFormat>,
+    target_folder: Option<String>,
+) -> Result<ImportReport, String> {
+    let source = Path::new(&source_path);
+    let target_root = Path::new(&workspace_path).join(target_folder.as_deref().unwrap_or("imported"));
+
+    let mut files = Vec::new();
+    if source.is_dir() {
+        collect_files(source, &mut files);
+    } else if source.is_file() {
+        files.push(source.to_path_buf());
+    } else {
+        return Err(format!("Source not found: {}", source_path));
+    }
+    files.sort();
+
+    let providers = AppDatabase::new()
+        .ok()
+        .and_then(|app_db| app_db.list_llm_providers().ok())
+        .unwrap_or_default();
+
+    let mut report = ImportReport { imported: Vec::new(), skipped: Vec::new() };
+    for file in files {
+        let source_display = file.display().to_string();
+        let skip = |reason: String| SkippedFile { source: source_display.clone(), reason };
+
+        let content = match fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(e) => {
+                report.skipped.push(skip(format!("Failed to read file: {}", e)));
+                continue;
+            }
+        };
+        let file_format = match format.or_else(|| PromptFormat::detect(&file, &content)) {
+            Some(file_format) => file_format,
+            None => {
+                report.skipped.push(skip("Unrecognized format".to_string()));
+                continue;
+            }
+        };
+        let conversions = match converters::import_prompt(&content, &base_name(&file), file_format) {
+            Ok(conversions) => conversions,
+            Err(e) => {
+                report.skipped.push(skip(e));
+                continue;
+            }
+        };
+
+        // Keep the folder structure of the source
+        let relative_dir = file
+            .parent()
+            .and_then(|parent| parent.strip_prefix(source).ok())
+            .unwrap_or(Path::new(""));
+        let target_dir = target_root.join(relative_dir);
+        fs::create_dir_all(&target_dir).map_err(|e| format!("Failed to create folder: {}", e))?;
+
+        for conversion in conversions {
+            let markdown = match converters::to_vibe_markdown(&conversion.value, &providers) {
+                Ok(markdown) => markdown,
+                Err(e) => {
+                    report.skipped.push(skip(e));
+                    continue;
+                }
+            };
+            let target = unique_path(&target_dir, &sanitize(&conversion.value.name), "vibe.md");
+            fs::write(&target, &markdown.value).map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+
+            let mut lossy = conversion.lossy;
+            lossy.extend(markdown.lossy);
+            report.imported.push(ImportedPrompt {
+                source: source_display.clone(),
+                target: target.display().to_string(),
+                lossy,
+            });
+        }
+    }
+
+    println!(
+        "📥 Imported {} prompts ({} files skipped)",
+        report.imported.len(),
+        report.skipped.len()
+    );
+    Ok(report)
+}
+
+/// Export a prompt file to another format, next to it unless an output path is given
+/// Existing files are never overwritten: a free name is picked next to the prompt, and
+/// an explicit output path must not exist
+#[tauri::command]
+pub fn export_prompt_file(
+    file_path: String,
+    format: PromptFormat,
+    output_path: Option<String>,
+    workspace_path: Option<String>,
+) -> Result<ExportResult, String> {
+    let mut prompt = load_prompt_runtime(file_path.clone(), workspace_path)?;
+    prompt.name = base_name(Path::new(&file_path));
+
+    let conversion = converters::export_prompt(&prompt, format)?;
+
+    let output_path = match output_path {
+        Some(output_path) => {
+            let output_path = PathBuf::from(output_path);
+            if output_path.exists() {
+                return Err(format!("{} already exists", output_path.display()));
+            }
+            output_path
+        }
+        None => {
+            let dir = Path::new(&file_path).parent().unwrap_or(Path::new(""));
+            unique_path(dir, &prompt.name, format.extension())
+        }
+    };
+    fs::write(&output_path, &conversion.value)
+        .map_err(|e| format!("Failed to write {}: {}", output_path.display(), e))?;
+
+    println!("📤 Exported {} to {}", file_path, output_path.display());
+    Ok(ExportResult {
+        output_path: output_path.display().to_string(),
+        lossy: conversion.lossy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_prompt_folder() {
+        let root = std::env::temp_dir().join(format!("vibebase-import-{}", uuid::Uuid::new_v4()));
+        let source = root.join("source");
+        fs::create_dir_all(source.join("team")).unwrap();
+        fs::write(
+            source.join("team/support.json"),
+            r#"{"model":"gpt-4o","temperature":0.3,"tools":[],"messages":[{"role":"system","content":"Be brief."},{"role":"user","content":"{{question}}"}]}"#,
+        )
+        .unwrap();
+        fs::write(source.join("ideas.txt"), "Name {{thing}}\n---\nDescribe {{thing}}\n").unwrap();
+        fs::write(source.join("notes.bin"), "x").unwrap();
+        fs::create_dir_all(root.join("imported")).unwrap();
+        fs::write(root.join("imported/ideas-1.vibe.md"), "keep").unwrap();
+
+        let report = import_prompt_folder(
+            root.display().to_string(),
+            source.display().to_string(),
+            None,
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(report.imported.len(), 3);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(fs::read_to_string(root.join("imported/ideas-1.vibe.md")).unwrap(), "keep");
+        assert!(root.join("imported/ideas-1-2.vibe.md").exists());
+
+        let support = report.imported.iter().find(|i| i.target.ends_with("support.vibe.md")).unwrap();
+        assert_eq!(support.lossy[0], "Ignored field 'tools'");
+        let content = fs::read_to_string(root.join("imported/team/support.vibe.md")).unwrap();
+        assert!(content.contains("model: gpt-4o"));
+        assert!(content.contains("## User Message\n\n{{question}}"));
+
+        fs::remove_dir_all(&root).ok();
+    }
+}
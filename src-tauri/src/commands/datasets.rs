@@ -0,0 +1,197 @@
+use crate::commands::execution::read_prompt_file;
+use crate::commands::prompt::get_variable_fields;
+use crate::services::database::{ProjectDatabase, TestDataset};
+use crate::services::datasets::{self, DatasetCoverage, DatasetIndex, DatasetRow};
+use serde::Serialize;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const DEFAULT_PREVIEW_ROWS: usize = 50;
+
+#[derive(Debug, Serialize)]
+pub struct DatasetPreview {
+    pub dataset: TestDataset,
+    pub rows: Vec<DatasetRow>,
+    pub offset: usize,
+    pub total: usize,
+}
+
+fn open_database(workspace_path: &str) -> Result<ProjectDatabase, String> {
+    ProjectDatabase::new(Path::new(workspace_path)).map_err(|e| format!("Failed to open database: {}", e))
+}
+
+/// Workspace-relative path of a dataset file given either an absolute or a relative path
+fn relative_dataset_path(workspace_path: &str, file_path: &str) -> Result<String, String> {
+    let workspace = Path::new(workspace_path)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+    let full_path = workspace
+        .join(file_path)
+        .canonicalize()
+        .map_err(|_| format!("File not found: {}", file_path))?;
+
+    let relative = full_path
+        .strip_prefix(&workspace)
+        .map_err(|_| format!("File {} is outside the workspace", file_path))?;
+    Ok(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn apply_index(dataset: &mut TestDataset, index: &DatasetIndex) {
+    dataset.format = index.format.as_str().to_string();
+    dataset.row_count = index.row_count as i64;
+    dataset.columns = serde_json::to_string(&index.columns).ok();
+    dataset.content_hash = Some(index.content_hash.clone());
+    dataset.updated_at = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+}
+
+/// Read a registered dataset, re-indexing it when the file changed since it was indexed
+pub(crate) fn load_dataset(
+    db: &ProjectDatabase,
+    workspace_path: &str,
+    dataset_id: &str,
+) -> Result<(TestDataset, Vec<DatasetRow>), String> {
+    let mut dataset = db
+        .get_test_dataset(dataset_id)
+        .map_err(|e| format!("Dataset {} not found: {}", dataset_id, e))?;
+    let (index, rows) = datasets::index_file(&Path::new(workspace_path).join(&dataset.file_path))?;
+
+    if dataset.content_hash.as_deref() != Some(index.content_hash.as_str()) {
+        apply_index(&mut dataset, &index);
+        db.save_test_dataset(&dataset)
+            .map_err(|e| format!("Failed to save dataset: {}", e))?;
+        println!("🔄 Re-indexed dataset {} ({} rows)", dataset.file_path, dataset.row_count);
+    }
+    Ok((dataset, rows))
+}
+
+/// Re-index a dataset whose file content changed (only the hash is computed otherwise)
+fn refresh_dataset(db: &ProjectDatabase, workspace_path: &str, dataset: &mut TestDataset) -> Result<(), String> {
+    let path = Path::new(workspace_path).join(&dataset.file_path);
+    if dataset.content_hash.as_deref() == Some(datasets::file_hash(&path)?.as_str()) {
+        return Ok(());
+    }
+
+    let (index, _) = datasets::index_file(&path)?;
+    apply_index(dataset, &index);
+    db.save_test_dataset(dataset)
+        .map_err(|e| format!("Failed to save dataset: {}", e))?;
+    println!("🔄 Re-indexed dataset {} ({} rows)", dataset.file_path, dataset.row_count);
+    Ok(())
+}
+
+/// Register a CSV, JSONL or YAML file as a test dataset (re-indexes it when already registered)
+#[tauri::command]
+pub fn register_dataset(
+    workspace_path: String,
+    file_path: String,
+    name: Option<String>,
+) -> Result<TestDataset, String> {
+    let relative_path = relative_dataset_path(&workspace_path, &file_path)?;
+    let (index, _) = datasets::index_file(&Path::new(&workspace_path).join(&relative_path))?;
+
+    let db = open_database(&workspace_path)?;
+    let existing = db
+        .find_test_dataset(&relative_path)
+        .map_err(|e| format!("Failed to load datasets: {}", e))?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    let mut dataset = existing.unwrap_or_else(|| TestDataset {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: String::new(),
+        file_path: relative_path.clone(),
+        format: String::new(),
+        row_count: 0,
+        columns: None,
+        content_hash: None,
+        created_at: now,
+        updated_at: now,
+    });
+    match name.filter(|n| !n.trim().is_empty()) {
+        Some(name) => dataset.name = name,
+        None if dataset.name.is_empty() => {
+            dataset.name = Path::new(&relative_path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_else(|| relative_path.clone());
+        }
+        None => {}
+    }
+    apply_index(&mut dataset, &index);
+
+    db.save_test_dataset(&dataset)
+        .map_err(|e| format!("Failed to save dataset: {}", e))?;
+
+    println!("📊 Registered dataset {} ({} rows, {} columns)", relative_path, index.row_count, index.columns.len());
+    Ok(dataset)
+}
+
+/// Registered datasets; changed files are re-indexed first
+#[tauri::command]
+pub fn list_datasets(workspace_path: String) -> Result<Vec<TestDataset>, String> {
+    let db = open_database(&workspace_path)?;
+    let mut datasets = db
+        .get_test_datasets()
+        .map_err(|e| format!("Failed to load datasets: {}", e))?;
+
+    for dataset in datasets.iter_mut() {
+        // A missing or broken file keeps its last index
+        if let Err(e) = refresh_dataset(&db, &workspace_path, dataset) {
+            eprintln!("⚠️  Could not re-index dataset {}: {}", dataset.file_path, e);
+        }
+    }
+    Ok(datasets)
+}
+
+/// A page of a dataset's rows
+#[tauri::command]
+pub fn preview_dataset(
+    workspace_path: String,
+    dataset_id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<DatasetPreview, String> {
+    let db = open_database(&workspace_path)?;
+    let (dataset, rows) = load_dataset(&db, &workspace_path, &dataset_id)?;
+
+    let offset = offset.unwrap_or(0);
+    let total = rows.len();
+    let rows = rows
+        .into_iter()
+        .skip(offset)
+        .take(limit.unwrap_or(DEFAULT_PREVIEW_ROWS))
+        .collect();
+
+    Ok(DatasetPreview { dataset, rows, offset, total })
+}
+
+/// Check that a dataset has a column for every variable a prompt needs
+#[tauri::command]
+pub fn validate_dataset_for_prompt(
+    workspace_path: String,
+    dataset_id: String,
+    prompt_path: String,
+) -> Result<DatasetCoverage, String> {
+    let content = read_prompt_file(&workspace_path, &prompt_path)?;
+    let variables: Vec<(String, bool)> = get_variable_fields(content, prompt_path)?
+        .into_iter()
+        .map(|field| (field.declaration.name, !field.optional))
+        .collect();
+
+    let db = open_database(&workspace_path)?;
+    let (dataset, rows) = load_dataset(&db, &workspace_path, &dataset_id)?;
+    let columns: Vec<String> = dataset
+        .columns
+        .as_deref()
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default();
+
+    Ok(datasets::check_coverage(&columns, &rows, &variables))
+}
+
+/// Unregister a dataset; the file itself is kept
+#[tauri::command]
+pub fn delete_dataset(workspace_path: String, dataset_id: String) -> Result<(), String> {
+    let db = open_database(&workspace_path)?;
+    db.delete_test_dataset(&dataset_id)
+        .map_err(|e| format!("Failed to delete dataset: {}", e))
+}
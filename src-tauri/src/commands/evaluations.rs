@@ -0,0 +1,484 @@
+use crate::commands::execution::{check_hard_limits, record_spending, AppState};
+use crate::commands::prompt::load_prompt_runtime;
+use crate::models::assertions::AssertionOutcome;
+use crate::models::execution::{ExecutionResult, OpenAIMessage};
+use crate::models::frontmatter::parse_frontmatter;
+use crate::models::prompt::{EvaluationConfig, JudgeSettings, ModelParameters, PromptRuntime};
+use crate::services::assertions::ASSERTION_EVALUATOR;
+use crate::services::converters::new_prompt;
+use crate::services::database::{EvaluationResult, EvaluationRule, PassRate, ProjectDatabase};
+use crate::services::evaluation::{
+    self, EvaluationInput, JudgeUsage, LlmJudgeRule, Rule, RuleOutcome, AGGREGATE_EVALUATOR, JUDGE_EVALUATOR,
+    JUDGE_PASS_THRESHOLD, JUDGE_SCALE, RULE_EVALUATOR,
+};
+use crate::services::executor::Executor;
+use crate::services::llm_config::LLMConfigResolver;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+#[derive(Debug, Deserialize)]
+pub struct EvaluationRuleInput {
+    /// Omitted for a new rule
+    #[serde(default)]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub rule_type: String,
+    #[serde(default)]
+    pub rule_config: serde_json::Value,
+}
+
+/// Evaluator results of an execution with their weighted score
+#[derive(Debug, Serialize)]
+pub struct ExecutionEvaluation {
+    pub execution_id: String,
+    pub results: Vec<EvaluationResult>,
+    /// Weighted mean of the scores (EvaluationConfig.weight, 1 by default); evaluators that failed to run are left out
+    pub aggregate_score: Option<f64>,
+    /// Every evaluator ran and passed (false without evaluators)
+    pub passed: bool,
+    /// Spent on judge calls, on top of the execution's own cost
+    pub judge_cost_usd: f64,
+}
+
+/// A .vibe.md rubric evaluator (`ref` of an `evaluation:` entry) with its judge model
+struct Rubric {
+    path: String,
+    prompt: PromptRuntime,
+    provider_ref: Option<String>,
+    model: Option<String>,
+    parameters: Option<ModelParameters>,
+    settings: JudgeSettings,
+}
+
+enum Evaluator {
+    Rule(Rule),
+    Rubric(Box<Rubric>),
+}
+
+fn open_database(workspace_path: &str) -> Result<ProjectDatabase, String> {
+    ProjectDatabase::new(Path::new(workspace_path)).map_err(|e| format!("Failed to open database: {}", e))
+}
+
+/// evaluation_results rows for the assertion outcomes of an execution or matrix cell
+pub(crate) fn assertion_results(
+    outcomes: &[AssertionOutcome],
+    execution_id: &str,
+    prompt_file_id: &str,
+    model: &str,
+    matrix_run_id: Option<&str>,
+) -> Vec<EvaluationResult> {
+    let timestamp = chrono::Utc::now().timestamp();
+    outcomes
+        .iter()
+        .map(|outcome| EvaluationResult {
+            id: uuid::Uuid::new_v4().to_string(),
+            execution_id: execution_id.to_string(),
+            evaluator_name: outcome.name.clone(),
+            evaluator_file_path: None,
+            score: Some(if outcome.passed { 1.0 } else { 0.0 }),
+            reasoning: Some(outcome.reason.clone()),
+            passed: Some(outcome.passed),
+            timestamp,
+            evaluator_type: Some(ASSERTION_EVALUATOR.to_string()),
+            prompt_file_id: Some(prompt_file_id.to_string()),
+            model: Some(model.to_string()),
+            matrix_run_id: matrix_run_id.map(String::from),
+            judge_model: None,
+            judge_tokens_input: None,
+            judge_tokens_output: None,
+            judge_cost_usd: None,
+        })
+        .collect()
+}
+
+/// Save the assertion outcomes of an execution saved to history
+pub(crate) fn save_assertion_outcomes(workspace_path: &str, file_path: &str, result: &ExecutionResult) -> Result<(), String> {
+    if result.assertions.is_empty() {
+        return Ok(());
+    }
+    let db = open_database(workspace_path)?;
+    let prompt_file_id = db
+        .ensure_prompt_file(file_path)
+        .map_err(|e| format!("Failed to get prompt file: {}", e))?;
+
+    let results = assertion_results(&result.assertions, &result.id, &prompt_file_id, &result.metadata.model, None);
+    db.save_evaluation_results(&results)
+        .map_err(|e| format!("Failed to save evaluation results: {}", e))?;
+
+    let passed = result.assertions.iter().filter(|outcome| outcome.passed).count();
+    println!("✅ Assertions for {}: {}/{} passed", result.id, passed, result.assertions.len());
+    Ok(())
+}
+
+/// Evaluator outcomes of an execution or matrix cell
+#[tauri::command]
+pub fn get_evaluation_results(workspace_path: String, execution_id: String) -> Result<Vec<EvaluationResult>, String> {
+    open_database(&workspace_path)?
+        .get_evaluation_results(&execution_id)
+        .map_err(|e| format!("Failed to load evaluation results: {}", e))
+}
+
+/// Assertion pass rates per prompt × model × matrix run, optionally for one prompt or run
+#[tauri::command]
+pub fn get_assertion_pass_rates(
+    workspace_path: String,
+    prompt_path: Option<String>,
+    matrix_run_id: Option<String>,
+) -> Result<Vec<PassRate>, String> {
+    let db = open_database(&workspace_path)?;
+    let prompt_file_id = match prompt_path.as_deref() {
+        Some(path) => match db
+            .find_prompt_file_id(path)
+            .map_err(|e| format!("Failed to find prompt file: {}", e))?
+        {
+            Some(id) => Some(id),
+            None => return Ok(Vec::new()),
+        },
+        None => None,
+    };
+
+    db.get_pass_rates(ASSERTION_EVALUATOR, prompt_file_id.as_deref(), matrix_run_id.as_deref())
+        .map_err(|e| format!("Failed to load pass rates: {}", e))
+}
+
+/// Evaluation rules of the workspace
+#[tauri::command]
+pub fn list_evaluation_rules(workspace_path: String) -> Result<Vec<EvaluationRule>, String> {
+    open_database(&workspace_path)?
+        .get_evaluation_rules()
+        .map_err(|e| format!("Failed to load evaluation rules: {}", e))
+}
+
+/// Create or update an evaluation rule; its config is checked against its type
+#[tauri::command]
+pub fn save_evaluation_rule(workspace_path: String, rule: EvaluationRuleInput) -> Result<EvaluationRule, String> {
+    let name = rule.name.trim().to_string();
+    if name.is_empty() {
+        return Err("Rule name is required".to_string());
+    }
+    let rule_config = match &rule.rule_config {
+        serde_json::Value::Null => "{}".to_string(),
+        config => config.to_string(),
+    };
+    Rule::parse(&rule.rule_type, &rule_config)?;
+
+    let db = open_database(&workspace_path)?;
+    let existing = db
+        .find_evaluation_rule(&name)
+        .map_err(|e| format!("Failed to load evaluation rules: {}", e))?;
+    if let Some(existing) = &existing {
+        if rule.id.as_deref() != Some(existing.id.as_str()) {
+            return Err(format!("A rule named '{}' already exists", name));
+        }
+    }
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    let saved = EvaluationRule {
+        id: rule.id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        name,
+        description: rule.description.filter(|d| !d.trim().is_empty()),
+        rule_type: rule.rule_type,
+        rule_config,
+        created_at: existing.map(|r| r.created_at).unwrap_or(now),
+        updated_at: now,
+    };
+    db.save_evaluation_rule(&saved)
+        .map_err(|e| format!("Failed to save evaluation rule: {}", e))?;
+    Ok(saved)
+}
+
+#[tauri::command]
+pub fn delete_evaluation_rule(workspace_path: String, rule_id: String) -> Result<(), String> {
+    open_database(&workspace_path)?
+        .delete_evaluation_rule(&rule_id)
+        .map_err(|e| format!("Failed to delete evaluation rule: {}", e))
+}
+
+/// Apply the evaluators of each execution's prompt (its `evaluation:` list) to the execution output
+/// Earlier rule and judge results of the executions are replaced
+#[tauri::command]
+pub async fn evaluate_executions(
+    workspace_path: String,
+    execution_ids: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<ExecutionEvaluation>, String> {
+    let mut evaluations = Vec::new();
+    for execution_id in execution_ids {
+        evaluations.push(evaluate_execution(&workspace_path, &execution_id, &state).await?);
+    }
+    Ok(evaluations)
+}
+
+async fn evaluate_execution(workspace_path: &str, execution_id: &str, state: &AppState) -> Result<ExecutionEvaluation, String> {
+    // Everything is read up front: the database must not be borrowed across await
+    let (record, matrix_run_id, evaluators) = {
+        let db = open_database(workspace_path)?;
+        let record = db
+            .get_execution(execution_id)
+            .map_err(|e| format!("Execution {} not found: {}", execution_id, e))?;
+        let matrix_run_id = db
+            .find_test_result_run(&record.id)
+            .map_err(|e| format!("Failed to load test result: {}", e))?;
+        let evaluators: Vec<(EvaluationConfig, Result<Evaluator, String>)> = db
+            .get_evaluation_config(&record.prompt_file_id)
+            .map_err(|e| format!("Failed to load evaluation config: {}", e))?
+            .and_then(|json| serde_json::from_str::<Vec<EvaluationConfig>>(&json).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|evaluator| {
+                let loaded = match &evaluator.ref_path {
+                    Some(ref_path) => load_rubric(workspace_path, ref_path, &evaluator)
+                        .map(|rubric| Evaluator::Rubric(Box::new(rubric))),
+                    None => load_rule(&db, &evaluator).map(Evaluator::Rule),
+                };
+                (evaluator, loaded)
+            })
+            .collect();
+        (record, matrix_run_id, evaluators)
+    };
+    let input = EvaluationInput::from_record(&record);
+    let timestamp = chrono::Utc::now().timestamp();
+
+    let mut results = Vec::new();
+    let mut scores = Vec::new();
+    let mut passed = true;
+    for (evaluator, loaded) in &evaluators {
+        let outcome = match loaded {
+            Ok(Evaluator::Rule(rule)) => apply_rule(rule, &input, workspace_path, state).await,
+            Ok(Evaluator::Rubric(rubric)) => judge_rubric(rubric, &input, workspace_path, state).await,
+            Err(e) => Err(e.clone()),
+        };
+        if let Ok(outcome) = &outcome {
+            scores.push((outcome.score, evaluator.weight.unwrap_or(1.0) as f64));
+        }
+        passed &= outcome.as_ref().is_ok_and(|outcome| outcome.passed);
+
+        let (score, verdict, reasoning, judge) = match outcome {
+            Ok(outcome) => (Some(outcome.score), Some(outcome.passed), outcome.reasoning, outcome.judge),
+            Err(e) => (None, None, e, None),
+        };
+        let evaluator_type = if evaluator.ref_path.is_some() { JUDGE_EVALUATOR } else { RULE_EVALUATOR };
+        results.push(EvaluationResult {
+            id: uuid::Uuid::new_v4().to_string(),
+            execution_id: record.id.clone(),
+            evaluator_name: evaluator.name.clone(),
+            evaluator_file_path: evaluator.ref_path.clone(),
+            score,
+            reasoning: Some(reasoning),
+            passed: verdict,
+            timestamp,
+            evaluator_type: Some(evaluator_type.to_string()),
+            prompt_file_id: Some(record.prompt_file_id.clone()),
+            model: Some(record.model.clone()),
+            matrix_run_id: matrix_run_id.clone(),
+            judge_model: judge.as_ref().map(|judge| judge.model.clone()),
+            judge_tokens_input: judge.as_ref().map(|judge| judge.tokens_input),
+            judge_tokens_output: judge.as_ref().map(|judge| judge.tokens_output),
+            judge_cost_usd: judge.as_ref().map(|judge| judge.cost_usd),
+        });
+    }
+
+    let aggregate_score = evaluation::aggregate_score(&scores);
+    let passed = passed && !results.is_empty();
+    let judge_cost_usd: f64 = results.iter().filter_map(|result| result.judge_cost_usd).sum();
+    let mut saved = results.clone();
+    if !results.is_empty() {
+        saved.push(EvaluationResult {
+            id: uuid::Uuid::new_v4().to_string(),
+            execution_id: record.id.clone(),
+            evaluator_name: "aggregate".to_string(),
+            evaluator_file_path: None,
+            score: aggregate_score,
+            reasoning: Some(format!("Weighted score of {} evaluators", scores.len())),
+            passed: Some(passed),
+            timestamp,
+            evaluator_type: Some(AGGREGATE_EVALUATOR.to_string()),
+            prompt_file_id: Some(record.prompt_file_id.clone()),
+            model: Some(record.model.clone()),
+            matrix_run_id,
+            judge_model: None,
+            judge_tokens_input: None,
+            judge_tokens_output: None,
+            judge_cost_usd: None,
+        });
+    }
+
+    let db = open_database(workspace_path)?;
+    for evaluator_type in [RULE_EVALUATOR, JUDGE_EVALUATOR, AGGREGATE_EVALUATOR] {
+        db.delete_evaluation_results(&record.id, evaluator_type)
+            .map_err(|e| format!("Failed to clear evaluation results: {}", e))?;
+    }
+    db.save_evaluation_results(&saved)
+        .map_err(|e| format!("Failed to save evaluation results: {}", e))?;
+
+    println!(
+        "🧪 Evaluated {} with {} evaluators (score {}, judge cost ${:.4})",
+        record.id,
+        results.len(),
+        aggregate_score.map(|s| format!("{:.2}", s)).unwrap_or_else(|| "-".to_string()),
+        judge_cost_usd
+    );
+    Ok(ExecutionEvaluation {
+        execution_id: record.id.clone(),
+        results,
+        aggregate_score,
+        passed,
+        judge_cost_usd,
+    })
+}
+
+/// The evaluation rule an `evaluation:` entry names; its `type` must be the rule's type
+fn load_rule(db: &ProjectDatabase, evaluator: &EvaluationConfig) -> Result<Rule, String> {
+    let stored = db
+        .find_evaluation_rule(&evaluator.name)
+        .map_err(|e| format!("Failed to load evaluation rule: {}", e))?
+        .ok_or_else(|| format!("Evaluation rule '{}' not found", evaluator.name))?;
+    if stored.rule_type != evaluator.eval_type {
+        return Err(format!(
+            "Rule '{}' is a {} rule, not {}",
+            stored.name, stored.rule_type, evaluator.eval_type
+        ));
+    }
+
+    Rule::parse(&stored.rule_type, &stored.rule_config)
+}
+
+/// The .vibe.md rubric at `ref_path` (relative to the workspace)
+/// The entry's provider_ref and model take precedence over the rubric's frontmatter
+fn load_rubric(workspace_path: &str, ref_path: &str, evaluator: &EvaluationConfig) -> Result<Rubric, String> {
+    if !ref_path.ends_with(".vibe.md") {
+        return Err(format!("Evaluator {} is not a .vibe.md file", ref_path));
+    }
+    let full_path = Path::new(workspace_path).join(ref_path);
+    let content = fs::read_to_string(&full_path).map_err(|e| format!("Failed to read evaluator {}: {}", ref_path, e))?;
+    let frontmatter = parse_frontmatter(&content)?.0.unwrap_or_default();
+    let settings = frontmatter.judge.unwrap_or_default();
+    if settings.scale.is_some_and(|scale| scale <= 0.0) {
+        return Err(format!("Evaluator {}: judge scale must be positive", ref_path));
+    }
+
+    let prompt = load_prompt_runtime(full_path.to_string_lossy().to_string(), Some(workspace_path.to_string()))?;
+    Ok(Rubric {
+        path: ref_path.to_string(),
+        prompt,
+        provider_ref: evaluator.provider_ref.clone().or(frontmatter.provider_ref),
+        model: evaluator.model.clone().or(frontmatter.model),
+        parameters: frontmatter.parameters,
+        settings,
+    })
+}
+
+async fn apply_rule(rule: &Rule, input: &EvaluationInput, workspace_path: &str, state: &AppState) -> Result<RuleOutcome, String> {
+    match rule {
+        Rule::Script(script) => evaluation::run_script(script, input, Path::new(workspace_path)).await,
+        Rule::LlmJudge(judge) => judge_output(judge, input, workspace_path, state).await,
+        _ => evaluation::check(rule, input),
+    }
+}
+
+async fn judge_output(
+    rule: &LlmJudgeRule,
+    input: &EvaluationInput,
+    workspace_path: &str,
+    state: &AppState,
+) -> Result<RuleOutcome, String> {
+    let messages = evaluation::judge_messages(&rule.criteria, input);
+    let result =
+        run_judge(state, workspace_path, rule.provider_ref.as_deref(), rule.model.as_deref(), None, messages).await?;
+    let (score, reasoning) = evaluation::parse_judge_response(&result.output, JUDGE_SCALE)?;
+    Ok(RuleOutcome { score, passed: score >= rule.pass_threshold, reasoning, judge: Some(judge_usage(&result)) })
+}
+
+/// Render a rubric with `{{input}}`, `{{output}}` and `{{expected}}` (empty without one) and grade with its judge
+async fn judge_rubric(
+    rubric: &Rubric,
+    input: &EvaluationInput,
+    workspace_path: &str,
+    state: &AppState,
+) -> Result<RuleOutcome, String> {
+    let variables = HashMap::from([
+        ("input".to_string(), evaluation::format_inputs(input)),
+        ("output".to_string(), input.output.clone()),
+        ("expected".to_string(), input.expected.clone().unwrap_or_default()),
+    ]);
+    let scale = rubric.settings.scale.unwrap_or(JUDGE_SCALE);
+    let messages = Executor::new()
+        .render_messages(&rubric.prompt, &variables)
+        .map_err(|e| format!("Failed to render evaluator {}: {}", rubric.path, e))?;
+    let messages = evaluation::with_answer_format(messages, scale);
+
+    let result = run_judge(
+        state,
+        workspace_path,
+        rubric.provider_ref.as_deref(),
+        rubric.model.as_deref(),
+        rubric.parameters.clone(),
+        messages,
+    )
+    .await?;
+    let (score, reasoning) = evaluation::parse_judge_response(&result.output, scale)?;
+    let pass_threshold = rubric.settings.pass_threshold.unwrap_or(JUDGE_PASS_THRESHOLD);
+    Ok(RuleOutcome { score, passed: score >= pass_threshold, reasoning, judge: Some(judge_usage(&result)) })
+}
+
+fn judge_usage(result: &ExecutionResult) -> JudgeUsage {
+    JudgeUsage {
+        model: result.metadata.model.clone(),
+        tokens_input: result.metadata.tokens_input,
+        tokens_output: result.metadata.tokens_output,
+        cost_usd: result.metadata.cost_usd,
+    }
+}
+
+/// Send judge messages to a provider config (the default one without provider_ref), at temperature 0 unless
+/// the parameters set one. Hard limits apply and the cost is recorded like any execution
+pub(crate) async fn run_judge(
+    state: &AppState,
+    workspace_path: &str,
+    provider_ref: Option<&str>,
+    model: Option<&str>,
+    parameters: Option<ModelParameters>,
+    messages: Vec<OpenAIMessage>,
+) -> Result<ExecutionResult, String> {
+    // Lock must not be held across await
+    let (provider_name, config, model, pricing) = {
+        let app_db = state.app_database.lock().map_err(|e| e.to_string())?;
+        let provider_name = match provider_ref {
+            Some(name) => name.to_string(),
+            None => app_db
+                .list_llm_providers()
+                .map_err(|e| format!("Failed to load providers: {}", e))?
+                .into_iter()
+                .find(|provider| provider.is_default)
+                .map(|provider| provider.name)
+                .ok_or("No judge provider: set provider_ref or a default provider")?,
+        };
+        let config = LLMConfigResolver::new(&app_db).resolve_provider_ref(&provider_name)?;
+        let model = model.map(String::from).unwrap_or_else(|| config.model.clone());
+
+        check_hard_limits(&app_db, Some(workspace_path), Some(&provider_name)).map_err(|e| e.to_string())?;
+        let pricing = app_db
+            .find_model_pricing(config.provider.as_str(), &model)
+            .map_err(|e| format!("Failed to load model pricing: {}", e))?;
+        (provider_name, config, model, pricing)
+    };
+
+    let mut parameters = parameters.unwrap_or(ModelParameters { temperature: None, top_p: None, max_tokens: None });
+    parameters.temperature.get_or_insert(0.0);
+    let prompt = new_prompt("judge", config.provider.clone(), model, Some(parameters), Vec::new());
+    let mut result = Executor::new()
+        .execute_messages(&prompt, messages, &config.api_key, config.base_url.as_deref(), pricing.as_ref())
+        .await?;
+
+    {
+        let app_db = state.app_database.lock().map_err(|e| e.to_string())?;
+        record_spending(&app_db, Some(workspace_path), Some(&provider_name), &mut result)?;
+    }
+    Ok(result)
+}
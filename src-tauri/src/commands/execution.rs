@@ -1,13 +1,25 @@
-use crate::models::execution::ExecutionResult;
+use crate::commands::evaluations::save_assertion_outcomes;
+use crate::models::execution::{ExecutionError, ExecutionResult, OpenAIMessage};
 use crate::models::prompt::PromptRuntime;
-use crate::services::database::AppDatabase;
+use crate::services::assertions;
+use crate::services::budget;
+use crate::services::database::{
+    AppDatabase, ExecutionAggregate, ExecutionFilter, ExecutionGroupBy, ExecutionQueryPage,
+    ExecutionRecord, ExecutionSort, ProjectDatabase,
+};
+use crate::services::dynamic_variables;
 use crate::services::executor::Executor;
+use crate::services::file_variables::{self, LoadedFile};
+use crate::commands::variables::VariablesState;
+use crate::services::git_service::GitService;
+use crate::services::secret_variables;
+use serde::Serialize;
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{State, Window};
 
 pub struct AppState {
-    #[allow(dead_code)]
     pub app_database: Mutex<AppDatabase>,
 }
 
@@ -19,38 +31,458 @@ impl AppState {
     }
 }
 
+/// Execute a prompt
+/// Secret variables are passed masked and substituted here; their values are redacted from the
+/// returned output (and so from Arena battles) and from the saved execution
+/// prompt_content is the file content prompt_yaml was loaded from, snapshotted with the execution;
+/// without it the file is read before the provider call
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn execute_prompt(
     prompt_yaml: String,
     variables: HashMap<String, String>,
     api_key: String,
     base_url: Option<String>,
-    _state: State<'_, AppState>,
-) -> Result<ExecutionResult, String> {
+    workspace_path: Option<String>,
+    provider_name: Option<String>,
+    file_path: Option<String>,
+    run_mode: Option<String>,
+    prompt_content: Option<String>,
+    window: Window,
+    state: State<'_, AppState>,
+    variables_state: State<'_, VariablesState>,
+) -> Result<ExecutionResult, ExecutionError> {
     // Parse YAML
     let prompt: PromptRuntime =
         serde_yaml::from_str(&prompt_yaml).map_err(|e| format!("YAML parse error: {}", e))?;
 
-    // Execute (create new executor to avoid holding lock across await)
+    // The file may be saved again while the provider answers
+    let prompt_content = match (prompt_content, workspace_path.as_deref(), file_path.as_deref()) {
+        (Some(content), _, _) => Some(content),
+        (None, Some(workspace), Some(file)) => read_prompt_file(workspace, file).ok(),
+        _ => None,
+    };
+
+    let request = ExecutionRequest {
+        prompt: &prompt,
+        variables: &variables,
+        dynamic: HashMap::new(),
+        messages: None,
+        api_key: &api_key,
+        base_url: base_url.as_deref(),
+        provider_name: provider_name.as_deref(),
+        workspace_path: workspace_path.as_deref(),
+        window_label: Some(window.label()),
+        variable_profile: None,
+        run_mode: run_mode.as_deref().unwrap_or("single"),
+    };
+    let execution = execute(request, &state, &variables_state).await?;
+
+    // Save to the workspace's execution history
+    Ok(match (workspace_path.as_deref(), file_path.as_deref()) {
+        (Some(workspace), Some(file)) => execution.save(workspace, file, prompt_content.as_deref()),
+        _ => execution.result,
+    })
+}
+
+/// A prompt to send to a provider
+pub(crate) struct ExecutionRequest<'a> {
+    pub prompt: &'a PromptRuntime,
+    /// Inputs as given; they are saved without secret, file and {{$...}} values
+    pub variables: &'a HashMap<String, String>,
+    /// {{$...}} values to reuse instead of resolving them again (those of a replayed execution)
+    pub dynamic: HashMap<String, String>,
+    /// Messages to send as they are instead of rendering the prompt
+    pub messages: Option<Vec<OpenAIMessage>>,
+    pub api_key: &'a str,
+    pub base_url: Option<&'a str>,
+    /// Configured provider the execution is billed to
+    pub provider_name: Option<&'a str>,
+    pub workspace_path: Option<&'a str>,
+    /// Window whose active variable profile supplies secrets
+    pub window_label: Option<&'a str>,
+    /// Variable profile to use without a window (the one a replayed execution used)
+    pub variable_profile: Option<&'a str>,
+    pub run_mode: &'a str,
+}
+
+/// A finished execution with its execution_history row, not saved yet
+pub(crate) struct Execution {
+    pub result: ExecutionResult,
+    pub record: ExecutionRecord,
+    pub input_files: Vec<LoadedFile>,
+}
+
+impl Execution {
+    /// Save to the workspace's execution history along with the assertion outcomes
+    /// The provider call already happened (and was paid for), so failures are only logged
+    pub(crate) fn save(self, workspace_path: &str, file_path: &str, prompt_content: Option<&str>) -> ExecutionResult {
+        if let Err(e) = self.save_records(workspace_path, file_path, prompt_content) {
+            eprintln!("⚠️  {}", e);
+        }
+        self.result
+    }
+
+    /// Save like `save`, returning execution history errors (assertion results are only logged)
+    /// A record whose file_history_id is already set keeps that prompt version instead of a snapshot
+    pub(crate) fn save_records(&self, workspace_path: &str, file_path: &str, prompt_content: Option<&str>) -> Result<(), String> {
+        save_execution_history(workspace_path, file_path, prompt_content, self.record.clone(), &self.input_files)
+            .map_err(|e| format!("Failed to save execution history: {}", e))?;
+        if let Err(e) = save_assertion_outcomes(workspace_path, file_path, &self.result) {
+            eprintln!("⚠️  Failed to save assertion results: {}", e);
+        }
+        Ok(())
+    }
+}
+
+/// Send a prompt: budgets are checked, variables loaded, spending recorded and the prompt's assertions evaluated
+/// Secret values are redacted from the output and from the execution_history row
+pub(crate) async fn execute(
+    request: ExecutionRequest<'_>,
+    state: &AppState,
+    variables_state: &VariablesState,
+) -> Result<Execution, ExecutionError> {
+    let prompt = request.prompt;
+
+    // Check budgets and look up pricing before executing (lock must not be held across await)
+    let pricing = {
+        let db = state.app_database.lock().map_err(|e| e.to_string())?;
+        check_hard_limits(&db, request.workspace_path, request.provider_name)?;
+
+        db.find_model_pricing(prompt.config.provider.as_str(), &prompt.config.model)
+            .map_err(|e| format!("Failed to load model pricing: {}", e))?
+    };
+
+    let profile = match (request.window_label, request.workspace_path) {
+        (Some(label), Some(workspace)) => variables_state.active_profile(label, workspace)?,
+        _ => request.variable_profile.map(String::from),
+    };
+
     let executor = Executor::new();
-    let result = executor
-        .execute(&prompt, variables, &api_key, base_url.as_deref())
+    let mut dynamic = request.dynamic;
+    let mut secrets = Vec::new();
+    let mut input_files = Vec::new();
+    let messages = match request.messages {
+        Some(messages) => messages,
+        None => {
+            // Load secrets, {{file:...}}, declared file variables and {{$...}} values
+            let mut render_variables = request.variables.clone();
+            render_variables.extend(dynamic);
+            secrets = variables_state.substitute_secrets(
+                profile.as_deref(),
+                request.workspace_path,
+                &mut render_variables,
+            )?;
+            input_files = file_variables::load_file_variables(
+                prompt,
+                request.workspace_path.map(Path::new),
+                &mut render_variables,
+            )?;
+            dynamic = dynamic_variables::load_dynamic_variables(prompt, request.workspace_path, &mut render_variables)?;
+            executor.render_messages(prompt, &render_variables)?
+        }
+    };
+
+    let mut result = executor
+        .execute_messages(prompt, messages.clone(), request.api_key, request.base_url, pricing.as_ref())
         .await?;
+    result.output = secret_variables::redact(&result.output, &secrets);
+
+    // Record spending and report soft limits reached by this execution
+    {
+        let db = state.app_database.lock().map_err(|e| e.to_string())?;
+        record_spending(&db, request.workspace_path, request.provider_name, &mut result)?;
+    }
+    result.assertions = assertions::evaluate(&prompt.assertions, &result);
+
+    let mut record = execution_record(
+        prompt,
+        request.provider_name,
+        request.run_mode,
+        request.variables,
+        &secret_variables::redact_messages(&messages, &secrets),
+        &input_files,
+        &result,
+    );
+    record.dynamic_variables = dynamic_variables_json(&dynamic);
+    record.variable_profile = profile;
+
+    Ok(Execution { result, record, input_files })
+}
+
+/// Refuse to execute when a hard spending limit has been reached
+pub(crate) fn check_hard_limits(
+    db: &AppDatabase,
+    workspace_path: Option<&str>,
+    provider_name: Option<&str>,
+) -> Result<(), ExecutionError> {
+    let statuses = budget::applicable_budgets(db, workspace_path, provider_name)?;
+    if let Some(status) = statuses.iter().find(|s| s.hard_exceeded) {
+        return Err(ExecutionError::BudgetExceeded {
+            message: format!(
+                "Hard limit reached for {}: ${:.4} spent of ${:.4}",
+                budget::describe(&status.budget),
+                status.spent_usd,
+                status.budget.hard_limit_usd.unwrap_or(0.0),
+            ),
+            budget_id: status.budget.id.clone(),
+            spent_usd: status.spent_usd,
+            hard_limit_usd: status.budget.hard_limit_usd.unwrap_or(0.0),
+            resets_at: status.period_end,
+        });
+    }
+
+    Ok(())
+}
 
-    // Note: Execution history will be saved to project database
-    // For now, just return the result
-    // TODO: Implement project database integration in workspace context
+/// Record the cost of an execution and add warnings for soft limits it reached
+pub(crate) fn record_spending(
+    db: &AppDatabase,
+    workspace_path: Option<&str>,
+    provider_name: Option<&str>,
+    result: &mut ExecutionResult,
+) -> Result<(), String> {
+    db.record_spending(&result.id, workspace_path, provider_name, &result.metadata)
+        .map_err(|e| format!("Failed to record spending: {}", e))?;
+
+    let statuses = budget::applicable_budgets(db, workspace_path, provider_name)?;
+    for status in statuses.iter().filter(|s| s.soft_exceeded || s.hard_exceeded) {
+        result.budget_warnings.push(format!(
+            "{} reached: ${:.4} spent this period",
+            budget::describe(&status.budget),
+            status.spent_usd,
+        ));
+    }
 
-    Ok(result)
+    Ok(())
 }
 
+/// Build the execution_history row for a finished execution
+/// prompt_file_id, git context and file_history_id are filled in when saving;
+/// dynamic_variables and variable_profile by the caller
+fn execution_record(
+    prompt: &PromptRuntime,
+    provider_name: Option<&str>,
+    run_mode: &str,
+    variables: &HashMap<String, String>,
+    messages: &[OpenAIMessage],
+    input_files: &[LoadedFile],
+    result: &ExecutionResult,
+) -> ExecutionRecord {
+    ExecutionRecord {
+        id: result.id.clone(),
+        prompt_file_id: String::new(),
+        prompt_name: prompt.name.clone(),
+        llm_provider_name: provider_name
+            .map(String::from)
+            .unwrap_or_else(|| prompt.config.provider.as_str().to_string()),
+        input_variables: serde_json::to_string(variables).ok(),
+        output: result.output.clone(),
+        model: result.metadata.model.clone(),
+        provider: result.metadata.provider.clone(),
+        latency_ms: result.metadata.latency_ms as i64,
+        tokens_input: result.metadata.tokens_input as i64,
+        tokens_output: result.metadata.tokens_output as i64,
+        cost_usd: result.metadata.cost_usd,
+        pricing_version: result.metadata.pricing_version,
+        pricing_id: result.metadata.pricing_id.clone(),
+        rendered_messages: serde_json::to_string(messages).ok(),
+        parameters: prompt.config.parameters.as_ref().and_then(|p| serde_json::to_string(p).ok()),
+        run_mode: run_mode.to_string(),
+        timestamp: result.metadata.timestamp,
+        git_commit: None,
+        git_branch: None,
+        file_history_id: None,
+        input_files: if input_files.is_empty() {
+            None
+        } else {
+            serde_json::to_string(input_files).ok()
+        },
+        dynamic_variables: None,
+        prompt_messages: serde_json::to_string(&prompt.messages).ok(),
+        variable_profile: None,
+    }
+}
+
+/// execution_history.dynamic_variables for the values resolved by dynamic_variables::load_dynamic_variables
+fn dynamic_variables_json(values: &HashMap<String, String>) -> Option<String> {
+    if values.is_empty() {
+        None
+    } else {
+        serde_json::to_string(values).ok()
+    }
+}
+
+/// Save an execution with a file_history snapshot of the prompt content it ran
+pub(crate) fn save_execution_history(
+    workspace_path: &str,
+    file_path: &str,
+    prompt_content: Option<&str>,
+    mut record: ExecutionRecord,
+    input_files: &[LoadedFile],
+) -> Result<(), String> {
+    let db = ProjectDatabase::new(Path::new(workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    record.prompt_file_id = db.ensure_prompt_file(file_path)
+        .map_err(|e| format!("Failed to get prompt file: {}", e))?;
+
+    let head = GitService::new(workspace_path).get_head_info();
+    record.git_commit = head.commit;
+    record.git_branch = head.branch;
+
+    // Snapshot the executed prompt so the execution can be traced back to this exact version
+    record.file_history_id = match (record.file_history_id.take(), prompt_content) {
+        // Replays run a version that is already stored
+        (Some(history_id), _) => Some(history_id),
+        (None, Some(content)) => db.snapshot_file_history(file_path, content)
+            .map(|(id, _)| Some(id))
+            .map_err(|e| format!("Failed to snapshot prompt: {}", e))?,
+        (None, None) => {
+            eprintln!("⚠️  No prompt content to snapshot for {}", file_path);
+            None
+        }
+    };
+
+    db.save_execution(&record)
+        .map_err(|e| format!("Failed to save execution: {}", e))?;
+
+    if !input_files.is_empty() {
+        let paths: Vec<String> = input_files.iter().map(|f| f.path.clone()).collect();
+        file_variables::record_files(&db, file_path, Path::new(workspace_path), &paths)?;
+    }
+
+    println!("📝 Saved execution {} ({}) to history", record.id, record.run_mode);
+    Ok(())
+}
+
+/// Read a prompt file given either an absolute or a workspace-relative path
+pub fn read_prompt_file(workspace_path: &str, file_path: &str) -> Result<String, String> {
+    let path = Path::new(workspace_path).join(file_path);
+    std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExecutionHistoryPage {
+    pub items: Vec<ExecutionRecord>,
+    pub total: i64,
+    pub limit: usize,
+    pub offset: usize,
+}
+
+/// Get execution history for a workspace, newest first
+/// If file_path is given, only executions of that prompt are returned
 #[tauri::command]
 pub fn get_execution_history(
-    _limit: usize,
-    _state: State<'_, AppState>,
-) -> Result<Vec<ExecutionResult>, String> {
-    // TODO: Implement with project database
-    // For now, return empty list
-    Ok(Vec::new())
+    workspace_path: String,
+    file_path: Option<String>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> Result<ExecutionHistoryPage, String> {
+    let limit = limit.unwrap_or(50);
+    let offset = offset.unwrap_or(0);
+
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let prompt_file_id = match file_path {
+        Some(file) => match db.find_prompt_file_id(&file)
+            .map_err(|e| format!("Failed to get prompt file: {}", e))? {
+            Some(id) => Some(id),
+            // Never executed
+            None => return Ok(ExecutionHistoryPage { items: Vec::new(), total: 0, limit, offset }),
+        },
+        None => None,
+    };
+
+    let items = db.get_executions(prompt_file_id.as_deref(), limit, offset)
+        .map_err(|e| format!("Failed to get execution history: {}", e))?;
+    let total = db.count_executions(prompt_file_id.as_deref())
+        .map_err(|e| format!("Failed to count executions: {}", e))?;
+
+    Ok(ExecutionHistoryPage { items, total, limit, offset })
 }
 
+/// Search executions with filters and sorting
+/// Pass the returned next_cursor back to get the following page
+#[tauri::command]
+pub fn query_executions(
+    workspace_path: String,
+    filter: Option<ExecutionFilter>,
+    sort: Option<ExecutionSort>,
+    cursor: Option<String>,
+    limit: Option<usize>,
+) -> Result<ExecutionQueryPage, String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    db.query_executions(
+        &filter.unwrap_or_default(),
+        sort.unwrap_or_default(),
+        cursor.as_deref(),
+        limit.unwrap_or(50).min(500),
+    )
+    .map_err(|e| format!("Failed to query executions: {}", e))
+}
+
+/// Count, total cost and p50/p95 latency of executions, grouped by day, model or prompt
+#[tauri::command]
+pub fn get_execution_aggregates(
+    workspace_path: String,
+    filter: Option<ExecutionFilter>,
+    group_by: Option<ExecutionGroupBy>,
+) -> Result<Vec<ExecutionAggregate>, String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    db.aggregate_executions(&filter.unwrap_or_default(), group_by)
+        .map_err(|e| format!("Failed to aggregate executions: {}", e))
+}
+
+/// A past execution as it was sent to the provider
+#[derive(Debug, Serialize)]
+pub struct ExecutedPrompt {
+    pub execution: ExecutionRecord,
+    pub messages: Vec<OpenAIMessage>,
+    pub parameters: Option<serde_json::Value>,
+    pub input_variables: HashMap<String, String>,
+    /// Prompt file content at execution time (None for executions recorded before snapshots)
+    pub prompt_content: Option<String>,
+}
+
+/// Show the prompt exactly as it was executed: rendered messages, parameters and file version
+#[tauri::command]
+pub fn get_prompt_as_executed(
+    workspace_path: String,
+    execution_id: String,
+) -> Result<ExecutedPrompt, String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let execution = db.get_execution(&execution_id)
+        .map_err(|e| format!("Execution not found: {}", e))?;
+
+    let messages = match &execution.rendered_messages {
+        Some(json) => serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse rendered messages: {}", e))?,
+        None => Vec::new(),
+    };
+    let parameters = execution.parameters.as_deref()
+        .and_then(|json| serde_json::from_str(json).ok());
+    let input_variables = execution.input_variables.as_deref()
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default();
+    let prompt_content = match &execution.file_history_id {
+        Some(history_id) => db.get_history_content(history_id).ok(),
+        None => None,
+    };
+
+    Ok(ExecutedPrompt {
+        execution,
+        messages,
+        parameters,
+        input_variables,
+        prompt_content,
+    })
+}
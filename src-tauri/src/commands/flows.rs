@@ -0,0 +1,374 @@
+use crate::commands::execution::{execute, AppState, ExecutionRequest};
+use crate::commands::prompt::load_prompt_runtime;
+use crate::commands::variables::VariablesState;
+use crate::models::execution::ExecutionResult;
+use crate::models::frontmatter::parse_frontmatter;
+use crate::services::database::{ExecutionRecord, FlowRun, FlowRunStep, ProjectDatabase};
+use crate::services::flows::{self, Flow, FlowStep, MAX_EXECUTED_STEPS};
+use crate::services::llm_config::LLMConfigResolver;
+use crate::services::partials;
+use crate::services::template;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{State, Window};
+
+#[derive(Debug, Serialize)]
+pub struct FlowStepReport {
+    pub step_id: String,
+    pub prompt_path: String,
+    pub status: String,  // "completed" | "skipped" | "failed"
+    pub execution_id: Option<String>,
+    pub provider_name: Option<String>,
+    pub model: Option<String>,
+    pub output: Option<String>,
+    pub outputs: serde_json::Map<String, serde_json::Value>,
+    pub tokens_input: u32,
+    pub tokens_output: u32,
+    pub cost_usd: f64,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+    /// Variables given to the prompt (not part of the report, saved with the trace)
+    #[serde(skip)]
+    variables: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FlowRunReport {
+    pub id: String,
+    pub flow_name: Option<String>,
+    pub status: String,  // "completed" | "failed"
+    pub steps: Vec<FlowStepReport>,
+    /// Output of the last completed step
+    pub output: Option<String>,
+    pub total_tokens_input: u32,
+    pub total_tokens_output: u32,
+    pub total_cost_usd: f64,
+    pub total_latency_ms: u64,
+    pub error: Option<String>,
+    pub budget_warnings: Vec<String>,
+}
+
+/// A saved flow run with its steps and their executions
+#[derive(Debug, Serialize)]
+pub struct FlowRunTrace {
+    pub run: FlowRun,
+    pub steps: Vec<FlowRunStep>,
+    pub executions: Vec<ExecutionRecord>,
+}
+
+impl FlowStepReport {
+    fn new(step: &FlowStep, prompt_path: &Path) -> Self {
+        FlowStepReport {
+            step_id: step.id.clone(),
+            prompt_path: prompt_path.display().to_string(),
+            status: "skipped".to_string(),
+            execution_id: None,
+            provider_name: None,
+            model: None,
+            output: None,
+            outputs: serde_json::Map::new(),
+            tokens_input: 0,
+            tokens_output: 0,
+            cost_usd: 0.0,
+            latency_ms: 0,
+            error: None,
+            variables: HashMap::new(),
+        }
+    }
+}
+
+/// Execute a .vibe.flow.yaml file: each step runs a prompt through the executor, is saved to
+/// execution_history, and its mapped outputs become variables of the following steps
+#[tauri::command]
+pub async fn run_flow(
+    workspace_path: String,
+    flow_path: String,
+    inputs: HashMap<String, String>,
+    window: Window,
+    state: State<'_, AppState>,
+    variables_state: State<'_, VariablesState>,
+) -> Result<FlowRunReport, String> {
+    if !flows::is_flow_file(&flow_path) {
+        return Err(format!("Not a flow file ({}): {}", flows::FLOW_EXTENSION, flow_path));
+    }
+    let flow_file = Path::new(&workspace_path).join(&flow_path);
+    let content = fs::read_to_string(&flow_file)
+        .map_err(|e| format!("Failed to read {}: {}", flow_file.display(), e))?;
+    let flow = flows::parse_flow(&content)?;
+
+    let missing: Vec<&str> = flow
+        .inputs
+        .iter()
+        .filter(|name| !inputs.contains_key(*name))
+        .map(|name| name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Missing flow inputs: {}", missing.join(", ")));
+    }
+
+    let mut report = FlowRunReport {
+        id: uuid::Uuid::new_v4().to_string(),
+        flow_name: flow.name.clone(),
+        status: "completed".to_string(),
+        steps: Vec::new(),
+        output: None,
+        total_tokens_input: 0,
+        total_tokens_output: 0,
+        total_cost_usd: 0.0,
+        total_latency_ms: 0,
+        error: None,
+        budget_warnings: Vec::new(),
+    };
+    println!("🔗 Running flow {} ({} steps)", flow_path, flow.steps.len());
+
+    let mut variables = inputs.clone();
+    let mut index = Some(0);
+    while let Some(current) = index {
+        if report.steps.len() == MAX_EXECUTED_STEPS {
+            report.error = Some(format!("Stopped after {} steps (goto loop?)", MAX_EXECUTED_STEPS));
+            break;
+        }
+
+        let step = &flow.steps[current];
+        let prompt_path = partials::resolve_include_path(&step.prompt, &flow_file, Some(Path::new(&workspace_path)));
+        let mut step_report = FlowStepReport::new(step, &prompt_path);
+
+        if step.when.as_ref().is_some_and(|condition| !condition.holds(&variables)) {
+            report.steps.push(step_report);
+            index = if current + 1 < flow.steps.len() { Some(current + 1) } else { None };
+            continue;
+        }
+
+        match run_step(&workspace_path, step, &prompt_path, &variables, &state, window.label(), &variables_state).await {
+            Ok((provider_name, step_variables, result)) => {
+                step_report.execution_id = Some(result.id.clone());
+                step_report.provider_name = Some(provider_name);
+                step_report.model = Some(result.metadata.model.clone());
+                step_report.tokens_input = result.metadata.tokens_input;
+                step_report.tokens_output = result.metadata.tokens_output;
+                step_report.cost_usd = result.metadata.cost_usd;
+                step_report.latency_ms = result.metadata.latency_ms;
+                step_report.variables = step_variables;
+                report.budget_warnings.extend(result.budget_warnings.iter().cloned());
+
+                report.total_tokens_input += result.metadata.tokens_input;
+                report.total_tokens_output += result.metadata.tokens_output;
+                report.total_cost_usd += result.metadata.cost_usd;
+                report.total_latency_ms += result.metadata.latency_ms;
+
+                match map_outputs(step, &result.output) {
+                    Ok(outputs) => {
+                        variables.insert(step.id.clone(), flows::step_value(&result.output, &outputs));
+                        step_report.status = "completed".to_string();
+                        step_report.outputs = outputs;
+                        report.output = Some(result.output.clone());
+                    }
+                    Err(e) => {
+                        step_report.status = "failed".to_string();
+                        step_report.error = Some(e);
+                    }
+                }
+                step_report.output = Some(result.output);
+            }
+            Err(e) => {
+                step_report.status = "failed".to_string();
+                step_report.error = Some(e);
+            }
+        }
+
+        if let Some(e) = &step_report.error {
+            report.error = Some(format!("Step '{}' failed: {}", step.id, e));
+            report.steps.push(step_report);
+            break;
+        }
+        report.steps.push(step_report);
+        index = flows::next_step(&flow, current, &variables);
+    }
+
+    if report.error.is_some() {
+        report.status = "failed".to_string();
+    }
+    report.budget_warnings.sort();
+    report.budget_warnings.dedup();
+
+    if let Err(e) = save_flow_run(&workspace_path, &flow_path, &flow, &inputs, &report) {
+        // The steps already ran (and were paid for), so don't drop the report
+        eprintln!("⚠️  Failed to save flow run: {}", e);
+    }
+
+    println!(
+        "🔗 Flow {} {}: {} steps, ${:.4}",
+        flow_path,
+        report.status,
+        report.steps.len(),
+        report.total_cost_usd
+    );
+    Ok(report)
+}
+
+/// Execute one step and save it to execution_history
+/// Returns the provider name, the variables given to the prompt and the execution result
+/// Secret variables are substituted with the window's profile and redacted from the output
+async fn run_step(
+    workspace_path: &str,
+    step: &FlowStep,
+    prompt_path: &Path,
+    variables: &HashMap<String, String>,
+    state: &AppState,
+    window_label: &str,
+    variables_state: &VariablesState,
+) -> Result<(String, HashMap<String, String>, ExecutionResult), String> {
+    let file_path = prompt_path.display().to_string();
+    let content = fs::read_to_string(prompt_path).map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+    let (frontmatter, _) = parse_frontmatter(&content)?;
+    let frontmatter = frontmatter.unwrap_or_default();
+    let mut prompt = load_prompt_runtime(file_path.clone(), Some(workspace_path.to_string()))?;
+
+    let mut step_variables = variables.clone();
+    for (name, value) in &step.inputs {
+        let rendered = template::replace_variables(value, variables)
+            .map_err(|e| format!("Input '{}': {}", name, e))?;
+        step_variables.insert(name.clone(), rendered);
+    }
+
+    // Resolve the provider (lock must not be held across await)
+    let (provider_name, config) = {
+        let db = state.app_database.lock().map_err(|e| e.to_string())?;
+        let provider_name = match step.provider.clone().or(frontmatter.provider_ref.clone()) {
+            Some(name) => name,
+            None => db
+                .list_llm_providers()
+                .map_err(|e| format!("Failed to list providers: {}", e))?
+                .into_iter()
+                .find(|p| p.is_default)
+                .map(|p| p.name)
+                .ok_or("No provider_ref and no default provider configured")?,
+        };
+        let config = LLMConfigResolver::new(&db).resolve_provider_ref(&provider_name)?;
+        (provider_name, config)
+    };
+
+    // A step's provider override replaces the prompt's provider (and its model unless the prompt sets one)
+    if step.provider.is_some() {
+        prompt.config.provider = config.provider.clone();
+        if frontmatter.model.is_none() {
+            prompt.config.model = config.model.clone();
+        }
+    }
+
+    let request = ExecutionRequest {
+        prompt: &prompt,
+        variables: &step_variables,
+        dynamic: HashMap::new(),
+        messages: None,
+        api_key: &config.api_key,
+        base_url: config.base_url.as_deref(),
+        provider_name: Some(&provider_name),
+        workspace_path: Some(workspace_path),
+        window_label: Some(window_label),
+        variable_profile: None,
+        run_mode: "flow",
+    };
+    let result = execute(request, state, variables_state)
+        .await
+        .map_err(|e| e.to_string())?
+        .save(workspace_path, &partials::workspace_relative(Path::new(workspace_path), prompt_path), Some(&content));
+
+    Ok((provider_name, step_variables, result))
+}
+
+/// Mapped outputs of a step, by name
+fn map_outputs(step: &FlowStep, output: &str) -> Result<serde_json::Map<String, serde_json::Value>, String> {
+    let mut names: Vec<&String> = step.outputs.keys().collect();
+    names.sort();
+
+    let mut outputs = serde_json::Map::new();
+    for name in names {
+        let value = flows::extract_output(output, &step.outputs[name])
+            .map_err(|e| format!("Output '{}': {}", name, e))?;
+        outputs.insert(name.clone(), value);
+    }
+    Ok(outputs)
+}
+
+fn save_flow_run(
+    workspace_path: &str,
+    flow_path: &str,
+    flow: &Flow,
+    inputs: &HashMap<String, String>,
+    report: &FlowRunReport,
+) -> Result<(), String> {
+    let db = ProjectDatabase::new(Path::new(workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let run = FlowRun {
+        id: report.id.clone(),
+        flow_path: flow_path.to_string(),
+        flow_name: flow.name.clone(),
+        inputs: serde_json::to_string(inputs).ok(),
+        status: report.status.clone(),
+        error: report.error.clone(),
+        total_tokens_input: report.total_tokens_input as i64,
+        total_tokens_output: report.total_tokens_output as i64,
+        total_cost_usd: report.total_cost_usd,
+        total_latency_ms: report.total_latency_ms as i64,
+        created_at: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64,
+    };
+    let steps: Vec<FlowRunStep> = report
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(position, step)| FlowRunStep {
+            id: uuid::Uuid::new_v4().to_string(),
+            flow_run_id: report.id.clone(),
+            position: position as i64,
+            step_id: step.step_id.clone(),
+            prompt_path: step.prompt_path.clone(),
+            status: step.status.clone(),
+            execution_id: step.execution_id.clone(),
+            variables: if step.variables.is_empty() { None } else { serde_json::to_string(&step.variables).ok() },
+            outputs: if step.outputs.is_empty() { None } else { serde_json::to_string(&step.outputs).ok() },
+            error: step.error.clone(),
+        })
+        .collect();
+
+    db.save_flow_run(&run, &steps)
+        .map_err(|e| format!("Failed to save flow run: {}", e))
+}
+
+/// Past runs of a flow file (all flows when none is given), newest first
+#[tauri::command]
+pub fn get_flow_runs(
+    workspace_path: String,
+    flow_path: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<FlowRun>, String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    db.get_flow_runs(flow_path.as_deref(), limit.unwrap_or(50))
+        .map_err(|e| format!("Failed to get flow runs: {}", e))
+}
+
+/// A saved flow run with its step trace and the executions of its steps
+#[tauri::command]
+pub fn get_flow_run(workspace_path: String, run_id: String) -> Result<FlowRunTrace, String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let run = db.get_flow_run(&run_id)
+        .map_err(|e| format!("Flow run not found: {}", e))?;
+    let steps = db.get_flow_run_steps(&run_id)
+        .map_err(|e| format!("Failed to get flow run steps: {}", e))?;
+    // Executions deleted from history are left out
+    let executions = steps
+        .iter()
+        .filter_map(|step| step.execution_id.as_deref())
+        .filter_map(|id| db.get_execution(id).ok())
+        .collect();
+
+    Ok(FlowRunTrace { run, steps, executions })
+}
@@ -0,0 +1,85 @@
+use crate::services::database::ProjectDatabase;
+use crate::services::lint::{self, LintContext, LintFinding, LintRuleInfo, Severity};
+use std::fs;
+use std::path::Path;
+
+/// All lint rules with the workspace's severities and options
+#[tauri::command]
+pub fn list_lint_rules(workspace_path: String) -> Result<Vec<LintRuleInfo>, String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    Ok(lint::rule_infos(&lint::load_settings(&db)))
+}
+
+/// Configure a rule for the workspace; no severity resets it to its defaults
+/// Options are merged over the rule's default options
+#[tauri::command]
+pub fn save_lint_rule(
+    workspace_path: String,
+    rule_id: String,
+    severity: Option<Severity>,
+    options: Option<serde_json::Value>,
+) -> Result<(), String> {
+    if !lint::is_rule(&rule_id) {
+        return Err(format!("Unknown lint rule: {}", rule_id));
+    }
+    if options.as_ref().is_some_and(|o| !o.is_object()) {
+        return Err("Rule options must be a JSON object".to_string());
+    }
+
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    match severity {
+        Some(severity) => {
+            let options = options.map(|o| o.to_string());
+            db.save_lint_rule_setting(&rule_id, severity.as_str(), options.as_deref())
+                .map_err(|e| format!("Failed to save lint rule: {}", e))
+        }
+        None => db
+            .delete_lint_rule_setting(&rule_id)
+            .map_err(|e| format!("Failed to reset lint rule: {}", e)),
+    }
+}
+
+fn lint_file(db: &ProjectDatabase, workspace_path: &str, full_path: &Path, content: &str) -> Vec<LintFinding> {
+    let ctx = LintContext::new(content, Some(full_path), Some(Path::new(workspace_path)));
+    lint::lint(&ctx, &lint::load_settings(db))
+}
+
+#[tauri::command]
+pub fn lint_prompt(workspace_path: String, file_path: String) -> Result<Vec<LintFinding>, String> {
+    let full_path = Path::new(&workspace_path).join(&file_path);
+    let content = fs::read_to_string(&full_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    Ok(lint_file(&db, &workspace_path, &full_path, &content))
+}
+
+/// Apply the autofixes of a prompt's findings (all rules, or only the given ones)
+/// Returns the new content
+#[tauri::command]
+pub fn apply_lint_fixes(
+    workspace_path: String,
+    file_path: String,
+    rule_ids: Option<Vec<String>>,
+) -> Result<String, String> {
+    let full_path = Path::new(&workspace_path).join(&file_path);
+    let content = fs::read_to_string(&full_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let findings: Vec<LintFinding> = lint_file(&db, &workspace_path, &full_path, &content)
+        .into_iter()
+        .filter(|f| rule_ids.as_ref().is_none_or(|ids| ids.contains(&f.rule_id)))
+        .collect();
+    let (fixed, applied) = lint::apply_fixes(&content, &findings);
+
+    if applied > 0 {
+        fs::write(&full_path, &fixed).map_err(|e| format!("Failed to write file: {}", e))?;
+        println!("🧹 Applied {} lint fixes to {}", applied, file_path);
+    }
+    Ok(fixed)
+}
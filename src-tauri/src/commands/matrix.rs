@@ -0,0 +1,534 @@
+use crate::commands::datasets::load_dataset;
+use crate::commands::evaluations::assertion_results;
+use crate::commands::execution::{execute, save_execution_history, AppState, Execution, ExecutionRequest};
+use crate::commands::prompt::load_prompt_runtime;
+use crate::commands::variables::VariablesState;
+use crate::models::assertions::{Assertion, AssertionOutcome};
+use crate::models::execution::{ExecutionError, ExecutionResult};
+use crate::models::prompt::PromptRuntime;
+use crate::services::assertions::{self, ASSERTION_EVALUATOR};
+use crate::services::database::{EvaluationResult, MatrixRun, PassRate, ProjectDatabase, TestResult};
+use crate::services::datasets::{self, DatasetRow};
+use crate::services::llm_config::{LLMConfigResolver, ResolvedLLMConfig};
+use crate::services::matrix::{self, MatrixCell, MatrixModel};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, State, Window};
+
+/// Emitted to the window that started (or resumed) a run, after every cell
+pub const MATRIX_PROGRESS_EVENT: &str = "matrix-progress";
+
+/// Matrix runs in progress in this app session
+/// Runs left as 'running' by a previous session were interrupted and can be resumed.
+#[derive(Default)]
+pub struct MatrixRunState {
+    running: Mutex<HashSet<String>>,
+    pause_requests: Mutex<HashSet<String>>,
+}
+
+impl MatrixRunState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn is_running(&self, run_id: &str) -> bool {
+        self.running.lock().unwrap().contains(run_id)
+    }
+
+    fn pause_requested(&self, run_id: &str) -> bool {
+        self.pause_requests.lock().unwrap().contains(run_id)
+    }
+
+    fn request_pause(&self, run_id: &str) {
+        self.pause_requests.lock().unwrap().insert(run_id.to_string());
+    }
+}
+
+/// Marks a run as running for as long as it lives
+struct RunningGuard<'a> {
+    state: &'a MatrixRunState,
+    run_id: String,
+}
+
+impl<'a> RunningGuard<'a> {
+    fn acquire(state: &'a MatrixRunState, run_id: &str) -> Result<Self, String> {
+        if !state.running.lock().unwrap().insert(run_id.to_string()) {
+            return Err(format!("Matrix run {} is already running", run_id));
+        }
+        Ok(Self { state, run_id: run_id.to_string() })
+    }
+}
+
+impl Drop for RunningGuard<'_> {
+    fn drop(&mut self) {
+        self.state.running.lock().unwrap().remove(&self.run_id);
+        self.state.pause_requests.lock().unwrap().remove(&self.run_id);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MatrixRunRequest {
+    pub workspace_path: String,
+    pub prompt_path: String,
+    pub dataset_id: String,
+    /// Dataset rows to run (0-based); every row when omitted
+    #[serde(default)]
+    pub rows: Option<Vec<usize>>,
+    /// Ids from list_enabled_models ("provider::model")
+    pub models: Vec<String>,
+    #[serde(default)]
+    pub concurrency: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MatrixProgress {
+    pub run_id: String,
+    pub status: String,
+    pub total_cells: i64,
+    pub completed_cells: i64,
+    pub failed_cells: i64,
+    pub total_cost_usd: f64,
+    /// The cell that just finished
+    pub cell: Option<TestResult>,
+    /// Outcomes of its assertions
+    pub assertions: Vec<AssertionOutcome>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatrixRunDetail {
+    pub run: MatrixRun,
+    pub results: Vec<TestResult>,
+    /// Assertion outcomes of the cells (execution_id is the test result id)
+    pub evaluations: Vec<EvaluationResult>,
+    /// Assertion pass rate per model
+    pub pass_rates: Vec<PassRate>,
+}
+
+/// How a cell ended
+enum CellOutcome {
+    /// Not started because the run is pausing
+    Skipped,
+    /// A hard spending limit was reached before the call
+    BudgetExceeded(String),
+    Executed(Result<Box<Execution>, String>),
+}
+
+/// Everything cells of a run share
+struct MatrixContext<'a> {
+    workspace_path: &'a str,
+    run_id: &'a str,
+    prompt: &'a PromptRuntime,
+    rows: &'a [DatasetRow],
+    /// Assertions of each row's cells
+    assertions: &'a HashMap<usize, Vec<Assertion>>,
+    configs: &'a HashMap<String, Result<ResolvedLLMConfig, String>>,
+    state: &'a AppState,
+    variables_state: &'a VariablesState,
+    matrix_state: &'a MatrixRunState,
+}
+
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64
+}
+
+fn open_database(workspace_path: &str) -> Result<ProjectDatabase, String> {
+    ProjectDatabase::new(Path::new(workspace_path)).map_err(|e| format!("Failed to open database: {}", e))
+}
+
+/// Run a prompt over dataset rows × models; cells are written to test_results as they finish
+#[tauri::command]
+pub async fn run_matrix(
+    request: MatrixRunRequest,
+    window: Window,
+    state: State<'_, AppState>,
+    variables_state: State<'_, VariablesState>,
+    matrix_state: State<'_, MatrixRunState>,
+) -> Result<MatrixRun, String> {
+    if request.models.is_empty() {
+        return Err("Select at least one model".to_string());
+    }
+    let models = request
+        .models
+        .iter()
+        .map(|model| MatrixModel::parse(model))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let run = {
+        let db = open_database(&request.workspace_path)?;
+        let (dataset, _) = load_dataset(&db, &request.workspace_path, &request.dataset_id)?;
+        let rows = matrix::select_rows(request.rows.as_deref(), dataset.row_count as usize)?;
+        if rows.is_empty() {
+            return Err(format!("Dataset {} has no rows", dataset.name));
+        }
+
+        let prompt_file_id = db
+            .ensure_prompt_file(&request.prompt_path)
+            .map_err(|e| format!("Failed to get prompt file: {}", e))?;
+        let models: Vec<String> = models.iter().map(MatrixModel::model_ref).collect();
+
+        // Pin the prompt: cells run this version even when resumed after the file changed
+        let prompt_file = Path::new(&request.workspace_path).join(&request.prompt_path);
+        let prompt_content = std::fs::read_to_string(&prompt_file)
+            .map_err(|e| format!("Failed to read {}: {}", prompt_file.display(), e))?;
+        let prompt = load_prompt_runtime(prompt_file.display().to_string(), Some(request.workspace_path.clone()))?;
+
+        let now = now();
+        let run = MatrixRun {
+            id: uuid::Uuid::new_v4().to_string(),
+            prompt_path: request.prompt_path.clone(),
+            prompt_file_id,
+            dataset_id: dataset.id.clone(),
+            row_indices: request.rows.as_ref().and_then(|_| serde_json::to_string(&rows).ok()),
+            models: serde_json::to_string(&models).map_err(|e| e.to_string())?,
+            concurrency: matrix::concurrency(request.concurrency) as i64,
+            status: "running".to_string(),
+            total_cells: (rows.len() * models.len()) as i64,
+            completed_cells: 0,
+            failed_cells: 0,
+            total_cost_usd: 0.0,
+            error: None,
+            created_at: now,
+            updated_at: now,
+            prompt_content,
+            prompt_runtime: serde_json::to_string(&prompt).map_err(|e| e.to_string())?,
+        };
+        db.save_matrix_run(&run)
+            .map_err(|e| format!("Failed to save matrix run: {}", e))?;
+        run
+    };
+
+    println!("🧮 Matrix run {} ({} cells)", run.id, run.total_cells);
+    execute_matrix(run, &request.workspace_path, &window, &state, &variables_state, &matrix_state).await
+}
+
+/// Continue a paused or interrupted run; failed cells are retried
+#[tauri::command]
+pub async fn resume_matrix_run(
+    workspace_path: String,
+    run_id: String,
+    window: Window,
+    state: State<'_, AppState>,
+    variables_state: State<'_, VariablesState>,
+    matrix_state: State<'_, MatrixRunState>,
+) -> Result<MatrixRun, String> {
+    let run = open_database(&workspace_path)?
+        .get_matrix_run(&run_id)
+        .map_err(|e| format!("Matrix run {} not found: {}", run_id, e))?;
+    if run.status == "completed" && run.failed_cells == 0 {
+        return Err("Matrix run is already completed".to_string());
+    }
+
+    println!("▶️  Resuming matrix run {}", run.id);
+    execute_matrix(run, &workspace_path, &window, &state, &variables_state, &matrix_state).await
+}
+
+/// Stop starting new cells; cells in flight finish and the run is saved as paused
+#[tauri::command]
+pub fn pause_matrix_run(run_id: String, matrix_state: State<'_, MatrixRunState>) -> Result<(), String> {
+    if !matrix_state.is_running(&run_id) {
+        return Err(format!("Matrix run {} is not running", run_id));
+    }
+    matrix_state.request_pause(&run_id);
+    Ok(())
+}
+
+/// Matrix runs, newest first; runs interrupted by an app restart are reported as paused
+#[tauri::command]
+pub fn list_matrix_runs(
+    workspace_path: String,
+    limit: Option<usize>,
+    matrix_state: State<'_, MatrixRunState>,
+) -> Result<Vec<MatrixRun>, String> {
+    let mut runs = open_database(&workspace_path)?
+        .get_matrix_runs(limit.unwrap_or(50))
+        .map_err(|e| format!("Failed to load matrix runs: {}", e))?;
+
+    for run in runs.iter_mut() {
+        if run.status == "running" && !matrix_state.is_running(&run.id) {
+            run.status = "paused".to_string();
+        }
+    }
+    Ok(runs)
+}
+
+/// A matrix run with its cells
+#[tauri::command]
+pub fn get_matrix_run(
+    workspace_path: String,
+    run_id: String,
+    matrix_state: State<'_, MatrixRunState>,
+) -> Result<MatrixRunDetail, String> {
+    let db = open_database(&workspace_path)?;
+    let mut run = db
+        .get_matrix_run(&run_id)
+        .map_err(|e| format!("Matrix run {} not found: {}", run_id, e))?;
+    if run.status == "running" && !matrix_state.is_running(&run.id) {
+        run.status = "paused".to_string();
+    }
+    let results = db
+        .get_test_results(&run_id)
+        .map_err(|e| format!("Failed to load test results: {}", e))?;
+    let evaluations = db
+        .get_run_evaluation_results(&run_id)
+        .map_err(|e| format!("Failed to load evaluation results: {}", e))?;
+    let pass_rates = db
+        .get_pass_rates(ASSERTION_EVALUATOR, None, Some(&run_id))
+        .map_err(|e| format!("Failed to load pass rates: {}", e))?;
+
+    Ok(MatrixRunDetail { run, results, evaluations, pass_rates })
+}
+
+/// Run the cells of a matrix run that have no successful result yet
+async fn execute_matrix(
+    mut run: MatrixRun,
+    workspace_path: &str,
+    window: &Window,
+    state: &AppState,
+    variables_state: &VariablesState,
+    matrix_state: &MatrixRunState,
+) -> Result<MatrixRun, String> {
+    let _guard = RunningGuard::acquire(matrix_state, &run.id)?;
+
+    let db = open_database(workspace_path)?;
+    let (_, rows) = load_dataset(&db, workspace_path, &run.dataset_id)?;
+    let selected: Vec<usize> = match run.row_indices.as_deref() {
+        Some(json) => serde_json::from_str(json).map_err(|e| format!("Invalid row selection: {}", e))?,
+        None => (0..rows.len()).collect(),
+    };
+    // The dataset may have shrunk since the run started
+    let selected: Vec<usize> = selected.into_iter().filter(|row| *row < rows.len()).collect();
+    let models = serde_json::from_str::<Vec<String>>(&run.models)
+        .map_err(|e| format!("Invalid model list: {}", e))?
+        .iter()
+        .map(|model| MatrixModel::parse(model))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // The prompt as it was when the run was created
+    let prompt: PromptRuntime = serde_json::from_str(&run.prompt_runtime)
+        .map_err(|e| format!("Invalid pinned prompt: {}", e))?;
+
+    // The prompt's assertions apply to every cell, a row's own ones to the cells of that row
+    let mut cell_assertions: HashMap<usize, Vec<Assertion>> = HashMap::new();
+    for row in &selected {
+        let mut row_assertions = prompt.assertions.clone();
+        row_assertions.extend(assertions::row_assertions(&rows[*row]).map_err(|e| format!("Row {}: {}", row, e))?);
+        cell_assertions.insert(*row, row_assertions);
+    }
+
+    // Resolve provider configs up front (lock must not be held across await)
+    let configs: HashMap<String, Result<ResolvedLLMConfig, String>> = {
+        let app_db = state.app_database.lock().map_err(|e| e.to_string())?;
+        let resolver = LLMConfigResolver::new(&app_db);
+        models
+            .iter()
+            .map(|model| model.provider_name.clone())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(|name| {
+                let config = resolver.resolve_provider_ref(&name);
+                (name, config)
+            })
+            .collect()
+    };
+
+    // Earlier attempts: successful cells are kept, failed ones are run again
+    let previous = db
+        .get_test_results(&run.id)
+        .map_err(|e| format!("Failed to load test results: {}", e))?;
+    let (done, mut failed) = matrix::cell_status(&previous);
+    let pending = matrix::pending_cells(&selected, &models, &done);
+
+    run.status = "running".to_string();
+    run.error = None;
+    run.total_cells = (selected.len() * models.len()) as i64;
+    run.completed_cells = done.len() as i64;
+    run.failed_cells = failed.len() as i64;
+    run.total_cost_usd = previous.iter().map(|r| r.cost_usd).sum();
+    run.updated_at = now();
+    db.save_matrix_run(&run)
+        .map_err(|e| format!("Failed to save matrix run: {}", e))?;
+    emit_progress(window, &run, None, Vec::new());
+
+    let run_id = run.id.clone();
+    let context = MatrixContext {
+        workspace_path,
+        run_id: &run_id,
+        prompt: &prompt,
+        rows: &rows,
+        assertions: &cell_assertions,
+        configs: &configs,
+        state,
+        variables_state,
+        matrix_state,
+    };
+    let mut cells = stream::iter(pending)
+        .map(|cell| run_cell(&context, cell))
+        .buffer_unordered(run.concurrency.max(1) as usize);
+
+    let mut skipped = 0;
+    let mut budget_error = None;
+    while let Some((cell, outcome)) = cells.next().await {
+        let result = match outcome {
+            CellOutcome::Skipped => {
+                skipped += 1;
+                continue;
+            }
+            CellOutcome::BudgetExceeded(message) => {
+                // Remaining cells are skipped; the run can be resumed once the limit allows it
+                matrix_state.request_pause(&run_id);
+                budget_error = Some(message);
+                skipped += 1;
+                continue;
+            }
+            CellOutcome::Executed(result) => result,
+        };
+
+        // Successful cells are also saved to execution_history, which evaluation_results refer to
+        let (result, outcomes) = match result {
+            Ok(execution) => {
+                let Execution { result, record, input_files } = *execution;
+                let outcomes = result.assertions.clone();
+                if let Err(e) =
+                    save_execution_history(workspace_path, &run.prompt_path, Some(&run.prompt_content), record, &input_files)
+                {
+                    eprintln!("⚠️  Failed to save execution history: {}", e);
+                }
+                (Ok(result), outcomes)
+            }
+            Err(e) => (Err(e), Vec::new()),
+        };
+        let record = test_result(&run, &cell, &datasets::row_variables(&rows[cell.row]), result);
+        if let Err(e) = db.save_test_result(&record) {
+            eprintln!("⚠️  Failed to save test result: {}", e);
+        } else if !outcomes.is_empty() {
+            let results = assertion_results(&outcomes, &record.id, &run.prompt_file_id, &record.model, Some(&run.id));
+            if let Err(e) = db.save_evaluation_results(&results) {
+                eprintln!("⚠️  Failed to save assertion results: {}", e);
+            }
+        }
+
+        if record.error.is_none() {
+            failed.remove(&cell.key());
+            run.completed_cells += 1;
+            run.total_cost_usd += record.cost_usd;
+        } else {
+            failed.insert(cell.key());
+        }
+        run.failed_cells = failed.len() as i64;
+        emit_progress(window, &run, Some(record), outcomes);
+    }
+    drop(cells);
+
+    run.status = if skipped > 0 { "paused" } else { "completed" }.to_string();
+    run.error = budget_error;
+    run.updated_at = now();
+    db.save_matrix_run(&run)
+        .map_err(|e| format!("Failed to save matrix run: {}", e))?;
+    emit_progress(window, &run, None, Vec::new());
+
+    println!(
+        "🧮 Matrix run {} {}: {}/{} cells, {} failed",
+        run.id, run.status, run.completed_cells, run.total_cells, run.failed_cells
+    );
+    Ok(run)
+}
+
+fn emit_progress(window: &Window, run: &MatrixRun, cell: Option<TestResult>, assertions: Vec<AssertionOutcome>) {
+    let progress = MatrixProgress {
+        run_id: run.id.clone(),
+        status: run.status.clone(),
+        total_cells: run.total_cells,
+        completed_cells: run.completed_cells,
+        failed_cells: run.failed_cells,
+        total_cost_usd: run.total_cost_usd,
+        cell,
+        assertions,
+    };
+    if let Err(e) = window.emit(MATRIX_PROGRESS_EVENT, progress) {
+        eprintln!("⚠️  Failed to emit matrix progress: {}", e);
+    }
+}
+
+fn test_result(
+    run: &MatrixRun,
+    cell: &MatrixCell,
+    variables: &HashMap<String, String>,
+    result: Result<ExecutionResult, String>,
+) -> TestResult {
+    let mut record = TestResult {
+        id: uuid::Uuid::new_v4().to_string(),
+        prompt_file_id: run.prompt_file_id.clone(),
+        dataset_id: run.dataset_id.clone(),
+        test_case_index: cell.row as i64,
+        input_variables: serde_json::to_string(variables).unwrap_or_default(),
+        output: String::new(),
+        model: cell.model.model.clone(),
+        provider: String::new(),
+        latency_ms: 0,
+        tokens_input: 0,
+        tokens_output: 0,
+        cost_usd: 0.0,
+        timestamp: now(),
+        matrix_run_id: Some(run.id.clone()),
+        llm_provider_name: Some(cell.model.provider_name.clone()),
+        error: None,
+    };
+    match result {
+        Ok(result) => {
+            record.id = result.id;
+            record.output = result.output;
+            record.provider = result.metadata.provider;
+            record.latency_ms = result.metadata.latency_ms as i64;
+            record.tokens_input = result.metadata.tokens_input as i64;
+            record.tokens_output = result.metadata.tokens_output as i64;
+            record.cost_usd = result.metadata.cost_usd;
+            record.timestamp = result.metadata.timestamp;
+        }
+        Err(e) => record.error = Some(e),
+    }
+    record
+}
+
+async fn run_cell(context: &MatrixContext<'_>, cell: MatrixCell) -> (MatrixCell, CellOutcome) {
+    if context.matrix_state.pause_requested(context.run_id) {
+        return (cell, CellOutcome::Skipped);
+    }
+    let outcome = match execute_cell(context, &cell).await {
+        Ok(execution) => CellOutcome::Executed(Ok(Box::new(execution))),
+        Err(ExecutionError::BudgetExceeded { message, .. }) => CellOutcome::BudgetExceeded(message),
+        Err(ExecutionError::Failed { message }) => CellOutcome::Executed(Err(message)),
+    };
+    (cell, outcome)
+}
+
+async fn execute_cell(context: &MatrixContext<'_>, cell: &MatrixCell) -> Result<Execution, ExecutionError> {
+    let config = match &context.configs[&cell.model.provider_name] {
+        Ok(config) => config,
+        Err(e) => return Err(e.clone().into()),
+    };
+    let mut prompt = context.prompt.clone();
+    prompt.config.provider = config.provider.clone();
+    prompt.config.model = cell.model.model.clone();
+    prompt.assertions = context.assertions[&cell.row].clone();
+
+    // Budgets are checked per cell
+    let variables = datasets::row_variables(&context.rows[cell.row]);
+    let request = ExecutionRequest {
+        prompt: &prompt,
+        variables: &variables,
+        dynamic: HashMap::new(),
+        messages: None,
+        api_key: &config.api_key,
+        base_url: config.base_url.as_deref(),
+        provider_name: Some(&cell.model.provider_name),
+        workspace_path: Some(context.workspace_path),
+        window_label: None,
+        variable_profile: None,
+        run_mode: "matrix",
+    };
+    execute(request, context.state, context.variables_state).await
+}
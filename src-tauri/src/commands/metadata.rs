@@ -1,4 +1,5 @@
 use crate::services::database::ProjectDatabase;
+use crate::services::prompt_config;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
@@ -39,12 +40,16 @@ pub fn get_prompt_metadata(
     db.ensure_prompt_file(&file_path)
         .map_err(|e| format!("Failed to ensure file record: {}", e))?;
     
+    // The file's frontmatter wins over what the database has
+    prompt_config::sync_file_to_db(&db, &workspace_path, &file_path)?;
+    
     let metadata = db.get_prompt_metadata(&file_path)
         .map_err(|e| format!("Failed to get metadata: {}", e))?;
     
+    // Answer with the path as requested; the database keeps it workspace-relative
     Ok(PromptMetadataResponse {
         id: metadata.id,
-        file_path: metadata.file_path,
+        file_path,
         provider_ref: metadata.provider_ref,
         model_override: metadata.model_override,
         parameters: metadata.parameters,
@@ -79,6 +84,12 @@ pub fn save_prompt_metadata(
         metadata.test_data_path.as_deref(),
     ).map_err(|e| format!("Failed to save metadata: {}", e))?;
     
+    // Keep the file's frontmatter in sync, then read it back so the file stays the source of truth
+    let saved = db.get_prompt_metadata(&metadata.file_path)
+        .map_err(|e| format!("Failed to get metadata: {}", e))?;
+    prompt_config::write_config_to_file(&workspace_path, &metadata.file_path, &saved)?;
+    prompt_config::sync_file_to_db(&db, &workspace_path, &metadata.file_path)?;
+    
     Ok(())
 }
 
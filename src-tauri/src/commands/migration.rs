@@ -0,0 +1,27 @@
+use crate::services::database::{AppDatabase, ProjectDatabase};
+use crate::services::yaml_migration::{self, MigrationReport};
+use std::path::Path;
+
+/// Convert the workspace's legacy .vibe.yaml prompts to .vibe.md
+/// With dry_run, only reports what would happen (including the converted content)
+#[tauri::command]
+pub fn migrate_legacy_prompts(workspace_path: String, dry_run: Option<bool>) -> Result<MigrationReport, String> {
+    let workspace = Path::new(&workspace_path);
+    let db = ProjectDatabase::new(workspace)
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let providers = AppDatabase::new()
+        .ok()
+        .and_then(|app_db| app_db.list_llm_providers().ok())
+        .unwrap_or_default();
+
+    yaml_migration::migrate_workspace(&db, workspace, &providers, dry_run.unwrap_or(false))
+}
+
+/// Undo a migration batch (the latest one when no id is given)
+#[tauri::command]
+pub fn undo_legacy_migration(workspace_path: String, batch_id: Option<String>) -> Result<MigrationReport, String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    yaml_migration::undo_migration(&db, batch_id.as_deref())
+}
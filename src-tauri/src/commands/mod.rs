@@ -12,4 +12,13 @@ pub mod history;
 pub mod metadata;
 pub mod update;
 pub mod git;
-
+pub mod pricing;
+pub mod budget;
+pub mod replay;
+pub mod converters;
+pub mod migration;
+pub mod lint;
+pub mod flows;
+pub mod datasets;
+pub mod matrix;
+pub mod evaluations;
@@ -0,0 +1,74 @@
+use crate::commands::config::AppSettingsState;
+use crate::services::database::ModelPricing;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPricingInput {
+    pub provider: String,
+    pub model: String,                   // Model ID, or "*" for a provider-wide default
+    pub input_price: f64,                // USD per 1M input tokens
+    pub output_price: f64,               // USD per 1M output tokens
+    pub cache_read_price: Option<f64>,   // USD per 1M cached input tokens
+    pub cache_write_price: Option<f64>,  // USD per 1M cache write tokens
+}
+
+/// List all model pricing entries
+#[tauri::command]
+pub fn list_model_pricing(state: State<AppSettingsState>) -> Result<Vec<ModelPricing>, String> {
+    let db = state.app_db.lock().map_err(|e| e.to_string())?;
+    db.list_model_pricing()
+        .map_err(|e| format!("Failed to list model pricing: {}", e))
+}
+
+/// Save a user-edited pricing entry
+/// User entries are never overwritten by pricing fetched from provider APIs
+#[tauri::command]
+pub fn save_model_pricing(
+    input: ModelPricingInput,
+    state: State<AppSettingsState>,
+) -> Result<(), String> {
+    if input.provider.trim().is_empty() || input.model.trim().is_empty() {
+        return Err("Provider and model are required".to_string());
+    }
+
+    let prices = [
+        Some(input.input_price),
+        Some(input.output_price),
+        input.cache_read_price,
+        input.cache_write_price,
+    ];
+    if prices.iter().flatten().any(|p| *p < 0.0 || !p.is_finite()) {
+        return Err("Prices must be non-negative numbers".to_string());
+    }
+
+    let pricing = ModelPricing {
+        id: Uuid::new_v4().to_string(),
+        provider: input.provider.trim().to_string(),
+        model: input.model.trim().to_string(),
+        input_price: input.input_price,
+        output_price: input.output_price,
+        cache_read_price: input.cache_read_price,
+        cache_write_price: input.cache_write_price,
+        source: "user".to_string(),
+        version: 1,
+        updated_at: 0,
+    };
+
+    let db = state.app_db.lock().map_err(|e| e.to_string())?;
+    db.save_model_pricing(&pricing)
+        .map_err(|e| format!("Failed to save model pricing: {}", e))
+}
+
+/// Delete a pricing entry
+#[tauri::command]
+pub fn delete_model_pricing(
+    provider: String,
+    model: String,
+    state: State<AppSettingsState>,
+) -> Result<(), String> {
+    let db = state.app_db.lock().map_err(|e| e.to_string())?;
+    db.delete_model_pricing(&provider, &model)
+        .map_err(|e| format!("Failed to delete model pricing: {}", e))
+}
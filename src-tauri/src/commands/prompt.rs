@@ -1,4 +1,10 @@
-use crate::models::prompt::{PromptRuntime, parse_markdown_prompt, ModelConfig, Provider, ModelParameters};
+use crate::models::frontmatter::{parse_frontmatter, PromptFrontmatter};
+use crate::models::prompt::{PromptRuntime, parse_markdown_prompt};
+use crate::models::variables::{declared_variables, VariableDeclaration};
+use crate::services::database::{AppDatabase, ProjectDatabase};
+use crate::services::template::{self, VariableUsage};
+use crate::services::{file_variables, partials, prompt_config};
+use serde::Serialize;
 use std::fs;
 use std::path::Path;
 
@@ -8,16 +14,46 @@ pub fn read_prompt(file_path: String) -> Result<String, String> {
 }
 
 #[tauri::command]
-pub fn load_prompt_runtime(file_path: String) -> Result<PromptRuntime, String> {
+pub fn load_prompt_runtime(file_path: String, workspace_path: Option<String>) -> Result<PromptRuntime, String> {
     let content = fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
     
     // Determine file type by extension
     if file_path.ends_with(".vibe.md") {
-        // Parse Markdown file
-        let messages = parse_markdown_prompt(&content)?;
+        // Parse Markdown file, with partials ({{> path}}) expanded
+        let (frontmatter, _) = parse_frontmatter(&content)?;
+        let workspace = workspace_path.as_deref().map(Path::new);
+        let expanded = partials::expand_includes(&content, Path::new(&file_path), workspace)?;
+        let messages = parse_markdown_prompt(&expanded.content)?;
+        
+        if let Some(workspace) = workspace {
+            let db = ProjectDatabase::new(workspace)
+                .map_err(|e| format!("Failed to open database: {}", e))?;
+            partials::record_includes(&db, workspace, &file_path, &expanded.includes)?;
+            file_variables::record_files(&db, &file_path, workspace, &file_variables::referenced_paths(&messages))?;
+        }
+        
+        // Config comes from the frontmatter; prompt_files is only a fallback for files without one
+        let frontmatter = match (frontmatter, workspace_path.as_deref()) {
+            (Some(frontmatter), Some(workspace)) => {
+                let db = ProjectDatabase::new(Path::new(workspace))
+                    .map_err(|e| format!("Failed to open database: {}", e))?;
+                db.ensure_prompt_file(&file_path)
+                    .map_err(|e| format!("Failed to ensure file record: {}", e))?;
+                db.apply_prompt_frontmatter(&file_path, &frontmatter)
+                    .map_err(|e| format!("Failed to sync frontmatter: {}", e))?;
+                frontmatter
+            }
+            (Some(frontmatter), None) => frontmatter,
+            (None, Some(workspace)) => ProjectDatabase::new(Path::new(workspace))
+                .ok()
+                .and_then(|db| db.get_prompt_metadata(&file_path).ok())
+                .map(|metadata| prompt_config::frontmatter_from_metadata(&metadata))
+                .unwrap_or_default(),
+            (None, None) => PromptFrontmatter::default(),
+        };
+        
+        let app_db = AppDatabase::new().ok();
         
-        // For Markdown files, metadata comes from database
-        // For now, return a basic runtime with placeholder config
         Ok(PromptRuntime {
             schema: "v1".to_string(),
             name: Path::new(&file_path)
@@ -25,19 +61,13 @@ pub fn load_prompt_runtime(file_path: String) -> Result<PromptRuntime, String> {
                 .and_then(|s| s.to_str())
                 .unwrap_or("Untitled")
                 .to_string(),
-            description: None,
-            config: ModelConfig {
-                provider: Provider::OpenAI,
-                model: "gpt-4o-mini".to_string(),
-                parameters: Some(ModelParameters {
-                    temperature: Some(0.7),
-                    top_p: None,
-                    max_tokens: None,
-                }),
-            },
-            test_data: None,
+            description: frontmatter.description.clone(),
+            config: prompt_config::resolve_model_config(&frontmatter, app_db.as_ref()),
+            test_data: frontmatter.test_data.clone(),
             messages,
-            evaluation: None,
+            evaluation: frontmatter.evaluation.clone(),
+            assertions: frontmatter.assertions.clone(),
+            variables: declared_variables(&content)?,
         })
     } else {
         // Parse YAML file (legacy support)
@@ -132,18 +162,66 @@ pub fn extract_variables(content: String) -> Result<Vec<String>, String> {
 #[tauri::command]
 pub fn extract_variables_from_markdown(content: String) -> Result<Vec<String>, String> {
     let messages = parse_markdown_prompt(&content)?;
-    
-    let mut variables = Vec::new();
-    let regex = regex::Regex::new(r"\{\{([a-zA-Z_][a-zA-Z0-9_]*)\}\}").unwrap();
-
-    for message in &messages {
-        for cap in regex.captures_iter(&message.content) {
-            let var_name = cap[1].to_string();
-            if !variables.contains(&var_name) {
-                variables.push(var_name);
-            }
+    let usages = template::extract_message_variables(&messages)?;
+    Ok(usages.into_iter().map(|usage| usage.name).collect())
+}
+
+/// A prompt variable as shown in the input form: its declaration, or a plain string
+/// field for variables that are used without being declared
+#[derive(Debug, Clone, Serialize)]
+pub struct VariableField {
+    #[serde(flatten)]
+    pub declaration: VariableDeclaration,
+    pub declared: bool,
+    /// Can be left empty
+    pub optional: bool,
+}
+
+/// Input fields for a prompt: declared variables in declaration order, then undeclared ones
+#[tauri::command]
+pub fn get_variable_fields(content: String, file_path: String) -> Result<Vec<VariableField>, String> {
+    let (declarations, messages) = if file_path.ends_with(".vibe.md") {
+        (declared_variables(&content)?, parse_markdown_prompt(&content)?)
+    } else {
+        let prompt = parse_yaml(content)?;
+        (prompt.variables, prompt.messages)
+    };
+    let usages = template::extract_message_variables(&messages)?;
+
+    let mut fields: Vec<VariableField> = declarations
+        .into_iter()
+        .map(|declaration| VariableField {
+            optional: !declaration.is_required(),
+            declaration,
+            declared: true,
+        })
+        .collect();
+
+    for usage in usages {
+        if fields.iter().any(|f| f.declaration.name == usage.name) {
+            continue;
         }
+        fields.push(VariableField {
+            declaration: VariableDeclaration {
+                name: usage.name,
+                default: usage.default.map(serde_json::Value::String),
+                ..Default::default()
+            },
+            declared: false,
+            optional: usage.optional,
+        });
     }
 
-    Ok(variables)
+    Ok(fields)
+}
+
+/// Variables of a prompt with whether they are optional and their default value
+#[tauri::command]
+pub fn extract_variable_usage(content: String, file_path: String) -> Result<Vec<VariableUsage>, String> {
+    let messages = if file_path.ends_with(".vibe.md") {
+        parse_markdown_prompt(&content)?
+    } else {
+        parse_yaml(content)?.messages
+    };
+    template::extract_message_variables(&messages)
 }
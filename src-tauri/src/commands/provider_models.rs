@@ -1,3 +1,4 @@
+use crate::services::database::{AppDatabase, ModelPricing};
 use serde::{Deserialize, Serialize};
 use reqwest;
 
@@ -54,6 +55,16 @@ async fn fetch_openrouter_models(api_key: String, base_url: Option<String>) -> R
     struct OpenRouterModel {
         id: String,
         name: Option<String>,
+        pricing: Option<OpenRouterPricing>,
+    }
+
+    // Prices are USD per token, encoded as strings
+    #[derive(Deserialize)]
+    struct OpenRouterPricing {
+        prompt: Option<String>,
+        completion: Option<String>,
+        input_cache_read: Option<String>,
+        input_cache_write: Option<String>,
     }
 
     let data: OpenRouterResponse = response
@@ -61,6 +72,51 @@ async fn fetch_openrouter_models(api_key: String, base_url: Option<String>) -> R
         .await
         .map_err(|e| format!("Failed to parse response: {}", e))?;
 
+    // Keep the pricing table in sync with OpenRouter
+    let per_million = |price: &Option<String>| -> Option<f64> {
+        price.as_deref()
+            .and_then(|p| p.parse::<f64>().ok())
+            .filter(|p| *p >= 0.0)  // Negative values mean variable pricing
+            .map(|p| p * 1_000_000.0)
+    };
+
+    match AppDatabase::new() {
+        Ok(db) => {
+            let mut synced = 0;
+            for model in &data.data {
+                let pricing = match &model.pricing {
+                    Some(p) => p,
+                    None => continue,
+                };
+                let (input_price, output_price) =
+                    match (per_million(&pricing.prompt), per_million(&pricing.completion)) {
+                        (Some(input), Some(output)) => (input, output),
+                        _ => continue,
+                    };
+
+                let entry = ModelPricing {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    provider: "openrouter".to_string(),
+                    model: model.id.clone(),
+                    input_price,
+                    output_price,
+                    cache_read_price: per_million(&pricing.input_cache_read),
+                    cache_write_price: per_million(&pricing.input_cache_write),
+                    source: "openrouter".to_string(),
+                    version: 1,
+                    updated_at: 0,
+                };
+
+                match db.sync_model_pricing(&entry) {
+                    Ok(()) => synced += 1,
+                    Err(e) => eprintln!("Failed to sync pricing for {}: {}", model.id, e),
+                }
+            }
+            println!("✅ [fetch_openrouter_models] Synced pricing for {} models", synced);
+        }
+        Err(e) => eprintln!("Failed to open app database for pricing sync: {}", e),
+    }
+
     Ok(data.data.into_iter().map(|m| ModelInfo {
         id: m.id.clone(),
         name: m.name.unwrap_or(m.id),
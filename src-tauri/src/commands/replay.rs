@@ -0,0 +1,313 @@
+use crate::commands::execution::{execute, AppState, ExecutionRequest};
+use crate::commands::variables::VariablesState;
+use crate::models::execution::{ExecutionError, OpenAIMessage};
+use crate::models::prompt::{ModelConfig, ModelParameters, PromptRuntime};
+use crate::models::variables::declared_variables;
+use crate::services::database::{
+    ExecutionFilter, ExecutionRecord, ExecutionReplay, ExecutionSort, ProjectDatabase,
+};
+use crate::services::llm_config::{LLMConfigResolver, ResolvedLLMConfig};
+use crate::services::replay::{self, ReplayDiff, ReplayOverrides};
+use crate::services::secret_variables::SECRET_MASK;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::State;
+
+#[derive(Debug, Serialize)]
+pub struct ReplayItem {
+    pub source_execution_id: String,
+    pub replay_execution_id: Option<String>,
+    pub provider_name: String,
+    pub model: String,
+    pub diff: Option<ReplayDiff>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayReport {
+    pub batch_id: String,
+    pub items: Vec<ReplayItem>,
+    pub total_cost_usd: f64,
+}
+
+/// A source execution ready to be sent again
+struct PreparedReplay {
+    source: ExecutionRecord,
+    /// Prompt file the replay is saved under
+    file_path: String,
+    provider_name: String,
+    prompt: PromptRuntime,
+    variables: HashMap<String, String>,
+    /// {{$...}} values, reused from the source execution
+    dynamic: HashMap<String, String>,
+    /// Stored rendered messages, for executions recorded before prompt snapshots
+    messages: Option<Vec<OpenAIMessage>>,
+}
+
+/// Re-run historical executions with the prompt version and variables they used
+/// Executions are selected by id, or by a history query when no ids are given
+#[tauri::command]
+pub async fn replay_executions(
+    workspace_path: String,
+    execution_ids: Option<Vec<String>>,
+    filter: Option<ExecutionFilter>,
+    limit: Option<usize>,
+    overrides: Option<ReplayOverrides>,
+    state: State<'_, AppState>,
+    variables_state: State<'_, VariablesState>,
+) -> Result<ReplayReport, String> {
+    let overrides = overrides.unwrap_or_default();
+    let batch_id = uuid::Uuid::new_v4().to_string();
+
+    let sources = {
+        let db = ProjectDatabase::new(Path::new(&workspace_path))
+            .map_err(|e| format!("Failed to open database: {}", e))?;
+
+        match execution_ids {
+            Some(ids) => ids
+                .iter()
+                .map(|id| db.get_execution(id).map_err(|e| format!("Execution {} not found: {}", id, e)))
+                .collect::<Result<Vec<_>, String>>()?,
+            None => db
+                .query_executions(
+                    &filter.unwrap_or_default(),
+                    ExecutionSort::default(),
+                    None,
+                    limit.unwrap_or(20).min(100),
+                )
+                .map_err(|e| format!("Failed to query executions: {}", e))?
+                .items,
+        }
+    };
+
+    println!("🔁 Replaying {} executions (batch {})", sources.len(), batch_id);
+
+    // Resolve provider configs up front (lock must not be held across await)
+    let providers: HashMap<String, Result<ResolvedLLMConfig, String>> = {
+        let db = state.app_database.lock().map_err(|e| e.to_string())?;
+        let resolver = LLMConfigResolver::new(&db);
+        sources
+            .iter()
+            .map(|source| overrides.provider_name.clone().unwrap_or_else(|| source.llm_provider_name.clone()))
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .map(|name| {
+                let config = resolver.resolve_provider_ref(&name);
+                (name, config)
+            })
+            .collect()
+    };
+
+    let mut items = Vec::new();
+    let mut total_cost_usd = 0.0;
+
+    for source in sources {
+        let provider_name = overrides
+            .provider_name
+            .clone()
+            .unwrap_or_else(|| source.llm_provider_name.clone());
+        let mut item = ReplayItem {
+            source_execution_id: source.id.clone(),
+            replay_execution_id: None,
+            provider_name: provider_name.clone(),
+            model: overrides.model.clone().unwrap_or_else(|| source.model.clone()),
+            diff: None,
+            error: None,
+        };
+
+        let config = match &providers[&provider_name] {
+            Ok(config) => config,
+            Err(e) => {
+                item.error = Some(e.clone());
+                items.push(item);
+                continue;
+            }
+        };
+
+        let prepared = match prepare_replay(&workspace_path, source, provider_name, config, &overrides) {
+            Ok(prepared) => prepared,
+            Err(e) => {
+                item.error = Some(e);
+                items.push(item);
+                continue;
+            }
+        };
+
+        // Masked secret variables get their current values from the profile the source used;
+        // file variables are read again from the workspace, their hashes show what changed
+        let request = ExecutionRequest {
+            prompt: &prepared.prompt,
+            variables: &prepared.variables,
+            dynamic: prepared.dynamic.clone(),
+            messages: prepared.messages.clone(),
+            api_key: &config.api_key,
+            base_url: config.base_url.as_deref(),
+            provider_name: Some(&prepared.provider_name),
+            workspace_path: Some(&workspace_path),
+            window_label: None,
+            variable_profile: prepared.source.variable_profile.as_deref(),
+            run_mode: "replay",
+        };
+        let mut execution = match execute(request, &state, &variables_state).await {
+            Ok(execution) => execution,
+            // Budgets apply to replays like any other execution
+            Err(e @ ExecutionError::BudgetExceeded { .. }) => {
+                item.error = Some(e.to_string());
+                items.push(item);
+                break;
+            }
+            Err(e) => {
+                item.error = Some(e.to_string());
+                items.push(item);
+                continue;
+            }
+        };
+        total_cost_usd += execution.result.metadata.cost_usd;
+
+        // Saved like any other run (assertion results, file dependencies), against the replayed version
+        execution.record.file_history_id = prepared.source.file_history_id.clone();
+        let saved = execution
+            .save_records(&workspace_path, &prepared.file_path, None)
+            .and_then(|_| save_replay(&workspace_path, &batch_id, &prepared.source, &execution.record));
+        match saved {
+            Ok(diff) => {
+                item.replay_execution_id = Some(execution.result.id.clone());
+                item.diff = Some(diff);
+            }
+            Err(e) => item.error = Some(e),
+        }
+        items.push(item);
+    }
+
+    Ok(ReplayReport {
+        batch_id,
+        items,
+        total_cost_usd,
+    })
+}
+
+/// Rebuild the request of a past execution, applying overrides
+/// Dynamic variables keep the values recorded by the source, new ones are resolved when executing
+fn prepare_replay(
+    workspace_path: &str,
+    source: ExecutionRecord,
+    provider_name: String,
+    config: &ResolvedLLMConfig,
+    overrides: &ReplayOverrides,
+) -> Result<PreparedReplay, String> {
+    let variables: HashMap<String, String> = source
+        .input_variables
+        .as_deref()
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default();
+    let dynamic: HashMap<String, String> = source
+        .dynamic_variables
+        .as_deref()
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default();
+    let original_parameters: Option<ModelParameters> = source
+        .parameters
+        .as_deref()
+        .and_then(|json| serde_json::from_str(json).ok());
+
+    let mut prompt = PromptRuntime {
+        schema: "v1".to_string(),
+        name: source.prompt_name.clone(),
+        description: None,
+        config: ModelConfig {
+            provider: config.provider.clone(),
+            model: overrides.model.clone().unwrap_or_else(|| source.model.clone()),
+            parameters: replay::merge_parameters(original_parameters, overrides.parameters.as_ref()),
+        },
+        test_data: None,
+        messages: Vec::new(),
+        evaluation: None,
+        assertions: Vec::new(),
+        variables: Vec::new(),
+    };
+
+    let db = ProjectDatabase::new(Path::new(workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    let (file_path, messages) = match &source.file_history_id {
+        // Re-render the stored prompt version with the stored variables
+        Some(history_id) => {
+            let (file_path, content) = db
+                .get_history_snapshot(history_id)
+                .map_err(|e| format!("Prompt version {} not found: {}", history_id, e))?;
+
+            // Messages as executed; executions saved before they were stored re-expand current partials
+            prompt.messages = match source.prompt_messages.as_deref() {
+                Some(json) => serde_json::from_str(json)
+                    .map_err(|e| format!("Failed to parse prompt messages: {}", e))?,
+                None => replay::snapshot_messages(&file_path, &content, Some(Path::new(workspace_path)))?,
+            };
+            // Declared defaults of that version fill inputs that weren't given
+            if file_path.ends_with(".vibe.md") {
+                prompt.variables = declared_variables(&content).unwrap_or_default();
+            }
+            prompt.assertions = replay::snapshot_assertions(&file_path, &content);
+            (file_path, None)
+        }
+        // Recorded before snapshots existed: resend the stored rendered messages
+        None => {
+            let messages: Vec<OpenAIMessage> = match source.rendered_messages.as_deref() {
+                Some(json) => serde_json::from_str(json)
+                    .map_err(|e| format!("Failed to parse rendered messages: {}", e))?,
+                None => return Err("Execution has no stored prompt version to replay".to_string()),
+            };
+            // Secret values were redacted from them and can't be filled back in
+            if messages.iter().any(|message| message.content.contains(SECRET_MASK)) {
+                return Err("Execution was recorded with redacted secrets and no prompt version to fill them".to_string());
+            }
+            let file_path = db
+                .get_prompt_file_path(&source.prompt_file_id)
+                .map_err(|e| format!("Failed to get prompt file: {}", e))?
+                .ok_or("Prompt file of the execution no longer exists")?;
+            (file_path, Some(messages))
+        }
+    };
+
+    Ok(PreparedReplay {
+        source,
+        file_path,
+        provider_name,
+        prompt,
+        variables,
+        dynamic,
+        messages,
+    })
+}
+
+/// Pair a saved replay with its source
+fn save_replay(
+    workspace_path: &str,
+    batch_id: &str,
+    source: &ExecutionRecord,
+    record: &ExecutionRecord,
+) -> Result<ReplayDiff, String> {
+    let db = ProjectDatabase::new(Path::new(workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let diff = replay::diff_summary(source, record);
+    let diff_json = serde_json::to_string(&diff)
+        .map_err(|e| format!("Failed to serialize diff: {}", e))?;
+    db.save_execution_replay(batch_id, &source.id, &record.id, &diff_json)
+        .map_err(|e| format!("Failed to save replay: {}", e))?;
+
+    Ok(diff)
+}
+
+/// Get saved replays by batch and/or source execution
+#[tauri::command]
+pub fn get_execution_replays(
+    workspace_path: String,
+    batch_id: Option<String>,
+    source_execution_id: Option<String>,
+) -> Result<Vec<ExecutionReplay>, String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    db.get_execution_replays(batch_id.as_deref(), source_execution_id.as_deref())
+        .map_err(|e| format!("Failed to get replays: {}", e))
+}
@@ -1,4 +1,5 @@
 use crate::services::database::ProjectDatabase;
+use crate::services::{file_variables, partials};
 use crate::services::validator::{FileValidator, ValidationResult};
 use std::path::Path;
 
@@ -40,3 +41,49 @@ pub fn quick_validate_file(
     let full_path = Path::new(&workspace_path).join(&file_path);
     Ok(validator.quick_validate(&full_path))
 }
+
+/// List the prompts affected by editing a file: prompts including it or loading it as a
+/// file variable, directly or through other partials (absolute paths, as the editor opens files)
+#[tauri::command]
+pub fn get_affected_prompts(
+    workspace_path: String,
+    file_path: String,
+) -> Result<Vec<String>, String> {
+    let project_db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open project database: {}", e))?;
+
+    // Dependencies may have changed on disk since they were last recorded
+    partials::index_workspace(&project_db, &workspace_path)?;
+    file_variables::index_workspace(&project_db, &workspace_path)?;
+
+    let mut affected = project_db.get_dependent_files(&file_path, partials::INCLUDE_DEPENDENCY)
+        .map_err(|e| format!("Failed to get affected prompts: {}", e))?;
+
+    let loading = project_db.get_dependent_files(&file_path, file_variables::FILE_VARIABLE_DEPENDENCY)
+        .map_err(|e| format!("Failed to get affected prompts: {}", e))?;
+    for prompt in loading {
+        let including = project_db.get_dependent_files(&prompt, partials::INCLUDE_DEPENDENCY)
+            .map_err(|e| format!("Failed to get affected prompts: {}", e))?;
+        affected.push(prompt);
+        affected.extend(including);
+    }
+
+    affected.sort();
+    affected.dedup();
+    Ok(affected
+        .iter()
+        .map(|prompt| Path::new(&workspace_path).join(prompt).display().to_string())
+        .collect())
+}
+
+/// Re-scan all prompts and record their includes and file variables in file_dependencies
+#[tauri::command]
+pub fn refresh_file_dependencies(
+    workspace_path: String,
+) -> Result<usize, String> {
+    let project_db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open project database: {}", e))?;
+
+    file_variables::index_workspace(&project_db, &workspace_path)?;
+    partials::index_workspace(&project_db, &workspace_path)
+}
@@ -1,49 +1,87 @@
-use crate::services::database::AppDatabase;
+use crate::services::database::{AppDatabase, ProjectDatabase, StoredVariable, VariableProfile};
+use crate::services::secret_variables;
+use crate::services::variable_io::{self, ConflictStrategy, MergeSummary, VariableFileFormat};
+use crate::services::variable_resolution::{ResolvedVariable, VariableLayers};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 use std::sync::Mutex;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tauri::State;
-use uuid::Uuid;
+use tauri::{State, Window};
 
 pub struct VariablesState {
     pub app_db: Mutex<AppDatabase>,
+    /// Active profile by (window label, workspace path)
+    pub active_profiles: Mutex<HashMap<(String, String), String>>,
 }
 
 impl VariablesState {
     pub fn new(app_db: AppDatabase) -> Self {
         Self {
             app_db: Mutex::new(app_db),
+            active_profiles: Mutex::new(HashMap::new()),
         }
     }
+
+    pub(crate) fn active_profile(&self, window_label: &str, workspace_path: &str) -> Result<Option<String>, String> {
+        let active = self.active_profiles.lock()
+            .map_err(|e| format!("Failed to lock active profiles: {}", e))?;
+        Ok(active.get(&(window_label.to_string(), workspace_path.to_string())).cloned())
+    }
+
+    /// Substitute secret variables passed masked (or empty) with their keychain values
+    /// `profile` is the variable profile layered over the project variables (see active_profile)
+    /// Returns the substituted values, to be redacted from anything stored
+    pub fn substitute_secrets(
+        &self,
+        profile: Option<&str>,
+        workspace_path: Option<&str>,
+        variables: &mut HashMap<String, String>,
+    ) -> Result<Vec<String>, String> {
+        let project_db = match workspace_path {
+            Some(workspace) => Some(
+                ProjectDatabase::new(Path::new(workspace))
+                    .map_err(|e| format!("Failed to open database: {}", e))?,
+            ),
+            None => None,
+        };
+
+        let db = self.app_db.lock()
+            .map_err(|e| format!("Failed to lock app database: {}", e))?;
+        let layers = VariableLayers::load(&db, project_db.as_ref(), profile, HashMap::new())?;
+        secret_variables::fill_secrets(&layers, variables)
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GlobalVariable {
-    pub id: String,
-    pub key: String,
-    pub value: String,
+/// Global variable as exchanged with the frontend (secret values are masked)
+pub type GlobalVariable = StoredVariable;
+
+/// Store secret values in the keychain, then replace the saved variables
+fn save_variables(
+    previous: &[StoredVariable],
+    mut variables: Vec<StoredVariable>,
+    save: impl FnOnce(&[StoredVariable]) -> rusqlite::Result<()>,
+) -> Result<(), String> {
+    for variable in variables.iter_mut() {
+        let existing = previous.iter().find(|p| !variable.id.is_empty() && p.id == variable.id);
+        secret_variables::store(variable, existing)?;
+    }
+    save(&variables).map_err(|e| format!("Failed to save variables: {}", e))?;
+    secret_variables::forget_removed(previous, &variables);
+    Ok(())
 }
 
 #[tauri::command]
 pub fn list_global_variables(
     state: State<'_, VariablesState>,
 ) -> Result<Vec<GlobalVariable>, String> {
-    let db = state.app_db.lock().unwrap();
-    
-    let mut stmt = db.conn.prepare(
-        "SELECT id, key, value FROM global_variables ORDER BY key"
-    ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-    let variables = stmt.query_map([], |row| {
-        Ok(GlobalVariable {
-            id: row.get(0)?,
-            key: row.get(1)?,
-            value: row.get(2)?,
-        })
-    }).map_err(|e| format!("Failed to query: {}", e))?;
+    let db = state.app_db.lock()
+        .map_err(|e| format!("Failed to lock app database: {}", e))?;
 
-    variables.collect::<Result<Vec<_>, _>>()
-        .map_err(|e| format!("Failed to collect results: {}", e))
+    let mut variables = db.get_global_variables()
+        .map_err(|e| format!("Failed to get global variables: {}", e))?;
+    secret_variables::mask(&mut variables);
+    Ok(variables)
 }
 
 #[tauri::command]
@@ -51,77 +89,332 @@ pub fn save_global_variables(
     variables: Vec<GlobalVariable>,
     state: State<'_, VariablesState>,
 ) -> Result<(), String> {
-    let db = state.app_db.lock().unwrap();
-    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    let db = state.app_db.lock()
+        .map_err(|e| format!("Failed to lock app database: {}", e))?;
 
-    // Start transaction
-    db.conn.execute("BEGIN TRANSACTION", [])
-        .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+    let previous = db.get_global_variables()
+        .map_err(|e| format!("Failed to get global variables: {}", e))?;
+    save_variables(&previous, variables, |variables| db.save_global_variables(variables))
+}
 
-    // Clear existing variables
-    db.conn.execute("DELETE FROM global_variables", [])
-        .map_err(|e| format!("Failed to clear variables: {}", e))?;
+#[tauri::command]
+pub fn get_global_variable(
+    key: String,
+    state: State<'_, VariablesState>,
+) -> Result<GlobalVariable, String> {
+    let db = state.app_db.lock()
+        .map_err(|e| format!("Failed to lock app database: {}", e))?;
 
-    // Insert new variables
-    for var in variables {
-        let id = if var.id.is_empty() {
-            Uuid::new_v4().to_string()
-        } else {
-            var.id
-        };
+    let mut variables: Vec<GlobalVariable> = db.get_global_variables()
+        .map_err(|e| format!("Failed to get global variables: {}", e))?
+        .into_iter()
+        .filter(|v| v.key == key)
+        .collect();
+    secret_variables::mask(&mut variables);
+    variables.pop().ok_or_else(|| format!("Variable not found: {}", key))
+}
 
-        db.conn.execute(
-            "INSERT INTO global_variables (id, key, value, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5)",
-            rusqlite::params![
-                id,
-                var.key,
-                var.value,
-                now,
-                now,
-            ],
-        ).map_err(|e| format!("Failed to insert variable: {}", e))?;
-    }
+#[tauri::command]
+pub fn delete_global_variable(
+    key: String,
+    state: State<'_, VariablesState>,
+) -> Result<(), String> {
+    let db = state.app_db.lock()
+        .map_err(|e| format!("Failed to lock app database: {}", e))?;
 
-    // Commit transaction
-    db.conn.execute("COMMIT", [])
-        .map_err(|e| format!("Failed to commit: {}", e))?;
+    let previous = db.get_global_variables()
+        .map_err(|e| format!("Failed to get global variables: {}", e))?;
+    let remaining: Vec<StoredVariable> = previous.iter().filter(|v| v.key != key).cloned().collect();
+    db.save_global_variables(&remaining)
+        .map_err(|e| format!("Failed to delete: {}", e))?;
+    secret_variables::forget_removed(&previous, &remaining);
 
     Ok(())
 }
 
 #[tauri::command]
-pub fn get_global_variable(
-    key: String,
+pub fn list_project_variables(workspace_path: String) -> Result<Vec<StoredVariable>, String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let mut variables = db.get_project_variables()
+        .map_err(|e| format!("Failed to get project variables: {}", e))?;
+    secret_variables::mask(&mut variables);
+    Ok(variables)
+}
+
+/// Replace the project variables (they override global variables of the same key)
+#[tauri::command]
+pub fn save_project_variables(workspace_path: String, variables: Vec<StoredVariable>) -> Result<(), String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let previous = db.get_project_variables()
+        .map_err(|e| format!("Failed to get project variables: {}", e))?;
+    save_variables(&previous, variables, |variables| db.save_project_variables(variables))
+}
+
+#[tauri::command]
+pub fn list_variable_profiles(workspace_path: String) -> Result<Vec<VariableProfile>, String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    db.get_variable_profiles()
+        .map_err(|e| format!("Failed to get profiles: {}", e))
+}
+
+#[tauri::command]
+pub fn get_profile_variables(workspace_path: String, profile: String) -> Result<Vec<StoredVariable>, String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let mut variables = db.get_profile_variables(&profile)
+        .map_err(|e| format!("Failed to get profile variables: {}", e))?;
+    secret_variables::mask(&mut variables);
+    Ok(variables)
+}
+
+/// Create or update a profile, replacing its variables
+#[tauri::command]
+pub fn save_variable_profile(
+    workspace_path: String,
+    name: String,
+    description: Option<String>,
+    variables: Vec<StoredVariable>,
+) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let previous = db.get_profile_variables(name)
+        .map_err(|e| format!("Failed to get profile variables: {}", e))?;
+    save_variables(&previous, variables, |variables| {
+        db.save_variable_profile(name, description.as_deref(), variables).map(|_| ())
+    })
+}
+
+/// Delete a profile; windows using it go back to no profile
+#[tauri::command]
+pub fn delete_variable_profile(
+    workspace_path: String,
+    name: String,
     state: State<'_, VariablesState>,
-) -> Result<GlobalVariable, String> {
-    let db = state.app_db.lock().unwrap();
+) -> Result<(), String> {
+    let db = ProjectDatabase::new(Path::new(&workspace_path))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+
+    let previous = db.get_profile_variables(&name)
+        .map_err(|e| format!("Failed to get profile variables: {}", e))?;
+    db.delete_variable_profile(&name)
+        .map_err(|e| format!("Failed to delete profile: {}", e))?;
+    secret_variables::forget_removed(&previous, &[]);
 
-    db.conn.query_row(
-        "SELECT id, key, value FROM global_variables WHERE key = ?1",
-        rusqlite::params![key],
-        |row| {
-            Ok(GlobalVariable {
-                id: row.get(0)?,
-                key: row.get(1)?,
-                value: row.get(2)?,
-            })
-        },
-    ).map_err(|e| format!("Variable not found: {}", e))
+    let mut active = state.active_profiles.lock()
+        .map_err(|e| format!("Failed to lock active profiles: {}", e))?;
+    active.retain(|(_, workspace), profile| !(workspace == &workspace_path && profile == &name));
+    Ok(())
 }
 
+/// Switch the profile used by the calling window (None to use no profile)
 #[tauri::command]
-pub fn delete_global_variable(
-    key: String,
+pub fn set_active_profile(
+    window: Window,
+    workspace_path: String,
+    profile: Option<String>,
     state: State<'_, VariablesState>,
 ) -> Result<(), String> {
-    let db = state.app_db.lock().unwrap();
+    let key = (window.label().to_string(), workspace_path.clone());
+    let mut active = state.active_profiles.lock()
+        .map_err(|e| format!("Failed to lock active profiles: {}", e))?;
 
-    db.conn.execute(
-        "DELETE FROM global_variables WHERE key = ?1",
-        rusqlite::params![key],
-    ).map_err(|e| format!("Failed to delete: {}", e))?;
+    match profile {
+        Some(profile) => {
+            let db = ProjectDatabase::new(Path::new(&workspace_path))
+                .map_err(|e| format!("Failed to open database: {}", e))?;
+            let exists = db
+                .get_variable_profiles()
+                .map_err(|e| format!("Failed to get profiles: {}", e))?
+                .iter()
+                .any(|p| p.name == profile);
+            if !exists {
+                return Err(format!("Profile '{}' not found", profile));
+            }
+            active.insert(key, profile);
+        }
+        None => {
+            active.remove(&key);
+        }
+    }
 
     Ok(())
 }
 
+#[tauri::command]
+pub fn get_active_profile(
+    window: Window,
+    workspace_path: String,
+    state: State<'_, VariablesState>,
+) -> Result<Option<String>, String> {
+    state.active_profile(window.label(), &workspace_path)
+}
+
+/// Resolve variables for the calling window: manual input > active profile > project > global
+/// Every variable defined by a source is returned when no names are given; secret values are masked
+#[tauri::command]
+pub fn resolve_variables(
+    window: Window,
+    workspace_path: Option<String>,
+    names: Option<Vec<String>>,
+    manual: Option<HashMap<String, String>>,
+    state: State<'_, VariablesState>,
+) -> Result<Vec<ResolvedVariable>, String> {
+    let project_db = match &workspace_path {
+        Some(workspace) => Some(
+            ProjectDatabase::new(Path::new(workspace))
+                .map_err(|e| format!("Failed to open database: {}", e))?,
+        ),
+        None => None,
+    };
+    let profile = match workspace_path.as_deref() {
+        Some(workspace) => state.active_profile(window.label(), workspace)?,
+        None => None,
+    };
+
+    let db = state.app_db.lock()
+        .map_err(|e| format!("Failed to lock app database: {}", e))?;
+    let layers = VariableLayers::load(&db, project_db.as_ref(), profile.as_deref(), manual.unwrap_or_default())?;
+    Ok(layers.resolve_all(names.as_deref()))
+}
+
+/// Variables an import or export applies to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableScope {
+    Global,
+    Project,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VariableExportReport {
+    pub output_path: String,
+    pub exported: usize,
+    /// Secret variables left out of the file
+    pub excluded_secrets: Vec<String>,
+}
+
+fn open_project(workspace_path: Option<&str>) -> Result<ProjectDatabase, String> {
+    let workspace = workspace_path.ok_or("Workspace path is required for project variables")?;
+    ProjectDatabase::new(Path::new(workspace)).map_err(|e| format!("Failed to open database: {}", e))
+}
+
+fn file_format(path: &Path, format: Option<VariableFileFormat>) -> Result<VariableFileFormat, String> {
+    format
+        .or_else(|| VariableFileFormat::detect(path))
+        .ok_or_else(|| format!("Unknown variables file format: {} (use .env, .json or .csv)", path.display()))
+}
+
+/// Import global or project variables from a .env, JSON object or two-column CSV file
+/// Existing keys are skipped, overwritten or imported under a new name depending on the strategy
+#[tauri::command]
+pub fn import_variables(
+    scope: VariableScope,
+    workspace_path: Option<String>,
+    source_path: String,
+    format: Option<VariableFileFormat>,
+    strategy: Option<ConflictStrategy>,
+    state: State<'_, VariablesState>,
+) -> Result<MergeSummary, String> {
+    let source = Path::new(&source_path);
+    let content = fs::read_to_string(source)
+        .map_err(|e| format!("Failed to read {}: {}", source_path, e))?;
+    let pairs = variable_io::parse(&content, file_format(source, format)?)?;
+    let strategy = strategy.unwrap_or_default();
+
+    // Secrets are masked so that untouched ones keep their keychain value
+    let merge = |previous: &[StoredVariable]| {
+        let mut merged = previous.to_vec();
+        secret_variables::mask(&mut merged);
+        let summary = variable_io::merge(&mut merged, pairs, strategy);
+        (merged, summary)
+    };
+
+    let summary = match scope {
+        VariableScope::Global => {
+            let db = state.app_db.lock()
+                .map_err(|e| format!("Failed to lock app database: {}", e))?;
+            let previous = db.get_global_variables()
+                .map_err(|e| format!("Failed to get global variables: {}", e))?;
+            let (merged, summary) = merge(&previous);
+            save_variables(&previous, merged, |variables| db.save_global_variables(variables))?;
+            summary
+        }
+        VariableScope::Project => {
+            let db = open_project(workspace_path.as_deref())?;
+            let previous = db.get_project_variables()
+                .map_err(|e| format!("Failed to get project variables: {}", e))?;
+            let (merged, summary) = merge(&previous);
+            save_variables(&previous, merged, |variables| db.save_project_variables(variables))?;
+            summary
+        }
+    };
+
+    println!(
+        "📥 Imported variables from {}: {} added, {} overwritten, {} renamed, {} skipped",
+        source_path,
+        summary.added.len(),
+        summary.overwritten.len(),
+        summary.renamed.len(),
+        summary.skipped.len()
+    );
+    Ok(summary)
+}
+
+/// Export global or project variables; secret values are only written when include_secrets is set
+#[tauri::command]
+pub fn export_variables(
+    scope: VariableScope,
+    workspace_path: Option<String>,
+    output_path: String,
+    format: Option<VariableFileFormat>,
+    include_secrets: Option<bool>,
+    state: State<'_, VariablesState>,
+) -> Result<VariableExportReport, String> {
+    let output = Path::new(&output_path);
+    let format = file_format(output, format)?;
+
+    let variables = match scope {
+        VariableScope::Global => state.app_db.lock()
+            .map_err(|e| format!("Failed to lock app database: {}", e))?
+            .get_global_variables()
+            .map_err(|e| format!("Failed to get global variables: {}", e))?,
+        VariableScope::Project => open_project(workspace_path.as_deref())?.get_project_variables()
+            .map_err(|e| format!("Failed to get project variables: {}", e))?,
+    };
+
+    let mut pairs = Vec::new();
+    let mut excluded_secrets = Vec::new();
+    for variable in variables {
+        if !variable.secret {
+            pairs.push((variable.key, variable.value));
+        } else if include_secrets.unwrap_or(false) {
+            let value = secret_variables::reveal(&variable)?;
+            pairs.push((variable.key, value));
+        } else {
+            excluded_secrets.push(variable.key);
+        }
+    }
+
+    fs::write(output, variable_io::serialize(&pairs, format)?)
+        .map_err(|e| format!("Failed to write {}: {}", output_path, e))?;
+
+    println!("📤 Exported {} variables to {}", pairs.len(), output_path);
+    Ok(VariableExportReport {
+        output_path,
+        exported: pairs.len(),
+        excluded_secrets,
+    })
+}
@@ -567,6 +567,7 @@ pub fn save_arena_battle(
     input_variables: String,
     models: String,
     outputs: String,
+    file_path: Option<String>,
 ) -> Result<String, String> {
     // If workspace_path is not provided, try to get from current context
     let ws_path = workspace_path.ok_or("Workspace path is required")?;
@@ -577,12 +578,34 @@ pub fn save_arena_battle(
     let db = ProjectDatabase::new(Path::new(&ws_path))
         .map_err(|e| format!("Failed to open project database: {}", e))?;
     
+    // Link the battle to the prompt version it ran against
+    let (prompt_file_id, file_history_id) = match file_path.as_deref() {
+        Some(file) => {
+            let file_id = db.ensure_prompt_file(file)
+                .map_err(|e| format!("Failed to get prompt file: {}", e))?;
+            // prompt_content is what the battle executed; older callers sent the file path instead
+            let content = if prompt_content.is_empty() || prompt_content == file {
+                crate::commands::execution::read_prompt_file(&ws_path, file).ok()
+            } else {
+                Some(prompt_content.clone())
+            };
+            let history_id = match content {
+                Some(content) => Some(db.snapshot_file_history(file, &content)
+                    .map_err(|e| format!("Failed to snapshot prompt: {}", e))?.0),
+                None => None,
+            };
+            (prompt_file_id.or(Some(file_id)), history_id)
+        }
+        None => (prompt_file_id, None),
+    };
+    
     let id = db.save_arena_battle(
         prompt_file_id,
         &prompt_content,
         &input_variables,
         &models,
         &outputs,
+        file_history_id,
     ).map_err(|e| format!("Failed to save arena battle: {}", e))?;
     
     println!("[Rust] Arena battle saved with ID: {}", id);
@@ -21,6 +21,16 @@ use commands::history::*;
 use commands::metadata::*;
 use commands::update::*;
 use commands::git::*;
+use commands::pricing::*;
+use commands::budget::*;
+use commands::replay::*;
+use commands::converters::*;
+use commands::migration::*;
+use commands::lint::*;
+use commands::flows::*;
+use commands::datasets::*;
+use commands::matrix::*;
+use commands::evaluations::*;
 
 use commands::config::AppSettingsState;
 
@@ -30,6 +40,7 @@ fn main() {
     let llm_provider_state = LLMProviderState::new();
     let variables_state = VariablesState::new(app_db);
     let app_settings_state = AppSettingsState::new();
+    let matrix_run_state = MatrixRunState::new();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
@@ -40,6 +51,7 @@ fn main() {
         .manage(llm_provider_state)
         .manage(variables_state)
         .manage(app_settings_state)
+        .manage(matrix_run_state)
         .invoke_handler(tauri::generate_handler![
             open_workspace,
             list_prompts,
@@ -54,9 +66,14 @@ fn main() {
             parse_yaml,
             extract_variables,
             extract_variables_from_markdown,
+            extract_variable_usage,
+            get_variable_fields,
             load_prompt_runtime,
             execute_prompt,
             get_execution_history,
+            query_executions,
+            get_prompt_as_executed,
+            get_execution_aggregates,
             read_config,
             save_config,
             save_api_key_to_keychain,
@@ -78,6 +95,8 @@ fn main() {
             validate_prompt_file,
             validate_workspace,
             quick_validate_file,
+            get_affected_prompts,
+            refresh_file_dependencies,
             list_global_variables,
             save_global_variables,
             get_global_variable,
@@ -130,6 +149,53 @@ fn main() {
             generate_commit_message,
             get_system_git_config,
             init_git_repository,
+            list_model_pricing,
+            save_model_pricing,
+            delete_model_pricing,
+            list_spending_budgets,
+            save_spending_budget,
+            delete_spending_budget,
+            get_budget_status,
+            replay_executions,
+            get_execution_replays,
+            import_prompt_folder,
+            export_prompt_file,
+            migrate_legacy_prompts,
+            undo_legacy_migration,
+            list_lint_rules,
+            save_lint_rule,
+            lint_prompt,
+            apply_lint_fixes,
+            run_flow,
+            get_flow_runs,
+            get_flow_run,
+            list_project_variables,
+            save_project_variables,
+            list_variable_profiles,
+            get_profile_variables,
+            save_variable_profile,
+            delete_variable_profile,
+            set_active_profile,
+            get_active_profile,
+            resolve_variables,
+            import_variables,
+            export_variables,
+            register_dataset,
+            list_datasets,
+            preview_dataset,
+            validate_dataset_for_prompt,
+            delete_dataset,
+            run_matrix,
+            resume_matrix_run,
+            pause_matrix_run,
+            list_matrix_runs,
+            get_matrix_run,
+            get_evaluation_results,
+            get_assertion_pass_rates,
+            list_evaluation_rules,
+            save_evaluation_rule,
+            delete_evaluation_rule,
+            evaluate_executions,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+/// Expectation on an execution, from the frontmatter `assertions:` key or a dataset row's
+/// `assertions` column (see services::assertions)
+///
+/// ```yaml
+/// assertions:
+///   - type: contains
+///     value: refund
+///   - type: json_path_equals
+///     path: $.status
+///     value: ok
+///   - type: max_latency_ms
+///     value: 3000
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    /// Output equals the value (surrounding whitespace ignored)
+    Equals { value: String },
+    Contains { value: String },
+    NotContains { value: String },
+    StartsWith { value: String },
+    /// Output matches the regex somewhere
+    Regex { pattern: String },
+    /// Output is JSON (optionally inside a ``` code block)
+    JsonValid,
+    JsonSchema { schema: serde_json::Value },
+    /// Value at a `$.a.b[0]` path of the JSON output
+    JsonPathEquals { path: String, value: serde_json::Value },
+    /// In characters
+    MaxLength { value: usize },
+    MaxLatencyMs { value: u64 },
+    MaxCostUsd { value: f64 },
+}
+
+impl Assertion {
+    /// evaluation_results.evaluator_name
+    pub fn label(&self) -> String {
+        match self {
+            Assertion::Equals { value } => format!("equals {:?}", value),
+            Assertion::Contains { value } => format!("contains {:?}", value),
+            Assertion::NotContains { value } => format!("not_contains {:?}", value),
+            Assertion::StartsWith { value } => format!("starts_with {:?}", value),
+            Assertion::Regex { pattern } => format!("regex /{}/", pattern),
+            Assertion::JsonValid => "json_valid".to_string(),
+            Assertion::JsonSchema { .. } => "json_schema".to_string(),
+            Assertion::JsonPathEquals { path, value } => format!("json_path_equals {} = {}", path, value),
+            Assertion::MaxLength { value } => format!("max_length {}", value),
+            Assertion::MaxLatencyMs { value } => format!("max_latency_ms {}", value),
+            Assertion::MaxCostUsd { value } => format!("max_cost_usd {}", value),
+        }
+    }
+}
+
+/// Result of checking one assertion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssertionOutcome {
+    pub name: String,
+    pub passed: bool,
+    pub reason: String,
+}
@@ -1,3 +1,4 @@
+use crate::models::assertions::AssertionOutcome;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -17,6 +18,46 @@ pub struct ExecutionResult {
     pub id: String,
     pub output: String,
     pub metadata: ExecutionMetadata,
+    /// Soft budget limits reached by this execution
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub budget_warnings: Vec<String>,
+    /// Outcomes of the prompt's assertions (see services::assertions)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<AssertionOutcome>,
+}
+
+/// Error returned by execution commands
+/// Serialized as { "kind": "...", "message": "...", ... } so the frontend can tell errors apart
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExecutionError {
+    /// A hard spending limit has been reached
+    BudgetExceeded {
+        message: String,
+        budget_id: String,
+        spent_usd: f64,
+        hard_limit_usd: f64,
+        resets_at: i64,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+impl From<String> for ExecutionError {
+    fn from(message: String) -> Self {
+        ExecutionError::Failed { message }
+    }
+}
+
+impl std::fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionError::BudgetExceeded { message, .. } | ExecutionError::Failed { message } => {
+                write!(f, "{}", message)
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,6 +68,11 @@ pub struct ExecutionMetadata {
     pub tokens_input: u32,
     pub tokens_output: u32,
     pub cost_usd: f64,
+    /// Version of the model_pricing entry used for cost_usd (None when pricing is unknown)
+    pub pricing_version: Option<i64>,
+    /// id of that model_pricing entry: versions are counted per entry
+    #[serde(default)]
+    pub pricing_id: Option<String>,
     pub timestamp: i64,
 }
 
@@ -39,7 +85,7 @@ pub struct OpenAIRequest {
     pub stream: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OpenAIMessage {
     pub role: String,
     pub content: String,
@@ -64,6 +110,27 @@ pub struct OpenAIUsage {
     pub completion_tokens: u32,
     #[allow(dead_code)]
     pub total_tokens: u32,
+    #[serde(default)]
+    pub prompt_tokens_details: Option<PromptTokensDetails>,
+    /// Prompt tokens written to the provider's prompt cache (reported by Anthropic)
+    #[serde(default)]
+    pub cache_write_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PromptTokensDetails {
+    #[serde(default)]
+    pub cached_tokens: u32,
+}
+
+impl OpenAIUsage {
+    /// Prompt tokens served from the provider's prompt cache
+    pub fn cached_tokens(&self) -> u32 {
+        self.prompt_tokens_details
+            .as_ref()
+            .map(|d| d.cached_tokens)
+            .unwrap_or(0)
+    }
 }
 
 
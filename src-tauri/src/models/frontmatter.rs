@@ -0,0 +1,181 @@
+use crate::models::assertions::Assertion;
+use crate::models::prompt::{EvaluationConfig, JudgeSettings, ModelParameters};
+use crate::models::variables::{deserialize_declarations, serialize_declarations, VariableDeclaration};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Optional YAML frontmatter at the top of a .vibe.md file
+///
+/// ```markdown
+/// ---
+/// provider_ref: openai_default
+/// model: gpt-4o
+/// parameters:
+///   temperature: 0.2
+/// tags: [support, email]
+/// ---
+/// ## System Message
+/// ...
+/// ```
+///
+/// The file is the source of truth: prompt_files in project.db mirrors it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptFrontmatter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider_ref: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<ModelParameters>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test_data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub evaluation: Option<Vec<EvaluationConfig>>,
+    /// Checked after every execution (see models::assertions)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
+    /// Set on evaluator rubrics referenced by other prompts' `evaluation:` entries
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub judge: Option<JudgeSettings>,
+    /// See models::variables
+    #[serde(
+        default,
+        skip_serializing_if = "Vec::is_empty",
+        deserialize_with = "deserialize_declarations",
+        serialize_with = "serialize_declarations"
+    )]
+    pub variables: Vec<VariableDeclaration>,
+    /// Keys VibeBase doesn't know about, kept as-is when the frontmatter is rewritten
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_yaml::Value>,
+}
+
+impl PromptFrontmatter {
+    pub fn is_empty(&self) -> bool {
+        self.provider_ref.is_none()
+            && self.model.is_none()
+            && self.parameters.is_none()
+            && self.tags.is_none()
+            && self.description.is_none()
+            && self.test_data.is_none()
+            && self.evaluation.is_none()
+            && self.assertions.is_empty()
+            && self.judge.is_none()
+            && self.variables.is_empty()
+            && self.extra.is_empty()
+    }
+}
+
+/// Split a document into (frontmatter YAML, body)
+/// Frontmatter must start on the first line with `---` and end with a `---` or `...` line
+pub fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    let rest = match content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    {
+        Some(rest) => rest,
+        None => return (None, content),
+    };
+
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        let trimmed = line.trim_end_matches(['\n', '\r']);
+        if trimmed == "---" || trimmed == "..." {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+
+    // No closing delimiter: not frontmatter
+    (None, content)
+}
+
+/// Parse the frontmatter of a document, returning it with the remaining body
+pub fn parse_frontmatter(content: &str) -> Result<(Option<PromptFrontmatter>, &str), String> {
+    match split_frontmatter(content) {
+        (Some(yaml), body) => {
+            if yaml.trim().is_empty() {
+                return Ok((Some(PromptFrontmatter::default()), body));
+            }
+            let frontmatter = serde_yaml::from_str(yaml)
+                .map_err(|e| format!("Invalid frontmatter: {}", e))?;
+            Ok((Some(frontmatter), body))
+        }
+        (None, body) => Ok((None, body)),
+    }
+}
+
+/// Replace (or add) the frontmatter of a document, keeping the body untouched
+/// An empty frontmatter removes the block entirely
+pub fn write_frontmatter(content: &str, frontmatter: &PromptFrontmatter) -> Result<String, String> {
+    let (_, body) = split_frontmatter(content);
+
+    if frontmatter.is_empty() {
+        return Ok(body.to_string());
+    }
+
+    let yaml = serde_yaml::to_string(frontmatter)
+        .map_err(|e| format!("Failed to serialize frontmatter: {}", e))?;
+
+    Ok(format!("---\n{}---\n{}", yaml, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "---\nprovider_ref: openai_default\nmodel: gpt-4o\nparameters:\n  temperature: 0.2\ntags: [a, b]\nowner: team-x\n---\n## User Message\nHello\n";
+
+    #[test]
+    fn test_parse_frontmatter() {
+        let (frontmatter, body) = parse_frontmatter(DOC).unwrap();
+        let frontmatter = frontmatter.unwrap();
+
+        assert_eq!(frontmatter.provider_ref.as_deref(), Some("openai_default"));
+        assert_eq!(frontmatter.model.as_deref(), Some("gpt-4o"));
+        assert_eq!(frontmatter.parameters.unwrap().temperature, Some(0.2));
+        assert_eq!(frontmatter.tags, Some(vec!["a".to_string(), "b".to_string()]));
+        assert!(frontmatter.extra.contains_key("owner"));
+        assert_eq!(body, "## User Message\nHello\n");
+    }
+
+    #[test]
+    fn test_no_frontmatter() {
+        let content = "## User Message\n---\nHello\n";
+        let (frontmatter, body) = parse_frontmatter(content).unwrap();
+        assert!(frontmatter.is_none());
+        assert_eq!(body, content);
+    }
+
+    #[test]
+    fn test_write_frontmatter_keeps_body_and_unknown_keys() {
+        let (frontmatter, _) = parse_frontmatter(DOC).unwrap();
+        let mut frontmatter = frontmatter.unwrap();
+        frontmatter.model = Some("gpt-4o-mini".to_string());
+
+        let written = write_frontmatter(DOC, &frontmatter).unwrap();
+        let (reparsed, body) = parse_frontmatter(&written).unwrap();
+        let reparsed = reparsed.unwrap();
+
+        assert_eq!(reparsed.model.as_deref(), Some("gpt-4o-mini"));
+        assert!(reparsed.extra.contains_key("owner"));
+        assert_eq!(body, "## User Message\nHello\n");
+
+        let removed = write_frontmatter(DOC, &PromptFrontmatter::default()).unwrap();
+        assert_eq!(removed, "## User Message\nHello\n");
+    }
+    #[test]
+    fn test_judge_settings() {
+        let rubric = "---\nmodel: gpt-4o\njudge:\n  scale: 5\n---\n## User Message\nRate {{output}}\n";
+        let frontmatter = parse_frontmatter(rubric).unwrap().0.unwrap();
+        let judge = frontmatter.judge.unwrap();
+
+        assert_eq!(judge.scale, Some(5.0));
+        assert_eq!(judge.pass_threshold, None);
+        assert!(!frontmatter.extra.contains_key("judge"));
+    }
+}
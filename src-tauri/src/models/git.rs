@@ -86,6 +86,13 @@ pub struct GitSummary {
     pub behind: usize,
 }
 
+/// Commit and branch checked out in the workspace (recorded with executions)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GitHeadInfo {
+    pub commit: Option<String>,
+    pub branch: Option<String>,
+}
+
 impl Default for GitConfig {
     fn default() -> Self {
         let now = std::time::SystemTime::now()
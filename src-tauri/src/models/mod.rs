@@ -2,6 +2,9 @@ pub mod prompt;
 pub mod execution;
 pub mod config;
 pub mod git;
+pub mod frontmatter;
+pub mod variables;
+pub mod assertions;
 
 use serde::{Deserialize, Serialize};
 
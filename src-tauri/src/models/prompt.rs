@@ -1,3 +1,5 @@
+use crate::models::assertions::Assertion;
+use crate::models::variables::{deserialize_declarations, VariableDeclaration};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,6 +14,12 @@ pub struct PromptRuntime {
     pub messages: Vec<Message>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub evaluation: Option<Vec<EvaluationConfig>>,
+    /// Checked after every execution (see services::assertions)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assertions: Vec<Assertion>,
+    /// Declared variables (see models::variables)
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "deserialize_declarations")]
+    pub variables: Vec<VariableDeclaration>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,7 +28,7 @@ pub struct Message {
     pub content: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum MessageRole {
     System,
@@ -71,27 +79,87 @@ pub enum Provider {
     Custom,
 }
 
+impl Provider {
+    /// Provider identifier as stored in llm_providers.provider
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Provider::OpenAI => "openai",
+            Provider::Anthropic => "anthropic",
+            Provider::DeepSeek => "deepseek",
+            Provider::OpenRouter => "openrouter",
+            Provider::Ollama => "ollama",
+            Provider::AzureOpenAI => "azure_openai",
+            Provider::Google => "google",
+            Provider::AiHubMix => "aihubmix",
+            Provider::GitHub => "github",
+            Provider::Custom => "custom",
+        }
+    }
+
+    /// Parse a provider identifier as stored in llm_providers.provider
+    pub fn from_name(name: &str) -> Option<Provider> {
+        match name {
+            "openai" => Some(Provider::OpenAI),
+            "anthropic" => Some(Provider::Anthropic),
+            "deepseek" => Some(Provider::DeepSeek),
+            "openrouter" => Some(Provider::OpenRouter),
+            "ollama" => Some(Provider::Ollama),
+            "azure_openai" => Some(Provider::AzureOpenAI),
+            "google" => Some(Provider::Google),
+            "aihubmix" => Some(Provider::AiHubMix),
+            "github" => Some(Provider::GitHub),
+            "custom" => Some(Provider::Custom),
+            _ => None,
+        }
+    }
+}
+
+/// Evaluator of a prompt: the evaluation rule called `name`, whose rule_type is `type`,
+/// or with `ref`, a .vibe.md rubric run by a judge model (see JudgeSettings)
+/// Scores are combined into a weighted mean using `weight` (1 by default)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EvaluationConfig {
     pub name: String,
     #[serde(rename = "type")]
     pub eval_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "ref", alias = "ref_path", skip_serializing_if = "Option::is_none")]
     pub ref_path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub weight: Option<f32>,
+    /// Judge provider config and model of a rubric, over the ones in its frontmatter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_ref: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+}
+
+/// `judge:` frontmatter of a .vibe.md rubric, which receives `{{input}}`, `{{output}}` and `{{expected}}`
+///
+/// ```yaml
+/// judge:
+///   scale: 5            # the judge scores from 0 to 5 (10 by default)
+///   pass_threshold: 0.8 # on the normalized 0..1 score (0.7 by default)
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JudgeSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scale: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pass_threshold: Option<f64>,
 }
 
 impl PromptRuntime {
+    /// Names of the variables used by the messages (see services::template)
+    /// Messages with template syntax errors are skipped
     pub fn extract_variables(&self) -> Vec<String> {
-        let mut variables = Vec::new();
-        let regex = regex::Regex::new(r"\{\{([a-zA-Z_][a-zA-Z0-9_]*)\}\}").unwrap();
+        let mut variables: Vec<String> = Vec::new();
 
         for message in &self.messages {
-            for cap in regex.captures_iter(&message.content) {
-                let var_name = cap[1].to_string();
-                if !variables.contains(&var_name) {
-                    variables.push(var_name);
+            if let Ok(usages) = crate::services::template::extract_variables(&message.content) {
+                for usage in usages {
+                    if !variables.contains(&usage.name) {
+                        variables.push(usage.name);
+                    }
                 }
             }
         }
@@ -100,140 +168,90 @@ impl PromptRuntime {
     }
 }
 
-/// Parse pure Markdown prompt file (no frontmatter)
+/// Parse Markdown prompt file
 /// Content is organized by H2 headings: ## System Message, ## User Message, ## Assistant
+/// Each message is the source text between its heading and the next H1/H2 heading, unchanged
+/// Optional YAML frontmatter is skipped (see models::frontmatter)
 pub fn parse_markdown_prompt(content: &str) -> Result<Vec<Message>, String> {
-    use pulldown_cmark::{Parser, Event, Tag, HeadingLevel};
-    
+    use pulldown_cmark::{Event, HeadingLevel, Parser, Tag};
+
+    let (_, content) = crate::models::frontmatter::split_frontmatter(content);
+
     let mut messages = Vec::new();
-    let mut current_role: Option<MessageRole> = None;
-    let mut current_content = String::new();
-    let mut in_heading = false;
-    let mut heading_level = HeadingLevel::H1;
-    let mut heading_text = String::new();
-    
-    let parser = Parser::new(content);
-    
-    for event in parser {
-        match event {
-            Event::Start(Tag::Heading(level, _, _)) => {
-                heading_level = level;
-                if level == HeadingLevel::H2 {
-                    // Save previous message if exists
-                    if let Some(role) = current_role.take() {
-                        if !current_content.trim().is_empty() {
-                            messages.push(Message {
-                                role,
-                                content: current_content.trim().to_string(),
-                            });
-                        }
-                        current_content.clear();
-                    }
-                    in_heading = true;
-                    heading_text.clear();
-                }
-            }
-            Event::End(Tag::Heading(..)) => {
-                if heading_level == HeadingLevel::H2 {
-                    in_heading = false;
-                    // Determine role from heading text
-                    let heading_lower = heading_text.to_lowercase();
-                    current_role = if heading_lower.contains("system") {
-                        Some(MessageRole::System)
-                    } else if heading_lower.contains("user") {
-                        Some(MessageRole::User)
-                    } else if heading_lower.contains("assistant") {
-                        Some(MessageRole::Assistant)
-                    } else {
-                        None
-                    };
-                }
-            }
-            Event::Text(text) => {
-                if in_heading {
-                    heading_text.push_str(&text);
-                } else if current_role.is_some() {
-                    current_content.push_str(&text);
-                }
-            }
-            Event::Code(code) => {
-                if !in_heading && current_role.is_some() {
-                    current_content.push('`');
-                    current_content.push_str(&code);
-                    current_content.push('`');
-                }
-            }
-            Event::SoftBreak | Event::HardBreak => {
-                if !in_heading && current_role.is_some() {
-                    current_content.push('\n');
-                }
-            }
-            Event::Start(Tag::Paragraph) => {
-                if !in_heading && current_role.is_some() && !current_content.is_empty() {
-                    current_content.push('\n');
-                }
-            }
-            Event::End(Tag::Paragraph) => {
-                if !in_heading && current_role.is_some() {
-                    current_content.push('\n');
-                }
-            }
-            Event::Start(Tag::List(_)) | Event::Start(Tag::Item) => {
-                if !in_heading && current_role.is_some() {
-                    current_content.push('\n');
-                }
-            }
-            Event::Start(Tag::Strong) => {
-                if !in_heading && current_role.is_some() {
-                    current_content.push_str("**");
-                }
-            }
-            Event::End(Tag::Strong) => {
-                if !in_heading && current_role.is_some() {
-                    current_content.push_str("**");
-                }
-            }
-            Event::Start(Tag::Emphasis) => {
-                if !in_heading && current_role.is_some() {
-                    current_content.push('*');
-                }
+    // Role of the open section and where its content starts
+    let mut current: Option<(MessageRole, usize)> = None;
+    // Text of the H2 heading being read
+    let mut heading_text: Option<String> = None;
+
+    let mut close_section = |current: &mut Option<(MessageRole, usize)>, end: usize| {
+        if let Some((role, start)) = current.take() {
+            let text = trim_blank_lines(&content[start..end]);
+            if !text.is_empty() {
+                messages.push(Message {
+                    role,
+                    content: text.to_string(),
+                });
             }
-            Event::End(Tag::Emphasis) => {
-                if !in_heading && current_role.is_some() {
-                    current_content.push('*');
-                }
+        }
+    };
+
+    for (event, range) in Parser::new(content).into_offset_iter() {
+        match event {
+            // Only ATX H2 headings (`## ...`) split sections; H1s and setext headings stay content
+            Event::Start(Tag::Heading(HeadingLevel::H2, _, _))
+                if content[range.clone()].trim_start().starts_with('#') =>
+            {
+                close_section(&mut current, range.start);
+                heading_text = Some(String::new());
             }
-            Event::Start(Tag::CodeBlock(_)) => {
-                if !in_heading && current_role.is_some() {
-                    current_content.push_str("\n```\n");
+            Event::Text(text) | Event::Code(text) => {
+                if let Some(heading) = heading_text.as_mut() {
+                    heading.push_str(&text);
                 }
             }
-            Event::End(Tag::CodeBlock(_)) => {
-                if !in_heading && current_role.is_some() {
-                    current_content.push_str("\n```\n");
+            Event::End(Tag::Heading(..)) => {
+                if let Some(heading) = heading_text.take() {
+                    current = heading_role(&heading).map(|role| (role, range.end));
                 }
             }
             _ => {}
         }
     }
-    
-    // Save last message
-    if let Some(role) = current_role {
-        if !current_content.trim().is_empty() {
-            messages.push(Message {
-                role,
-                content: current_content.trim().to_string(),
-            });
-        }
-    }
-    
+    close_section(&mut current, content.len());
+
     if messages.is_empty() {
         return Err("No valid messages found in Markdown. Use ## System Message, ## User Message, or ## Assistant headings.".to_string());
     }
-    
+
     Ok(messages)
 }
 
+/// Role of an H2 section from its heading text (None: the section is not a message)
+pub fn heading_role(heading: &str) -> Option<MessageRole> {
+    let heading_lower = heading.to_lowercase();
+    if heading_lower.contains("system") {
+        Some(MessageRole::System)
+    } else if heading_lower.contains("user") {
+        Some(MessageRole::User)
+    } else if heading_lower.contains("assistant") {
+        Some(MessageRole::Assistant)
+    } else {
+        None
+    }
+}
+
+/// Drop blank lines before the content and trailing whitespace, keeping the first line's indentation
+fn trim_blank_lines(text: &str) -> &str {
+    let mut start = 0;
+    for line in text.split_inclusive('\n') {
+        if !line.trim().is_empty() {
+            break;
+        }
+        start += line.len();
+    }
+    text[start..].trim_end()
+}
+
 #[cfg(test)]
 #[path = "prompt_test.rs"]
 mod prompt_test;
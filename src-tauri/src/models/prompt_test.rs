@@ -122,18 +122,8 @@ Name again: {{name}}
 
         let messages = parse_markdown_prompt(content).unwrap();
         
-        // Extract variables using regex
-        let mut variables = Vec::new();
-        let regex = regex::Regex::new(r"\{\{([a-zA-Z_][a-zA-Z0-9_]*)\}\}").unwrap();
-
-        for message in &messages {
-            for cap in regex.captures_iter(&message.content) {
-                let var_name = cap[1].to_string();
-                if !variables.contains(&var_name) {
-                    variables.push(var_name);
-                }
-            }
-        }
+        let usages = crate::services::template::extract_message_variables(&messages).unwrap();
+        let variables: Vec<String> = usages.into_iter().map(|u| u.name).collect();
 
         assert_eq!(variables.len(), 3);
         assert!(variables.contains(&"name".to_string()));
@@ -228,5 +218,67 @@ mixed case
         assert!(matches!(messages[1].role, MessageRole::User));
         assert!(matches!(messages[2].role, MessageRole::Assistant));
     }
-}
 
+    #[test]
+    fn test_parse_with_frontmatter() {
+        let content = "---\nmodel: gpt-4o\ntags: [demo]\n---\n## System Message\nYou are terse.\n\n## User Message\nHi\n";
+
+        let messages = parse_markdown_prompt(content).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, "You are terse.");
+        assert_eq!(messages[1].content, "Hi");
+    }
+
+    // Markdown constructs that must reach the model exactly as written
+    const CONSTRUCTS: &[&str] = &[
+        "Read [the docs](https://example.com \"Docs\") and ![diagram](img/diagram.png).",
+        "[Reference link][spec]\n\n[spec]: https://example.com/spec",
+        "<https://example.com> autolink",
+        "1. First\n2. Second\n   - nested *item*\n   - another\n3. Third",
+        "3) Starts at three\n4) Four",
+        "* star bullet\n+ plus bullet\n- [ ] todo\n- [x] done",
+        "### Details\nH3 content\n#### Deeper\n###### Deepest",
+        "# H1 inside a message\nkept with the text under it",
+        "> Quoted\n> > nested quote\n>\n> - list in quote",
+        "| Name | Value |\n|------|:-----:|\n| a    | 1     |\n| b    | 2     |",
+        "```python\nprint('hi')\n\n## not a heading\n```",
+        "~~~json\n{\"a\": 1}\n~~~",
+        "    indented code block\n    second line",
+        "Text with `code`, **bold**, __bold__, *em*, _em_ and ~~strike~~.",
+        "Paragraph\n\n---\n\nAfter a rule\n\n***",
+        "Setext title\n---\nbody under it",
+        "Line one  \nhard break\\\nbackslash break",
+        "Footnote[^1]\n\n[^1]: The note.",
+        "<!-- comment -->\n<div class=\"box\">\nblock html\n</div>",
+        "Escaped \\*stars\\* and entities &amp; &copy; &#35;",
+        "{{#if context}}\n- {{context | indent: 2}}\n{{/if}}",
+        "Trailing spaces kept inside  \n\n\n\nafter several blank lines",
+    ];
+
+    #[test]
+    fn test_round_trip_markdown_constructs() {
+        for construct in CONSTRUCTS {
+            let content = format!("# Title\n\n## System Message\n\n{}\n\n## User Message\nHi\n", construct);
+            let messages = parse_markdown_prompt(&content).unwrap();
+
+            assert_eq!(messages.len(), 2, "{}", construct);
+            assert_eq!(messages[0].content, *construct);
+            assert_eq!(messages[1].content, "Hi");
+        }
+    }
+
+    #[test]
+    fn test_round_trip_full_document() {
+        let system = CONSTRUCTS[..10].join("\n\n");
+        let user = CONSTRUCTS[10..].join("\n\n");
+        let content = format!(
+            "---\nmodel: gpt-4o\n---\n# Prompt\n\nIntro is not a message.\n\n## System Message\n{}\n\n## User Message\n\n{}\n\n## Variables\n```yaml\nname: string\n```\n",
+            system, user
+        );
+
+        let messages = parse_markdown_prompt(&content).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].content, system);
+        assert_eq!(messages[1].content, user);
+    }
+}
@@ -0,0 +1,318 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableType {
+    #[default]
+    String,
+    Number,
+    Enum,
+    Json,
+    Multiline,
+    File,
+}
+
+/// Declared prompt variable, from the frontmatter `variables:` key or a `## Variables` section
+///
+/// ```yaml
+/// variables:
+///   tone:
+///     type: enum
+///     values: [friendly, formal]
+///     default: friendly
+///   max_words:
+///     type: number
+///     min: 10
+///     description: Length limit for the answer
+///   notes: multiline
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VariableDeclaration {
+    /// Empty in map form, where the key is the name
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub name: String,
+    #[serde(rename = "type", default)]
+    pub var_type: VariableType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+    /// Defaults to true unless a default value is declared
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub required: Option<bool>,
+    /// Allowed values of an enum
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub values: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    /// Regex the whole value must match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub examples: Option<Vec<serde_json::Value>>,
+}
+
+fn value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl VariableDeclaration {
+    pub fn is_required(&self) -> bool {
+        self.required.unwrap_or(self.default.is_none())
+    }
+
+    /// Default value as passed to the template
+    pub fn default_text(&self) -> Option<String> {
+        self.default.as_ref().map(value_text)
+    }
+
+    /// Check a value against the declared type and constraints
+    pub fn validate_value(&self, value: &str) -> Result<(), String> {
+        match self.var_type {
+            VariableType::Number => {
+                let number: f64 = value.trim().parse().map_err(|_| "must be a number".to_string())?;
+                if let Some(min) = self.min.filter(|min| number < *min) {
+                    return Err(format!("must be at least {}", min));
+                }
+                if let Some(max) = self.max.filter(|max| number > *max) {
+                    return Err(format!("must be at most {}", max));
+                }
+            }
+            VariableType::Enum => {
+                let values = self.values.as_deref().unwrap_or_default();
+                if !values.iter().any(|v| v == value) {
+                    return Err(format!("must be one of: {}", values.join(", ")));
+                }
+            }
+            VariableType::Json => {
+                serde_json::from_str::<serde_json::Value>(value)
+                    .map_err(|e| format!("must be valid JSON ({})", e))?;
+            }
+            VariableType::String | VariableType::Multiline | VariableType::File => {}
+        }
+
+        let length = value.chars().count();
+        if let Some(min_length) = self.min_length.filter(|min| length < *min) {
+            return Err(format!("must be at least {} characters", min_length));
+        }
+        if let Some(max_length) = self.max_length.filter(|max| length > *max) {
+            return Err(format!("must be at most {} characters", max_length));
+        }
+        if let Some(pattern) = &self.pattern {
+            let regex = regex::Regex::new(&format!("^(?:{})$", pattern))
+                .map_err(|e| format!("has an invalid pattern: {}", e))?;
+            if !regex.is_match(value) {
+                return Err(format!("must match the pattern {}", pattern));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Problems with the declaration itself
+    pub fn check(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.var_type == VariableType::Enum && self.values.as_ref().is_none_or(|v| v.is_empty()) {
+            problems.push(format!("'{}': enum variables need a list of values", self.name));
+        }
+        if let Some(pattern) = &self.pattern {
+            if let Err(e) = regex::Regex::new(pattern) {
+                problems.push(format!("'{}': invalid pattern: {}", self.name, e));
+            }
+        }
+        if let Some(default) = self.default_text() {
+            if let Err(e) = self.validate_value(&default) {
+                problems.push(format!("'{}': default value {}", self.name, e));
+            }
+        }
+
+        problems
+    }
+}
+
+/// Deserialize declarations from a list (`- name: tone`) or a map keyed by name
+/// Map values may be a full declaration, a type name (`notes: multiline`) or empty
+pub fn deserialize_declarations<'de, D>(deserializer: D) -> Result<Vec<VariableDeclaration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Repr {
+        List(Vec<VariableDeclaration>),
+        Map(serde_yaml::Mapping),
+    }
+
+    let map = match Option::<Repr>::deserialize(deserializer)? {
+        None => return Ok(Vec::new()),
+        Some(Repr::List(list)) => return Ok(list),
+        Some(Repr::Map(map)) => map,
+    };
+
+    let mut declarations = Vec::new();
+    for (key, value) in map {
+        let name = key
+            .as_str()
+            .ok_or_else(|| serde::de::Error::custom("variable names must be strings"))?
+            .to_string();
+        let mut declaration = match value {
+            serde_yaml::Value::Null => VariableDeclaration::default(),
+            serde_yaml::Value::String(type_name) => VariableDeclaration {
+                var_type: serde_yaml::from_value(serde_yaml::Value::String(type_name))
+                    .map_err(|e| serde::de::Error::custom(format!("variable '{}': {}", name, e)))?,
+                ..Default::default()
+            },
+            value => serde_yaml::from_value(value)
+                .map_err(|e| serde::de::Error::custom(format!("variable '{}': {}", name, e)))?,
+        };
+        declaration.name = name;
+        declarations.push(declaration);
+    }
+    Ok(declarations)
+}
+
+/// Serialize declarations as a map keyed by name (the form used in frontmatter)
+pub fn serialize_declarations<S>(declarations: &[VariableDeclaration], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_map(declarations.iter().map(|declaration| {
+        let mut body = declaration.clone();
+        body.name.clear();
+        (declaration.name.clone(), body)
+    }))
+}
+
+/// Declarations of a `## Variables` section: YAML, optionally in a code fence
+pub fn parse_variables_section(body: &str) -> Result<Vec<VariableDeclaration>, String> {
+    #[derive(Deserialize)]
+    struct Section(#[serde(deserialize_with = "deserialize_declarations")] Vec<VariableDeclaration>);
+
+    let mut in_fence = false;
+    let mut in_section = false;
+    let mut yaml = Vec::new();
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if !in_fence && (trimmed.starts_with("# ") || trimmed.starts_with("## ")) {
+            if in_section {
+                break;
+            }
+            in_section = trimmed.starts_with("## ")
+                && trimmed.trim_start_matches('#').trim().eq_ignore_ascii_case("variables");
+            continue;
+        }
+        if in_section {
+            yaml.push(line);
+        }
+    }
+
+    let yaml = yaml.join("\n");
+    if yaml.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_yaml::from_str::<Section>(&yaml)
+        .map(|section| section.0)
+        .map_err(|e| format!("Invalid ## Variables section: {}", e))
+}
+
+/// All declarations of a .vibe.md file: frontmatter first, then the `## Variables` section
+/// (the frontmatter wins when a variable is declared in both)
+pub fn declared_variables(content: &str) -> Result<Vec<VariableDeclaration>, String> {
+    let (frontmatter, body) = crate::models::frontmatter::parse_frontmatter(content)?;
+    let mut declarations = frontmatter.map(|f| f.variables).unwrap_or_default();
+
+    for declaration in parse_variables_section(body)? {
+        if !declarations.iter().any(|d| d.name == declaration.name) {
+            declarations.push(declaration);
+        }
+    }
+    Ok(declarations)
+}
+
+/// Apply declared defaults and check inputs against the declarations
+/// Undeclared variables are passed through as-is
+pub fn resolve_inputs(
+    declarations: &[VariableDeclaration],
+    variables: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    let mut resolved = variables.clone();
+    let mut problems = Vec::new();
+
+    for declaration in declarations {
+        let value = variables
+            .get(&declaration.name)
+            .filter(|v| !v.is_empty())
+            .cloned()
+            .or_else(|| declaration.default_text());
+
+        match value {
+            Some(value) => {
+                if let Err(e) = declaration.validate_value(&value) {
+                    problems.push(format!("{} {}", declaration.name, e));
+                }
+                resolved.insert(declaration.name.clone(), value);
+            }
+            None if declaration.is_required() => problems.push(format!("{} is required", declaration.name)),
+            None => {}
+        }
+    }
+
+    if !problems.is_empty() {
+        return Err(format!("Invalid variables: {}", problems.join("; ")));
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "---\nvariables:\n  tone:\n    type: enum\n    values: [friendly, formal]\n    default: friendly\n  count:\n    type: number\n    min: 1\n---\n## User Message\n{{tone}} {{count}} {{notes}}\n\n## Variables\n```yaml\nnotes: multiline\ntone: string\n```\n";
+
+    #[test]
+    fn test_declared_variables() {
+        let declarations = declared_variables(DOC).unwrap();
+        let names: Vec<&str> = declarations.iter().map(|d| d.name.as_str()).collect();
+
+        assert_eq!(names, vec!["tone", "count", "notes"]);
+        assert_eq!(declarations[0].var_type, VariableType::Enum);
+        assert!(!declarations[0].is_required());
+        assert_eq!(declarations[2].var_type, VariableType::Multiline);
+    }
+
+    #[test]
+    fn test_resolve_inputs() {
+        let declarations = declared_variables(DOC).unwrap();
+        let mut inputs = HashMap::new();
+        inputs.insert("count".to_string(), "3".to_string());
+        inputs.insert("notes".to_string(), "n".to_string());
+
+        let resolved = resolve_inputs(&declarations, &inputs).unwrap();
+        assert_eq!(resolved["tone"], "friendly");
+
+        inputs.insert("tone".to_string(), "rude".to_string());
+        inputs.insert("count".to_string(), "0".to_string());
+        let err = resolve_inputs(&declarations, &inputs).unwrap_err();
+        assert!(err.contains("tone must be one of: friendly, formal"), "{}", err);
+        assert!(err.contains("count must be at least 1"), "{}", err);
+
+        inputs.remove("count");
+        assert!(resolve_inputs(&declarations, &inputs).unwrap_err().contains("count is required"));
+    }
+}
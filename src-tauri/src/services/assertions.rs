@@ -0,0 +1,402 @@
+use crate::models::assertions::{Assertion, AssertionOutcome};
+use crate::models::execution::ExecutionResult;
+use crate::services::datasets::{DatasetRow, ASSERTIONS_COLUMN};
+use crate::services::json_output::{json_path, parse_json_output};
+use regex::Regex;
+use serde_json::Value;
+
+/// evaluation_results.evaluator_type of assertion outcomes
+pub const ASSERTION_EVALUATOR: &str = "assertion";
+
+/// Characters of the output quoted in failure reasons
+const PREVIEW_CHARS: usize = 80;
+
+/// Assertions of a dataset row: a list (JSONL, YAML) or JSON text (CSV) in the `assertions` column
+pub fn row_assertions(row: &DatasetRow) -> Result<Vec<Assertion>, String> {
+    let value = match row.get(ASSERTIONS_COLUMN) {
+        None | Some(Value::Null) => return Ok(Vec::new()),
+        Some(Value::String(text)) if text.trim().is_empty() => return Ok(Vec::new()),
+        Some(Value::String(text)) => {
+            serde_json::from_str(text).map_err(|e| format!("Invalid assertions JSON: {}", e))?
+        }
+        Some(value) => value.clone(),
+    };
+    serde_json::from_value(value).map_err(|e| format!("Invalid assertions: {}", e))
+}
+
+/// Check every assertion against a finished execution
+pub fn evaluate(assertions: &[Assertion], result: &ExecutionResult) -> Vec<AssertionOutcome> {
+    assertions.iter().map(|assertion| check(assertion, result)).collect()
+}
+
+pub fn check(assertion: &Assertion, result: &ExecutionResult) -> AssertionOutcome {
+    let (passed, reason) = match run_check(assertion, result) {
+        Ok(reason) => (true, reason),
+        Err(reason) => (false, reason),
+    };
+    AssertionOutcome { name: assertion.label(), passed, reason }
+}
+
+/// Ok(why it passed) or Err(why it failed)
+fn run_check(assertion: &Assertion, result: &ExecutionResult) -> Result<String, String> {
+    let output = result.output.as_str();
+    match assertion {
+        Assertion::Equals { value } => expect(
+            output.trim() == value.trim(),
+            "Output matches".to_string(),
+            format!("Expected {:?}, got {:?}", value, preview(output)),
+        ),
+        Assertion::Contains { value } => expect(
+            output.contains(value.as_str()),
+            format!("Output contains {:?}", value),
+            format!("Output does not contain {:?}", value),
+        ),
+        Assertion::NotContains { value } => expect(
+            !output.contains(value.as_str()),
+            format!("Output does not contain {:?}", value),
+            format!("Output contains {:?}", value),
+        ),
+        Assertion::StartsWith { value } => expect(
+            output.trim_start().starts_with(value.as_str()),
+            format!("Output starts with {:?}", value),
+            format!("Output starts with {:?}", preview(output.trim_start())),
+        ),
+        Assertion::Regex { pattern } => {
+            let regex = Regex::new(pattern).map_err(|e| format!("Invalid regex: {}", e))?;
+            expect(
+                regex.is_match(output),
+                format!("Output matches /{}/", pattern),
+                format!("Output does not match /{}/", pattern),
+            )
+        }
+        Assertion::JsonValid => parse_json_output(output).map(|_| "Output is valid JSON".to_string()),
+        Assertion::JsonSchema { schema } => {
+            let json = parse_json_output(output)?;
+            validate_schema(&json, schema, "$")?;
+            Ok("Output matches the schema".to_string())
+        }
+        Assertion::JsonPathEquals { path, value } => {
+            let json = parse_json_output(output)?;
+            match json_path(&json, path)? {
+                Some(found) if values_equal(found, value) => Ok(format!("{} is {}", path, value)),
+                Some(found) => Err(format!("{} is {}, expected {}", path, found, value)),
+                None => Err(format!("{} not found in the output", path)),
+            }
+        }
+        Assertion::MaxLength { value } => {
+            let length = output.chars().count();
+            expect(
+                length <= *value,
+                format!("{} characters", length),
+                format!("{} characters, limit {}", length, value),
+            )
+        }
+        Assertion::MaxLatencyMs { value } => {
+            let latency = result.metadata.latency_ms;
+            expect(
+                latency <= *value,
+                format!("{} ms", latency),
+                format!("{} ms, limit {} ms", latency, value),
+            )
+        }
+        Assertion::MaxCostUsd { value } => {
+            let cost = result.metadata.cost_usd;
+            expect(
+                cost <= *value,
+                format!("${:.6}", cost),
+                format!("${:.6}, limit ${:.6}", cost, value),
+            )
+        }
+    }
+}
+
+fn expect(condition: bool, passed: String, failed: String) -> Result<String, String> {
+    if condition {
+        Ok(passed)
+    } else {
+        Err(failed)
+    }
+}
+
+fn preview(text: &str) -> String {
+    if text.chars().count() <= PREVIEW_CHARS {
+        return text.to_string();
+    }
+    format!("{}…", text.chars().take(PREVIEW_CHARS).collect::<String>())
+}
+
+/// Numbers compare by value (1 == 1.0)
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(x), Some(y)) if a.is_number() && b.is_number() => x == y,
+        _ => a == b,
+    }
+}
+
+/// Keywords validate_schema checks
+const SCHEMA_KEYWORDS: &[&str] = &[
+    "type", "enum", "const", "properties", "required", "additionalProperties",
+    "items", "minItems", "maxItems", "minLength", "maxLength", "minimum", "maximum",
+];
+
+/// Keywords that don't constrain values
+const SCHEMA_ANNOTATIONS: &[&str] = &[
+    "$schema", "$id", "$comment", "title", "description", "default", "examples", "deprecated", "readOnly", "writeOnly",
+];
+
+/// Check a value against a JSON Schema, returning the first violation
+/// Schemas using other keywords than SCHEMA_KEYWORDS ($ref, oneOf, pattern, format...) are rejected
+/// rather than partially checked
+pub fn validate_schema(value: &Value, schema: &Value, at: &str) -> Result<(), String> {
+    check_keywords(schema, "schema")?;
+    validate(value, schema, at)
+}
+
+fn check_keywords(schema: &Value, at: &str) -> Result<(), String> {
+    let schema = match schema {
+        Value::Object(schema) => schema,
+        _ => return Ok(()),
+    };
+
+    for (keyword, value) in schema {
+        if SCHEMA_ANNOTATIONS.contains(&keyword.as_str()) {
+            continue;
+        }
+        if !SCHEMA_KEYWORDS.contains(&keyword.as_str()) {
+            return Err(format!(
+                "Unsupported JSON Schema keyword '{}' in {} (supported: {})",
+                keyword,
+                at,
+                SCHEMA_KEYWORDS.join(", ")
+            ));
+        }
+        match (keyword.as_str(), value) {
+            ("properties", Value::Object(properties)) => {
+                for (name, property) in properties {
+                    check_keywords(property, &format!("{}.properties.{}", at, name))?;
+                }
+            }
+            ("items" | "additionalProperties", subschema) => check_keywords(subschema, &format!("{}.{}", at, keyword))?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn validate(value: &Value, schema: &Value, at: &str) -> Result<(), String> {
+    let schema = match schema {
+        Value::Bool(true) => return Ok(()),
+        Value::Bool(false) => return Err(format!("{}: not allowed", at)),
+        Value::Object(schema) => schema,
+        _ => return Err("Invalid schema: expected an object".to_string()),
+    };
+
+    if let Some(expected) = schema.get("type") {
+        let types: Vec<&str> = match expected {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.is_empty() && !types.iter().any(|name| has_type(value, name)) {
+            return Err(format!("{}: expected {}, found {}", at, types.join(" or "), type_name(value)));
+        }
+    }
+    if let Some(Value::Array(allowed)) = schema.get("enum") {
+        if !allowed.iter().any(|candidate| values_equal(candidate, value)) {
+            return Err(format!("{}: {} is not one of the allowed values", at, value));
+        }
+    }
+    if let Some(expected) = schema.get("const") {
+        if !values_equal(expected, value) {
+            return Err(format!("{}: expected {}, found {}", at, expected, value));
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            if let Some(Value::Array(required)) = schema.get("required") {
+                if let Some(missing) = required.iter().filter_map(Value::as_str).find(|key| !object.contains_key(*key)) {
+                    return Err(format!("{}: missing property '{}'", at, missing));
+                }
+            }
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for (key, item) in object {
+                let path = format!("{}.{}", at, key);
+                match (properties.and_then(|p| p.get(key)), schema.get("additionalProperties")) {
+                    (Some(property_schema), _) => validate(item, property_schema, &path)?,
+                    (None, Some(Value::Bool(false))) => return Err(format!("{}: unexpected property", path)),
+                    (None, Some(additional @ Value::Object(_))) => validate(item, additional, &path)?,
+                    (None, _) => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            check_bound(items.len() as f64, schema, "minItems", "maxItems", at, "items")?;
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate(item, item_schema, &format!("{}[{}]", at, index))?;
+                }
+            }
+        }
+        Value::String(text) => {
+            check_bound(text.chars().count() as f64, schema, "minLength", "maxLength", at, "characters")?;
+        }
+        Value::Number(number) => {
+            if let Some(number) = number.as_f64() {
+                check_bound(number, schema, "minimum", "maximum", at, "")?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn check_bound(actual: f64, schema: &serde_json::Map<String, Value>, min: &str, max: &str, at: &str, unit: &str) -> Result<(), String> {
+    let unit = if unit.is_empty() { String::new() } else { format!(" {}", unit) };
+    if let Some(limit) = schema.get(min).and_then(Value::as_f64) {
+        if actual < limit {
+            return Err(format!("{}: {}{} is below the {} of {}", at, actual, unit, min, limit));
+        }
+    }
+    if let Some(limit) = schema.get(max).and_then(Value::as_f64) {
+        if actual > limit {
+            return Err(format!("{}: {}{} is above the {} of {}", at, actual, unit, max, limit));
+        }
+    }
+    Ok(())
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "integer" => value.as_f64().is_some_and(|n| n.fract() == 0.0),
+        "number" => value.is_number(),
+        other => type_name(value) == other,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::execution::ExecutionMetadata;
+    use serde_json::json;
+
+    fn result(output: &str) -> ExecutionResult {
+        ExecutionResult {
+            id: "exec-1".to_string(),
+            output: output.to_string(),
+            metadata: ExecutionMetadata {
+                model: "gpt-4o".to_string(),
+                provider: "OpenAI".to_string(),
+                latency_ms: 1200,
+                tokens_input: 10,
+                tokens_output: 20,
+                cost_usd: 0.002,
+                pricing_version: None,
+                pricing_id: None,
+                timestamp: 0,
+            },
+            budget_warnings: Vec::new(),
+            assertions: Vec::new(),
+        }
+    }
+
+    fn passes(assertion: Assertion, output: &str) -> bool {
+        check(&assertion, &result(output)).passed
+    }
+
+    #[test]
+    fn test_text_assertions() {
+        let value = |v: &str| v.to_string();
+        assert!(passes(Assertion::Equals { value: value("Paris") }, "Paris\n"));
+        assert!(!passes(Assertion::Equals { value: value("Paris") }, "paris"));
+        assert!(passes(Assertion::Contains { value: value("refund") }, "We will refund you"));
+        assert!(!passes(Assertion::NotContains { value: value("sorry") }, "I'm sorry"));
+        assert!(passes(Assertion::StartsWith { value: value("Dear") }, "  Dear customer"));
+        assert!(passes(Assertion::Regex { pattern: value(r"\d{3}-\d{4}") }, "Call 555-1234"));
+        assert!(!passes(Assertion::MaxLength { value: 5 }, "héllo!"));
+        assert!(passes(Assertion::MaxLatencyMs { value: 2000 }, ""));
+        assert!(!passes(Assertion::MaxCostUsd { value: 0.001 }, ""));
+
+        let invalid = check(&Assertion::Regex { pattern: value("(") }, &result("x"));
+        assert!(!invalid.passed);
+        assert!(invalid.reason.starts_with("Invalid regex"));
+    }
+
+    #[test]
+    fn test_json_assertions() {
+        let output = "```json\n{\"status\": \"ok\", \"items\": [{\"id\": 1}], \"a key\": true}\n```";
+        assert!(passes(Assertion::JsonValid, output));
+        assert!(!passes(Assertion::JsonValid, "{status: ok}"));
+
+        let path = |path: &str, value: Value| Assertion::JsonPathEquals { path: path.to_string(), value };
+        assert!(passes(path("$.status", json!("ok")), output));
+        assert!(passes(path("$.items[0].id", json!(1.0)), output));
+        assert!(passes(path("$[\"a key\"]", json!(true)), output));
+        assert!(!passes(path("$.items[1].id", json!(1)), output));
+
+        let schema = json!({
+            "type": "object",
+            "required": ["status", "items"],
+            "properties": {
+                "status": {"enum": ["ok", "error"]},
+                "items": {"type": "array", "minItems": 1, "items": {"type": "object", "required": ["id"]}}
+            }
+        });
+        assert!(passes(Assertion::JsonSchema { schema: schema.clone() }, output));
+        let failed = check(&Assertion::JsonSchema { schema }, &result("{\"status\": \"ok\", \"items\": [{}]}"));
+        assert!(!failed.passed);
+        assert_eq!(failed.reason, "$.items[0]: missing property 'id'");
+
+        // Keywords that aren't checked fail the assertion instead of passing everything
+        for unsupported in [
+            json!({"$ref": "#/definitions/reply"}),
+            json!({"oneOf": [{"type": "string"}, {"type": "number"}]}),
+            json!({"type": "object", "properties": {"status": {"type": "string", "pattern": "^ok$"}}}),
+            json!({"type": "array", "items": {"type": "string", "format": "email"}}),
+        ] {
+            let failed = check(&Assertion::JsonSchema { schema: unsupported }, &result(output));
+            assert!(!failed.passed);
+            assert!(failed.reason.starts_with("Unsupported JSON Schema keyword"), "{}", failed.reason);
+        }
+        let annotated = json!({"$schema": "http://json-schema.org/draft-07/schema#", "title": "Reply", "type": "object"});
+        assert!(passes(Assertion::JsonSchema { schema: annotated }, output));
+    }
+
+    #[test]
+    fn test_row_assertions() {
+        let row: DatasetRow = serde_json::from_value(json!({
+            "topic": "Rust",
+            "assertions": "[{\"type\": \"contains\", \"value\": \"Rust\"}, {\"type\": \"json_valid\"}]"
+        }))
+        .unwrap();
+        assert_eq!(row_assertions(&row).unwrap().len(), 2);
+
+        let row: DatasetRow = serde_json::from_value(json!({"assertions": [{"type": "max_length", "value": 10}]})).unwrap();
+        assert_eq!(row_assertions(&row).unwrap(), vec![Assertion::MaxLength { value: 10 }]);
+
+        let row: DatasetRow = serde_json::from_value(json!({"assertions": [{"type": "unknown"}]})).unwrap();
+        assert!(row_assertions(&row).is_err());
+        assert!(row_assertions(&DatasetRow::new()).unwrap().is_empty());
+
+        let document = "---\nassertions:\n  - type: json_path_equals\n    path: $.status\n    value: ok\n  - type: json_valid\n---\nHi\n";
+        let (frontmatter, _) = crate::models::frontmatter::parse_frontmatter(document).unwrap();
+        assert_eq!(
+            frontmatter.unwrap().assertions,
+            vec![
+                Assertion::JsonPathEquals { path: "$.status".to_string(), value: json!("ok") },
+                Assertion::JsonValid,
+            ]
+        );
+    }
+}
@@ -0,0 +1,120 @@
+use crate::services::database::{AppDatabase, SpendingBudget};
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+
+/// Current state of a spending budget
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub budget: SpendingBudget,
+    pub spent_usd: f64,
+    pub remaining_usd: Option<f64>,  // Remaining before the hard limit (or soft limit if no hard limit)
+    pub period_start: i64,
+    pub period_end: i64,             // When the period rolls over
+    pub soft_exceeded: bool,
+    pub hard_exceeded: bool,
+}
+
+/// Get the [start, end) timestamps of the current budget period in local time
+pub fn period_bounds(period: &str) -> Result<(i64, i64), String> {
+    let today = Local::now().date_naive();
+
+    let (start, end) = match period {
+        "daily" => (today, today + Duration::days(1)),
+        "monthly" => {
+            let start = today.with_day(1).ok_or("Invalid date")?;
+            let end = if start.month() == 12 {
+                NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+            } else {
+                NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+            }
+            .ok_or("Invalid date")?;
+            (start, end)
+        }
+        _ => return Err(format!("Invalid budget period: {}. Must be 'daily' or 'monthly'", period)),
+    };
+
+    Ok((local_midnight(start)?, local_midnight(end)?))
+}
+
+fn local_midnight(date: NaiveDate) -> Result<i64, String> {
+    let midnight = date.and_hms_opt(0, 0, 0).ok_or("Invalid date")?;
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|dt| dt.timestamp())
+        .ok_or_else(|| "Invalid local time".to_string())
+}
+
+/// Compute the status of a single budget from the spending ledger
+pub fn budget_status(db: &AppDatabase, budget: &SpendingBudget) -> Result<BudgetStatus, String> {
+    let (period_start, period_end) = period_bounds(&budget.period)?;
+
+    let spent_usd = db
+        .get_spending_since(&budget.scope, &budget.scope_ref, period_start)
+        .map_err(|e| format!("Failed to compute spending: {}", e))?;
+
+    let limit = budget.hard_limit_usd.or(budget.soft_limit_usd);
+
+    Ok(BudgetStatus {
+        budget: budget.clone(),
+        spent_usd,
+        remaining_usd: limit.map(|l| (l - spent_usd).max(0.0)),
+        period_start,
+        period_end,
+        soft_exceeded: budget.soft_limit_usd.is_some_and(|l| spent_usd >= l),
+        hard_exceeded: budget.hard_limit_usd.is_some_and(|l| spent_usd >= l),
+    })
+}
+
+/// Compute the status of every enabled budget that applies to an execution
+pub fn applicable_budgets(
+    db: &AppDatabase,
+    workspace_path: Option<&str>,
+    provider_name: Option<&str>,
+) -> Result<Vec<BudgetStatus>, String> {
+    let mut scopes = Vec::new();
+    if let Some(ws) = workspace_path {
+        scopes.push(("workspace", ws));
+    }
+    if let Some(name) = provider_name {
+        scopes.push(("provider", name));
+    }
+
+    let mut statuses = Vec::new();
+    for (scope, scope_ref) in scopes {
+        let budgets = db
+            .get_spending_budgets(scope, scope_ref)
+            .map_err(|e| format!("Failed to load budgets: {}", e))?;
+
+        for budget in &budgets {
+            statuses.push(budget_status(db, budget)?);
+        }
+    }
+
+    Ok(statuses)
+}
+
+/// Human readable description of a budget, e.g. "monthly provider budget for openrouter"
+pub fn describe(budget: &SpendingBudget) -> String {
+    format!("{} {} budget for {}", budget.period, budget.scope, budget.scope_ref)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_period_bounds() {
+        let now = Local::now().timestamp();
+
+        let (start, end) = period_bounds("daily").unwrap();
+        assert!(start <= now && now < end);
+        assert!(end - start >= 23 * 3600 && end - start <= 25 * 3600);
+
+        let (start, end) = period_bounds("monthly").unwrap();
+        assert!(start <= now && now < end);
+        assert!(end - start >= 28 * 86400 - 3600 && end - start <= 31 * 86400 + 3600);
+
+        assert!(period_bounds("weekly").is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use super::{content_text, declarations_note, ignored_keys, json_parameters, new_prompt, parse_role, template_notes, Conversion};
+use crate::models::prompt::{Message, MessageRole, PromptRuntime, Provider};
+use serde_json::json;
+
+/// max_tokens is required by the Messages API
+const DEFAULT_MAX_TOKENS: u32 = 1024;
+
+/// Messages API request as exported by the Workbench ("Get code" → JSON)
+/// Workbench variables use the same `{{name}}` syntax, so contents are kept as-is
+pub fn export(prompt: &PromptRuntime) -> Result<Conversion<String>, String> {
+    let mut lossy = template_notes(prompt, "the Anthropic Workbench");
+    lossy.extend(declarations_note(prompt, "the Anthropic Workbench"));
+
+    let parameters = prompt.config.parameters.as_ref();
+    let max_tokens = match parameters.and_then(|p| p.max_tokens) {
+        Some(max_tokens) => max_tokens,
+        None => {
+            lossy.push(format!("max_tokens is required by Anthropic: set to {}", DEFAULT_MAX_TOKENS));
+            DEFAULT_MAX_TOKENS
+        }
+    };
+
+    let mut system = Vec::new();
+    let mut messages = Vec::new();
+    for message in &prompt.messages {
+        let role = match message.role {
+            MessageRole::System => {
+                system.push(message.content.as_str());
+                continue;
+            }
+            MessageRole::User => "user",
+            MessageRole::Assistant => "assistant",
+        };
+        messages.push(json!({
+            "role": role,
+            "content": [{ "type": "text", "text": message.content }],
+        }));
+    }
+    if system.len() > 1 {
+        lossy.push("System messages were merged into one system prompt".to_string());
+    }
+
+    let mut body = json!({ "model": prompt.config.model, "max_tokens": max_tokens });
+    if let Some(temperature) = parameters.and_then(|p| p.temperature) {
+        body["temperature"] = json!(temperature);
+    }
+    if let Some(top_p) = parameters.and_then(|p| p.top_p) {
+        body["top_p"] = json!(top_p);
+    }
+    if !system.is_empty() {
+        body["system"] = json!(system.join("\n\n"));
+    }
+    body["messages"] = json!(messages);
+
+    let value = serde_json::to_string_pretty(&body).map_err(|e| format!("Failed to serialize request: {}", e))?;
+    Ok(Conversion { value, lossy })
+}
+
+pub fn import(content: &str, name: &str) -> Result<Conversion<PromptRuntime>, String> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    if !value.is_object() {
+        return Err("Expected an Anthropic request object".to_string());
+    }
+
+    let mut lossy = Vec::new();
+    ignored_keys(&value, &["model", "max_tokens", "temperature", "top_p", "system", "messages"], &mut lossy);
+
+    let mut messages = Vec::new();
+    if let Some(system) = value.get("system") {
+        let text = content_text(system, &mut lossy);
+        if !text.is_empty() {
+            messages.push(Message { role: MessageRole::System, content: text });
+        }
+    }
+
+    let items = value
+        .get("messages")
+        .and_then(|m| m.as_array())
+        .ok_or("Missing 'messages' array")?;
+    for item in items {
+        let role_text = item.get("role").and_then(|r| r.as_str()).unwrap_or_default();
+        match parse_role(role_text) {
+            Some(role) => {
+                let content = content_text(item.get("content").unwrap_or(&serde_json::Value::Null), &mut lossy);
+                messages.push(Message { role, content });
+            }
+            None => lossy.push(format!("Dropped a '{}' message", role_text)),
+        }
+    }
+
+    if messages.is_empty() {
+        return Err("No messages to import".to_string());
+    }
+
+    let model = value.get("model").and_then(|m| m.as_str()).unwrap_or_default().to_string();
+    let parameters = json_parameters(&value, &["max_tokens"]);
+    Ok(Conversion {
+        value: new_prompt(name, Provider::Anthropic, model, parameters, messages),
+        lossy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::prompt::ModelParameters;
+
+    fn summary(messages: &[Message]) -> Vec<(&'static str, &str)> {
+        messages.iter().map(|m| (super::super::role_name(&m.role), m.content.as_str())).collect()
+    }
+
+    fn parameters(prompt: &PromptRuntime) -> serde_json::Value {
+        serde_json::to_value(&prompt.config.parameters).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_request() {
+        let prompt = new_prompt(
+            "support",
+            Provider::Anthropic,
+            "claude-sonnet-4-5".to_string(),
+            Some(ModelParameters { temperature: Some(0.2), top_p: None, max_tokens: Some(500) }),
+            vec![
+                Message { role: MessageRole::System, content: "Be brief.".to_string() },
+                Message { role: MessageRole::User, content: "{{question}}".to_string() },
+                Message { role: MessageRole::Assistant, content: "Sure.".to_string() },
+            ],
+        );
+
+        let exported = export(&prompt).unwrap();
+        assert!(exported.lossy.is_empty());
+        let imported = import(&exported.value, "support").unwrap();
+
+        assert!(imported.lossy.is_empty(), "{:?}", imported.lossy);
+        assert_eq!(imported.value.config.provider.as_str(), Provider::Anthropic.as_str());
+        assert_eq!(imported.value.config.model, "claude-sonnet-4-5");
+        assert_eq!(parameters(&imported.value), parameters(&prompt));
+        assert_eq!(summary(&imported.value.messages), summary(&prompt.messages));
+    }
+
+    #[test]
+    fn test_export_merges_system_messages() {
+        let prompt = new_prompt(
+            "support",
+            Provider::Anthropic,
+            "claude-sonnet-4-5".to_string(),
+            None,
+            vec![
+                Message { role: MessageRole::System, content: "One.".to_string() },
+                Message { role: MessageRole::System, content: "Two.".to_string() },
+                Message { role: MessageRole::User, content: "Hi".to_string() },
+            ],
+        );
+
+        let exported = export(&prompt).unwrap();
+        assert_eq!(exported.lossy.len(), 2);
+        let imported = import(&exported.value, "support").unwrap();
+        assert_eq!(imported.value.messages[0].content, "One.\n\nTwo.");
+        assert_eq!(imported.value.config.parameters.unwrap().max_tokens, Some(DEFAULT_MAX_TOKENS));
+    }
+}
@@ -0,0 +1,249 @@
+use super::{content_text, new_prompt, parse_role, template_notes, Conversion};
+use crate::models::prompt::{Message, MessageRole, PromptRuntime, Provider};
+use crate::models::variables::VariableDeclaration;
+use regex::Regex;
+use serde_json::json;
+
+fn constructor(id: &[&str], kwargs: serde_json::Value) -> serde_json::Value {
+    json!({ "lc": 1, "type": "constructor", "id": id, "kwargs": kwargs })
+}
+
+/// Plain `{{name}}` variables, the only template tags f-strings can express
+fn variable_regex() -> Regex {
+    Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap()
+}
+
+/// `{{name}}` → `{name}`, every other brace doubled
+/// Returns false when template tags other than plain variables had to be escaped
+fn to_f_string(content: &str, variable: &Regex) -> (String, bool) {
+    let escape = |text: &str| text.replace('{', "{{").replace('}', "}}");
+
+    let mut result = String::new();
+    let mut last = 0;
+    for captures in variable.captures_iter(content) {
+        let whole = captures.get(0).unwrap();
+        result.push_str(&escape(&content[last..whole.start()]));
+        result.push('{');
+        result.push_str(&captures[1]);
+        result.push('}');
+        last = whole.end();
+    }
+    let rest = &content[last..];
+    result.push_str(&escape(rest));
+
+    let clean = !variable.replace_all(content, "").contains("{{");
+    (result, clean)
+}
+
+/// `{name}` → `{{name}}`, `{{` / `}}` → `{` / `}`
+fn from_f_string(template: &str) -> String {
+    let mut result = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                result.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                result.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                for next in chars.by_ref() {
+                    if next == '}' {
+                        break;
+                    }
+                    name.push(next);
+                }
+                result.push_str(&format!("{{{{{}}}}}", name.trim()));
+            }
+            other => result.push(other),
+        }
+    }
+    result
+}
+
+/// ChatPromptTemplate serialized with `langchain_core.load.dumpd` (f-string templates)
+/// Load it with `langchain_core.load.load(json.load(f))`
+pub fn export(prompt: &PromptRuntime) -> Result<Conversion<String>, String> {
+    let mut lossy = template_notes(prompt, "LangChain f-strings");
+    let defaults: Vec<&VariableDeclaration> = prompt.variables.iter().filter(|d| d.default.is_some()).collect();
+    if !prompt.variables.is_empty() {
+        lossy.push("Variable types and constraints are not exported to LangChain (defaults become partial variables)".to_string());
+    }
+    if !prompt.config.model.is_empty() || prompt.config.parameters.is_some() {
+        lossy.push("Model and parameters belong to the LangChain chat model: not exported".to_string());
+    }
+
+    let variable = variable_regex();
+    let mut input_variables: Vec<String> = Vec::new();
+    let mut messages = Vec::new();
+    for message in &prompt.messages {
+        let (template, clean) = to_f_string(&message.content, &variable);
+        if !clean {
+            lossy.push("Template tags other than {{variable}} were escaped as literal text".to_string());
+        }
+        let variables: Vec<String> = variable
+            .captures_iter(&message.content)
+            .map(|c| c[1].to_string())
+            .fold(Vec::new(), |mut names, name| {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+                names
+            });
+        for name in &variables {
+            if !input_variables.contains(name) && !defaults.iter().any(|d| &d.name == name) {
+                input_variables.push(name.clone());
+            }
+        }
+
+        let class = match message.role {
+            MessageRole::System => "SystemMessagePromptTemplate",
+            MessageRole::User => "HumanMessagePromptTemplate",
+            MessageRole::Assistant => "AIMessagePromptTemplate",
+        };
+        let inner = constructor(
+            &["langchain", "prompts", "prompt", "PromptTemplate"],
+            json!({ "input_variables": variables, "template": template, "template_format": "f-string" }),
+        );
+        messages.push(constructor(&["langchain", "prompts", "chat", class], json!({ "prompt": inner })));
+    }
+    lossy.dedup();
+
+    let mut kwargs = json!({ "input_variables": input_variables, "messages": messages });
+    if !defaults.is_empty() {
+        let partials: serde_json::Map<String, serde_json::Value> = defaults
+            .iter()
+            .map(|d| (d.name.clone(), json!(d.default_text().unwrap_or_default())))
+            .collect();
+        kwargs["partial_variables"] = serde_json::Value::Object(partials);
+    }
+
+    let document = constructor(&["langchain", "prompts", "chat", "ChatPromptTemplate"], kwargs);
+    let value = serde_json::to_string_pretty(&document).map_err(|e| format!("Failed to serialize template: {}", e))?;
+    Ok(Conversion { value, lossy })
+}
+
+fn class_name(value: &serde_json::Value) -> &str {
+    value
+        .get("id")
+        .and_then(|id| id.as_array())
+        .and_then(|id| id.last())
+        .and_then(|name| name.as_str())
+        .unwrap_or_default()
+}
+
+/// Template text in VibeBase syntax
+fn import_template(template: &str, format: &str, lossy: &mut Vec<String>) -> String {
+    match format {
+        "mustache" => template.to_string(),
+        "jinja2" => {
+            if template.contains("{%") {
+                lossy.push("Jinja2 statements ({% ... %}) are kept as literal text".to_string());
+            }
+            template.to_string()
+        }
+        _ => from_f_string(template),
+    }
+}
+
+fn import_message(value: &serde_json::Value, lossy: &mut Vec<String>) -> Option<Message> {
+    // Tuple form: ["human", "{question}"]
+    if let Some([role, template]) = value.as_array().map(|v| v.as_slice()) {
+        let role = role.as_str().and_then(parse_role)?;
+        return Some(Message { role, content: from_f_string(template.as_str()?) });
+    }
+
+    let class = class_name(value);
+    let kwargs = value.get("kwargs")?;
+    let role = match class {
+        "SystemMessagePromptTemplate" | "SystemMessage" => MessageRole::System,
+        "HumanMessagePromptTemplate" | "HumanMessage" => MessageRole::User,
+        "AIMessagePromptTemplate" | "AIMessage" => MessageRole::Assistant,
+        "ChatMessagePromptTemplate" | "ChatMessage" => {
+            match kwargs.get("role").and_then(|r| r.as_str()).and_then(parse_role) {
+                Some(role) => role,
+                None => {
+                    lossy.push("Dropped a chat message with an unsupported role".to_string());
+                    return None;
+                }
+            }
+        }
+        "MessagesPlaceholder" => {
+            let name = kwargs.get("variable_name").and_then(|n| n.as_str()).unwrap_or_default();
+            lossy.push(format!("Dropped MessagesPlaceholder '{}'", name));
+            return None;
+        }
+        other => {
+            lossy.push(format!("Dropped unsupported message '{}'", other));
+            return None;
+        }
+    };
+
+    // Plain messages hold their content directly
+    if let Some(content) = kwargs.get("content") {
+        return Some(Message { role, content: content_text(content, lossy) });
+    }
+
+    let prompt = kwargs.get("prompt")?;
+    let prompt_kwargs = prompt.get("kwargs").unwrap_or(prompt);
+    let template = prompt_kwargs.get("template").and_then(|t| t.as_str()).unwrap_or_default();
+    let format = prompt_kwargs
+        .get("template_format")
+        .and_then(|f| f.as_str())
+        .unwrap_or("f-string");
+    Some(Message { role, content: import_template(template, format, lossy) })
+}
+
+pub fn import(content: &str, name: &str) -> Result<Conversion<PromptRuntime>, String> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let class = class_name(&value);
+    if class != "ChatPromptTemplate" {
+        return Err(format!("Expected a serialized ChatPromptTemplate, found '{}'", class));
+    }
+
+    let mut lossy = Vec::new();
+    let kwargs = value.get("kwargs").ok_or("Missing 'kwargs'")?;
+    let items = kwargs
+        .get("messages")
+        .and_then(|m| m.as_array())
+        .ok_or("Missing 'messages' array")?;
+    let messages: Vec<Message> = items.iter().filter_map(|item| import_message(item, &mut lossy)).collect();
+    if messages.is_empty() {
+        return Err("No messages to import".to_string());
+    }
+
+    let mut prompt = new_prompt(name, Provider::OpenAI, String::new(), None, messages);
+
+    // Partial variables become declared defaults
+    if let Some(partials) = kwargs.get("partial_variables").and_then(|p| p.as_object()) {
+        for (variable, default) in partials {
+            prompt.variables.push(VariableDeclaration {
+                name: variable.clone(),
+                default: Some(default.clone()),
+                ..Default::default()
+            });
+        }
+    }
+
+    Ok(Conversion { value: prompt, lossy })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_f_string_conversion() {
+        let (template, clean) = to_f_string("Hi {{ name }}, JSON: {\"a\": 1}", &variable_regex());
+        assert_eq!(template, "Hi {name}, JSON: {{\"a\": 1}}");
+        assert!(clean);
+        assert_eq!(from_f_string(&template), "Hi {{name}}, JSON: {\"a\": 1}");
+
+        let (_, clean) = to_f_string("{{#if name}}x{{/if}}", &variable_regex());
+        assert!(!clean);
+    }
+}
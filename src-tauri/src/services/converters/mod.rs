@@ -0,0 +1,368 @@
+pub mod openai;
+pub mod anthropic;
+pub mod prompty;
+pub mod promptfoo;
+pub mod langchain;
+
+use crate::models::frontmatter::{write_frontmatter, PromptFrontmatter};
+use crate::models::prompt::{Message, MessageRole, ModelConfig, ModelParameters, PromptRuntime, Provider};
+use crate::services::database::LLMProviderConfig;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Formats prompts can be imported from and exported to
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptFormat {
+    /// OpenAI chat completions messages (bare array or request body)
+    OpenAI,
+    /// Anthropic Workbench / Messages API request
+    Anthropic,
+    /// Microsoft Prompty (.prompty)
+    Prompty,
+    /// Promptfoo prompt file (.json chat messages, .yaml messages or prompts, .txt split by `---`)
+    Promptfoo,
+    /// LangChain ChatPromptTemplate, serialized with dumpd()
+    LangChain,
+}
+
+impl PromptFormat {
+    /// Extension used for exported files (after the prompt name)
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PromptFormat::OpenAI => "openai.json",
+            PromptFormat::Anthropic => "anthropic.json",
+            PromptFormat::Prompty => "prompty",
+            PromptFormat::Promptfoo => "promptfoo.json",
+            PromptFormat::LangChain => "langchain.json",
+        }
+    }
+
+    /// Guess the format of a file from its name and content
+    pub fn detect(path: &Path, content: &str) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".prompty") {
+            return Some(PromptFormat::Prompty);
+        }
+        // .vibe.yaml prompts are migrated, not imported (see services::yaml_migration)
+        if name.ends_with(".vibe.yaml") || name.ends_with(".vibe.yml") {
+            return None;
+        }
+        if name.ends_with(".txt") {
+            return promptfoo::is_prompt_list(content).then_some(PromptFormat::Promptfoo);
+        }
+        if name.ends_with(".yaml") || name.ends_with(".yml") {
+            let value: serde_json::Value = serde_yaml::from_str(content).ok()?;
+            return promptfoo::is_structured_prompt(&value).then_some(PromptFormat::Promptfoo);
+        }
+        if !name.ends_with(".json") {
+            return None;
+        }
+
+        let value: serde_json::Value = serde_json::from_str(content).ok()?;
+        if value.get("lc").is_some() || name.ends_with(".langchain.json") {
+            Some(PromptFormat::LangChain)
+        } else if name.ends_with(".promptfoo.json") {
+            Some(PromptFormat::Promptfoo)
+        } else if value.get("system").is_some() || name.ends_with(".anthropic.json") {
+            Some(PromptFormat::Anthropic)
+        } else if value.is_array() || value.get("messages").is_some() {
+            Some(PromptFormat::OpenAI)
+        } else {
+            None
+        }
+    }
+}
+
+/// Converted value with everything that could not be carried over
+#[derive(Debug, Clone)]
+pub struct Conversion<T> {
+    pub value: T,
+    pub lossy: Vec<String>,
+}
+
+pub fn export_prompt(prompt: &PromptRuntime, format: PromptFormat) -> Result<Conversion<String>, String> {
+    let mut conversion = match format {
+        PromptFormat::OpenAI => openai::export(prompt)?,
+        PromptFormat::Anthropic => anthropic::export(prompt)?,
+        PromptFormat::Prompty => prompty::export(prompt)?,
+        PromptFormat::Promptfoo => promptfoo::export(prompt)?,
+        PromptFormat::LangChain => langchain::export(prompt)?,
+    };
+
+    if prompt.evaluation.as_ref().is_some_and(|e| !e.is_empty()) {
+        conversion.lossy.push("Evaluation config is not exported".to_string());
+    }
+    if !prompt.assertions.is_empty() {
+        conversion.lossy.push("Assertions are not exported".to_string());
+    }
+    if prompt.test_data.is_some() {
+        conversion.lossy.push("Test data reference is not exported".to_string());
+    }
+    Ok(conversion)
+}
+
+/// Import a file; some formats hold several prompts
+pub fn import_prompt(content: &str, name: &str, format: PromptFormat) -> Result<Vec<Conversion<PromptRuntime>>, String> {
+    match format {
+        PromptFormat::OpenAI => openai::import(content, name).map(|c| vec![c]),
+        PromptFormat::Anthropic => anthropic::import(content, name).map(|c| vec![c]),
+        PromptFormat::Prompty => prompty::import(content, name).map(|c| vec![c]),
+        PromptFormat::Promptfoo => promptfoo::import(content, name),
+        PromptFormat::LangChain => langchain::import(content, name).map(|c| vec![c]),
+    }
+}
+
+/// Provider config to record for the prompt's provider, which frontmatter can't name directly
+/// None when the default config has that type; formats without a model carry no provider
+fn provider_ref(prompt: &PromptRuntime, providers: &[LLMProviderConfig], lossy: &mut Vec<String>) -> Option<String> {
+    if prompt.config.model.is_empty() {
+        return None;
+    }
+    let provider = prompt.config.provider.as_str();
+    if providers.iter().any(|p| p.is_default && p.provider == provider) {
+        return None;
+    }
+    match providers.iter().find(|p| p.provider == provider && p.enabled) {
+        Some(config) => Some(config.name.clone()),
+        None => {
+            lossy.push(format!("No '{}' provider is configured: the default provider will be used", provider));
+            None
+        }
+    }
+}
+
+/// Write a prompt as a .vibe.md document (config in frontmatter, one H2 section per message)
+/// `providers` are the configured providers, to keep the prompt's provider as a provider_ref
+pub fn to_vibe_markdown(prompt: &PromptRuntime, providers: &[LLMProviderConfig]) -> Result<Conversion<String>, String> {
+    let mut lossy = Vec::new();
+
+    let frontmatter = PromptFrontmatter {
+        provider_ref: provider_ref(prompt, providers, &mut lossy),
+        model: Some(prompt.config.model.clone()).filter(|m| !m.is_empty()),
+        parameters: prompt.config.parameters.clone().filter(|p| {
+            p.temperature.is_some() || p.top_p.is_some() || p.max_tokens.is_some()
+        }),
+        description: prompt.description.clone(),
+        test_data: prompt.test_data.clone(),
+        evaluation: prompt.evaluation.clone(),
+        assertions: prompt.assertions.clone(),
+        variables: prompt.variables.clone(),
+        ..Default::default()
+    };
+
+    let heading = Regex::new(r"^ {0,3}##? ").unwrap();
+    let mut body = format!("# {}\n", prompt.name);
+    for message in &prompt.messages {
+        let title = match message.role {
+            MessageRole::System => "System Message",
+            MessageRole::User => "User Message",
+            MessageRole::Assistant => "Assistant",
+        };
+
+        // H1/H2 lines would start a new section: demote them
+        let mut in_fence = false;
+        let mut demoted = false;
+        let content: Vec<String> = message
+            .content
+            .lines()
+            .map(|line| {
+                if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+                    in_fence = !in_fence;
+                }
+                if !in_fence && heading.is_match(line) {
+                    demoted = true;
+                    format!("#{}", line.trim_start())
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+        if demoted {
+            lossy.push(format!("Headings in the {} were demoted to keep sections apart", title.to_lowercase()));
+        }
+
+        body.push_str(&format!("\n## {}\n\n{}\n", title, content.join("\n")));
+    }
+
+    let value = write_frontmatter(&body, &frontmatter)?;
+    Ok(Conversion { value, lossy })
+}
+
+pub(crate) fn new_prompt(
+    name: &str,
+    provider: Provider,
+    model: String,
+    parameters: Option<ModelParameters>,
+    messages: Vec<Message>,
+) -> PromptRuntime {
+    PromptRuntime {
+        schema: "v1".to_string(),
+        name: name.to_string(),
+        description: None,
+        config: ModelConfig {
+            provider,
+            model,
+            parameters,
+        },
+        test_data: None,
+        messages,
+        evaluation: None,
+        assertions: Vec::new(),
+        variables: Vec::new(),
+    }
+}
+
+pub(crate) fn parse_role(role: &str) -> Option<MessageRole> {
+    match role.to_lowercase().as_str() {
+        "system" | "developer" => Some(MessageRole::System),
+        "user" | "human" => Some(MessageRole::User),
+        "assistant" | "ai" => Some(MessageRole::Assistant),
+        _ => None,
+    }
+}
+
+pub(crate) fn role_name(role: &MessageRole) -> &'static str {
+    match role {
+        MessageRole::System => "system",
+        MessageRole::User => "user",
+        MessageRole::Assistant => "assistant",
+    }
+}
+
+/// Text of a message content: a string or a list of content parts
+/// Non-text parts (images, tool calls, ...) are reported as lossy
+pub(crate) fn content_text(content: &serde_json::Value, lossy: &mut Vec<String>) -> String {
+    match content {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Array(parts) => {
+            let mut texts = Vec::new();
+            for part in parts {
+                match part.get("text").and_then(|t| t.as_str()) {
+                    Some(text) => texts.push(text.to_string()),
+                    None => {
+                        let kind = part.get("type").and_then(|t| t.as_str()).unwrap_or("unknown");
+                        lossy.push(format!("Dropped a '{}' content part (only text is imported)", kind));
+                    }
+                }
+            }
+            texts.join("\n\n")
+        }
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Read temperature / top_p / max_tokens from a JSON object
+pub(crate) fn json_parameters(value: &serde_json::Value, max_tokens_keys: &[&str]) -> Option<ModelParameters> {
+    let number = |key: &str| value.get(key).and_then(|v| v.as_f64());
+    let parameters = ModelParameters {
+        temperature: number("temperature").map(|t| t as f32),
+        top_p: number("top_p").map(|t| t as f32),
+        max_tokens: max_tokens_keys.iter().find_map(|key| number(key)).map(|t| t as u32),
+    };
+    (parameters.temperature.is_some() || parameters.top_p.is_some() || parameters.max_tokens.is_some())
+        .then_some(parameters)
+}
+
+/// Keys of a JSON object that the converter doesn't map, reported as lossy
+pub(crate) fn ignored_keys(value: &serde_json::Value, known: &[&str], lossy: &mut Vec<String>) {
+    if let Some(object) = value.as_object() {
+        for key in object.keys().filter(|k| !known.contains(&k.as_str())) {
+            lossy.push(format!("Ignored field '{}'", key));
+        }
+    }
+}
+
+/// Notes for VibeBase-only template features that the target format would take literally
+pub(crate) fn template_notes(prompt: &PromptRuntime, target: &str) -> Vec<String> {
+    let logic = Regex::new(r"\{\{\s*[#/!>]|\{\{\s*else\s*\}\}").unwrap();
+    let filters = Regex::new(r"\{\{[^}]*\|[^}]*\}\}").unwrap();
+    let files = Regex::new(r"\{\{\s*file:").unwrap();
+
+    let mut notes = Vec::new();
+    let contents = || prompt.messages.iter().map(|m| m.content.as_str());
+    if contents().any(|c| logic.is_match(c)) {
+        notes.push(format!("Template blocks ({{{{#if}}}}, {{{{#each}}}}, includes) are kept as literal text in {}", target));
+    }
+    if contents().any(|c| filters.is_match(c)) {
+        notes.push(format!("Template filters are not applied by {}", target));
+    }
+    if contents().any(|c| files.is_match(c)) {
+        notes.push(format!("{{{{file:...}}}} variables are not loaded by {}", target));
+    }
+    notes
+}
+
+pub(crate) fn declarations_note(prompt: &PromptRuntime, target: &str) -> Option<String> {
+    (!prompt.variables.is_empty()).then(|| format!("Variable declarations (types, defaults, constraints) are not exported to {}", target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> PromptRuntime {
+        let mut prompt = new_prompt(
+            "support",
+            Provider::OpenAI,
+            "gpt-4o".to_string(),
+            Some(ModelParameters { temperature: Some(0.2), top_p: None, max_tokens: Some(300) }),
+            vec![
+                Message { role: MessageRole::System, content: "You help {{customer}}.".to_string() },
+                Message { role: MessageRole::User, content: "Question: {{question}}".to_string() },
+            ],
+        );
+        prompt.description = Some("Support replies".to_string());
+        prompt
+    }
+
+    #[test]
+    fn test_round_trip_all_formats() {
+        let prompt = sample();
+        for format in [
+            PromptFormat::OpenAI,
+            PromptFormat::Anthropic,
+            PromptFormat::Prompty,
+            PromptFormat::Promptfoo,
+            PromptFormat::LangChain,
+        ] {
+            let exported = export_prompt(&prompt, format).unwrap();
+            let imported = import_prompt(&exported.value, "support", format).unwrap();
+            let imported = &imported[0].value;
+
+            assert_eq!(imported.messages.len(), 2, "{:?}", format);
+            assert_eq!(imported.messages[0].content, "You help {{customer}}.", "{:?}", format);
+            assert_eq!(imported.messages[1].content, "Question: {{question}}", "{:?}", format);
+            if format != PromptFormat::Promptfoo && format != PromptFormat::LangChain {
+                assert_eq!(imported.config.model, "gpt-4o", "{:?}", format);
+                assert_eq!(imported.config.parameters.as_ref().unwrap().max_tokens, Some(300), "{:?}", format);
+            }
+        }
+    }
+
+    #[test]
+    fn test_detect_and_markdown() {
+        assert_eq!(PromptFormat::detect(Path::new("a.prompty"), ""), Some(PromptFormat::Prompty));
+        assert_eq!(PromptFormat::detect(Path::new("a.json"), r#"[{"role":"user","content":"x"}]"#), Some(PromptFormat::OpenAI));
+        assert_eq!(PromptFormat::detect(Path::new("a.json"), r#"{"system":"x","messages":[]}"#), Some(PromptFormat::Anthropic));
+        assert_eq!(PromptFormat::detect(Path::new("a.json"), r#"{"lc":1}"#), Some(PromptFormat::LangChain));
+        assert_eq!(PromptFormat::detect(Path::new("a.txt"), "one\n---\ntwo"), Some(PromptFormat::Promptfoo));
+        assert_eq!(PromptFormat::detect(Path::new("notes.txt"), "just some notes"), None);
+        assert_eq!(PromptFormat::detect(Path::new("a.yaml"), "- role: user\n  content: hi\n"), Some(PromptFormat::Promptfoo));
+        assert_eq!(PromptFormat::detect(Path::new("a.yaml"), "prompts:\n  - 'Hi {{name}}'\n"), Some(PromptFormat::Promptfoo));
+        assert_eq!(PromptFormat::detect(Path::new("ci.yml"), "jobs:\n  build: {}\n"), None);
+        assert_eq!(PromptFormat::detect(Path::new("a.vibe.yaml"), "messages:\n  - role: user\n"), None);
+
+        let mut prompt = sample();
+        prompt.messages[1].content = "## Context\n{{question}}".to_string();
+        let markdown = to_vibe_markdown(&prompt, &[]).unwrap();
+        assert_eq!(markdown.lossy.len(), 2);
+        assert!(markdown.lossy[0].starts_with("No 'openai' provider is configured"));
+
+        let messages = crate::models::prompt::parse_markdown_prompt(&markdown.value).unwrap();
+        assert_eq!(messages[1].content, "### Context\n{{question}}");
+        assert!(markdown.value.starts_with("---\nmodel: gpt-4o\n"));
+    }
+}
@@ -0,0 +1,138 @@
+use super::{content_text, declarations_note, ignored_keys, json_parameters, new_prompt, parse_role, role_name, template_notes, Conversion};
+use crate::models::prompt::{Message, PromptRuntime, Provider};
+use serde_json::json;
+
+/// Chat completions request body: `{model, messages, temperature, top_p, max_tokens}`
+pub fn export(prompt: &PromptRuntime) -> Result<Conversion<String>, String> {
+    let mut lossy = template_notes(prompt, "OpenAI messages");
+    lossy.extend(declarations_note(prompt, "OpenAI messages"));
+
+    let messages: Vec<serde_json::Value> = prompt
+        .messages
+        .iter()
+        .map(|m| json!({ "role": role_name(&m.role), "content": m.content }))
+        .collect();
+
+    let mut body = json!({ "model": prompt.config.model, "messages": messages });
+    if let Some(parameters) = &prompt.config.parameters {
+        if let Some(temperature) = parameters.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if let Some(top_p) = parameters.top_p {
+            body["top_p"] = json!(top_p);
+        }
+        if let Some(max_tokens) = parameters.max_tokens {
+            body["max_tokens"] = json!(max_tokens);
+        }
+    }
+
+    let value = serde_json::to_string_pretty(&body).map_err(|e| format!("Failed to serialize messages: {}", e))?;
+    Ok(Conversion { value, lossy })
+}
+
+/// Accepts a bare messages array or a request body
+pub fn import(content: &str, name: &str) -> Result<Conversion<PromptRuntime>, String> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let mut lossy = Vec::new();
+
+    let (messages_value, model, parameters) = match &value {
+        serde_json::Value::Array(_) => (&value, String::new(), None),
+        serde_json::Value::Object(_) => {
+            ignored_keys(
+                &value,
+                &["model", "messages", "temperature", "top_p", "max_tokens", "max_completion_tokens"],
+                &mut lossy,
+            );
+            let model = value.get("model").and_then(|m| m.as_str()).unwrap_or_default().to_string();
+            let messages = value.get("messages").ok_or("Missing 'messages'")?;
+            (messages, model, json_parameters(&value, &["max_tokens", "max_completion_tokens"]))
+        }
+        _ => return Err("Expected a messages array or a request object".to_string()),
+    };
+
+    let messages = import_messages(messages_value, &mut lossy)?;
+    Ok(Conversion {
+        value: new_prompt(name, Provider::OpenAI, model, parameters, messages),
+        lossy,
+    })
+}
+
+/// `[{role, content}]`; tool messages and unknown roles are dropped
+pub(crate) fn import_messages(value: &serde_json::Value, lossy: &mut Vec<String>) -> Result<Vec<Message>, String> {
+    let items = value.as_array().ok_or("'messages' must be an array")?;
+
+    let mut messages = Vec::new();
+    for item in items {
+        let role_text = item.get("role").and_then(|r| r.as_str()).unwrap_or_default();
+        let role = match parse_role(role_text) {
+            Some(role) => role,
+            None => {
+                lossy.push(format!("Dropped a '{}' message", role_text));
+                continue;
+            }
+        };
+        if item.get("tool_calls").is_some() {
+            lossy.push("Dropped tool calls of an assistant message".to_string());
+        }
+        if item.get("name").is_some() {
+            lossy.push("Ignored message 'name' field".to_string());
+        }
+
+        let content = content_text(item.get("content").unwrap_or(&serde_json::Value::Null), lossy);
+        messages.push(Message { role, content });
+    }
+
+    if messages.is_empty() {
+        return Err("No messages to import".to_string());
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::prompt::{MessageRole, ModelParameters};
+
+    fn summary(messages: &[Message]) -> Vec<(&'static str, &str)> {
+        messages.iter().map(|m| (super::super::role_name(&m.role), m.content.as_str())).collect()
+    }
+
+    fn parameters(prompt: &PromptRuntime) -> serde_json::Value {
+        serde_json::to_value(&prompt.config.parameters).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_request_body() {
+        let prompt = new_prompt(
+            "support",
+            Provider::OpenAI,
+            "gpt-4o".to_string(),
+            Some(ModelParameters { temperature: Some(0.5), top_p: Some(0.9), max_tokens: Some(200) }),
+            vec![
+                Message { role: MessageRole::System, content: "Be brief.".to_string() },
+                Message { role: MessageRole::User, content: "{{question}}".to_string() },
+                Message { role: MessageRole::Assistant, content: "Sure.".to_string() },
+            ],
+        );
+
+        let exported = export(&prompt).unwrap();
+        assert!(exported.lossy.is_empty());
+        let imported = import(&exported.value, "support").unwrap();
+
+        assert!(imported.lossy.is_empty(), "{:?}", imported.lossy);
+        assert_eq!(imported.value.config.model, "gpt-4o");
+        assert_eq!(parameters(&imported.value), parameters(&prompt));
+        assert_eq!(summary(&imported.value.messages), summary(&prompt.messages));
+    }
+
+    #[test]
+    fn test_import_bare_array_with_parts() {
+        let content = r#"[{"role":"developer","content":[{"type":"text","text":"Rules"},{"type":"image_url"}]},{"role":"tool","content":"x"},{"role":"user","content":"Hi"}]"#;
+        let imported = import(content, "chat").unwrap();
+
+        assert_eq!(imported.value.messages[0].role, MessageRole::System);
+        assert_eq!(imported.value.messages[0].content, "Rules");
+        assert_eq!(imported.value.messages.len(), 2);
+        assert_eq!(imported.lossy.len(), 2);
+    }
+}
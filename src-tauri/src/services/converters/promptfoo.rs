@@ -0,0 +1,178 @@
+use super::{declarations_note, new_prompt, openai, role_name, template_notes, Conversion};
+use crate::models::prompt::{Message, MessageRole, PromptRuntime, Provider};
+use serde_json::json;
+
+/// Chat prompt file (`file://prompt.json` in promptfooconfig.yaml)
+/// Promptfoo renders `{{var}}` with Nunjucks, so contents are kept as-is
+pub fn export(prompt: &PromptRuntime) -> Result<Conversion<String>, String> {
+    let mut lossy = template_notes(prompt, "promptfoo (Nunjucks)");
+    lossy.extend(declarations_note(prompt, "promptfoo"));
+    if !prompt.config.model.is_empty() || prompt.config.parameters.is_some() {
+        lossy.push("Model and parameters belong to the providers of promptfooconfig.yaml: not exported".to_string());
+    }
+
+    let messages: Vec<serde_json::Value> = prompt
+        .messages
+        .iter()
+        .map(|m| json!({ "role": role_name(&m.role), "content": m.content }))
+        .collect();
+
+    let value = serde_json::to_string_pretty(&messages).map_err(|e| format!("Failed to serialize messages: {}", e))?;
+    Ok(Conversion { value, lossy })
+}
+
+fn is_separator(line: &str) -> bool {
+    line.trim_end() == "---"
+}
+
+/// Text file holding several prompts separated by `---` lines
+pub fn is_prompt_list(content: &str) -> bool {
+    content.lines().any(is_separator)
+}
+
+/// A chat messages array, `{messages: [...]}`, or a config with a `prompts:` list
+pub fn is_structured_prompt(value: &serde_json::Value) -> bool {
+    let is_messages = |v: &serde_json::Value| {
+        v.as_array().is_some_and(|items| !items.is_empty() && items.iter().all(|i| i.get("role").is_some()))
+    };
+    is_messages(value)
+        || value.get("messages").is_some_and(is_messages)
+        || value.get("prompts").is_some_and(|p| p.is_array())
+}
+
+/// A JSON/YAML chat messages array (or `{messages: [...]}`), the inline prompts of a
+/// `prompts:` list, or a text file of prompts separated by `---` lines
+pub fn import(content: &str, name: &str) -> Result<Vec<Conversion<PromptRuntime>>, String> {
+    let structured: Option<serde_json::Value> = serde_json::from_str(content)
+        .ok()
+        .or_else(|| serde_yaml::from_str(content).ok())
+        .filter(is_structured_prompt);
+
+    match structured {
+        Some(value) => match value.get("prompts") {
+            Some(prompts) => import_prompt_list(prompts, name),
+            None => {
+                let mut lossy = Vec::new();
+                let messages = openai::import_messages(value.get("messages").unwrap_or(&value), &mut lossy)?;
+                Ok(vec![Conversion {
+                    value: new_prompt(name, Provider::OpenAI, String::new(), None, messages),
+                    lossy,
+                }])
+            }
+        },
+        None => import_text(content, name),
+    }
+}
+
+/// Inline prompts of a `prompts:` list; `file://` references and objects are skipped
+fn import_prompt_list(prompts: &serde_json::Value, name: &str) -> Result<Vec<Conversion<PromptRuntime>>, String> {
+    let mut lossy = Vec::new();
+    let mut texts = Vec::new();
+    for prompt in prompts.as_array().into_iter().flatten() {
+        match prompt.as_str() {
+            Some(reference) if reference.starts_with("file://") => {
+                lossy.push(format!("Skipped prompt reference '{}': import that file instead", reference));
+            }
+            Some(text) if !text.trim().is_empty() => texts.push(text.trim().to_string()),
+            Some(_) => {}
+            None => lossy.push("Skipped a prompt that is not an inline string".to_string()),
+        }
+    }
+    if texts.is_empty() {
+        return Err("No inline prompts found".to_string());
+    }
+
+    let mut conversions = user_prompts(texts, name);
+    conversions[0].lossy = lossy;
+    Ok(conversions)
+}
+
+fn import_text(content: &str, name: &str) -> Result<Vec<Conversion<PromptRuntime>>, String> {
+    let mut prompts: Vec<String> = vec![String::new()];
+    for line in content.lines() {
+        if is_separator(line) {
+            prompts.push(String::new());
+        } else if let Some(current) = prompts.last_mut() {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    let prompts: Vec<String> = prompts
+        .into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if prompts.is_empty() {
+        return Err("No prompts found".to_string());
+    }
+    Ok(user_prompts(prompts, name))
+}
+
+/// One prompt per text, as a single user message; numbered when there are several
+fn user_prompts(prompts: Vec<String>, name: &str) -> Vec<Conversion<PromptRuntime>> {
+    let count = prompts.len();
+    prompts
+        .into_iter()
+        .enumerate()
+        .map(|(index, content)| {
+            let prompt_name = if count == 1 { name.to_string() } else { format!("{}-{}", name, index + 1) };
+            Conversion {
+                value: new_prompt(
+                    &prompt_name,
+                    Provider::OpenAI,
+                    String::new(),
+                    None,
+                    vec![Message { role: MessageRole::User, content }],
+                ),
+                lossy: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(messages: &[Message]) -> Vec<(&'static str, &str)> {
+        messages.iter().map(|m| (super::super::role_name(&m.role), m.content.as_str())).collect()
+    }
+
+    #[test]
+    fn test_round_trip_messages() {
+        let prompt = new_prompt(
+            "support",
+            Provider::OpenAI,
+            String::new(),
+            None,
+            vec![
+                Message { role: MessageRole::System, content: "Be brief.".to_string() },
+                Message { role: MessageRole::User, content: "{{question}}".to_string() },
+            ],
+        );
+
+        let exported = export(&prompt).unwrap();
+        assert!(exported.lossy.is_empty());
+        let imported = import(&exported.value, "support").unwrap();
+
+        assert_eq!(imported.len(), 1);
+        assert_eq!(summary(&imported[0].value.messages), summary(&prompt.messages));
+    }
+
+    #[test]
+    fn test_import_text_and_prompt_lists() {
+        let imported = import("Name {{thing}}\n---\nDescribe {{thing}}\n", "ideas").unwrap();
+        let names: Vec<&str> = imported.iter().map(|c| c.value.name.as_str()).collect();
+        assert_eq!(names, vec!["ideas-1", "ideas-2"]);
+        assert_eq!(imported[1].value.messages[0].content, "Describe {{thing}}");
+
+        let config = "prompts:\n  - file://prompts/a.txt\n  - 'Summarize {{text}}'\n";
+        let imported = import(config, "config").unwrap();
+        assert_eq!(imported.len(), 1);
+        assert_eq!(imported[0].value.messages[0].content, "Summarize {{text}}");
+        assert_eq!(imported[0].lossy.len(), 1);
+
+        let messages = "messages:\n  - role: user\n    content: Hi\n";
+        assert_eq!(import(messages, "chat").unwrap()[0].value.messages[0].content, "Hi");
+    }
+}
@@ -0,0 +1,309 @@
+use super::{new_prompt, parse_role, role_name, template_notes, Conversion};
+use crate::models::frontmatter::split_frontmatter;
+use crate::models::prompt::{Message, MessageRole, ModelParameters, PromptRuntime, Provider};
+use crate::models::variables::{VariableDeclaration, VariableType};
+use regex::Regex;
+use serde_yaml::{Mapping, Value};
+
+fn key(name: &str) -> Value {
+    Value::String(name.to_string())
+}
+
+/// Prompty `inputs` type for a declared variable
+fn input_type(var_type: VariableType) -> &'static str {
+    match var_type {
+        VariableType::Number => "number",
+        VariableType::Json => "object",
+        VariableType::String | VariableType::Enum | VariableType::Multiline | VariableType::File => "string",
+    }
+}
+
+/// ```text
+/// ---
+/// name: support
+/// model:
+///   api: chat
+///   configuration: {type: openai, name: gpt-4o}
+///   parameters: {temperature: 0.2}
+/// inputs:
+///   question: {type: string}
+/// ---
+/// system:
+/// You are helpful.
+///
+/// user:
+/// {{question}}
+/// ```
+pub fn export(prompt: &PromptRuntime) -> Result<Conversion<String>, String> {
+    let mut lossy = template_notes(prompt, "Prompty (Jinja2)");
+
+    let mut frontmatter = Mapping::new();
+    frontmatter.insert(key("name"), key(&prompt.name));
+    if let Some(description) = &prompt.description {
+        frontmatter.insert(key("description"), key(description));
+    }
+
+    let mut configuration = Mapping::new();
+    let provider_type = match prompt.config.provider {
+        Provider::AzureOpenAI => "azure_openai",
+        Provider::OpenAI => "openai",
+        ref other => {
+            lossy.push(format!("Prompty has no '{}' provider: exported as openai", other.as_str()));
+            "openai"
+        }
+    };
+    configuration.insert(key("type"), key(provider_type));
+    configuration.insert(key("name"), key(&prompt.config.model));
+
+    let mut model = Mapping::new();
+    model.insert(key("api"), key("chat"));
+    model.insert(key("configuration"), Value::Mapping(configuration));
+    if let Some(parameters) = &prompt.config.parameters {
+        let parameters = serde_yaml::to_value(parameters).map_err(|e| format!("Failed to serialize parameters: {}", e))?;
+        model.insert(key("parameters"), parameters);
+    }
+    frontmatter.insert(key("model"), Value::Mapping(model));
+
+    // Declared variables first, then the ones only used in the messages
+    let mut inputs = Mapping::new();
+    for declaration in &prompt.variables {
+        let mut input = Mapping::new();
+        input.insert(key("type"), key(input_type(declaration.var_type)));
+        if let Some(description) = &declaration.description {
+            input.insert(key("description"), key(description));
+        }
+        if let Some(default) = &declaration.default {
+            let default = serde_yaml::to_value(default).map_err(|e| e.to_string())?;
+            input.insert(key("default"), default);
+        }
+        if declaration.values.is_some()
+            || declaration.min.is_some()
+            || declaration.max.is_some()
+            || declaration.min_length.is_some()
+            || declaration.max_length.is_some()
+            || declaration.pattern.is_some()
+        {
+            lossy.push(format!("Constraints of '{}' are not supported by Prompty inputs", declaration.name));
+        }
+        inputs.insert(key(&declaration.name), Value::Mapping(input));
+    }
+    for name in prompt.extract_variables() {
+        if !inputs.contains_key(key(&name)) {
+            let mut input = Mapping::new();
+            input.insert(key("type"), key("string"));
+            inputs.insert(key(&name), Value::Mapping(input));
+        }
+    }
+    if !inputs.is_empty() {
+        frontmatter.insert(key("inputs"), Value::Mapping(inputs));
+    }
+
+    let yaml = serde_yaml::to_string(&frontmatter).map_err(|e| format!("Failed to serialize frontmatter: {}", e))?;
+    let body: Vec<String> = prompt
+        .messages
+        .iter()
+        .map(|m| format!("{}:\n{}\n", role_name(&m.role), m.content))
+        .collect();
+
+    Ok(Conversion {
+        value: format!("---\n{}---\n{}", yaml, body.join("\n")),
+        lossy,
+    })
+}
+
+fn declaration(name: &str, input: &Value, lossy: &mut Vec<String>) -> VariableDeclaration {
+    let mut declaration = VariableDeclaration {
+        name: name.to_string(),
+        ..Default::default()
+    };
+
+    declaration.var_type = match input.get("type").and_then(|t| t.as_str()).unwrap_or("string") {
+        "string" => VariableType::String,
+        "number" | "integer" => VariableType::Number,
+        "object" | "array" => VariableType::Json,
+        other => {
+            lossy.push(format!("Input '{}' has type '{}': imported as string", name, other));
+            VariableType::String
+        }
+    };
+    declaration.description = input.get("description").and_then(|d| d.as_str()).map(|d| d.to_string());
+    declaration.default = input
+        .get("default")
+        .and_then(|d| serde_json::to_value(d).ok());
+
+    declaration
+}
+
+pub fn import(content: &str, name: &str) -> Result<Conversion<PromptRuntime>, String> {
+    let mut lossy = Vec::new();
+    let (yaml, body) = split_frontmatter(content);
+
+    let frontmatter: Value = match yaml {
+        Some(yaml) if !yaml.trim().is_empty() => {
+            serde_yaml::from_str(yaml).map_err(|e| format!("Invalid Prompty frontmatter: {}", e))?
+        }
+        _ => Value::Mapping(Mapping::new()),
+    };
+    let text = |value: Option<&Value>| value.and_then(|v| v.as_str()).map(|v| v.to_string());
+
+    let model = frontmatter.get("model");
+    let configuration = model.and_then(|m| m.get("configuration"));
+    let provider = match text(configuration.and_then(|c| c.get("type"))).as_deref() {
+        Some("azure_openai") | Some("azure") => Provider::AzureOpenAI,
+        _ => Provider::OpenAI,
+    };
+    let model_name = ["name", "model", "azure_deployment"]
+        .iter()
+        .find_map(|k| text(configuration.and_then(|c| c.get(*k))))
+        .unwrap_or_default();
+    let parameters = match model.and_then(|m| m.get("parameters")) {
+        Some(value) => {
+            if let Some(map) = value.as_mapping() {
+                for k in map.keys().filter_map(|k| k.as_str()) {
+                    if !["temperature", "top_p", "max_tokens"].contains(&k) {
+                        lossy.push(format!("Ignored model parameter '{}'", k));
+                    }
+                }
+            }
+            serde_yaml::from_value::<ModelParameters>(value.clone()).ok()
+        }
+        None => None,
+    };
+    if model.and_then(|m| m.get("api")).and_then(|a| a.as_str()).is_some_and(|a| a != "chat") {
+        lossy.push("Only the chat API is supported: imported as chat".to_string());
+    }
+
+    // Body: `role:` lines start a message
+    let role_line = Regex::new(r"^\s*#?\s*(system|user|assistant|developer)\s*:\s*$").unwrap();
+    let mut messages: Vec<Message> = Vec::new();
+    let mut current: Option<(MessageRole, Vec<&str>)> = None;
+    let mut flush = |current: &mut Option<(MessageRole, Vec<&str>)>| {
+        if let Some((role, lines)) = current.take() {
+            let content = lines.join("\n").trim().to_string();
+            if !content.is_empty() {
+                messages.push(Message { role, content });
+            }
+        }
+    };
+    for line in body.lines() {
+        let role = role_line
+            .captures(line)
+            .and_then(|c| parse_role(&c[1]));
+        match role {
+            Some(role) => {
+                flush(&mut current);
+                current = Some((role, Vec::new()));
+            }
+            None => match current.as_mut() {
+                Some((_, lines)) => lines.push(line),
+                // Text before the first role line is a system prompt
+                None if !line.trim().is_empty() => {
+                    current = Some((MessageRole::System, vec![line]));
+                }
+                None => {}
+            },
+        }
+    }
+    flush(&mut current);
+
+    if messages.is_empty() {
+        return Err("No messages found in the Prompty body".to_string());
+    }
+    if messages.iter().any(|m| m.content.contains("{%")) {
+        lossy.push("Jinja2 statements ({% ... %}) are kept as literal text".to_string());
+    }
+
+    let mut variables = Vec::new();
+    if let Some(inputs) = frontmatter.get("inputs").and_then(|i| i.as_mapping()) {
+        for (input_name, input) in inputs {
+            if let Some(input_name) = input_name.as_str() {
+                variables.push(declaration(input_name, input, &mut lossy));
+            }
+        }
+    }
+    for ignored in ["outputs", "sample", "authors", "tags", "version", "template"] {
+        if frontmatter.get(ignored).is_some() {
+            lossy.push(format!("Ignored field '{}'", ignored));
+        }
+    }
+
+    let mut prompt = new_prompt(
+        &text(frontmatter.get("name")).unwrap_or_else(|| name.to_string()),
+        provider,
+        model_name,
+        parameters,
+        messages,
+    );
+    prompt.description = text(frontmatter.get("description"));
+    prompt.variables = variables;
+
+    Ok(Conversion { value: prompt, lossy })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(messages: &[Message]) -> Vec<(&'static str, &str)> {
+        messages.iter().map(|m| (super::super::role_name(&m.role), m.content.as_str())).collect()
+    }
+
+    fn parameters(prompt: &PromptRuntime) -> serde_json::Value {
+        serde_json::to_value(&prompt.config.parameters).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_with_inputs() {
+        let mut prompt = new_prompt(
+            "support",
+            Provider::AzureOpenAI,
+            "gpt-4o".to_string(),
+            Some(ModelParameters { temperature: Some(0.2), top_p: None, max_tokens: None }),
+            vec![
+                Message { role: MessageRole::System, content: "You help {{customer}}.".to_string() },
+                Message { role: MessageRole::User, content: "{{question}}".to_string() },
+            ],
+        );
+        prompt.description = Some("Support replies".to_string());
+        prompt.variables = vec![VariableDeclaration {
+            name: "customer".to_string(),
+            var_type: VariableType::String,
+            description: Some("Customer name".to_string()),
+            default: Some(serde_json::json!("Ada")),
+            ..Default::default()
+        }];
+
+        let exported = export(&prompt).unwrap();
+        assert!(exported.lossy.is_empty(), "{:?}", exported.lossy);
+        let imported = import(&exported.value, "ignored").unwrap();
+        let value = &imported.value;
+
+        assert!(imported.lossy.is_empty(), "{:?}", imported.lossy);
+        assert_eq!(value.name, "support");
+        assert_eq!(value.description, prompt.description);
+        assert_eq!(value.config.provider.as_str(), Provider::AzureOpenAI.as_str());
+        assert_eq!(value.config.model, "gpt-4o");
+        assert_eq!(parameters(value), parameters(&prompt));
+        assert_eq!(summary(&value.messages), summary(&prompt.messages));
+
+        let names: Vec<&str> = value.variables.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(names, vec!["customer", "question"]);
+        assert_eq!(value.variables[0].default, Some(serde_json::json!("Ada")));
+        assert_eq!(value.variables[0].description.as_deref(), Some("Customer name"));
+    }
+
+    #[test]
+    fn test_export_unsupported_provider() {
+        let prompt = new_prompt(
+            "support",
+            Provider::Anthropic,
+            "claude-sonnet-4-5".to_string(),
+            None,
+            vec![Message { role: MessageRole::User, content: "Hi".to_string() }],
+        );
+
+        let exported = export(&prompt).unwrap();
+        assert_eq!(exported.lossy, vec!["Prompty has no 'anthropic' provider: exported as openai".to_string()]);
+        assert_eq!(import(&exported.value, "support").unwrap().value.config.provider.as_str(), Provider::OpenAI.as_str());
+    }
+}
@@ -1,4 +1,7 @@
-use rusqlite::{Connection, Result, params};
+use crate::models::execution::ExecutionMetadata;
+use crate::models::frontmatter::PromptFrontmatter;
+use crate::services::partials::workspace_relative;
+use rusqlite::{Connection, OptionalExtension, Result, params};
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
@@ -26,6 +29,8 @@ impl AppDatabase {
         
         // Run migrations
         db.migrate_v0_1_11()?;
+        db.migrate_model_pricing()?;
+        db.migrate_secret_variables()?;
 
         Ok(db)
     }
@@ -241,6 +246,333 @@ impl AppDatabase {
             |row| row.get(0),
         )
     }
+
+    /// Add the secret flag to global variables (v1.3.0)
+    /// Secret values are stored in the keychain; the value column stays empty
+    fn migrate_secret_variables(&self) -> Result<()> {
+        let column_exists: bool = self.conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('global_variables') WHERE name = 'secret'",
+                [],
+                |row| row.get::<_, i32>(0).map(|count| count > 0),
+            )
+            .unwrap_or(false);
+
+        if !column_exists {
+            self.conn.execute_batch("ALTER TABLE global_variables ADD COLUMN secret INTEGER NOT NULL DEFAULT 0;")?;
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        self.conn.execute(
+            "INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (?1, ?2)",
+            params!["1.3.0", now],
+        )?;
+
+        Ok(())
+    }
+
+    /// Seed default model pricing (runs once)
+    /// Users can edit or delete seeded rows afterwards without them coming back
+    fn migrate_model_pricing(&self) -> Result<()> {
+        let migration_applied: bool = self.conn
+            .query_row(
+                "SELECT COUNT(*) FROM schema_version WHERE version = '1.2.0'",
+                [],
+                |row| row.get::<_, i32>(0).map(|count| count > 0),
+            )
+            .unwrap_or(false);
+
+        if migration_applied {
+            return Ok(());
+        }
+
+        println!("🔄 [Migration] Seeding default model pricing...");
+
+        let defaults: Vec<PricingSeed> = vec![
+            ("openai", "gpt-4o", 2.5, 10.0, Some(1.25), None),
+            ("openai", "gpt-4o-mini", 0.15, 0.60, Some(0.075), None),
+            ("openai", "gpt-4-turbo", 10.0, 30.0, None, None),
+            ("openai", "gpt-4", 30.0, 60.0, None, None),
+            ("openai", "gpt-3.5-turbo", 0.5, 1.5, None, None),
+            ("anthropic", "claude-3-opus-20240229", 15.0, 75.0, Some(1.5), Some(18.75)),
+            ("anthropic", "claude-3-sonnet-20240229", 3.0, 15.0, None, None),
+            ("anthropic", "claude-3-haiku-20240307", 0.25, 1.25, Some(0.03), Some(0.30)),
+            ("anthropic", "claude-3-5-sonnet-20241022", 3.0, 15.0, Some(0.30), Some(3.75)),
+            ("anthropic", "claude-3-5-haiku-20241022", 0.80, 4.0, Some(0.08), Some(1.0)),
+            ("deepseek", "*", 0.14, 0.28, None, None),
+            ("ollama", "*", 0.0, 0.0, None, None),
+        ];
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+
+        for (provider, model, input_price, output_price, cache_read_price, cache_write_price) in defaults {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO model_pricing (
+                    id, provider, model, input_price, output_price,
+                    cache_read_price, cache_write_price, source, version, updated_at
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 'default', 1, ?8)",
+                params![
+                    uuid::Uuid::new_v4().to_string(),
+                    provider,
+                    model,
+                    input_price,
+                    output_price,
+                    cache_read_price,
+                    cache_write_price,
+                    now,
+                ],
+            )?;
+        }
+
+        self.conn.execute(
+            "INSERT OR IGNORE INTO schema_version (version, applied_at) VALUES (?1, ?2)",
+            params!["1.2.0", now],
+        )?;
+
+        println!("✅ [Migration] Model pricing seeded");
+
+        Ok(())
+    }
+
+    /// Find pricing for a provider/model pair
+    /// Falls back to the provider-wide '*' entry when there is no exact match
+    pub fn find_model_pricing(&self, provider: &str, model: &str) -> Result<Option<ModelPricing>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, provider, model, input_price, output_price, cache_read_price, cache_write_price, source, version, updated_at
+             FROM model_pricing
+             WHERE provider = ?1 AND (model = ?2 OR model = '*')
+             ORDER BY CASE WHEN model = '*' THEN 1 ELSE 0 END
+             LIMIT 1"
+        )?;
+
+        let mut rows = stmt.query_map(params![provider, model], Self::map_model_pricing)?;
+        rows.next().transpose()
+    }
+
+    pub fn list_model_pricing(&self) -> Result<Vec<ModelPricing>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, provider, model, input_price, output_price, cache_read_price, cache_write_price, source, version, updated_at
+             FROM model_pricing ORDER BY provider, model"
+        )?;
+
+        let pricing = stmt.query_map([], Self::map_model_pricing)?;
+
+        pricing.collect()
+    }
+
+    /// Insert or update a pricing entry, bumping its version when prices change
+    pub fn save_model_pricing(&self, pricing: &ModelPricing) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+
+        self.conn.execute(
+            "INSERT INTO model_pricing (
+                id, provider, model, input_price, output_price,
+                cache_read_price, cache_write_price, source, version, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 1, ?9)
+             ON CONFLICT(provider, model) DO UPDATE SET
+                input_price = ?4,
+                output_price = ?5,
+                cache_read_price = ?6,
+                cache_write_price = ?7,
+                source = ?8,
+                version = CASE
+                    WHEN input_price IS ?4 AND output_price IS ?5
+                         AND cache_read_price IS ?6 AND cache_write_price IS ?7
+                    THEN version ELSE version + 1 END,
+                updated_at = ?9",
+            params![
+                pricing.id,
+                pricing.provider,
+                pricing.model,
+                pricing.input_price,
+                pricing.output_price,
+                pricing.cache_read_price,
+                pricing.cache_write_price,
+                pricing.source,
+                now,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Save pricing fetched from a provider API
+    /// Entries edited by the user are left untouched
+    pub fn sync_model_pricing(&self, pricing: &ModelPricing) -> Result<()> {
+        let user_edited = self.conn
+            .query_row(
+                "SELECT COUNT(*) FROM model_pricing WHERE provider = ?1 AND model = ?2 AND source = 'user'",
+                params![pricing.provider, pricing.model],
+                |row| row.get::<_, i32>(0),
+            )
+            .unwrap_or(0) > 0;
+
+        if user_edited {
+            return Ok(());
+        }
+
+        self.save_model_pricing(pricing)
+    }
+
+    pub fn delete_model_pricing(&self, provider: &str, model: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM model_pricing WHERE provider = ?1 AND model = ?2",
+            params![provider, model],
+        )?;
+        Ok(())
+    }
+
+    pub fn list_spending_budgets(&self) -> Result<Vec<SpendingBudget>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, scope, scope_ref, period, soft_limit_usd, hard_limit_usd, enabled
+             FROM spending_budgets ORDER BY scope, scope_ref, period"
+        )?;
+
+        let budgets = stmt.query_map([], Self::map_spending_budget)?;
+
+        budgets.collect()
+    }
+
+    /// Get enabled budgets for a scope
+    pub fn get_spending_budgets(&self, scope: &str, scope_ref: &str) -> Result<Vec<SpendingBudget>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, scope, scope_ref, period, soft_limit_usd, hard_limit_usd, enabled
+             FROM spending_budgets
+             WHERE scope = ?1 AND scope_ref = ?2 AND enabled = 1
+             ORDER BY period"
+        )?;
+
+        let budgets = stmt.query_map(params![scope, scope_ref], Self::map_spending_budget)?;
+
+        budgets.collect()
+    }
+
+    pub fn save_spending_budget(&self, budget: &SpendingBudget) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+
+        self.conn.execute(
+            "INSERT INTO spending_budgets (id, scope, scope_ref, period, soft_limit_usd, hard_limit_usd, enabled, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(scope, scope_ref, period) DO UPDATE SET
+                soft_limit_usd = ?5,
+                hard_limit_usd = ?6,
+                enabled = ?7,
+                updated_at = ?9",
+            params![
+                budget.id,
+                budget.scope,
+                budget.scope_ref,
+                budget.period,
+                budget.soft_limit_usd,
+                budget.hard_limit_usd,
+                budget.enabled as i32,
+                now,
+                now,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn delete_spending_budget(&self, id: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM spending_budgets WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    /// Record the cost of an execution in the spending ledger
+    pub fn record_spending(
+        &self,
+        execution_id: &str,
+        workspace_path: Option<&str>,
+        provider_name: Option<&str>,
+        metadata: &ExecutionMetadata,
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO spending_ledger (id, workspace_path, provider_name, provider, model, cost_usd, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                execution_id,
+                workspace_path,
+                provider_name,
+                metadata.provider,
+                metadata.model,
+                metadata.cost_usd,
+                metadata.timestamp,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Total spending for a budget scope since the given timestamp
+    pub fn get_spending_since(&self, scope: &str, scope_ref: &str, since: i64) -> Result<f64> {
+        let query = match scope {
+            "workspace" => "SELECT COALESCE(SUM(cost_usd), 0) FROM spending_ledger WHERE workspace_path = ?1 AND timestamp >= ?2",
+            "provider" => "SELECT COALESCE(SUM(cost_usd), 0) FROM spending_ledger WHERE provider_name = ?1 AND timestamp >= ?2",
+            _ => return Ok(0.0),
+        };
+
+        self.conn.query_row(query, params![scope_ref, since], |row| row.get(0))
+    }
+
+    pub fn get_global_variables(&self) -> Result<Vec<StoredVariable>> {
+        let mut stmt = self.conn.prepare("SELECT id, key, value, secret FROM global_variables ORDER BY key")?;
+        let variables = stmt.query_map([], Self::map_stored_variable)?;
+        variables.collect()
+    }
+
+    /// Replace all global variables
+    pub fn save_global_variables(&self, variables: &[StoredVariable]) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        let tx = self.conn.unchecked_transaction()?;
+
+        tx.execute("DELETE FROM global_variables", [])?;
+        for variable in variables {
+            let id = if variable.id.is_empty() { uuid::Uuid::new_v4().to_string() } else { variable.id.clone() };
+            tx.execute(
+                "INSERT INTO global_variables (id, key, value, secret, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                params![id, variable.key, variable.value, variable.secret as i32, now],
+            )?;
+        }
+
+        tx.commit()
+    }
+
+    fn map_stored_variable(row: &rusqlite::Row) -> Result<StoredVariable> {
+        Ok(StoredVariable {
+            id: row.get(0)?,
+            key: row.get(1)?,
+            value: row.get(2)?,
+            secret: row.get::<_, i32>(3)? != 0,
+        })
+    }
+
+    fn map_spending_budget(row: &rusqlite::Row) -> Result<SpendingBudget> {
+        Ok(SpendingBudget {
+            id: row.get(0)?,
+            scope: row.get(1)?,
+            scope_ref: row.get(2)?,
+            period: row.get(3)?,
+            soft_limit_usd: row.get(4)?,
+            hard_limit_usd: row.get(5)?,
+            enabled: row.get::<_, i32>(6)? != 0,
+        })
+    }
+
+    fn map_model_pricing(row: &rusqlite::Row) -> Result<ModelPricing> {
+        Ok(ModelPricing {
+            id: row.get(0)?,
+            provider: row.get(1)?,
+            model: row.get(2)?,
+            input_price: row.get(3)?,
+            output_price: row.get(4)?,
+            cache_read_price: row.get(5)?,
+            cache_write_price: row.get(6)?,
+            source: row.get(7)?,
+            version: row.get(8)?,
+            updated_at: row.get(9)?,
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -259,10 +591,41 @@ pub struct LLMProviderConfig {
     pub is_default: bool,
 }
 
+/// Default pricing row: (provider, model, input, output, cache_read, cache_write) in USD per 1M tokens
+type PricingSeed = (&'static str, &'static str, f64, f64, Option<f64>, Option<f64>);
+
+/// Model pricing entry (prices in USD per 1M tokens)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ModelPricing {
+    pub id: String,
+    pub provider: String,
+    pub model: String,
+    pub input_price: f64,
+    pub output_price: f64,
+    pub cache_read_price: Option<f64>,
+    pub cache_write_price: Option<f64>,
+    pub source: String,  // "default" | "user" | "openrouter"
+    pub version: i64,
+    pub updated_at: i64,
+}
+
+/// Spending budget for a workspace or provider config
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SpendingBudget {
+    pub id: String,
+    pub scope: String,      // "workspace" | "provider"
+    pub scope_ref: String,  // Workspace path or provider config name
+    pub period: String,     // "daily" | "monthly"
+    pub soft_limit_usd: Option<f64>,
+    pub hard_limit_usd: Option<f64>,
+    pub enabled: bool,
+}
+
 /// Project Database ({project}/.vibebase/project.db)
 /// Stores file metadata, execution history, evaluation results
 pub struct ProjectDatabase {
     conn: Connection,
+    workspace: PathBuf,
 }
 
 impl ProjectDatabase {
@@ -278,8 +641,15 @@ impl ProjectDatabase {
 
         // Run migrations for git_config if needed
         Self::migrate_git_config(&conn)?;
+        Self::migrate_execution_history(&conn)?;
+        Self::migrate_prompt_paths(&conn, workspace_path)?;
+
+        Ok(Self { conn, workspace: workspace_path.to_path_buf() })
+    }
 
-        Ok(Self { conn })
+    /// Files are recorded by workspace-relative path; callers may pass absolute paths
+    fn relative_path(&self, file_path: &str) -> String {
+        workspace_relative(&self.workspace, Path::new(file_path))
     }
 
     fn migrate_git_config(conn: &Connection) -> Result<()> {
@@ -304,6 +674,143 @@ impl ProjectDatabase {
         Ok(())
     }
 
+    fn migrate_execution_history(conn: &Connection) -> Result<()> {
+        // Columns for persisted executions (v1.5.0)
+        Self::add_column_if_missing(conn, "execution_history", "pricing_version", "INTEGER")?;
+        Self::add_column_if_missing(conn, "execution_history", "rendered_messages", "TEXT")?;
+        Self::add_column_if_missing(conn, "execution_history", "parameters", "TEXT")?;
+        Self::add_column_if_missing(conn, "execution_history", "run_mode", "TEXT DEFAULT 'single'")?;
+
+        // Prompt version that produced the output (v1.6.0)
+        Self::add_column_if_missing(conn, "execution_history", "file_history_id", "TEXT")?;
+        Self::add_column_if_missing(conn, "arena_battles", "file_history_id", "TEXT")?;
+
+        // Files loaded by {{file:...}} variables (v1.8.0)
+        Self::add_column_if_missing(conn, "execution_history", "input_files", "TEXT")?;
+
+        // Values of {{$now}}, {{$uuid}}... used by the execution (v1.14.0)
+        Self::add_column_if_missing(conn, "execution_history", "dynamic_variables", "TEXT")?;
+
+        // Dataset files are re-indexed when their content changes (v1.15.0)
+        Self::add_column_if_missing(conn, "test_datasets", "content_hash", "TEXT")?;
+
+        // Cells of matrix runs (v1.16.0)
+        Self::add_column_if_missing(conn, "test_results", "matrix_run_id", "TEXT")?;
+        Self::add_column_if_missing(conn, "test_results", "llm_provider_name", "TEXT")?;
+        Self::add_column_if_missing(conn, "test_results", "error", "TEXT")?;
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_test_results_run ON test_results(matrix_run_id, test_case_index)",
+            [],
+        )?;
+
+        // Assertion results and pass rates (v1.17.0)
+        Self::add_column_if_missing(conn, "evaluation_results", "evaluator_type", "TEXT")?;
+        Self::add_column_if_missing(conn, "evaluation_results", "prompt_file_id", "TEXT")?;
+        Self::add_column_if_missing(conn, "evaluation_results", "model", "TEXT")?;
+        Self::add_column_if_missing(conn, "evaluation_results", "matrix_run_id", "TEXT")?;
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_evaluation_prompt ON evaluation_results(prompt_file_id, model);
+             CREATE INDEX IF NOT EXISTS idx_evaluation_run ON evaluation_results(matrix_run_id);",
+        )?;
+
+        // Judge cost of LLM evaluators (v1.18.0)
+        Self::add_column_if_missing(conn, "evaluation_results", "judge_model", "TEXT")?;
+        Self::add_column_if_missing(conn, "evaluation_results", "judge_tokens_input", "INTEGER")?;
+        Self::add_column_if_missing(conn, "evaluation_results", "judge_tokens_output", "INTEGER")?;
+        Self::add_column_if_missing(conn, "evaluation_results", "judge_cost_usd", "REAL")?;
+
+        // model_pricing entry used for cost_usd (v1.19.0)
+        Self::add_column_if_missing(conn, "execution_history", "pricing_id", "TEXT")?;
+
+        // Prompt messages as executed, for replays (v1.20.0)
+        Self::add_column_if_missing(conn, "execution_history", "prompt_messages", "TEXT")?;
+
+        // Variable profile that supplied secrets, for replays (v1.21.0)
+        Self::add_column_if_missing(conn, "execution_history", "variable_profile", "TEXT")?;
+
+        Ok(())
+    }
+
+    /// Rewrite absolute prompt_files and file_history paths inside the workspace as relative ones
+    /// A prompt recorded under both forms is merged into the relative record
+    fn migrate_prompt_paths(conn: &Connection, workspace: &Path) -> Result<()> {
+        let absolute: Vec<(String, String)> = {
+            let mut stmt = conn.prepare("SELECT id, file_path FROM prompt_files")?;
+            let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<Result<Vec<(String, String)>>>()?
+                .into_iter()
+                .filter(|(_, path)| Path::new(path).is_absolute())
+                .collect()
+        };
+
+        for (id, path) in absolute {
+            let relative = workspace_relative(workspace, Path::new(&path));
+            if relative == path {
+                continue;
+            }
+            let existing: Option<String> = conn.query_row(
+                "SELECT id FROM prompt_files WHERE file_path = ?1",
+                params![relative],
+                |row| row.get(0),
+            ).optional()?;
+
+            match existing {
+                Some(existing) => {
+                    for table in ["execution_history", "evaluation_results", "test_results", "arena_battles", "matrix_runs"] {
+                        conn.execute(
+                            &format!("UPDATE {} SET prompt_file_id = ?2 WHERE prompt_file_id = ?1", table),
+                            params![id, existing],
+                        )?;
+                    }
+                    conn.execute(
+                        "UPDATE file_dependencies SET source_file = ?2 WHERE source_file = ?1",
+                        params![id, existing],
+                    )?;
+                    conn.execute("DELETE FROM prompt_files WHERE id = ?1", params![id])?;
+                }
+                None => {
+                    conn.execute(
+                        "UPDATE prompt_files SET file_path = ?2 WHERE id = ?1",
+                        params![id, relative],
+                    )?;
+                }
+            }
+        }
+
+        let history_paths: Vec<String> = {
+            let mut stmt = conn.prepare("SELECT DISTINCT file_path FROM file_history")?;
+            let rows = stmt.query_map([], |row| row.get(0))?;
+            rows.collect::<Result<Vec<String>>>()?
+        };
+        for path in history_paths.iter().filter(|path| Path::new(path).is_absolute()) {
+            let relative = workspace_relative(workspace, Path::new(path));
+            if &relative != path {
+                conn.execute(
+                    "UPDATE file_history SET file_path = ?2 WHERE file_path = ?1",
+                    params![path, relative],
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_column_if_missing(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+        let column_exists: bool = conn
+            .query_row(
+                &format!("SELECT COUNT(*) FROM pragma_table_info('{}') WHERE name = ?1", table),
+                params![column],
+                |row| row.get::<_, i32>(0),
+            )
+            .unwrap_or(0) > 0;
+
+        if !column_exists {
+            conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {};", table, column, definition))?;
+        }
+
+        Ok(())
+    }
+
     pub fn get_connection(&self) -> &Connection {
         &self.conn
     }
@@ -337,7 +844,7 @@ impl ProjectDatabase {
                 updated_at = ?20",
             params![
                 metadata.id,
-                metadata.file_path,
+                self.relative_path(&metadata.file_path),
                 metadata.name,
                 metadata.description,
                 metadata.schema_version,
@@ -372,6 +879,7 @@ impl ProjectDatabase {
         tags: Option<&str>,
         test_data_path: Option<&str>,
     ) -> Result<()> {
+        let file_path = self.relative_path(file_path);
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
         
         self.conn.execute(
@@ -397,76 +905,238 @@ impl ProjectDatabase {
         Ok(())
     }
 
-    /// Ensure a prompt file record exists (create if not)
-    pub fn ensure_prompt_file(&self, file_path: &str) -> Result<String> {
-        // Check if exists
-        let existing_id: Option<String> = self.conn.query_row(
-            "SELECT id FROM prompt_files WHERE file_path = ?1",
-            params![file_path],
-            |row| row.get(0),
-        ).ok();
-        
-        if let Some(id) = existing_id {
-            return Ok(id);
-        }
-        
-        // Create new record
+    /// Overwrite prompt config with the file's frontmatter (the file is the source of truth)
+    pub fn apply_prompt_frontmatter(&self, file_path: &str, frontmatter: &PromptFrontmatter) -> Result<()> {
+        let file_path = self.relative_path(file_path);
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
-        let id = uuid::Uuid::new_v4().to_string();
-        let name = std::path::Path::new(file_path)
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-        
+
         self.conn.execute(
-            "INSERT INTO prompt_files (
-                id, file_path, name, schema_version, provider_ref,
-                file_hash, file_size, last_modified, created_at, updated_at
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            "UPDATE prompt_files SET
+                provider_ref = ?2,
+                model_override = ?3,
+                parameters = ?4,
+                tags = ?5,
+                test_data_path = ?6,
+                description = ?7,
+                evaluation_config = ?8,
+                updated_at = ?9
+             WHERE file_path = ?1",
             params![
-                id,
                 file_path,
-                name,
-                "v1",
-                "default",
-                "",
-                0i64,
-                now,
-                now,
+                frontmatter.provider_ref.as_deref().unwrap_or("default"),
+                frontmatter.model,
+                frontmatter.parameters.as_ref().and_then(|p| serde_json::to_string(p).ok()),
+                frontmatter.tags.as_ref().and_then(|t| serde_json::to_string(t).ok()),
+                frontmatter.test_data,
+                frontmatter.description,
+                frontmatter.evaluation.as_ref().and_then(|e| serde_json::to_string(e).ok()),
                 now,
             ],
         )?;
-        
-        Ok(id)
+
+        Ok(())
     }
 
-    pub fn get_prompt_metadata(&self, file_path: &str) -> Result<PromptFileMetadata> {
+    /// Config columns of a prompt file (None when it has no record)
+    pub fn get_prompt_file_config(&self, file_path: &str) -> Result<Option<PromptFileConfig>> {
+        let file_path = self.relative_path(file_path);
         self.conn.query_row(
-            "SELECT id, file_path, name, description, schema_version,
-                    provider_ref, model_override, parameters,
-                    test_data_path, evaluation_config,
-                    tags, variables,
-                    file_hash, file_size, last_modified,
-                    last_validated, validation_status, validation_errors
+            "SELECT provider_ref, model_override, parameters, tags, test_data_path, description, evaluation_config
              FROM prompt_files WHERE file_path = ?1",
             params![file_path],
-            |row| {
-                Ok(PromptFileMetadata {
-                    id: row.get(0)?,
-                    file_path: row.get(1)?,
-                    name: row.get(2)?,
-                    description: row.get(3)?,
-                    schema_version: row.get(4)?,
-                    provider_ref: row.get(5)?,
-                    model_override: row.get(6)?,
-                    parameters: row.get(7)?,
-                    test_data_path: row.get(8)?,
-                    evaluation_config: row.get(9)?,
-                    tags: row.get(10)?,
-                    variables: row.get(11)?,
-                    file_hash: row.get(12)?,
-                    file_size: row.get(13)?,
+            |row| Ok(PromptFileConfig {
+                provider_ref: row.get(0)?,
+                model_override: row.get(1)?,
+                parameters: row.get(2)?,
+                tags: row.get(3)?,
+                test_data_path: row.get(4)?,
+                description: row.get(5)?,
+                evaluation_config: row.get(6)?,
+            }),
+        ).optional()
+    }
+
+    /// Put back config columns read with get_prompt_file_config
+    pub fn restore_prompt_file_config(&self, file_path: &str, config: &PromptFileConfig) -> Result<()> {
+        let file_path = self.relative_path(file_path);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+
+        self.conn.execute(
+            "UPDATE prompt_files SET
+                provider_ref = ?2,
+                model_override = ?3,
+                parameters = ?4,
+                tags = ?5,
+                test_data_path = ?6,
+                description = ?7,
+                evaluation_config = ?8,
+                updated_at = ?9
+             WHERE file_path = ?1",
+            params![
+                file_path,
+                config.provider_ref,
+                config.model_override,
+                config.parameters,
+                config.tags,
+                config.test_data_path,
+                config.description,
+                config.evaluation_config,
+                now,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Run `f` in one transaction, rolled back when it fails
+    /// `f` goes through this database, so its statements join the transaction
+    pub fn in_transaction<T>(&self, f: impl FnOnce() -> std::result::Result<T, String>) -> std::result::Result<T, String> {
+        let tx = self.conn.unchecked_transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        let value = f()?;
+        tx.commit().map_err(|e| format!("Failed to commit: {}", e))?;
+        Ok(value)
+    }
+
+    /// Replace the dependencies of one type recorded for a prompt file
+    pub fn replace_file_dependencies(
+        &self,
+        source_file_id: &str,
+        dependency_type: &str,
+        targets: &[String],
+    ) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+
+        self.conn.execute(
+            "DELETE FROM file_dependencies WHERE source_file = ?1 AND dependency_type = ?2",
+            params![source_file_id, dependency_type],
+        )?;
+
+        for target in targets {
+            self.conn.execute(
+                "INSERT INTO file_dependencies (id, source_file, target_file, dependency_type, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![uuid::Uuid::new_v4().to_string(), source_file_id, target, dependency_type, now],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Get the prompt files depending on a file, directly or through other files
+    pub fn get_dependent_files(&self, target_file: &str, dependency_type: &str) -> Result<Vec<String>> {
+        let target_file = self.relative_path(target_file);
+        let mut stmt = self.conn.prepare(
+            "WITH RECURSIVE affected(path) AS (
+                SELECT pf.file_path FROM file_dependencies d
+                JOIN prompt_files pf ON pf.id = d.source_file
+                WHERE d.target_file = ?1 AND d.dependency_type = ?2
+                UNION
+                SELECT pf.file_path FROM file_dependencies d
+                JOIN prompt_files pf ON pf.id = d.source_file
+                JOIN affected a ON d.target_file = a.path
+                WHERE d.dependency_type = ?2
+            )
+            SELECT path FROM affected WHERE path != ?1 ORDER BY path"
+        )?;
+
+        let paths = stmt.query_map(params![target_file, dependency_type], |row| row.get(0))?;
+        paths.collect()
+    }
+
+    /// Find the prompt_files id for a path without creating it
+    pub fn find_prompt_file_id(&self, file_path: &str) -> Result<Option<String>> {
+        let file_path = self.relative_path(file_path);
+        let id = self.conn.query_row(
+            "SELECT id FROM prompt_files WHERE file_path = ?1",
+            params![file_path],
+            |row| row.get(0),
+        ).ok();
+
+        Ok(id)
+    }
+
+    /// Path of a prompt_files record
+    pub fn get_prompt_file_path(&self, id: &str) -> Result<Option<String>> {
+        self.conn.query_row(
+            "SELECT file_path FROM prompt_files WHERE id = ?1",
+            params![id],
+            |row| row.get(0),
+        ).optional()
+    }
+
+    /// Ensure a prompt file record exists (create if not)
+    pub fn ensure_prompt_file(&self, file_path: &str) -> Result<String> {
+        let file_path = self.relative_path(file_path);
+        // Check if exists
+        let existing_id: Option<String> = self.conn.query_row(
+            "SELECT id FROM prompt_files WHERE file_path = ?1",
+            params![file_path],
+            |row| row.get(0),
+        ).ok();
+        
+        if let Some(id) = existing_id {
+            return Ok(id);
+        }
+        
+        // Create new record
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        let id = uuid::Uuid::new_v4().to_string();
+        let name = std::path::Path::new(&file_path)
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        
+        self.conn.execute(
+            "INSERT INTO prompt_files (
+                id, file_path, name, schema_version, provider_ref,
+                file_hash, file_size, last_modified, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                id,
+                file_path,
+                name,
+                "v1",
+                "default",
+                "",
+                0i64,
+                now,
+                now,
+                now,
+            ],
+        )?;
+        
+        Ok(id)
+    }
+
+    pub fn get_prompt_metadata(&self, file_path: &str) -> Result<PromptFileMetadata> {
+        let file_path = self.relative_path(file_path);
+        self.conn.query_row(
+            "SELECT id, file_path, name, description, schema_version,
+                    provider_ref, model_override, parameters,
+                    test_data_path, evaluation_config,
+                    tags, variables,
+                    file_hash, file_size, last_modified,
+                    last_validated, validation_status, validation_errors
+             FROM prompt_files WHERE file_path = ?1",
+            params![file_path],
+            |row| {
+                Ok(PromptFileMetadata {
+                    id: row.get(0)?,
+                    file_path: row.get(1)?,
+                    name: row.get(2)?,
+                    description: row.get(3)?,
+                    schema_version: row.get(4)?,
+                    provider_ref: row.get(5)?,
+                    model_override: row.get(6)?,
+                    parameters: row.get(7)?,
+                    test_data_path: row.get(8)?,
+                    evaluation_config: row.get(9)?,
+                    tags: row.get(10)?,
+                    variables: row.get(11)?,
+                    file_hash: row.get(12)?,
+                    file_size: row.get(13)?,
                     last_modified: row.get(14)?,
                     last_validated: row.get(15)?,
                     validation_status: row.get(16)?,
@@ -476,78 +1146,1179 @@ impl ProjectDatabase {
         )
     }
 
-    pub fn list_prompt_files(&self) -> Result<Vec<PromptFileMetadata>> {
+    pub fn list_prompt_files(&self) -> Result<Vec<PromptFileMetadata>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, file_path, name, description, schema_version,
+                    provider_ref, model_override, parameters,
+                    test_data_path, evaluation_config,
+                    tags, variables,
+                    file_hash, file_size, last_modified,
+                    last_validated, validation_status, validation_errors
+             FROM prompt_files ORDER BY file_path"
+        )?;
+
+        let files = stmt.query_map([], |row| {
+            Ok(PromptFileMetadata {
+                id: row.get(0)?,
+                file_path: row.get(1)?,
+                name: row.get(2)?,
+                description: row.get(3)?,
+                schema_version: row.get(4)?,
+                provider_ref: row.get(5)?,
+                model_override: row.get(6)?,
+                parameters: row.get(7)?,
+                test_data_path: row.get(8)?,
+                evaluation_config: row.get(9)?,
+                tags: row.get(10)?,
+                variables: row.get(11)?,
+                file_hash: row.get(12)?,
+                file_size: row.get(13)?,
+                last_modified: row.get(14)?,
+                last_validated: row.get(15)?,
+                validation_status: row.get(16)?,
+                validation_errors: row.get(17)?,
+            })
+        })?;
+
+        files.collect()
+    }
+
+    /// Get all unique tags from all prompt files in the workspace
+    pub fn get_all_tags(&self) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT tags FROM prompt_files WHERE tags IS NOT NULL"
+        )?;
+
+        let tags_rows = stmt.query_map([], |row| {
+            let tags_json: String = row.get(0)?;
+            Ok(tags_json)
+        })?;
+
+        let mut all_tags = std::collections::HashSet::new();
+        
+        for tags_row in tags_rows {
+            if let Ok(tags_json) = tags_row {
+                // Parse JSON array of tags
+                if let Ok(tags) = serde_json::from_str::<Vec<String>>(&tags_json) {
+                    for tag in tags {
+                        all_tags.insert(tag);
+                    }
+                }
+            }
+        }
+
+        let mut result: Vec<String> = all_tags.into_iter().collect();
+        result.sort();
+        Ok(result)
+    }
+
+    /// Save an execution to execution_history
+    pub fn save_execution(&self, record: &ExecutionRecord) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO execution_history (
+                id, prompt_file_id, prompt_name, llm_provider_name,
+                input_variables, output,
+                model, provider, latency_ms, tokens_input, tokens_output, cost_usd, pricing_version,
+                rendered_messages, parameters, run_mode,
+                timestamp, git_commit, git_branch, file_history_id, input_files, dynamic_variables, pricing_id,
+                prompt_messages, variable_profile
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)",
+            params![
+                record.id,
+                record.prompt_file_id,
+                record.prompt_name,
+                record.llm_provider_name,
+                record.input_variables,
+                record.output,
+                record.model,
+                record.provider,
+                record.latency_ms,
+                record.tokens_input,
+                record.tokens_output,
+                record.cost_usd,
+                record.pricing_version,
+                record.rendered_messages,
+                record.parameters,
+                record.run_mode,
+                record.timestamp,
+                record.git_commit,
+                record.git_branch,
+                record.file_history_id,
+                record.input_files,
+                record.dynamic_variables,
+                record.pricing_id,
+                record.prompt_messages,
+                record.variable_profile,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Get executions, newest first, optionally filtered by prompt file
+    pub fn get_executions(
+        &self,
+        prompt_file_id: Option<&str>,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<ExecutionRecord>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM execution_history
+             WHERE (?1 IS NULL OR prompt_file_id = ?1)
+             ORDER BY timestamp DESC, id DESC
+             LIMIT ?2 OFFSET ?3",
+            EXECUTION_COLUMNS
+        ))?;
+
+        let records = stmt.query_map(
+            params![prompt_file_id, limit as i64, offset as i64],
+            Self::map_execution_record,
+        )?;
+
+        records.collect()
+    }
+
+    /// Count executions, optionally filtered by prompt file
+    pub fn count_executions(&self, prompt_file_id: Option<&str>) -> Result<i64> {
+        self.conn.query_row(
+            "SELECT COUNT(*) FROM execution_history WHERE (?1 IS NULL OR prompt_file_id = ?1)",
+            params![prompt_file_id],
+            |row| row.get(0),
+        )
+    }
+
+    pub fn get_execution(&self, execution_id: &str) -> Result<ExecutionRecord> {
+        self.conn.query_row(
+            &format!("SELECT {} FROM execution_history WHERE id = ?1", EXECUTION_COLUMNS),
+            params![execution_id],
+            Self::map_execution_record,
+        )
+    }
+
+    fn relative_filter(&self, filter: &ExecutionFilter) -> ExecutionFilter {
+        ExecutionFilter {
+            file_path: filter.file_path.as_deref().map(|path| self.relative_path(path)),
+            ..filter.clone()
+        }
+    }
+
+    /// Query executions with filters, sorting and keyset (cursor) pagination
+    pub fn query_executions(
+        &self,
+        filter: &ExecutionFilter,
+        sort: ExecutionSort,
+        cursor: Option<&str>,
+        limit: usize,
+    ) -> Result<ExecutionQueryPage> {
+        let filter = self.relative_filter(filter);
+        let (where_sql, mut values) = filter.to_sql();
+
+        let total: i64 = {
+            let refs: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
+            self.conn.query_row(
+                &format!("SELECT COUNT(*) FROM execution_history WHERE {}", where_sql),
+                refs.as_slice(),
+                |row| row.get(0),
+            )?
+        };
+
+        let column = sort.field.column();
+        let (cmp, order) = if sort.descending { ("<", "DESC") } else { (">", "ASC") };
+
+        // Rows strictly after the cursor in (sort value, id) order
+        let mut cursor_sql = String::new();
+        if let Some((value, id)) = cursor.and_then(decode_cursor) {
+            cursor_sql = format!(" AND ({col} {cmp} ? OR ({col} = ? AND id {cmp} ?))", col = column, cmp = cmp);
+            values.push(Box::new(value));
+            values.push(Box::new(value));
+            values.push(Box::new(id));
+        }
+        // Fetch one extra row to know whether there is a next page
+        values.push(Box::new(limit as i64 + 1));
+
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {cols} FROM execution_history
+             WHERE {filter}{cursor}
+             ORDER BY {col} {order}, id {order}
+             LIMIT ?",
+            cols = EXECUTION_COLUMNS,
+            filter = where_sql,
+            cursor = cursor_sql,
+            col = column,
+            order = order,
+        ))?;
+
+        let refs: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        let mut items = stmt
+            .query_map(refs.as_slice(), Self::map_execution_record)?
+            .collect::<Result<Vec<_>>>()?;
+
+        let next_cursor = if items.len() > limit {
+            items.truncate(limit);
+            items.last().map(|last| encode_cursor(sort.field.value_of(last), &last.id))
+        } else {
+            None
+        };
+
+        Ok(ExecutionQueryPage { items, next_cursor, total })
+    }
+
+    /// Aggregate executions (count, cost, latency percentiles) per group
+    /// Percentiles use the nearest-rank method, computed with window functions
+    pub fn aggregate_executions(
+        &self,
+        filter: &ExecutionFilter,
+        group_by: Option<ExecutionGroupBy>,
+    ) -> Result<Vec<ExecutionAggregate>> {
+        let filter = self.relative_filter(filter);
+        let (where_sql, values) = filter.to_sql();
+        let (key_expr, label_expr) = match group_by {
+            Some(ExecutionGroupBy::Day) => ("date(timestamp, 'unixepoch', 'localtime')", "date(timestamp, 'unixepoch', 'localtime')"),
+            Some(ExecutionGroupBy::Model) => ("model", "model"),
+            Some(ExecutionGroupBy::Prompt) => ("prompt_file_id", "prompt_name"),
+            None => ("'all'", "'all'"),
+        };
+
+        let mut stmt = self.conn.prepare(&format!(
+            "WITH ranked AS (
+                SELECT {key} AS group_key, {label} AS label,
+                       latency_ms, cost_usd, tokens_input, tokens_output,
+                       ROW_NUMBER() OVER (PARTITION BY {key} ORDER BY latency_ms) AS rn,
+                       COUNT(*) OVER (PARTITION BY {key}) AS cnt
+                FROM execution_history
+                WHERE {filter}
+            )
+            SELECT group_key, MAX(label), COUNT(*), SUM(cost_usd),
+                   AVG(latency_ms),
+                   MAX(CASE WHEN rn = (cnt * 50 + 99) / 100 THEN latency_ms END),
+                   MAX(CASE WHEN rn = (cnt * 95 + 99) / 100 THEN latency_ms END),
+                   SUM(tokens_input), SUM(tokens_output)
+            FROM ranked
+            GROUP BY group_key
+            ORDER BY group_key",
+            key = key_expr,
+            label = label_expr,
+            filter = where_sql,
+        ))?;
+
+        let refs: Vec<&dyn rusqlite::ToSql> = values.iter().map(|v| v.as_ref()).collect();
+        let aggregates = stmt.query_map(refs.as_slice(), |row| {
+            Ok(ExecutionAggregate {
+                group_key: row.get(0)?,
+                label: row.get(1)?,
+                count: row.get(2)?,
+                total_cost_usd: row.get(3)?,
+                avg_latency_ms: row.get(4)?,
+                p50_latency_ms: row.get(5)?,
+                p95_latency_ms: row.get(6)?,
+                tokens_input: row.get(7)?,
+                tokens_output: row.get(8)?,
+            })
+        })?;
+
+        aggregates.collect()
+    }
+
+    /// Save a replayed execution paired with its source
+    pub fn save_execution_replay(
+        &self,
+        batch_id: &str,
+        source_execution_id: &str,
+        replay_execution_id: &str,
+        diff_summary: &str,  // JSON
+    ) -> Result<String> {
+        let id = uuid::Uuid::new_v4().to_string();
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+
+        self.conn.execute(
+            "INSERT INTO execution_replays (id, batch_id, source_execution_id, replay_execution_id, diff_summary, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![id, batch_id, source_execution_id, replay_execution_id, diff_summary, now],
+        )?;
+
+        Ok(id)
+    }
+
+    /// Get replays by batch and/or source execution, newest first
+    pub fn get_execution_replays(
+        &self,
+        batch_id: Option<&str>,
+        source_execution_id: Option<&str>,
+    ) -> Result<Vec<ExecutionReplay>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, batch_id, source_execution_id, replay_execution_id, diff_summary, created_at
+             FROM execution_replays
+             WHERE (?1 IS NULL OR batch_id = ?1) AND (?2 IS NULL OR source_execution_id = ?2)
+             ORDER BY created_at DESC"
+        )?;
+
+        let replays = stmt.query_map(params![batch_id, source_execution_id], |row| {
+            Ok(ExecutionReplay {
+                id: row.get(0)?,
+                batch_id: row.get(1)?,
+                source_execution_id: row.get(2)?,
+                replay_execution_id: row.get(3)?,
+                diff_summary: row.get(4)?,
+                created_at: row.get(5)?,
+            })
+        })?;
+
+        replays.collect()
+    }
+
+    /// Point history, metadata and dependencies of a file at its new path
+    /// Used when a prompt file is converted to another file (see services::yaml_migration)
+    pub fn move_file_records(&self, old_path: &str, new_path: &str) -> Result<()> {
+        let (old_path, new_path) = (self.relative_path(old_path), self.relative_path(new_path));
+        self.conn.execute(
+            "UPDATE file_history SET file_path = ?2 WHERE file_path = ?1",
+            params![old_path, new_path],
+        )?;
+        self.conn.execute(
+            "UPDATE prompt_files SET file_path = ?2
+             WHERE file_path = ?1 AND NOT EXISTS (SELECT 1 FROM prompt_files WHERE file_path = ?2)",
+            params![old_path, new_path],
+        )?;
+        self.conn.execute(
+            "UPDATE file_dependencies SET target_file = ?2 WHERE target_file = ?1",
+            params![old_path, new_path],
+        )?;
+
+        Ok(())
+    }
+
+    pub fn delete_history_entry(&self, history_id: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM file_history WHERE id = ?1", params![history_id])?;
+        Ok(())
+    }
+
+    pub fn save_prompt_migration(&self, migration: &PromptMigration) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO prompt_migrations (
+                id, batch_id, source_path, target_path, source_content, target_content,
+                target_history_id, source_config, created_at, undone_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                migration.id,
+                migration.batch_id,
+                migration.source_path,
+                migration.target_path,
+                migration.source_content,
+                migration.target_content,
+                migration.target_history_id,
+                migration.source_config,
+                migration.created_at,
+                migration.undone_at,
+            ],
+        )?;
+
+        Ok(())
+    }
+
+    /// Migrations of a batch that haven't been undone (the latest batch when none is given)
+    pub fn get_prompt_migrations(&self, batch_id: Option<&str>) -> Result<Vec<PromptMigration>> {
+        let batch_id: Option<String> = match batch_id {
+            Some(batch_id) => Some(batch_id.to_string()),
+            None => self.conn.query_row(
+                "SELECT batch_id FROM prompt_migrations WHERE undone_at IS NULL
+                 ORDER BY created_at DESC, rowid DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            ).ok(),
+        };
+        let batch_id = match batch_id {
+            Some(batch_id) => batch_id,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, batch_id, source_path, target_path, source_content, target_content,
+                    target_history_id, source_config, created_at, undone_at
+             FROM prompt_migrations
+             WHERE batch_id = ?1 AND undone_at IS NULL
+             ORDER BY rowid"
+        )?;
+
+        let migrations = stmt.query_map(params![batch_id], |row| {
+            Ok(PromptMigration {
+                id: row.get(0)?,
+                batch_id: row.get(1)?,
+                source_path: row.get(2)?,
+                target_path: row.get(3)?,
+                source_content: row.get(4)?,
+                target_content: row.get(5)?,
+                target_history_id: row.get(6)?,
+                source_config: row.get(7)?,
+                created_at: row.get(8)?,
+                undone_at: row.get(9)?,
+            })
+        })?;
+
+        migrations.collect()
+    }
+
+    pub fn mark_prompt_migration_undone(&self, id: &str) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        self.conn.execute(
+            "UPDATE prompt_migrations SET undone_at = ?2 WHERE id = ?1",
+            params![id, now],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_lint_rule_settings(&self) -> Result<Vec<LintRuleSetting>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT rule_id, severity, options, updated_at FROM lint_rule_settings ORDER BY rule_id"
+        )?;
+
+        let settings = stmt.query_map([], |row| {
+            Ok(LintRuleSetting {
+                rule_id: row.get(0)?,
+                severity: row.get(1)?,
+                options: row.get(2)?,
+                updated_at: row.get(3)?,
+            })
+        })?;
+
+        settings.collect()
+    }
+
+    pub fn save_lint_rule_setting(&self, rule_id: &str, severity: &str, options: Option<&str>) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+
+        self.conn.execute(
+            "INSERT INTO lint_rule_settings (rule_id, severity, options, updated_at)
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(rule_id) DO UPDATE SET severity = ?2, options = ?3, updated_at = ?4",
+            params![rule_id, severity, options, now],
+        )?;
+
+        Ok(())
+    }
+
+    /// Go back to the rule's defaults
+    pub fn delete_lint_rule_setting(&self, rule_id: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM lint_rule_settings WHERE rule_id = ?1", params![rule_id])?;
+        Ok(())
+    }
+
+    /// Save a finished flow run with its step trace
+    pub fn save_flow_run(&self, run: &FlowRun, steps: &[FlowRunStep]) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO flow_runs (
+                id, flow_path, flow_name, inputs, status, error,
+                total_tokens_input, total_tokens_output, total_cost_usd, total_latency_ms, created_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                run.id,
+                run.flow_path,
+                run.flow_name,
+                run.inputs,
+                run.status,
+                run.error,
+                run.total_tokens_input,
+                run.total_tokens_output,
+                run.total_cost_usd,
+                run.total_latency_ms,
+                run.created_at,
+            ],
+        )?;
+
+        for step in steps {
+            self.conn.execute(
+                "INSERT INTO flow_run_steps (
+                    id, flow_run_id, position, step_id, prompt_path, status, execution_id, variables, outputs, error
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    step.id,
+                    run.id,
+                    step.position,
+                    step.step_id,
+                    step.prompt_path,
+                    step.status,
+                    step.execution_id,
+                    step.variables,
+                    step.outputs,
+                    step.error,
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Flow runs, newest first, optionally for one flow file
+    pub fn get_flow_runs(&self, flow_path: Option<&str>, limit: usize) -> Result<Vec<FlowRun>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, flow_path, flow_name, inputs, status, error,
+                    total_tokens_input, total_tokens_output, total_cost_usd, total_latency_ms, created_at
+             FROM flow_runs
+             WHERE (?1 IS NULL OR flow_path = ?1)
+             ORDER BY created_at DESC
+             LIMIT ?2"
+        )?;
+
+        let runs = stmt.query_map(params![flow_path, limit as i64], Self::flow_run_from_row)?;
+        runs.collect()
+    }
+
+    pub fn get_flow_run(&self, id: &str) -> Result<FlowRun> {
+        self.conn.query_row(
+            "SELECT id, flow_path, flow_name, inputs, status, error,
+                    total_tokens_input, total_tokens_output, total_cost_usd, total_latency_ms, created_at
+             FROM flow_runs WHERE id = ?1",
+            params![id],
+            Self::flow_run_from_row,
+        )
+    }
+
+    fn flow_run_from_row(row: &rusqlite::Row) -> Result<FlowRun> {
+        Ok(FlowRun {
+            id: row.get(0)?,
+            flow_path: row.get(1)?,
+            flow_name: row.get(2)?,
+            inputs: row.get(3)?,
+            status: row.get(4)?,
+            error: row.get(5)?,
+            total_tokens_input: row.get(6)?,
+            total_tokens_output: row.get(7)?,
+            total_cost_usd: row.get(8)?,
+            total_latency_ms: row.get(9)?,
+            created_at: row.get(10)?,
+        })
+    }
+
+    /// Steps of a flow run in execution order
+    pub fn get_flow_run_steps(&self, flow_run_id: &str) -> Result<Vec<FlowRunStep>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, flow_run_id, position, step_id, prompt_path, status, execution_id, variables, outputs, error
+             FROM flow_run_steps WHERE flow_run_id = ?1 ORDER BY position"
+        )?;
+
+        let steps = stmt.query_map(params![flow_run_id], |row| {
+            Ok(FlowRunStep {
+                id: row.get(0)?,
+                flow_run_id: row.get(1)?,
+                position: row.get(2)?,
+                step_id: row.get(3)?,
+                prompt_path: row.get(4)?,
+                status: row.get(5)?,
+                execution_id: row.get(6)?,
+                variables: row.get(7)?,
+                outputs: row.get(8)?,
+                error: row.get(9)?,
+            })
+        })?;
+
+        steps.collect()
+    }
+
+    pub fn get_project_variables(&self) -> Result<Vec<StoredVariable>> {
+        let mut stmt = self.conn.prepare("SELECT id, key, value, secret FROM project_variables ORDER BY key")?;
+        let variables = stmt.query_map([], AppDatabase::map_stored_variable)?;
+
+        variables.collect()
+    }
+
+    /// Replace all project variables
+    pub fn save_project_variables(&self, variables: &[StoredVariable]) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        let tx = self.conn.unchecked_transaction()?;
+
+        tx.execute("DELETE FROM project_variables", [])?;
+        for variable in variables {
+            let id = if variable.id.is_empty() { uuid::Uuid::new_v4().to_string() } else { variable.id.clone() };
+            tx.execute(
+                "INSERT INTO project_variables (id, key, value, secret, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?5)",
+                params![id, variable.key, variable.value, variable.secret as i32, now],
+            )?;
+        }
+
+        tx.commit()
+    }
+
+    pub fn get_variable_profiles(&self) -> Result<Vec<VariableProfile>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, description, created_at, updated_at FROM variable_profiles ORDER BY name"
+        )?;
+
+        let profiles = stmt.query_map([], |row| {
+            Ok(VariableProfile {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                description: row.get(2)?,
+                created_at: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        })?;
+
+        profiles.collect()
+    }
+
+    /// Variables of a profile, by profile name
+    pub fn get_profile_variables(&self, profile_name: &str) -> Result<Vec<StoredVariable>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT pv.id, pv.key, pv.value, pv.secret
+             FROM profile_variables pv
+             JOIN variable_profiles p ON p.id = pv.profile_id
+             WHERE p.name = ?1
+             ORDER BY pv.key"
+        )?;
+
+        let variables = stmt.query_map(params![profile_name], AppDatabase::map_stored_variable)?;
+
+        variables.collect()
+    }
+
+    /// Create or update a profile and replace its variables
+    pub fn save_variable_profile(
+        &self,
+        name: &str,
+        description: Option<&str>,
+        variables: &[StoredVariable],
+    ) -> Result<String> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+        let tx = self.conn.unchecked_transaction()?;
+
+        tx.execute(
+            "INSERT INTO variable_profiles (id, name, description, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?4)
+             ON CONFLICT(name) DO UPDATE SET description = ?3, updated_at = ?4",
+            params![uuid::Uuid::new_v4().to_string(), name, description, now],
+        )?;
+        let profile_id: String = tx.query_row(
+            "SELECT id FROM variable_profiles WHERE name = ?1",
+            params![name],
+            |row| row.get(0),
+        )?;
+
+        tx.execute("DELETE FROM profile_variables WHERE profile_id = ?1", params![profile_id])?;
+        for variable in variables {
+            let id = if variable.id.is_empty() { uuid::Uuid::new_v4().to_string() } else { variable.id.clone() };
+            tx.execute(
+                "INSERT INTO profile_variables (id, profile_id, key, value, secret, created_at, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+                params![id, profile_id, variable.key, variable.value, variable.secret as i32, now],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(profile_id)
+    }
+
+    pub fn delete_variable_profile(&self, name: &str) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM profile_variables
+             WHERE profile_id IN (SELECT id FROM variable_profiles WHERE name = ?1)",
+            params![name],
+        )?;
+        tx.execute("DELETE FROM variable_profiles WHERE name = ?1", params![name])?;
+        tx.commit()
+    }
+
+    /// Insert or update a registered dataset
+    pub fn save_test_dataset(&self, dataset: &TestDataset) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO test_datasets (id, name, file_path, format, row_count, columns, content_hash, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+             ON CONFLICT(id) DO UPDATE SET
+                name = ?2, file_path = ?3, format = ?4, row_count = ?5, columns = ?6,
+                content_hash = ?7, updated_at = ?9",
+            params![
+                dataset.id,
+                dataset.name,
+                dataset.file_path,
+                dataset.format,
+                dataset.row_count,
+                dataset.columns,
+                dataset.content_hash,
+                dataset.created_at,
+                dataset.updated_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_test_datasets(&self) -> Result<Vec<TestDataset>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, name, file_path, format, row_count, columns, content_hash, created_at, updated_at
+             FROM test_datasets ORDER BY name"
+        )?;
+
+        let datasets = stmt.query_map([], Self::test_dataset_from_row)?;
+        datasets.collect()
+    }
+
+    pub fn get_test_dataset(&self, id: &str) -> Result<TestDataset> {
+        self.conn.query_row(
+            "SELECT id, name, file_path, format, row_count, columns, content_hash, created_at, updated_at
+             FROM test_datasets WHERE id = ?1",
+            params![id],
+            Self::test_dataset_from_row,
+        )
+    }
+
+    /// Dataset registered for a workspace-relative file path
+    pub fn find_test_dataset(&self, file_path: &str) -> Result<Option<TestDataset>> {
+        self.conn.query_row(
+            "SELECT id, name, file_path, format, row_count, columns, content_hash, created_at, updated_at
+             FROM test_datasets WHERE file_path = ?1",
+            params![file_path],
+            Self::test_dataset_from_row,
+        ).optional()
+    }
+
+    fn test_dataset_from_row(row: &rusqlite::Row) -> Result<TestDataset> {
+        Ok(TestDataset {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            file_path: row.get(2)?,
+            format: row.get(3)?,
+            row_count: row.get::<_, Option<i64>>(4)?.unwrap_or(0),
+            columns: row.get(5)?,
+            content_hash: row.get(6)?,
+            created_at: row.get(7)?,
+            updated_at: row.get(8)?,
+        })
+    }
+
+    /// Unregister a dataset and drop its test results (the file is kept)
+    pub fn delete_test_dataset(&self, id: &str) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM evaluation_results
+             WHERE matrix_run_id IN (SELECT id FROM matrix_runs WHERE dataset_id = ?1)",
+            params![id],
+        )?;
+        tx.execute("DELETE FROM test_results WHERE dataset_id = ?1", params![id])?;
+        tx.execute("DELETE FROM matrix_runs WHERE dataset_id = ?1", params![id])?;
+        tx.execute("DELETE FROM test_datasets WHERE id = ?1", params![id])?;
+        tx.commit()
+    }
+
+    /// Insert or update a matrix run
+    pub fn save_matrix_run(&self, run: &MatrixRun) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO matrix_runs (
+                id, prompt_path, prompt_file_id, dataset_id, row_indices, models, concurrency, status,
+                total_cells, completed_cells, failed_cells, total_cost_usd, error, created_at, updated_at,
+                prompt_content, prompt_runtime
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+            ON CONFLICT(id) DO UPDATE SET
+                concurrency = ?7, status = ?8, total_cells = ?9, completed_cells = ?10, failed_cells = ?11,
+                total_cost_usd = ?12, error = ?13, updated_at = ?15",
+            params![
+                run.id,
+                run.prompt_path,
+                run.prompt_file_id,
+                run.dataset_id,
+                run.row_indices,
+                run.models,
+                run.concurrency,
+                run.status,
+                run.total_cells,
+                run.completed_cells,
+                run.failed_cells,
+                run.total_cost_usd,
+                run.error,
+                run.created_at,
+                run.updated_at,
+                run.prompt_content,
+                run.prompt_runtime,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Matrix runs, newest first
+    pub fn get_matrix_runs(&self, limit: usize) -> Result<Vec<MatrixRun>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, prompt_path, prompt_file_id, dataset_id, row_indices, models, concurrency, status,
+                    total_cells, completed_cells, failed_cells, total_cost_usd, error, created_at, updated_at,
+                    prompt_content, prompt_runtime
+             FROM matrix_runs ORDER BY created_at DESC LIMIT ?1"
+        )?;
+
+        let runs = stmt.query_map(params![limit as i64], Self::matrix_run_from_row)?;
+        runs.collect()
+    }
+
+    pub fn get_matrix_run(&self, id: &str) -> Result<MatrixRun> {
+        self.conn.query_row(
+            "SELECT id, prompt_path, prompt_file_id, dataset_id, row_indices, models, concurrency, status,
+                    total_cells, completed_cells, failed_cells, total_cost_usd, error, created_at, updated_at,
+                    prompt_content, prompt_runtime
+             FROM matrix_runs WHERE id = ?1",
+            params![id],
+            Self::matrix_run_from_row,
+        )
+    }
+
+    fn matrix_run_from_row(row: &rusqlite::Row) -> Result<MatrixRun> {
+        Ok(MatrixRun {
+            id: row.get(0)?,
+            prompt_path: row.get(1)?,
+            prompt_file_id: row.get(2)?,
+            dataset_id: row.get(3)?,
+            row_indices: row.get(4)?,
+            models: row.get(5)?,
+            concurrency: row.get(6)?,
+            status: row.get(7)?,
+            total_cells: row.get(8)?,
+            completed_cells: row.get(9)?,
+            failed_cells: row.get(10)?,
+            total_cost_usd: row.get(11)?,
+            error: row.get(12)?,
+            created_at: row.get(13)?,
+            updated_at: row.get(14)?,
+            prompt_content: row.get(15)?,
+            prompt_runtime: row.get(16)?,
+        })
+    }
+
+    /// Save a matrix cell, replacing an earlier attempt of the same cell
+    pub fn save_test_result(&self, result: &TestResult) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM evaluation_results WHERE execution_id IN (
+                SELECT id FROM test_results
+                WHERE matrix_run_id = ?1 AND test_case_index = ?2 AND llm_provider_name = ?3 AND model = ?4
+            )",
+            params![result.matrix_run_id, result.test_case_index, result.llm_provider_name, result.model],
+        )?;
+        tx.execute(
+            "DELETE FROM test_results
+             WHERE matrix_run_id = ?1 AND test_case_index = ?2 AND llm_provider_name = ?3 AND model = ?4",
+            params![result.matrix_run_id, result.test_case_index, result.llm_provider_name, result.model],
+        )?;
+        tx.execute(
+            "INSERT INTO test_results (
+                id, prompt_file_id, dataset_id, test_case_index, input_variables, output,
+                model, provider, latency_ms, tokens_input, tokens_output, cost_usd, timestamp,
+                matrix_run_id, llm_provider_name, error
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+            params![
+                result.id,
+                result.prompt_file_id,
+                result.dataset_id,
+                result.test_case_index,
+                result.input_variables,
+                result.output,
+                result.model,
+                result.provider,
+                result.latency_ms,
+                result.tokens_input,
+                result.tokens_output,
+                result.cost_usd,
+                result.timestamp,
+                result.matrix_run_id,
+                result.llm_provider_name,
+                result.error,
+            ],
+        )?;
+        tx.commit()
+    }
+
+    /// Cells of a matrix run, by row then model
+    pub fn get_test_results(&self, matrix_run_id: &str) -> Result<Vec<TestResult>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, prompt_file_id, dataset_id, test_case_index, input_variables, output,
+                    model, provider, latency_ms, tokens_input, tokens_output, cost_usd, timestamp,
+                    matrix_run_id, llm_provider_name, error
+             FROM test_results WHERE matrix_run_id = ?1
+             ORDER BY test_case_index, llm_provider_name, model"
+        )?;
+
+        let results = stmt.query_map(params![matrix_run_id], |row| {
+            Ok(TestResult {
+                id: row.get(0)?,
+                prompt_file_id: row.get(1)?,
+                dataset_id: row.get(2)?,
+                test_case_index: row.get(3)?,
+                input_variables: row.get(4)?,
+                output: row.get(5)?,
+                model: row.get(6)?,
+                provider: row.get(7)?,
+                latency_ms: row.get(8)?,
+                tokens_input: row.get(9)?,
+                tokens_output: row.get(10)?,
+                cost_usd: row.get(11)?,
+                timestamp: row.get(12)?,
+                matrix_run_id: row.get(13)?,
+                llm_provider_name: row.get(14)?,
+                error: row.get(15)?,
+            })
+        })?;
+
+        results.collect()
+    }
+
+    /// Save the evaluator outcomes of an execution or matrix cell
+    pub fn save_evaluation_results(&self, results: &[EvaluationResult]) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for result in results {
+            tx.execute(
+                "INSERT INTO evaluation_results (
+                    id, execution_id, evaluator_name, evaluator_file_path, score, reasoning, passed,
+                    timestamp, evaluator_type, prompt_file_id, model, matrix_run_id,
+                    judge_model, judge_tokens_input, judge_tokens_output, judge_cost_usd
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+                params![
+                    result.id,
+                    result.execution_id,
+                    result.evaluator_name,
+                    result.evaluator_file_path,
+                    result.score,
+                    result.reasoning,
+                    result.passed,
+                    result.timestamp,
+                    result.evaluator_type,
+                    result.prompt_file_id,
+                    result.model,
+                    result.matrix_run_id,
+                    result.judge_model,
+                    result.judge_tokens_input,
+                    result.judge_tokens_output,
+                    result.judge_cost_usd,
+                ],
+            )?;
+        }
+        tx.commit()
+    }
+
+    /// Evaluator outcomes of an execution (or matrix cell), in the order they were saved
+    pub fn get_evaluation_results(&self, execution_id: &str) -> Result<Vec<EvaluationResult>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, execution_id, evaluator_name, evaluator_file_path, score, reasoning, passed,
+                    timestamp, evaluator_type, prompt_file_id, model, matrix_run_id,
+                    judge_model, judge_tokens_input, judge_tokens_output, judge_cost_usd
+             FROM evaluation_results WHERE execution_id = ?1 ORDER BY rowid"
+        )?;
+
+        let results = stmt.query_map(params![execution_id], Self::evaluation_result_from_row)?;
+        results.collect()
+    }
+
+    /// Evaluator outcomes of every cell of a matrix run
+    pub fn get_run_evaluation_results(&self, matrix_run_id: &str) -> Result<Vec<EvaluationResult>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, file_path, name, description, schema_version,
-                    provider_ref, model_override, parameters,
-                    test_data_path, evaluation_config,
-                    tags, variables,
-                    file_hash, file_size, last_modified,
-                    last_validated, validation_status, validation_errors
-             FROM prompt_files ORDER BY file_path"
+            "SELECT id, execution_id, evaluator_name, evaluator_file_path, score, reasoning, passed,
+                    timestamp, evaluator_type, prompt_file_id, model, matrix_run_id,
+                    judge_model, judge_tokens_input, judge_tokens_output, judge_cost_usd
+             FROM evaluation_results WHERE matrix_run_id = ?1 ORDER BY rowid"
         )?;
 
-        let files = stmt.query_map([], |row| {
-            Ok(PromptFileMetadata {
-                id: row.get(0)?,
-                file_path: row.get(1)?,
-                name: row.get(2)?,
-                description: row.get(3)?,
-                schema_version: row.get(4)?,
-                provider_ref: row.get(5)?,
-                model_override: row.get(6)?,
-                parameters: row.get(7)?,
-                test_data_path: row.get(8)?,
-                evaluation_config: row.get(9)?,
-                tags: row.get(10)?,
-                variables: row.get(11)?,
-                file_hash: row.get(12)?,
-                file_size: row.get(13)?,
-                last_modified: row.get(14)?,
-                last_validated: row.get(15)?,
-                validation_status: row.get(16)?,
-                validation_errors: row.get(17)?,
-            })
-        })?;
+        let results = stmt.query_map(params![matrix_run_id], Self::evaluation_result_from_row)?;
+        results.collect()
+    }
 
-        files.collect()
+    fn evaluation_result_from_row(row: &rusqlite::Row) -> Result<EvaluationResult> {
+        Ok(EvaluationResult {
+            id: row.get(0)?,
+            execution_id: row.get(1)?,
+            evaluator_name: row.get(2)?,
+            evaluator_file_path: row.get(3)?,
+            score: row.get(4)?,
+            reasoning: row.get(5)?,
+            passed: row.get(6)?,
+            timestamp: row.get(7)?,
+            evaluator_type: row.get(8)?,
+            prompt_file_id: row.get(9)?,
+            model: row.get(10)?,
+            matrix_run_id: row.get(11)?,
+            judge_model: row.get(12)?,
+            judge_tokens_input: row.get(13)?,
+            judge_tokens_output: row.get(14)?,
+            judge_cost_usd: row.get(15)?,
+        })
     }
 
-    /// Get all unique tags from all prompt files in the workspace
-    pub fn get_all_tags(&self) -> Result<Vec<String>> {
+    /// Remove an execution's earlier results of an evaluator type before it is evaluated again
+    pub fn delete_evaluation_results(&self, execution_id: &str, evaluator_type: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM evaluation_results WHERE execution_id = ?1 AND evaluator_type = ?2",
+            params![execution_id, evaluator_type],
+        )?;
+        Ok(())
+    }
+
+    /// Matrix run a test result belongs to (None for single executions)
+    pub fn find_test_result_run(&self, test_result_id: &str) -> Result<Option<String>> {
+        let run_id = self.conn.query_row(
+            "SELECT matrix_run_id FROM test_results WHERE id = ?1",
+            params![test_result_id],
+            |row| row.get::<_, Option<String>>(0),
+        ).optional()?;
+
+        Ok(run_id.flatten())
+    }
+
+    /// A prompt's evaluation config as mirrored from its frontmatter (JSON)
+    pub fn get_evaluation_config(&self, prompt_file_id: &str) -> Result<Option<String>> {
+        let config = self.conn.query_row(
+            "SELECT evaluation_config FROM prompt_files WHERE id = ?1",
+            params![prompt_file_id],
+            |row| row.get::<_, Option<String>>(0),
+        ).optional()?;
+
+        Ok(config.flatten())
+    }
+
+    /// Insert or update an evaluation rule
+    pub fn save_evaluation_rule(&self, rule: &EvaluationRule) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO evaluation_rules (id, name, description, rule_type, rule_config, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+             ON CONFLICT(id) DO UPDATE SET
+                name = excluded.name,
+                description = excluded.description,
+                rule_type = excluded.rule_type,
+                rule_config = excluded.rule_config,
+                updated_at = excluded.updated_at",
+            params![
+                rule.id,
+                rule.name,
+                rule.description,
+                rule.rule_type,
+                rule.rule_config,
+                rule.created_at,
+                rule.updated_at,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Evaluation rules by name
+    pub fn get_evaluation_rules(&self) -> Result<Vec<EvaluationRule>> {
         let mut stmt = self.conn.prepare(
-            "SELECT DISTINCT tags FROM prompt_files WHERE tags IS NOT NULL"
+            "SELECT id, name, description, rule_type, rule_config, created_at, updated_at
+             FROM evaluation_rules ORDER BY name"
         )?;
 
-        let tags_rows = stmt.query_map([], |row| {
-            let tags_json: String = row.get(0)?;
-            Ok(tags_json)
+        let rules = stmt.query_map([], Self::evaluation_rule_from_row)?;
+        rules.collect()
+    }
+
+    pub fn find_evaluation_rule(&self, name: &str) -> Result<Option<EvaluationRule>> {
+        self.conn.query_row(
+            "SELECT id, name, description, rule_type, rule_config, created_at, updated_at
+             FROM evaluation_rules WHERE name = ?1",
+            params![name],
+            Self::evaluation_rule_from_row,
+        ).optional()
+    }
+
+    pub fn delete_evaluation_rule(&self, id: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM evaluation_rules WHERE id = ?1", params![id])?;
+        Ok(())
+    }
+
+    fn evaluation_rule_from_row(row: &rusqlite::Row) -> Result<EvaluationRule> {
+        Ok(EvaluationRule {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            rule_type: row.get(3)?,
+            rule_config: row.get(4)?,
+            created_at: row.get(5)?,
+            updated_at: row.get(6)?,
+        })
+    }
+
+    /// Pass rates of an evaluator type per prompt × model × matrix run (None: single executions)
+    /// An execution passes when none of its checks failed
+    pub fn get_pass_rates(
+        &self,
+        evaluator_type: &str,
+        prompt_file_id: Option<&str>,
+        matrix_run_id: Option<&str>,
+    ) -> Result<Vec<PassRate>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT e.prompt_file_id, p.file_path, e.model, e.matrix_run_id,
+                    COUNT(DISTINCT e.execution_id),
+                    COUNT(DISTINCT e.execution_id) - COUNT(DISTINCT CASE WHEN e.passed = 0 THEN e.execution_id END),
+                    COUNT(*),
+                    COALESCE(SUM(e.passed = 1), 0)
+             FROM evaluation_results e
+             LEFT JOIN prompt_files p ON p.id = e.prompt_file_id
+             WHERE e.evaluator_type = ?1
+               AND (?2 IS NULL OR e.prompt_file_id = ?2)
+               AND (?3 IS NULL OR e.matrix_run_id = ?3)
+             GROUP BY e.prompt_file_id, e.model, e.matrix_run_id
+             ORDER BY p.file_path, e.model, MAX(e.timestamp) DESC"
+        )?;
+
+        let rates = stmt.query_map(params![evaluator_type, prompt_file_id, matrix_run_id], |row| {
+            let executions: i64 = row.get(4)?;
+            let executions_passed: i64 = row.get(5)?;
+            Ok(PassRate {
+                prompt_file_id: row.get(0)?,
+                prompt_path: row.get(1)?,
+                model: row.get(2)?,
+                matrix_run_id: row.get(3)?,
+                executions,
+                executions_passed,
+                checks: row.get(6)?,
+                checks_passed: row.get(7)?,
+                pass_rate: if executions > 0 { executions_passed as f64 / executions as f64 } else { 0.0 },
+            })
         })?;
 
-        let mut all_tags = std::collections::HashSet::new();
-        
-        for tags_row in tags_rows {
-            if let Ok(tags_json) = tags_row {
-                // Parse JSON array of tags
-                if let Ok(tags) = serde_json::from_str::<Vec<String>>(&tags_json) {
-                    for tag in tags {
-                        all_tags.insert(tag);
-                    }
-                }
-            }
-        }
+        rates.collect()
+    }
 
-        let mut result: Vec<String> = all_tags.into_iter().collect();
-        result.sort();
-        Ok(result)
+    /// Get the file path and content of a history entry
+    pub fn get_history_snapshot(&self, history_id: &str) -> Result<(String, String)> {
+        self.conn.query_row(
+            "SELECT file_path, content FROM file_history WHERE id = ?1",
+            params![history_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
     }
 
-    // Note: Execution history methods (save_execution, get_recent_executions) 
-    // are not currently used but kept for future implementation.
+    fn map_execution_record(row: &rusqlite::Row) -> Result<ExecutionRecord> {
+        Ok(ExecutionRecord {
+            id: row.get(0)?,
+            prompt_file_id: row.get(1)?,
+            prompt_name: row.get(2)?,
+            llm_provider_name: row.get(3)?,
+            input_variables: row.get(4)?,
+            output: row.get(5)?,
+            model: row.get(6)?,
+            provider: row.get(7)?,
+            latency_ms: row.get(8)?,
+            tokens_input: row.get(9)?,
+            tokens_output: row.get(10)?,
+            cost_usd: row.get(11)?,
+            pricing_version: row.get(12)?,
+            rendered_messages: row.get(13)?,
+            parameters: row.get(14)?,
+            run_mode: row.get::<_, Option<String>>(15)?.unwrap_or_else(|| "single".to_string()),
+            timestamp: row.get(16)?,
+            git_commit: row.get(17)?,
+            git_branch: row.get(18)?,
+            file_history_id: row.get(19)?,
+            input_files: row.get(20)?,
+            dynamic_variables: row.get(21)?,
+            pricing_id: row.get(22)?,
+            prompt_messages: row.get(23)?,
+            variable_profile: row.get(24)?,
+        })
+    }
 
     /// Save file history if content has changed
     /// Returns true if a new history entry was created, false if content unchanged
     pub fn save_file_history(&self, file_path: &str, content: &str) -> Result<bool> {
+        let (_, created) = self.snapshot_file_history(file_path, content)?;
+        Ok(created)
+    }
+
+    /// Get the id of the history entry holding this content, creating one if the content is new
+    /// Returns (history id, whether a new entry was created)
+    pub fn snapshot_file_history(&self, file_path: &str, content: &str) -> Result<(String, bool)> {
+        let file_path = self.relative_path(file_path);
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
         
@@ -557,15 +2328,17 @@ impl ProjectDatabase {
         let content_hash = format!("{:x}", hasher.finish());
         
         // Check if the same hash already exists for this file (most recent)
-        let existing: Option<String> = self.conn.query_row(
-            "SELECT content_hash FROM file_history WHERE file_path = ?1 ORDER BY created_at DESC LIMIT 1",
+        let existing: Option<(String, String)> = self.conn.query_row(
+            "SELECT id, content_hash FROM file_history WHERE file_path = ?1 ORDER BY created_at DESC, rowid DESC LIMIT 1",
             params![file_path],
-            |row| row.get(0),
+            |row| Ok((row.get(0)?, row.get(1)?)),
         ).ok();
         
-        if existing.as_ref() == Some(&content_hash) {
-            // Content hasn't changed
-            return Ok(false);
+        if let Some((id, hash)) = existing {
+            if hash == content_hash {
+                // Content hasn't changed
+                return Ok((id, false));
+            }
         }
         
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
@@ -577,11 +2350,12 @@ impl ProjectDatabase {
             params![id, file_path, content, content_hash, now],
         )?;
         
-        Ok(true)
+        Ok((id, true))
     }
     
     /// Get file history entries for a file
     pub fn get_file_history(&self, file_path: &str, limit: usize) -> Result<Vec<FileHistoryEntry>> {
+        let file_path = self.relative_path(file_path);
         let mut stmt = self.conn.prepare(
             "SELECT id, file_path, content_hash, created_at, 
                     substr(content, 1, 200) as preview
@@ -615,6 +2389,7 @@ impl ProjectDatabase {
     
     /// Delete all data related to a file (history, metadata, execution history, etc.)
     pub fn delete_file_related_data(&self, file_path: &str) -> Result<()> {
+        let file_path = self.relative_path(file_path);
         // Delete file history
         self.conn.execute(
             "DELETE FROM file_history WHERE file_path = ?1",
@@ -653,6 +2428,7 @@ impl ProjectDatabase {
         input_variables: &str,  // JSON
         models: &str,           // JSON array
         outputs: &str,          // JSON array
+        file_history_id: Option<String>,
     ) -> Result<String> {
         let id = uuid::Uuid::new_v4().to_string();
         let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
@@ -660,8 +2436,8 @@ impl ProjectDatabase {
         self.conn.execute(
             "INSERT INTO arena_battles (
                 id, prompt_file_id, prompt_content, input_variables,
-                models, outputs, winner_model, votes, timestamp
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                models, outputs, winner_model, votes, timestamp, file_history_id
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
             params![
                 id,
                 prompt_file_id,
@@ -672,6 +2448,7 @@ impl ProjectDatabase {
                 None::<String>,  // winner_model
                 None::<String>,  // votes
                 now,
+                file_history_id,
             ],
         )?;
         
@@ -682,7 +2459,7 @@ impl ProjectDatabase {
     pub fn get_arena_battles(&self, prompt_file_id: Option<&str>, limit: usize) -> Result<Vec<ArenaBattle>> {
         let (query, params): (&str, Vec<Box<dyn rusqlite::ToSql>>) = if let Some(file_id) = prompt_file_id {
             (
-                "SELECT id, prompt_file_id, prompt_content, input_variables, models, outputs, winner_model, votes, timestamp
+                "SELECT id, prompt_file_id, prompt_content, input_variables, models, outputs, winner_model, votes, timestamp, file_history_id
                  FROM arena_battles
                  WHERE prompt_file_id = ?1
                  ORDER BY timestamp DESC
@@ -691,7 +2468,7 @@ impl ProjectDatabase {
             )
         } else {
             (
-                "SELECT id, prompt_file_id, prompt_content, input_variables, models, outputs, winner_model, votes, timestamp
+                "SELECT id, prompt_file_id, prompt_content, input_variables, models, outputs, winner_model, votes, timestamp, file_history_id
                  FROM arena_battles
                  ORDER BY timestamp DESC
                  LIMIT ?1",
@@ -714,6 +2491,7 @@ impl ProjectDatabase {
                 winner_model: row.get(6)?,
                 votes: row.get(7)?,
                 timestamp: row.get(8)?,
+                file_history_id: row.get(9)?,
             })
         })?;
 
@@ -735,6 +2513,404 @@ impl ProjectDatabase {
     }
 }
 
+const EXECUTION_COLUMNS: &str =
+    "id, prompt_file_id, prompt_name, llm_provider_name, input_variables, output,
+     model, provider, latency_ms, tokens_input, tokens_output, cost_usd, pricing_version,
+     rendered_messages, parameters, run_mode, timestamp, git_commit, git_branch, file_history_id,
+     input_files, dynamic_variables, pricing_id, prompt_messages, variable_profile";
+
+/// Persisted execution (execution_history row)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExecutionRecord {
+    pub id: String,
+    pub prompt_file_id: String,
+    pub prompt_name: String,
+    pub llm_provider_name: String,
+    pub input_variables: Option<String>,    // JSON: {"var1": "value1", ...}
+    pub output: String,
+    pub model: String,
+    pub provider: String,
+    pub latency_ms: i64,
+    pub tokens_input: i64,
+    pub tokens_output: i64,
+    pub cost_usd: f64,
+    pub pricing_version: Option<i64>,
+    pub rendered_messages: Option<String>,  // JSON array of {role, content}
+    pub parameters: Option<String>,         // JSON
+    pub run_mode: String,                   // "single" | "arena" | "flow" | "replay" | "matrix"
+    pub timestamp: i64,
+    pub git_commit: Option<String>,
+    pub git_branch: Option<String>,
+    pub file_history_id: Option<String>,    // Prompt version that produced the output
+    pub input_files: Option<String>,        // JSON: files loaded by file variables, with content hashes
+    pub dynamic_variables: Option<String>,  // JSON: {"$now": "...", ...} values resolved at execution time
+    pub pricing_id: Option<String>,         // model_pricing entry of cost_usd, at pricing_version
+    pub prompt_messages: Option<String>,    // JSON: prompt messages before rendering, partials expanded
+    pub variable_profile: Option<String>,   // Variable profile that supplied secret values
+}
+
+/// Replayed execution paired with the execution it re-ran
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ExecutionReplay {
+    pub id: String,
+    pub batch_id: String,
+    pub source_execution_id: String,
+    pub replay_execution_id: String,
+    pub diff_summary: String,  // JSON
+    pub created_at: i64,
+}
+
+/// A .vibe.yaml file converted to .vibe.md, kept so the conversion can be undone
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PromptMigration {
+    pub id: String,
+    pub batch_id: String,
+    pub source_path: String,
+    pub target_path: String,
+    pub source_content: String,
+    pub target_content: String,
+    pub target_history_id: Option<String>,
+    /// JSON PromptFileConfig of the source, restored on undo
+    pub source_config: Option<String>,
+    pub created_at: i64,
+    pub undone_at: Option<i64>,
+}
+
+/// Workspace configuration of a lint rule
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct LintRuleSetting {
+    pub rule_id: String,
+    pub severity: String,  // "off" | "info" | "warning" | "error"
+    pub options: Option<String>,  // JSON
+    pub updated_at: i64,
+}
+
+/// A run of a .vibe.flow.yaml file
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FlowRun {
+    pub id: String,
+    pub flow_path: String,
+    pub flow_name: Option<String>,
+    pub inputs: Option<String>,  // JSON
+    pub status: String,          // "completed" | "failed"
+    pub error: Option<String>,
+    pub total_tokens_input: i64,
+    pub total_tokens_output: i64,
+    pub total_cost_usd: f64,
+    pub total_latency_ms: i64,
+    pub created_at: i64,
+}
+
+/// A step of a flow run, linked to the execution it produced
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct FlowRunStep {
+    pub id: String,
+    pub flow_run_id: String,
+    pub position: i64,
+    pub step_id: String,
+    pub prompt_path: String,
+    pub status: String,                // "completed" | "skipped" | "failed"
+    pub execution_id: Option<String>,
+    pub variables: Option<String>,     // JSON
+    pub outputs: Option<String>,       // JSON
+    pub error: Option<String>,
+}
+
+/// Global, project or profile variable
+/// The value of a secret variable is kept in the keychain, not in the database (see services::secret_variables)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct StoredVariable {
+    #[serde(default)]
+    pub id: String,
+    pub key: String,
+    pub value: String,
+    #[serde(default)]
+    pub secret: bool,
+}
+
+/// Named set of variables overriding the project ones, e.g. `dev` or `customer-acme`
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct VariableProfile {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Dataset file registered for test runs (test_datasets row)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TestDataset {
+    pub id: String,
+    pub name: String,
+    pub file_path: String,              // Workspace-relative
+    pub format: String,                 // "csv" | "jsonl" | "yaml"
+    pub row_count: i64,
+    pub columns: Option<String>,        // JSON array of column names
+    pub content_hash: Option<String>,   // SHA-256 of the indexed content
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Run of a prompt over dataset rows × models (matrix_runs row)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct MatrixRun {
+    pub id: String,
+    pub prompt_path: String,
+    pub prompt_file_id: String,
+    pub dataset_id: String,
+    pub row_indices: Option<String>,   // JSON array (None: every row)
+    pub models: String,                // JSON array of "provider::model" ids
+    pub concurrency: i64,
+    pub status: String,                // "running" | "paused" | "completed"
+    pub total_cells: i64,
+    pub completed_cells: i64,
+    pub failed_cells: i64,
+    pub total_cost_usd: f64,
+    pub error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    /// Prompt file content when the run was created
+    #[serde(skip)]
+    pub prompt_content: String,
+    /// JSON PromptRuntime loaded from prompt_content; cells run it even if the file changed since
+    #[serde(skip)]
+    pub prompt_runtime: String,
+}
+
+/// One cell of a matrix run: a dataset row executed on a model (test_results row)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct TestResult {
+    pub id: String,
+    pub prompt_file_id: String,
+    pub dataset_id: String,
+    pub test_case_index: i64,
+    pub input_variables: String,       // JSON: {"var1": "value1", ...}
+    pub output: String,
+    pub model: String,
+    pub provider: String,
+    pub latency_ms: i64,
+    pub tokens_input: i64,
+    pub tokens_output: i64,
+    pub cost_usd: f64,
+    pub timestamp: i64,
+    pub matrix_run_id: Option<String>,
+    pub llm_provider_name: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Outcome of an evaluator on an execution (evaluation_results row)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EvaluationResult {
+    pub id: String,
+    pub execution_id: String,                 // execution_history id, or test_results id for matrix cells
+    pub evaluator_name: String,
+    pub evaluator_file_path: Option<String>,
+    pub score: Option<f64>,
+    pub reasoning: Option<String>,
+    pub passed: Option<bool>,
+    pub timestamp: i64,
+    pub evaluator_type: Option<String>,       // "assertion" | "rule" | "judge" | "aggregate"
+    pub prompt_file_id: Option<String>,
+    pub model: Option<String>,
+    pub matrix_run_id: Option<String>,
+    pub judge_model: Option<String>,          // LLM evaluators: the judge call, apart from the execution's cost
+    pub judge_tokens_input: Option<u32>,
+    pub judge_tokens_output: Option<u32>,
+    pub judge_cost_usd: Option<f64>,
+}
+
+/// Reusable evaluator referenced by name from a prompt's `evaluation:` list (evaluation_rules row)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct EvaluationRule {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub rule_type: String,                    // See services::evaluation::RuleType
+    pub rule_config: String,                  // JSON, depends on rule_type
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// Pass rate of a prompt on a model, within a matrix run or over single executions
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PassRate {
+    pub prompt_file_id: Option<String>,
+    pub prompt_path: Option<String>,
+    pub model: Option<String>,
+    pub matrix_run_id: Option<String>,
+    pub executions: i64,
+    pub executions_passed: i64,
+    pub checks: i64,
+    pub checks_passed: i64,
+    pub pass_rate: f64,                       // executions_passed / executions
+}
+
+/// Filters for query_executions / aggregate_executions (all optional, combined with AND)
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ExecutionFilter {
+    pub file_path: Option<String>,
+    pub model: Option<String>,
+    pub provider: Option<String>,           // provider type ("openai") or provider config name
+    pub from_timestamp: Option<i64>,
+    pub to_timestamp: Option<i64>,
+    pub git_branch: Option<String>,
+    pub min_cost_usd: Option<f64>,
+    pub max_cost_usd: Option<f64>,
+    pub min_latency_ms: Option<i64>,
+    pub max_latency_ms: Option<i64>,
+    pub search: Option<String>,             // substring of the output
+    pub run_mode: Option<String>,
+}
+
+impl ExecutionFilter {
+    /// Build the WHERE clause (positional `?` parameters) for this filter
+    fn to_sql(&self) -> (String, Vec<Box<dyn rusqlite::ToSql>>) {
+        let mut clauses: Vec<&str> = vec!["1 = 1"];
+        let mut values: Vec<Box<dyn rusqlite::ToSql>> = Vec::new();
+
+        if let Some(file_path) = &self.file_path {
+            clauses.push("prompt_file_id IN (SELECT id FROM prompt_files WHERE file_path = ?)");
+            values.push(Box::new(file_path.clone()));
+        }
+        if let Some(model) = &self.model {
+            clauses.push("model = ?");
+            values.push(Box::new(model.clone()));
+        }
+        if let Some(provider) = &self.provider {
+            clauses.push("(provider = ? OR llm_provider_name = ?)");
+            values.push(Box::new(provider.clone()));
+            values.push(Box::new(provider.clone()));
+        }
+        if let Some(from) = self.from_timestamp {
+            clauses.push("timestamp >= ?");
+            values.push(Box::new(from));
+        }
+        if let Some(to) = self.to_timestamp {
+            clauses.push("timestamp <= ?");
+            values.push(Box::new(to));
+        }
+        if let Some(branch) = &self.git_branch {
+            clauses.push("git_branch = ?");
+            values.push(Box::new(branch.clone()));
+        }
+        if let Some(min) = self.min_cost_usd {
+            clauses.push("cost_usd >= ?");
+            values.push(Box::new(min));
+        }
+        if let Some(max) = self.max_cost_usd {
+            clauses.push("cost_usd <= ?");
+            values.push(Box::new(max));
+        }
+        if let Some(min) = self.min_latency_ms {
+            clauses.push("latency_ms >= ?");
+            values.push(Box::new(min));
+        }
+        if let Some(max) = self.max_latency_ms {
+            clauses.push("latency_ms <= ?");
+            values.push(Box::new(max));
+        }
+        if let Some(search) = self.search.as_deref().filter(|s| !s.is_empty()) {
+            clauses.push("output LIKE ? ESCAPE '\\'");
+            let escaped = search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+            values.push(Box::new(format!("%{}%", escaped)));
+        }
+        if let Some(run_mode) = &self.run_mode {
+            clauses.push("run_mode = ?");
+            values.push(Box::new(run_mode.clone()));
+        }
+
+        (clauses.join(" AND "), values)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionSortField {
+    #[default]
+    Timestamp,
+    Cost,
+    Latency,
+    Tokens,
+}
+
+impl ExecutionSortField {
+    fn column(&self) -> &'static str {
+        match self {
+            ExecutionSortField::Timestamp => "timestamp",
+            ExecutionSortField::Cost => "cost_usd",
+            ExecutionSortField::Latency => "latency_ms",
+            ExecutionSortField::Tokens => "(tokens_input + tokens_output)",
+        }
+    }
+
+    fn value_of(&self, record: &ExecutionRecord) -> f64 {
+        match self {
+            ExecutionSortField::Timestamp => record.timestamp as f64,
+            ExecutionSortField::Cost => record.cost_usd,
+            ExecutionSortField::Latency => record.latency_ms as f64,
+            ExecutionSortField::Tokens => (record.tokens_input + record.tokens_output) as f64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct ExecutionSort {
+    #[serde(default)]
+    pub field: ExecutionSortField,
+    #[serde(default = "default_descending")]
+    pub descending: bool,
+}
+
+fn default_descending() -> bool {
+    true
+}
+
+impl Default for ExecutionSort {
+    fn default() -> Self {
+        Self { field: ExecutionSortField::Timestamp, descending: true }
+    }
+}
+
+// Cursor format: "<sort value>:<execution id>" (opaque to the frontend)
+fn encode_cursor(value: f64, id: &str) -> String {
+    format!("{}:{}", value, id)
+}
+
+fn decode_cursor(cursor: &str) -> Option<(f64, String)> {
+    let (value, id) = cursor.split_once(':')?;
+    Some((value.parse().ok()?, id.to_string()))
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExecutionQueryPage {
+    pub items: Vec<ExecutionRecord>,
+    pub next_cursor: Option<String>,
+    pub total: i64,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionGroupBy {
+    Day,
+    Model,
+    Prompt,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ExecutionAggregate {
+    pub group_key: Option<String>,  // day (YYYY-MM-DD), model or prompt_file_id
+    pub label: Option<String>,
+    pub count: i64,
+    pub total_cost_usd: f64,
+    pub avg_latency_ms: f64,
+    pub p50_latency_ms: Option<i64>,
+    pub p95_latency_ms: Option<i64>,
+    pub tokens_input: i64,
+    pub tokens_output: i64,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ArenaBattle {
     pub id: String,
@@ -746,6 +2922,7 @@ pub struct ArenaBattle {
     pub winner_model: Option<String>,
     pub votes: Option<String>,
     pub timestamp: i64,
+    pub file_history_id: Option<String>,  // Prompt version the battle ran against
 }
 
 /// File history entry for version control
@@ -758,6 +2935,32 @@ pub struct FileHistoryEntry {
     pub preview: String,
 }
 
+/// prompt_files columns that a frontmatter sync overwrites (see apply_prompt_frontmatter)
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PromptFileConfig {
+    pub provider_ref: String,
+    pub model_override: Option<String>,
+    pub parameters: Option<String>,
+    pub tags: Option<String>,
+    pub test_data_path: Option<String>,
+    pub description: Option<String>,
+    pub evaluation_config: Option<String>,
+}
+
+impl Default for PromptFileConfig {
+    fn default() -> Self {
+        Self {
+            provider_ref: "default".to_string(),
+            model_override: None,
+            parameters: None,
+            tags: None,
+            test_data_path: None,
+            description: None,
+            evaluation_config: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PromptFileMetadata {
     pub id: String,
@@ -786,3 +2989,174 @@ pub struct PromptFileMetadata {
 
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(db: &ProjectDatabase, prompt_file_id: &str, id: &str, latency_ms: i64, model: &str) {
+        db.save_execution(&ExecutionRecord {
+            id: id.to_string(),
+            prompt_file_id: prompt_file_id.to_string(),
+            prompt_name: "test".to_string(),
+            llm_provider_name: "OpenAI".to_string(),
+            input_variables: None,
+            output: format!("output {}", id),
+            model: model.to_string(),
+            provider: "openai".to_string(),
+            latency_ms,
+            tokens_input: 10,
+            tokens_output: 5,
+            cost_usd: 0.01,
+            pricing_version: Some(1),
+            rendered_messages: None,
+            parameters: None,
+            run_mode: "single".to_string(),
+            timestamp: 1_700_000_000 + latency_ms,
+            git_commit: None,
+            git_branch: Some("main".to_string()),
+            file_history_id: None,
+            input_files: None,
+            dynamic_variables: None,
+            pricing_id: Some("pricing-1".to_string()),
+            prompt_messages: None,
+            variable_profile: None,
+        }).unwrap();
+    }
+
+    #[test]
+    fn test_query_and_aggregate_executions() {
+        let workspace = std::env::temp_dir().join(format!("vibebase-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&workspace).unwrap();
+        let db = ProjectDatabase::new(&workspace).unwrap();
+        let file_id = db.ensure_prompt_file("prompts/test.vibe.md").unwrap();
+
+        for i in 1..=20 {
+            let model = if i % 2 == 0 { "gpt-4o" } else { "gpt-4o-mini" };
+            record(&db, &file_id, &format!("exec-{:02}", i), i * 100, model);
+        }
+
+        // Cursor pagination walks every row exactly once
+        let mut seen = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = db.query_executions(&ExecutionFilter::default(), ExecutionSort::default(), cursor.as_deref(), 6).unwrap();
+            assert_eq!(page.total, 20);
+            seen.extend(page.items.into_iter().map(|r| r.latency_ms));
+            cursor = page.next_cursor;
+            if cursor.is_none() {
+                break;
+            }
+        }
+        assert_eq!(seen, (1..=20).rev().map(|i| i * 100).collect::<Vec<_>>());
+
+        let filter = ExecutionFilter {
+            min_latency_ms: Some(1500),
+            search: Some("exec-2".to_string()),
+            ..Default::default()
+        };
+        let page = db.query_executions(&filter, ExecutionSort::default(), None, 50).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].id, "exec-20");
+        assert_eq!(page.items[0].pricing_id.as_deref(), Some("pricing-1"));
+
+        // Nearest-rank percentiles: p50 of 100..2000 is 1000, p95 is 1900
+        let all = db.aggregate_executions(&ExecutionFilter::default(), None).unwrap();
+        assert_eq!(all.len(), 1);
+        assert_eq!(all[0].count, 20);
+        assert_eq!(all[0].p50_latency_ms, Some(1000));
+        assert_eq!(all[0].p95_latency_ms, Some(1900));
+        assert!((all[0].total_cost_usd - 0.2).abs() < 1e-9);
+
+        let by_model = db.aggregate_executions(&ExecutionFilter::default(), Some(ExecutionGroupBy::Model)).unwrap();
+        assert_eq!(by_model.len(), 2);
+        assert_eq!(by_model[0].group_key.as_deref(), Some("gpt-4o"));
+        assert_eq!(by_model[0].count, 10);
+
+        std::fs::remove_dir_all(&workspace).ok();
+    }
+
+    #[test]
+    fn test_prompt_paths_are_workspace_relative() {
+        let workspace = std::env::temp_dir().join(format!("vibebase-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&workspace).unwrap();
+        let absolute = workspace.join("prompts/test.vibe.md").display().to_string();
+
+        let db = ProjectDatabase::new(&workspace).unwrap();
+        let file_id = db.ensure_prompt_file("prompts/test.vibe.md").unwrap();
+        record(&db, &file_id, "exec-relative", 100, "gpt-4o");
+        // Records an older version kept under the absolute path
+        db.get_connection().execute(
+            "INSERT INTO prompt_files (id, file_path, name, schema_version, provider_ref, file_hash, file_size, last_modified, created_at, updated_at)
+             VALUES ('legacy', ?1, 'test.vibe.md', 'v1', 'default', '', 0, 0, 0, 0)",
+            params![absolute],
+        ).unwrap();
+        db.get_connection().execute(
+            "INSERT INTO file_history (id, file_path, content, content_hash, created_at) VALUES ('history-1', ?1, 'old', 'hash', 0)",
+            params![absolute],
+        ).unwrap();
+        record(&db, "legacy", "exec-absolute", 200, "gpt-4o");
+        drop(db);
+
+        // Reopening merges them into the relative record
+        let db = ProjectDatabase::new(&workspace).unwrap();
+        assert_eq!(db.list_prompt_files().unwrap().len(), 1);
+        assert_eq!(db.find_prompt_file_id(&absolute).unwrap(), Some(file_id.clone()));
+        assert_eq!(db.ensure_prompt_file(&absolute).unwrap(), file_id);
+        assert_eq!(db.get_file_history("prompts/test.vibe.md", 10).unwrap().len(), 1);
+
+        let filter = ExecutionFilter { file_path: Some(absolute), ..Default::default() };
+        let page = db.query_executions(&filter, ExecutionSort::default(), None, 10).unwrap();
+        assert_eq!(page.total, 2);
+
+        std::fs::remove_dir_all(&workspace).ok();
+    }
+
+    #[test]
+    fn test_pass_rates() {
+        let workspace = std::env::temp_dir().join(format!("vibebase-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&workspace).unwrap();
+        let db = ProjectDatabase::new(&workspace).unwrap();
+        let file_id = db.ensure_prompt_file("prompts/test.vibe.md").unwrap();
+        for (id, model) in [("cell-1", "gpt-4o"), ("cell-2", "gpt-4o"), ("cell-3", "gpt-4o-mini")] {
+            record(&db, &file_id, id, 100, model);
+        }
+
+        let outcome = |execution_id: &str, model: &str, passed: bool| EvaluationResult {
+            id: uuid::Uuid::new_v4().to_string(),
+            execution_id: execution_id.to_string(),
+            evaluator_name: "contains \"ok\"".to_string(),
+            evaluator_file_path: None,
+            score: Some(if passed { 1.0 } else { 0.0 }),
+            reasoning: None,
+            passed: Some(passed),
+            timestamp: 1_700_000_000,
+            evaluator_type: Some("assertion".to_string()),
+            prompt_file_id: Some(file_id.clone()),
+            model: Some(model.to_string()),
+            matrix_run_id: Some("run-1".to_string()),
+            judge_model: None,
+            judge_tokens_input: None,
+            judge_tokens_output: None,
+            judge_cost_usd: None,
+        };
+        db.save_evaluation_results(&[
+            outcome("cell-1", "gpt-4o", true),
+            outcome("cell-1", "gpt-4o", true),
+            outcome("cell-2", "gpt-4o", true),
+            outcome("cell-2", "gpt-4o", false),
+            outcome("cell-3", "gpt-4o-mini", false),
+        ]).unwrap();
+
+        let rates = db.get_pass_rates("assertion", Some(&file_id), Some("run-1")).unwrap();
+        assert_eq!(rates.len(), 2);
+        assert_eq!(rates[0].prompt_path.as_deref(), Some("prompts/test.vibe.md"));
+        assert_eq!((rates[0].executions, rates[0].executions_passed), (2, 1));
+        assert_eq!((rates[0].checks, rates[0].checks_passed), (4, 3));
+        assert!((rates[0].pass_rate - 0.5).abs() < 1e-9);
+        assert_eq!(rates[1].pass_rate, 0.0);
+        assert_eq!(db.get_evaluation_results("cell-2").unwrap().len(), 2);
+
+        std::fs::remove_dir_all(&workspace).ok();
+    }
+}
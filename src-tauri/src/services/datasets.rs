@@ -0,0 +1,309 @@
+use crate::services::file_tracker::FileTracker;
+use crate::services::variable_io::csv_records;
+use serde::de::{Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+pub const MAX_DATASET_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Column holding a row's assertions (see services::assertions); not a template variable
+pub const ASSERTIONS_COLUMN: &str = "assertions";
+
+/// A test case: column name → value
+pub type DatasetRow = serde_json::Map<String, Value>;
+
+/// Keys of a JSON or YAML mapping in source order (a DatasetRow sorts them)
+struct KeyOrder(Vec<String>);
+
+impl<'de> Deserialize<'de> for KeyOrder {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct KeyVisitor;
+
+        impl<'de> Visitor<'de> for KeyVisitor {
+            type Value = KeyOrder;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a mapping")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<KeyOrder, A::Error> {
+                let mut keys = Vec::new();
+                while let Some((key, IgnoredAny)) = map.next_entry::<String, IgnoredAny>()? {
+                    keys.push(key);
+                }
+                Ok(KeyOrder(keys))
+            }
+        }
+
+        deserializer.deserialize_map(KeyVisitor)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DatasetFormat {
+    Csv,
+    Jsonl,
+    Yaml,
+}
+
+impl DatasetFormat {
+    /// `.csv` → Csv; `.jsonl`, `.ndjson` → Jsonl; `.yaml`, `.yml` → Yaml
+    pub fn detect(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "csv" => Some(DatasetFormat::Csv),
+            "jsonl" | "ndjson" => Some(DatasetFormat::Jsonl),
+            "yaml" | "yml" => Some(DatasetFormat::Yaml),
+            _ => None,
+        }
+    }
+
+    /// test_datasets.format
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DatasetFormat::Csv => "csv",
+            DatasetFormat::Jsonl => "jsonl",
+            DatasetFormat::Yaml => "yaml",
+        }
+    }
+}
+
+/// What gets stored in test_datasets for a dataset file
+#[derive(Debug, Clone)]
+pub struct DatasetIndex {
+    pub format: DatasetFormat,
+    pub columns: Vec<String>,
+    pub row_count: usize,
+    pub content_hash: String,
+}
+
+/// Columns and rows of a dataset file
+/// CSV: a header row, then one test case per record (values stay text);
+/// JSONL: one JSON object per line; YAML: a list of mappings
+pub fn parse_rows(content: &str, format: DatasetFormat) -> Result<(Vec<String>, Vec<DatasetRow>), String> {
+    let (keys, rows): (Vec<KeyOrder>, Vec<DatasetRow>) = match format {
+        DatasetFormat::Csv => return parse_csv(content),
+        DatasetFormat::Jsonl => content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                let error = |e: serde_json::Error| format!("Line {}: expected a JSON object ({})", index + 1, e);
+                let row = serde_json::from_str::<DatasetRow>(line).map_err(error)?;
+                let keys = serde_json::from_str::<KeyOrder>(line).map_err(error)?;
+                Ok((keys, row))
+            })
+            .collect::<Result<Vec<_>, String>>()?
+            .into_iter()
+            .unzip(),
+        DatasetFormat::Yaml => {
+            let error = |e: serde_yaml::Error| format!("Expected a YAML list of mappings: {}", e);
+            let rows: Option<Vec<DatasetRow>> = serde_yaml::from_str(content).map_err(error)?;
+            let keys: Option<Vec<KeyOrder>> = serde_yaml::from_str(content).map_err(error)?;
+            (keys.unwrap_or_default(), rows.unwrap_or_default())
+        }
+    };
+    Ok((columns(&keys), rows))
+}
+
+fn parse_csv(content: &str) -> Result<(Vec<String>, Vec<DatasetRow>), String> {
+    let mut records = csv_records(content.trim_start_matches('\u{feff}'))?
+        .into_iter()
+        .filter(|record| record.iter().any(|field| !field.trim().is_empty()));
+
+    let header: Vec<String> = match records.next() {
+        Some(header) => header.into_iter().map(|name| name.trim().to_string()).collect(),
+        None => return Ok((Vec::new(), Vec::new())),
+    };
+    if header.iter().any(|name| name.is_empty()) {
+        return Err("Empty column name in the header row".to_string());
+    }
+
+    let rows = records
+        .enumerate()
+        .map(|(index, record)| {
+            if record.len() != header.len() {
+                return Err(format!(
+                    "Row {}: expected {} columns, found {}",
+                    index + 1,
+                    header.len(),
+                    record.len()
+                ));
+            }
+            Ok(header.iter().cloned().zip(record.into_iter().map(Value::String)).collect())
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    Ok((header, rows))
+}
+
+/// Column names in order of first appearance
+fn columns(rows: &[KeyOrder]) -> Vec<String> {
+    let mut columns: Vec<String> = Vec::new();
+    for KeyOrder(keys) in rows {
+        for name in keys {
+            if !columns.contains(name) {
+                columns.push(name.clone());
+            }
+        }
+    }
+    columns
+}
+
+/// Read and index a dataset file
+pub fn index_file(path: &Path) -> Result<(DatasetIndex, Vec<DatasetRow>), String> {
+    let format = DatasetFormat::detect(path)
+        .ok_or_else(|| format!("Unsupported dataset file {}: use .csv, .jsonl or .yaml", path.display()))?;
+
+    let size = fs::metadata(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .len();
+    if size > MAX_DATASET_BYTES {
+        return Err(format!(
+            "{} is too large ({} MB, limit {} MB)",
+            path.display(),
+            size / (1024 * 1024),
+            MAX_DATASET_BYTES / (1024 * 1024)
+        ));
+    }
+
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let content_hash = FileTracker::calculate_file_hash(&bytes);
+    let content = String::from_utf8(bytes).map_err(|_| format!("{} is not UTF-8 text", path.display()))?;
+    let (columns, rows) = parse_rows(&content, format)?;
+
+    let index = DatasetIndex {
+        format,
+        columns,
+        row_count: rows.len(),
+        content_hash,
+    };
+    Ok((index, rows))
+}
+
+/// Hash of a dataset file's current content, to tell whether its index is stale
+pub fn file_hash(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(FileTracker::calculate_file_hash(&bytes))
+}
+
+/// Template variables of a row; values that aren't strings are passed as JSON text
+pub fn row_variables(row: &DatasetRow) -> HashMap<String, String> {
+    row.iter()
+        .filter(|(name, _)| name.as_str() != ASSERTIONS_COLUMN)
+        .map(|(name, value)| {
+            let text = match value {
+                Value::String(s) => s.clone(),
+                Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            (name.clone(), text)
+        })
+        .collect()
+}
+
+/// How well a dataset's columns cover a prompt's variables
+#[derive(Debug, Default, Serialize)]
+pub struct DatasetCoverage {
+    /// Every required variable has a column
+    pub valid: bool,
+    pub covered: Vec<String>,
+    pub missing_required: Vec<String>,
+    pub missing_optional: Vec<String>,
+    /// Columns that no variable uses (the assertions column aside)
+    pub unused_columns: Vec<String>,
+    /// Rows (0-based) with an empty value for a required variable
+    pub incomplete_rows: Vec<usize>,
+}
+
+/// `variables` are (name, required) pairs
+pub fn check_coverage(columns: &[String], rows: &[DatasetRow], variables: &[(String, bool)]) -> DatasetCoverage {
+    let mut coverage = DatasetCoverage::default();
+    for (name, required) in variables {
+        if columns.contains(name) {
+            coverage.covered.push(name.clone());
+        } else if *required {
+            coverage.missing_required.push(name.clone());
+        } else {
+            coverage.missing_optional.push(name.clone());
+        }
+    }
+    coverage.unused_columns = columns
+        .iter()
+        .filter(|column| column.as_str() != ASSERTIONS_COLUMN && !variables.iter().any(|(name, _)| name == *column))
+        .cloned()
+        .collect();
+
+    let required: Vec<&String> = variables
+        .iter()
+        .filter(|(name, required)| *required && columns.contains(name))
+        .map(|(name, _)| name)
+        .collect();
+    coverage.incomplete_rows = rows
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| {
+            required.iter().any(|name| match row.get(name.as_str()) {
+                None | Some(Value::Null) => true,
+                Some(Value::String(s)) => s.trim().is_empty(),
+                Some(_) => false,
+            })
+        })
+        .map(|(index, _)| index)
+        .collect();
+
+    coverage.valid = coverage.missing_required.is_empty();
+    coverage
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_formats() {
+        let csv = "topic,notes\n\"Rust, the language\",\"said \"\"hi\"\"\"\nGo,\n";
+        let (columns, rows) = parse_rows(csv, DatasetFormat::Csv).unwrap();
+        assert_eq!(columns, vec!["topic", "notes"]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0]["topic"], "Rust, the language");
+        assert_eq!(rows[0]["notes"], "said \"hi\"");
+        assert!(parse_rows("a,b\n1\n", DatasetFormat::Csv).unwrap_err().contains("Row 1"));
+
+        let jsonl = "{\"topic\": \"Rust\", \"tags\": [\"a\"]}\n\n{\"topic\": \"Go\", \"level\": 2}\n";
+        let (columns, rows) = parse_rows(jsonl, DatasetFormat::Jsonl).unwrap();
+        assert_eq!(columns, vec!["topic", "tags", "level"]);
+        assert_eq!(row_variables(&rows[0])["tags"], "[\"a\"]");
+        assert!(parse_rows("[1]\n", DatasetFormat::Jsonl).unwrap_err().contains("Line 1"));
+
+        let yaml = "- topic: Rust\n  level: 1\n- topic: Go\n  audience: devs\n";
+        let (columns, rows) = parse_rows(yaml, DatasetFormat::Yaml).unwrap();
+        assert_eq!(columns, vec!["topic", "level", "audience"]);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(row_variables(&rows[0])["level"], "1");
+        assert!(parse_rows("", DatasetFormat::Yaml).unwrap().1.is_empty());
+    }
+
+    #[test]
+    fn test_check_coverage() {
+        let (columns, rows) = parse_rows("topic,tone,extra,assertions\nRust,,x,\n,calm,y,\n", DatasetFormat::Csv).unwrap();
+        let variables = vec![
+            ("topic".to_string(), true),
+            ("tone".to_string(), false),
+            ("audience".to_string(), true),
+            ("length".to_string(), false),
+        ];
+        let coverage = check_coverage(&columns, &rows, &variables);
+
+        assert!(!coverage.valid);
+        assert_eq!(coverage.covered, vec!["topic", "tone"]);
+        assert_eq!(coverage.missing_required, vec!["audience"]);
+        assert_eq!(coverage.missing_optional, vec!["length"]);
+        assert_eq!(coverage.unused_columns, vec!["extra"]);
+        assert_eq!(coverage.incomplete_rows, vec![1]);
+    }
+}
@@ -0,0 +1,179 @@
+use crate::models::git::GitHeadInfo;
+use crate::models::prompt::{Message, PromptRuntime};
+use crate::services::git_service::GitService;
+use crate::services::template;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local, SecondsFormat};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Reserved variables start with `$` and are resolved when the prompt is executed
+pub const DYNAMIC_PREFIX: char = '$';
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// `{{$name}}` or `{{$name:argument}}`
+#[derive(Debug, Clone, PartialEq)]
+pub enum DynamicVariable {
+    /// Local time, RFC 3339
+    Now,
+    /// Local date with a strftime format, `%Y-%m-%d` by default
+    Date(String),
+    Uuid,
+    GitBranch,
+    GitCommit,
+    PromptName,
+    /// Name of the workspace folder
+    Workspace,
+}
+
+impl DynamicVariable {
+    pub fn parse(reference: &str) -> Result<Self, String> {
+        let reference = reference.strip_prefix(DYNAMIC_PREFIX).unwrap_or(reference);
+        let (name, argument) = match reference.split_once(':') {
+            Some((name, argument)) => (name.trim(), Some(argument)),
+            None => (reference.trim(), None),
+        };
+
+        let variable = match name {
+            "now" => DynamicVariable::Now,
+            "date" => {
+                let format = argument.unwrap_or(DEFAULT_DATE_FORMAT).to_string();
+                if format.is_empty() || StrftimeItems::new(&format).any(|item| matches!(item, Item::Error)) {
+                    return Err(format!("Invalid date format '{}', e.g. {{{{$date:%Y-%m-%d}}}}", format));
+                }
+                return Ok(DynamicVariable::Date(format));
+            }
+            "uuid" => DynamicVariable::Uuid,
+            "git_branch" => DynamicVariable::GitBranch,
+            "git_commit" => DynamicVariable::GitCommit,
+            "prompt_name" => DynamicVariable::PromptName,
+            "workspace" => DynamicVariable::Workspace,
+            _ => {
+                return Err(format!(
+                    "Unknown variable '${}' (available: $now, $date, $uuid, $git_branch, $git_commit, $prompt_name, $workspace)",
+                    name
+                ))
+            }
+        };
+
+        if argument.is_some() {
+            return Err(format!("'${}' takes no argument", name));
+        }
+        Ok(variable)
+    }
+}
+
+/// What dynamic variables are resolved from, for one execution
+struct Context<'a> {
+    prompt_name: &'a str,
+    workspace: Option<&'a str>,
+    now: DateTime<Local>,
+    /// Read on first use
+    head: Option<GitHeadInfo>,
+}
+
+impl Context<'_> {
+    fn head(&mut self) -> &GitHeadInfo {
+        let workspace = self.workspace;
+        self.head.get_or_insert_with(|| match workspace {
+            Some(workspace) => GitService::new(workspace).get_head_info(),
+            None => GitHeadInfo::default(),
+        })
+    }
+
+    fn value(&mut self, variable: &DynamicVariable) -> String {
+        match variable {
+            DynamicVariable::Now => self.now.to_rfc3339_opts(SecondsFormat::Secs, false),
+            DynamicVariable::Date(format) => self.now.format(format).to_string(),
+            DynamicVariable::Uuid => uuid::Uuid::new_v4().to_string(),
+            DynamicVariable::GitBranch => self.head().branch.clone().unwrap_or_default(),
+            DynamicVariable::GitCommit => self.head().commit.clone().unwrap_or_default(),
+            DynamicVariable::PromptName => self.prompt_name.to_string(),
+            DynamicVariable::Workspace => self
+                .workspace
+                .and_then(|w| Path::new(w).file_name())
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Dynamic variable references in the messages (messages with template errors are skipped)
+pub fn dynamic_references(messages: &[Message]) -> Vec<String> {
+    let mut references = Vec::new();
+    for message in messages {
+        for reference in template::extract_dynamic_references(&message.content).unwrap_or_default() {
+            if !references.contains(&reference) {
+                references.push(reference);
+            }
+        }
+    }
+    references
+}
+
+/// Resolve the dynamic variables a prompt uses into `variables`
+/// Values already present (e.g. recorded by the execution being replayed) are kept.
+/// Returns the values used, to be stored with the execution.
+pub fn load_dynamic_variables(
+    prompt: &PromptRuntime,
+    workspace: Option<&str>,
+    variables: &mut HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    let mut context = Context {
+        prompt_name: &prompt.name,
+        workspace,
+        now: Local::now(),
+        head: None,
+    };
+
+    let mut resolved = HashMap::new();
+    for reference in dynamic_references(&prompt.messages) {
+        let value = match variables.get(&reference) {
+            Some(value) => value.clone(),
+            None => context.value(&DynamicVariable::parse(&reference)?),
+        };
+        variables.insert(reference.clone(), value.clone());
+        resolved.insert(reference, value);
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(DynamicVariable::parse("$now").unwrap(), DynamicVariable::Now);
+        assert_eq!(DynamicVariable::parse("$date").unwrap(), DynamicVariable::Date("%Y-%m-%d".to_string()));
+        assert_eq!(DynamicVariable::parse("$date:%d.%m.%Y").unwrap(), DynamicVariable::Date("%d.%m.%Y".to_string()));
+        assert!(DynamicVariable::parse("$date:%Q").is_err());
+        assert!(DynamicVariable::parse("$uuid:4").is_err());
+        assert!(DynamicVariable::parse("$today").unwrap_err().contains("Unknown variable"));
+    }
+
+    #[test]
+    fn test_load_dynamic_variables() {
+        let prompt: PromptRuntime = serde_yaml::from_str(
+            "schema: v1\nname: summary\nconfig: {provider: openai, model: gpt-4o}\nmessages:\n  - role: user\n    content: \"{{$prompt_name}} {{$workspace}} {{$uuid}} {{$date:%Y}} {{topic}}\"\n",
+        )
+        .unwrap();
+        let mut variables = HashMap::from([
+            ("topic".to_string(), "news".to_string()),
+            ("$uuid".to_string(), "recorded".to_string()),
+        ]);
+        let resolved = load_dynamic_variables(&prompt, Some("/work/reports"), &mut variables).unwrap();
+
+        assert_eq!(resolved.len(), 4);
+        assert_eq!(resolved["$prompt_name"], "summary");
+        assert_eq!(resolved["$workspace"], "reports");
+        // Recorded values are reused, so replays render the same text
+        assert_eq!(resolved["$uuid"], "recorded");
+        assert_eq!(resolved["$date:%Y"], Local::now().format("%Y").to_string());
+
+        let rendered = template::replace_variables(&prompt.messages[0].content, &variables).unwrap();
+        assert!(rendered.starts_with("summary reports recorded "));
+        assert!(rendered.ends_with(" news"));
+    }
+}
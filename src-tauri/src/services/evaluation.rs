@@ -0,0 +1,670 @@
+use crate::models::execution::OpenAIMessage;
+use crate::services::assertions::validate_schema;
+use crate::services::json_output::{json_path, parse_json_output};
+use crate::services::database::ExecutionRecord;
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// evaluation_results.evaluator_type of rule outcomes
+pub const RULE_EVALUATOR: &str = "rule";
+/// evaluation_results.evaluator_type of .vibe.md rubric evaluators
+pub const JUDGE_EVALUATOR: &str = "judge";
+/// evaluation_results.evaluator_type of the weighted score of an execution's evaluators
+pub const AGGREGATE_EVALUATOR: &str = "aggregate";
+
+/// Input variable holding the reference answer (a dataset column for matrix cells)
+pub const EXPECTED_VARIABLE: &str = "expected";
+
+const DEFAULT_SCRIPT_TIMEOUT_MS: u64 = 30_000;
+/// Judges score from 0 to JUDGE_SCALE unless a rubric sets its own scale; scores are stored normalized to 0..1
+pub const JUDGE_SCALE: f64 = 10.0;
+pub const JUDGE_PASS_THRESHOLD: f64 = 0.7;
+
+/// evaluation_rules.rule_type
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleType {
+    Regex,
+    Keyword,
+    Length,
+    Json,
+    Similarity,
+    LlmJudge,
+    Script,
+}
+
+impl RuleType {
+    pub fn parse(rule_type: &str) -> Result<Self, String> {
+        serde_json::from_value(Value::String(rule_type.to_string())).map_err(|_| {
+            format!(
+                "Unknown rule type '{}' (available: regex, keyword, length, json, similarity, llm_judge, script)",
+                rule_type
+            )
+        })
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RuleType::Regex => "regex",
+            RuleType::Keyword => "keyword",
+            RuleType::Length => "length",
+            RuleType::Json => "json",
+            RuleType::Similarity => "similarity",
+            RuleType::LlmJudge => "llm_judge",
+            RuleType::Script => "script",
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_similarity_threshold() -> f64 {
+    0.8
+}
+
+fn default_judge_threshold() -> f64 {
+    JUDGE_PASS_THRESHOLD
+}
+
+/// `{"pattern": "^Dear", "should_match": true, "case_insensitive": false}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegexRule {
+    pub pattern: String,
+    #[serde(default = "default_true")]
+    pub should_match: bool,
+    #[serde(default)]
+    pub case_insensitive: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeywordMode {
+    /// Every keyword must appear; the score is the share found
+    #[default]
+    All,
+    /// At least one keyword must appear
+    Any,
+    /// No keyword may appear
+    None,
+}
+
+/// `{"keywords": ["refund", "apolog"], "mode": "all", "case_sensitive": false}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeywordRule {
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub mode: KeywordMode,
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LengthUnit {
+    #[default]
+    Chars,
+    Words,
+}
+
+/// `{"min": 20, "max": 200, "unit": "words"}`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LengthRule {
+    #[serde(default)]
+    pub min: Option<usize>,
+    #[serde(default)]
+    pub max: Option<usize>,
+    #[serde(default)]
+    pub unit: LengthUnit,
+}
+
+/// `{"schema": {...}, "required_paths": ["$.status"]}`; an empty config only checks the output is JSON
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JsonRule {
+    #[serde(default)]
+    pub schema: Option<Value>,
+    #[serde(default)]
+    pub required_paths: Vec<String>,
+}
+
+/// `{"expected": "...", "threshold": 0.8}`; without `expected` the execution's `expected` input is used
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarityRule {
+    #[serde(default)]
+    pub expected: Option<String>,
+    #[serde(default = "default_similarity_threshold")]
+    pub threshold: f64,
+}
+
+/// `{"criteria": "Polite and answers the question", "provider_ref": "openai_default", "model": "gpt-4o-mini"}`
+/// Without provider_ref the default provider is used
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlmJudgeRule {
+    pub criteria: String,
+    #[serde(default)]
+    pub provider_ref: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default = "default_judge_threshold")]
+    pub pass_threshold: f64,
+}
+
+/// `{"command": "python3", "args": ["evals/check.py"], "timeout_ms": 10000}`
+/// The script runs in the workspace with `{"output", "variables", "expected"}` JSON on stdin. It passes
+/// when it exits with 0; it may print `{"score": 0.8, "passed": true, "reasoning": "..."}` or a score.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptRule {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// A rule with its parsed rule_config
+#[derive(Debug, Clone)]
+pub enum Rule {
+    Regex(RegexRule),
+    Keyword(KeywordRule),
+    Length(LengthRule),
+    Json(JsonRule),
+    Similarity(SimilarityRule),
+    LlmJudge(LlmJudgeRule),
+    Script(ScriptRule),
+}
+
+fn parse_config<T: serde::de::DeserializeOwned>(rule_type: RuleType, config: &str) -> Result<T, String> {
+    let config = if config.trim().is_empty() { "{}" } else { config };
+    serde_json::from_str(config).map_err(|e| format!("Invalid {} rule config: {}", rule_type.as_str(), e))
+}
+
+impl Rule {
+    /// Parse an evaluation_rules row's rule_type and rule_config
+    pub fn parse(rule_type: &str, config: &str) -> Result<Self, String> {
+        let rule_type = RuleType::parse(rule_type)?;
+        let rule = match rule_type {
+            RuleType::Regex => {
+                let rule: RegexRule = parse_config(rule_type, config)?;
+                RegexBuilder::new(&rule.pattern)
+                    .build()
+                    .map_err(|e| format!("Invalid regex: {}", e))?;
+                Rule::Regex(rule)
+            }
+            RuleType::Keyword => {
+                let rule: KeywordRule = parse_config(rule_type, config)?;
+                if rule.keywords.is_empty() {
+                    return Err("A keyword rule needs at least one keyword".to_string());
+                }
+                Rule::Keyword(rule)
+            }
+            RuleType::Length => {
+                let rule: LengthRule = parse_config(rule_type, config)?;
+                if rule.min.is_none() && rule.max.is_none() {
+                    return Err("A length rule needs a min or a max".to_string());
+                }
+                Rule::Length(rule)
+            }
+            RuleType::Json => Rule::Json(parse_config(rule_type, config)?),
+            RuleType::Similarity => Rule::Similarity(parse_config(rule_type, config)?),
+            RuleType::LlmJudge => Rule::LlmJudge(parse_config(rule_type, config)?),
+            RuleType::Script => Rule::Script(parse_config(rule_type, config)?),
+        };
+        Ok(rule)
+    }
+
+    pub fn rule_type(&self) -> RuleType {
+        match self {
+            Rule::Regex(_) => RuleType::Regex,
+            Rule::Keyword(_) => RuleType::Keyword,
+            Rule::Length(_) => RuleType::Length,
+            Rule::Json(_) => RuleType::Json,
+            Rule::Similarity(_) => RuleType::Similarity,
+            Rule::LlmJudge(_) => RuleType::LlmJudge,
+            Rule::Script(_) => RuleType::Script,
+        }
+    }
+}
+
+/// What an evaluator sees of an execution
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct EvaluationInput {
+    pub output: String,
+    pub variables: HashMap<String, String>,
+    pub expected: Option<String>,
+}
+
+impl EvaluationInput {
+    pub fn from_record(record: &ExecutionRecord) -> Self {
+        let variables: HashMap<String, String> = record
+            .input_variables
+            .as_deref()
+            .and_then(|json| serde_json::from_str(json).ok())
+            .unwrap_or_default();
+        Self {
+            output: record.output.clone(),
+            expected: variables.get(EXPECTED_VARIABLE).cloned(),
+            variables,
+        }
+    }
+}
+
+/// Model call made by a judge, kept apart from the cost of the evaluated execution
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct JudgeUsage {
+    pub model: String,
+    pub tokens_input: u32,
+    pub tokens_output: u32,
+    pub cost_usd: f64,
+}
+
+/// Score (0..1), verdict and explanation of an evaluator
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleOutcome {
+    pub score: f64,
+    pub passed: bool,
+    pub reasoning: String,
+    /// Set for LLM judges
+    pub judge: Option<JudgeUsage>,
+}
+
+impl RuleOutcome {
+    fn binary(passed: bool, reasoning: String) -> Self {
+        Self { score: if passed { 1.0 } else { 0.0 }, passed, reasoning, judge: None }
+    }
+}
+
+/// Apply a rule that needs neither a provider nor a process (every type but llm_judge and script)
+pub fn check(rule: &Rule, input: &EvaluationInput) -> Result<RuleOutcome, String> {
+    let output = input.output.as_str();
+    match rule {
+        Rule::Regex(rule) => {
+            let regex = RegexBuilder::new(&rule.pattern)
+                .case_insensitive(rule.case_insensitive)
+                .build()
+                .map_err(|e| format!("Invalid regex: {}", e))?;
+            let matched = regex.is_match(output);
+            let reasoning = if matched {
+                format!("Output matches /{}/", rule.pattern)
+            } else {
+                format!("Output does not match /{}/", rule.pattern)
+            };
+            Ok(RuleOutcome::binary(matched == rule.should_match, reasoning))
+        }
+        Rule::Keyword(rule) => Ok(check_keywords(rule, output)),
+        Rule::Length(rule) => {
+            let (length, unit) = match rule.unit {
+                LengthUnit::Chars => (output.chars().count(), "characters"),
+                LengthUnit::Words => (output.split_whitespace().count(), "words"),
+            };
+            let passed = rule.min.is_none_or(|min| length >= min) && rule.max.is_none_or(|max| length <= max);
+            let bounds = match (rule.min, rule.max) {
+                (Some(min), Some(max)) => format!("{}-{}", min, max),
+                (Some(min), None) => format!("at least {}", min),
+                (None, Some(max)) => format!("at most {}", max),
+                (None, None) => "any".to_string(),
+            };
+            Ok(RuleOutcome::binary(passed, format!("{} {} (expected {})", length, unit, bounds)))
+        }
+        Rule::Json(rule) => Ok(check_json(rule, output)),
+        Rule::Similarity(rule) => {
+            let expected = rule
+                .expected
+                .as_deref()
+                .or(input.expected.as_deref())
+                .ok_or("No expected output: set `expected` in the rule or an `expected` input variable")?;
+            let score = similarity(output, expected);
+            Ok(RuleOutcome {
+                score,
+                passed: score >= rule.threshold,
+                reasoning: format!("Similarity {:.2} to the expected output (threshold {:.2})", score, rule.threshold),
+                judge: None,
+            })
+        }
+        Rule::LlmJudge(_) | Rule::Script(_) => {
+            Err(format!("{} rules can't be checked locally", rule.rule_type().as_str()))
+        }
+    }
+}
+
+fn check_keywords(rule: &KeywordRule, output: &str) -> RuleOutcome {
+    let normalize = |text: &str| if rule.case_sensitive { text.to_string() } else { text.to_lowercase() };
+    let haystack = normalize(output);
+    let (found, missing): (Vec<&String>, Vec<&String>) =
+        rule.keywords.iter().partition(|keyword| haystack.contains(&normalize(keyword)));
+    let share = found.len() as f64 / rule.keywords.len() as f64;
+
+    let list = |keywords: &[&String]| keywords.iter().map(|k| k.as_str()).collect::<Vec<_>>().join(", ");
+    match rule.mode {
+        KeywordMode::All => RuleOutcome {
+            score: share,
+            passed: missing.is_empty(),
+            reasoning: if missing.is_empty() {
+                "Every keyword found".to_string()
+            } else {
+                format!("Missing: {}", list(&missing))
+            },
+            judge: None,
+        },
+        KeywordMode::Any => RuleOutcome::binary(
+            !found.is_empty(),
+            if found.is_empty() { "No keyword found".to_string() } else { format!("Found: {}", list(&found)) },
+        ),
+        KeywordMode::None => RuleOutcome {
+            score: 1.0 - share,
+            passed: found.is_empty(),
+            reasoning: if found.is_empty() {
+                "No forbidden keyword found".to_string()
+            } else {
+                format!("Forbidden: {}", list(&found))
+            },
+            judge: None,
+        },
+    }
+}
+
+fn check_json(rule: &JsonRule, output: &str) -> RuleOutcome {
+    let json = match parse_json_output(output) {
+        Ok(json) => json,
+        Err(e) => return RuleOutcome::binary(false, e),
+    };
+    if let Some(schema) = &rule.schema {
+        if let Err(e) = validate_schema(&json, schema, "$") {
+            return RuleOutcome::binary(false, e);
+        }
+    }
+    for path in &rule.required_paths {
+        match json_path(&json, path) {
+            Ok(Some(_)) => {}
+            Ok(None) => return RuleOutcome::binary(false, format!("{} not found in the output", path)),
+            Err(e) => return RuleOutcome::binary(false, e),
+        }
+    }
+    RuleOutcome::binary(true, "Output is valid JSON".to_string())
+}
+
+/// Cosine similarity of the lowercase word counts of two texts (0..1)
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let counts = |text: &str| {
+        let mut counts: HashMap<String, f64> = HashMap::new();
+        for word in text.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+            *counts.entry(word.to_lowercase()).or_default() += 1.0;
+        }
+        counts
+    };
+    let (a, b) = (counts(a), counts(b));
+    if a.is_empty() || b.is_empty() {
+        return if a.is_empty() && b.is_empty() { 1.0 } else { 0.0 };
+    }
+
+    let dot: f64 = a.iter().map(|(word, count)| count * b.get(word).unwrap_or(&0.0)).sum();
+    let norm = |counts: &HashMap<String, f64>| counts.values().map(|c| c * c).sum::<f64>().sqrt();
+    dot / (norm(&a) * norm(&b))
+}
+
+/// Run a script rule in the workspace
+pub async fn run_script(rule: &ScriptRule, input: &EvaluationInput, workspace: &Path) -> Result<RuleOutcome, String> {
+    let mut child = tokio::process::Command::new(&rule.command)
+        .args(&rule.args)
+        .current_dir(workspace)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", rule.command, e))?;
+
+    let payload = serde_json::to_vec(input).map_err(|e| e.to_string())?;
+    let stdin = child.stdin.take();
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+
+    // Feeding stdin runs alongside the reads and the wait, so a script that never reads
+    // its input is still bounded by the timeout
+    let run = async {
+        let write = async move {
+            if let Some(mut stdin) = stdin {
+                // A script that doesn't read its input closes the pipe early; that's fine
+                let _ = stdin.write_all(&payload).await;
+            }
+        };
+        let (_, stdout, stderr, status) = tokio::join!(write, read_pipe(stdout), read_pipe(stderr), child.wait());
+        Ok::<_, std::io::Error>((status?, stdout?, stderr?))
+    };
+
+    let timeout = Duration::from_millis(rule.timeout_ms.unwrap_or(DEFAULT_SCRIPT_TIMEOUT_MS));
+    let (status, stdout, stderr) = match tokio::time::timeout(timeout, run).await {
+        Ok(output) => output.map_err(|e| format!("Failed to run {}: {}", rule.command, e))?,
+        Err(_) => {
+            if let Err(e) = child.kill().await {
+                eprintln!("⚠️  Failed to kill {}: {}", rule.command, e);
+            }
+            return Err(format!("{} timed out after {} ms", rule.command, timeout.as_millis()));
+        }
+    };
+
+    let stdout = String::from_utf8_lossy(&stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&stderr).trim().to_string();
+    Ok(script_outcome(status.success(), status.code(), &stdout, &stderr))
+}
+
+async fn read_pipe(pipe: Option<impl tokio::io::AsyncRead + Unpin>) -> std::io::Result<Vec<u8>> {
+    let mut buffer = Vec::new();
+    if let Some(mut pipe) = pipe {
+        pipe.read_to_end(&mut buffer).await?;
+    }
+    Ok(buffer)
+}
+
+fn script_outcome(success: bool, code: Option<i32>, stdout: &str, stderr: &str) -> RuleOutcome {
+    let printed: Option<Value> = serde_json::from_str(stdout).ok();
+    let score = printed
+        .as_ref()
+        .and_then(|value| value.as_f64().or_else(|| value.get("score").and_then(Value::as_f64)))
+        .map(|score| score.clamp(0.0, 1.0));
+    let passed = printed
+        .as_ref()
+        .and_then(|value| value.get("passed"))
+        .and_then(Value::as_bool)
+        .unwrap_or(success);
+    let reasoning = printed
+        .as_ref()
+        .and_then(|value| value.get("reasoning"))
+        .and_then(Value::as_str)
+        .map(String::from)
+        .or_else(|| Some(stderr.to_string()).filter(|s| !s.is_empty()))
+        .or_else(|| Some(stdout.to_string()).filter(|s| !s.is_empty() && printed.is_none()))
+        .unwrap_or_else(|| match code {
+            Some(code) => format!("Exited with code {}", code),
+            None => "Terminated by a signal".to_string(),
+        });
+
+    RuleOutcome {
+        score: score.unwrap_or(if passed { 1.0 } else { 0.0 }),
+        passed,
+        reasoning,
+        judge: None,
+    }
+}
+
+/// Input variables as `name: value` lines, the expected output aside
+pub fn format_inputs(input: &EvaluationInput) -> String {
+    let mut variables: Vec<(&String, &String)> =
+        input.variables.iter().filter(|(name, _)| name.as_str() != EXPECTED_VARIABLE).collect();
+    variables.sort();
+    variables
+        .iter()
+        .map(|(name, value)| format!("{}: {}", name, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Messages asking a judge model to grade an output against criteria
+pub fn judge_messages(criteria: &str, input: &EvaluationInput) -> Vec<OpenAIMessage> {
+    let mut user = format!(
+        "## Criteria\n{}\n\n## Input\n{}\n\n## Output\n{}\n",
+        criteria,
+        format_inputs(input),
+        input.output
+    );
+    if let Some(expected) = &input.expected {
+        user.push_str(&format!("\n## Expected output\n{}\n", expected));
+    }
+
+    let messages = vec![
+        OpenAIMessage {
+            role: "system".to_string(),
+            content: "You are an impartial evaluator. Grade the output against the criteria.".to_string(),
+        },
+        OpenAIMessage { role: "user".to_string(), content: user },
+    ];
+    with_answer_format(messages, JUDGE_SCALE)
+}
+
+/// Ask for a `{"score", "reasoning"}` JSON answer in the system message (added when missing)
+pub fn with_answer_format(mut messages: Vec<OpenAIMessage>, scale: f64) -> Vec<OpenAIMessage> {
+    let format = format!(
+        "Score from 0 to {}. Answer with JSON only: {{\"score\": <number>, \"reasoning\": \"<one or two sentences>\"}}",
+        scale
+    );
+    match messages.first_mut() {
+        Some(first) if first.role == "system" => first.content = format!("{}\n\n{}", first.content.trim_end(), format),
+        _ => messages.insert(0, OpenAIMessage { role: "system".to_string(), content: format }),
+    }
+    messages
+}
+
+/// Score (normalized to 0..1) and reasoning from a judge's `{"score": ..., "reasoning": ...}` answer
+pub fn parse_judge_response(response: &str, scale: f64) -> Result<(f64, String), String> {
+    let json = parse_json_output(response)
+        .or_else(|_| {
+            // Some models wrap the JSON in prose
+            match (response.find('{'), response.rfind('}')) {
+                (Some(start), Some(end)) if start < end => parse_json_output(&response[start..=end]),
+                _ => Err("no JSON object".to_string()),
+            }
+        })
+        .map_err(|_| format!("Judge answer is not JSON: {}", response.trim()))?;
+
+    let score = json
+        .get("score")
+        .and_then(|score| score.as_f64().or_else(|| score.as_str().and_then(|s| s.trim().parse().ok())))
+        .ok_or_else(|| format!("Judge answer has no numeric score: {}", response.trim()))?;
+    let reasoning = json.get("reasoning").and_then(Value::as_str).unwrap_or_default().to_string();
+    Ok(((score / scale).clamp(0.0, 1.0), reasoning))
+}
+
+/// Weighted mean of (score, weight) pairs; None without any weight
+pub fn aggregate_score(scores: &[(f64, f64)]) -> Option<f64> {
+    let total_weight: f64 = scores.iter().map(|(_, weight)| weight.max(0.0)).sum();
+    if total_weight <= 0.0 {
+        return None;
+    }
+    Some(scores.iter().map(|(score, weight)| score * weight.max(0.0)).sum::<f64>() / total_weight)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(output: &str) -> EvaluationInput {
+        EvaluationInput { output: output.to_string(), ..Default::default() }
+    }
+
+    fn run(rule_type: &str, config: &str, output: &str) -> RuleOutcome {
+        check(&Rule::parse(rule_type, config).unwrap(), &input(output)).unwrap()
+    }
+
+    #[test]
+    fn test_local_rules() {
+        assert!(run("regex", r#"{"pattern": "^dear", "case_insensitive": true}"#, "Dear Ann").passed);
+        assert!(!run("regex", r#"{"pattern": "sorry", "should_match": false}"#, "So sorry").passed);
+
+        let keywords = run("keyword", r#"{"keywords": ["refund", "Order", "ship"]}"#, "Your order refund is done");
+        assert!(!keywords.passed);
+        assert!((keywords.score - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(keywords.reasoning, "Missing: ship");
+        assert!(run("keyword", r#"{"keywords": ["x", "order"], "mode": "any"}"#, "order").passed);
+        assert!(!run("keyword", r#"{"keywords": ["damn"], "mode": "none"}"#, "Damn").passed);
+
+        assert!(run("length", r#"{"min": 2, "max": 3, "unit": "words"}"#, "one two three").passed);
+        assert!(!run("length", r#"{"max": 3}"#, "four").passed);
+
+        assert!(run("json", "", "```json\n{\"a\": 1}\n```").passed);
+        assert!(!run("json", r#"{"required_paths": ["$.b"]}"#, "{\"a\": 1}").passed);
+
+        let similar = run("similarity", r#"{"expected": "The cat sat on the mat", "threshold": 0.5}"#, "the cat sat");
+        assert!(similar.passed);
+        assert_eq!(similarity("a b", "c d"), 0.0);
+        assert!((similarity("Same words", "same, WORDS") - 1.0).abs() < 1e-9);
+
+        assert!(Rule::parse("sentiment", "{}").is_err());
+        assert!(Rule::parse("regex", r#"{"pattern": "("}"#).is_err());
+        assert!(Rule::parse("keyword", r#"{"keywords": []}"#).is_err());
+        assert!(check(&Rule::parse("similarity", "{}").unwrap(), &input("x")).is_err());
+    }
+
+    #[test]
+    fn test_judge_and_aggregate() {
+        let (score, reasoning) = parse_judge_response("```json\n{\"score\": 8, \"reasoning\": \"Polite\"}\n```", JUDGE_SCALE).unwrap();
+        assert!((score - 0.8).abs() < 1e-9);
+        assert_eq!(reasoning, "Polite");
+        let (score, _) = parse_judge_response("Here you go: {\"score\": \"12\"}", JUDGE_SCALE).unwrap();
+        assert_eq!(score, 1.0);
+        assert!(parse_judge_response("Great answer!", JUDGE_SCALE).is_err());
+
+        let messages = judge_messages("Be brief", &EvaluationInput {
+            output: "Hi".to_string(),
+            variables: HashMap::from([("expected".to_string(), "Hello".to_string()), ("name".to_string(), "Ann".to_string())]),
+            expected: Some("Hello".to_string()),
+        });
+        assert!(messages[1].content.contains("name: Ann"));
+        assert!(messages[1].content.contains("## Expected output\nHello"));
+        assert!(messages[0].content.ends_with("\"reasoning\": \"<one or two sentences>\"}"));
+
+        let rubric = with_answer_format(vec![OpenAIMessage { role: "user".to_string(), content: "Rate it".to_string() }], 5.0);
+        assert_eq!(rubric.len(), 2);
+        assert!(rubric[0].content.starts_with("Score from 0 to 5."));
+
+        assert_eq!(aggregate_score(&[(1.0, 3.0), (0.0, 1.0)]), Some(0.75));
+        assert_eq!(aggregate_score(&[(1.0, 0.0)]), None);
+
+        let outcome = script_outcome(true, Some(0), "{\"score\": 0.4, \"reasoning\": \"Too long\"}", "");
+        assert_eq!((outcome.score, outcome.passed, outcome.reasoning.as_str()), (0.4, true, "Too long"));
+        let outcome = script_outcome(false, Some(2), "", "");
+        assert_eq!((outcome.score, outcome.reasoning.as_str()), (0.0, "Exited with code 2"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_script() {
+        let rule = ScriptRule {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), "grep -q '\"output\":\"ok\"' && echo 0.9".to_string()],
+            timeout_ms: None,
+        };
+        let outcome = run_script(&rule, &input("ok"), &std::env::temp_dir()).await.unwrap();
+        assert!(outcome.passed);
+        assert_eq!(outcome.score, 0.9);
+
+        let outcome = run_script(&rule, &input("no"), &std::env::temp_dir()).await.unwrap();
+        assert!(!outcome.passed);
+
+        // Ignores an input larger than the pipe buffer; the timeout still applies
+        let rule = ScriptRule {
+            command: "sh".to_string(),
+            args: vec!["-c".to_string(), "sleep 5".to_string()],
+            timeout_ms: Some(200),
+        };
+        let started = std::time::Instant::now();
+        let error = run_script(&rule, &input(&"x".repeat(1 << 20)), &std::env::temp_dir()).await.unwrap_err();
+        assert_eq!(error, "sh timed out after 200 ms");
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
@@ -1,5 +1,7 @@
 use crate::models::execution::*;
 use crate::models::prompt::*;
+use crate::models::variables::resolve_inputs;
+use crate::services::database::ModelPricing;
 use crate::services::template::replace_variables;
 use crate::services::providers;
 use std::collections::HashMap;
@@ -13,16 +15,14 @@ impl Executor {
         Self
     }
 
-    pub async fn execute(
+    /// Replace variables in prompt messages
+    /// Inputs are checked against the prompt's declared variables first
+    pub fn render_messages(
         &self,
         prompt: &PromptRuntime,
-        variables: HashMap<String, String>,
-        api_key: &str,
-        base_url: Option<&str>,
-    ) -> Result<ExecutionResult, String> {
-        let start = Instant::now();
-
-        // Replace variables in messages
+        variables: &HashMap<String, String>,
+    ) -> Result<Vec<OpenAIMessage>, String> {
+        let variables = resolve_inputs(&prompt.variables, variables)?;
         let mut messages = Vec::new();
         for msg in &prompt.messages {
             let content = replace_variables(&msg.content, &variables)?;
@@ -31,6 +31,19 @@ impl Executor {
                 content,
             });
         }
+        Ok(messages)
+    }
+
+    /// Send already rendered messages to the prompt's provider
+    pub async fn execute_messages(
+        &self,
+        prompt: &PromptRuntime,
+        messages: Vec<OpenAIMessage>,
+        api_key: &str,
+        base_url: Option<&str>,
+        pricing: Option<&ModelPricing>,
+    ) -> Result<ExecutionResult, String> {
+        let start = Instant::now();
 
         // Get temperature
         let temperature = prompt
@@ -54,12 +67,7 @@ impl Executor {
         let latency = start.elapsed().as_millis() as u64;
 
         // Calculate cost
-        let cost = calculate_cost(
-            &prompt.config.model,
-            &prompt.config.provider,
-            usage.prompt_tokens,
-            usage.completion_tokens,
-        );
+        let cost = calculate_cost(pricing, &usage);
 
         Ok(ExecutionResult {
             id: Uuid::new_v4().to_string(),
@@ -71,42 +79,85 @@ impl Executor {
                 tokens_input: usage.prompt_tokens,
                 tokens_output: usage.completion_tokens,
                 cost_usd: cost,
+                pricing_version: pricing.map(|p| p.version),
+                pricing_id: pricing.map(|p| p.id.clone()),
                 timestamp: chrono::Utc::now().timestamp(),
             },
+            budget_warnings: Vec::new(),
+            assertions: Vec::new(),
         })
     }
 }
 
-fn calculate_cost(model: &str, provider: &Provider, input_tokens: u32, output_tokens: u32) -> f64 {
-    let (input_price, output_price) = match provider {
-        Provider::OpenAI => match model {
-            "gpt-4o" => (2.5, 10.0),
-            "gpt-4o-mini" => (0.15, 0.60),
-            "gpt-4-turbo" => (10.0, 30.0),
-            "gpt-4" => (30.0, 60.0),
-            "gpt-3.5-turbo" => (0.5, 1.5),
-            _ => (0.0, 0.0),
-        },
-        Provider::Anthropic => match model {
-            "claude-3-opus-20240229" => (15.0, 75.0),
-            "claude-3-sonnet-20240229" => (3.0, 15.0),
-            "claude-3-haiku-20240307" => (0.25, 1.25),
-            "claude-3-5-sonnet-20241022" => (3.0, 15.0),
-            _ => (0.0, 0.0),
-        },
-        Provider::DeepSeek => (0.14, 0.28),     // DeepSeek pricing
-        Provider::Ollama => (0.0, 0.0),         // Local, free
-        Provider::OpenRouter => (0.0, 0.0),     // Depends on model
-        Provider::AiHubMix => (0.0, 0.0),       // Depends on model
-        Provider::Google => (0.0, 0.0),         // Google pricing varies
-        Provider::GitHub => (0.0, 0.0),         // GitHub pricing
-        Provider::Custom => (0.0, 0.0),         // Custom provider, pricing unknown
-        _ => (0.0, 0.0),
+/// Calculate execution cost from a model_pricing entry (prices per 1M tokens)
+/// Returns 0.0 when no pricing is known for the model
+pub fn calculate_cost(pricing: Option<&ModelPricing>, usage: &OpenAIUsage) -> f64 {
+    let pricing = match pricing {
+        Some(p) => p,
+        None => return 0.0,
     };
 
-    let cost_input = (input_tokens as f64 / 1_000_000.0) * input_price;
-    let cost_output = (output_tokens as f64 / 1_000_000.0) * output_price;
+    let cached_tokens = usage.cached_tokens();
+    let uncached_tokens = usage
+        .prompt_tokens
+        .saturating_sub(cached_tokens)
+        .saturating_sub(usage.cache_write_tokens);
 
-    cost_input + cost_output
+    // Cache prices fall back to the regular input price when not set
+    let cache_read_price = pricing.cache_read_price.unwrap_or(pricing.input_price);
+    let cache_write_price = pricing.cache_write_price.unwrap_or(pricing.input_price);
+
+    let cost_input = (uncached_tokens as f64 / 1_000_000.0) * pricing.input_price;
+    let cost_cache_read = (cached_tokens as f64 / 1_000_000.0) * cache_read_price;
+    let cost_cache_write = (usage.cache_write_tokens as f64 / 1_000_000.0) * cache_write_price;
+    let cost_output = (usage.completion_tokens as f64 / 1_000_000.0) * pricing.output_price;
+
+    cost_input + cost_cache_read + cost_cache_write + cost_output
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pricing(cache_read_price: Option<f64>) -> ModelPricing {
+        ModelPricing {
+            id: "test".to_string(),
+            provider: "openai".to_string(),
+            model: "gpt-4o".to_string(),
+            input_price: 2.5,
+            output_price: 10.0,
+            cache_read_price,
+            cache_write_price: None,
+            source: "default".to_string(),
+            version: 1,
+            updated_at: 0,
+        }
+    }
+
+    fn usage(prompt_tokens: u32, completion_tokens: u32, cached_tokens: u32) -> OpenAIUsage {
+        OpenAIUsage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            prompt_tokens_details: Some(PromptTokensDetails { cached_tokens }),
+            cache_write_tokens: 0,
+        }
+    }
+
+    #[test]
+    fn test_calculate_cost() {
+        let cost = calculate_cost(Some(&pricing(None)), &usage(1_000_000, 1_000_000, 0));
+        assert!((cost - 12.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_calculate_cost_with_cached_tokens() {
+        let cost = calculate_cost(Some(&pricing(Some(1.25))), &usage(1_000_000, 0, 500_000));
+        assert!((cost - 1.875).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_calculate_cost_unknown_pricing() {
+        assert_eq!(calculate_cost(None, &usage(1000, 1000, 0)), 0.0);
+    }
+}
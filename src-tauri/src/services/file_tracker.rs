@@ -4,6 +4,7 @@ use std::path::Path;
 use std::time::UNIX_EPOCH;
 use uuid::Uuid;
 use crate::services::database::{ProjectDatabase, PromptFileMetadata};
+use crate::models::frontmatter::{parse_frontmatter, split_frontmatter};
 use crate::models::prompt::parse_markdown_prompt;
 
 #[allow(dead_code)]
@@ -44,6 +45,10 @@ impl FileTracker {
             .unwrap()
             .as_secs() as i64;
         
+        // Prompt config from frontmatter (the file is the source of truth)
+        let (frontmatter, _) = parse_frontmatter(&content_str)?;
+        let frontmatter = frontmatter.unwrap_or_default();
+        
         // Parse Markdown to extract variables
         let messages = parse_markdown_prompt(&content_str)
             .map_err(|e| format!("Failed to parse Markdown: {}", e))?;
@@ -81,14 +86,14 @@ impl FileTracker {
             id: file_id.clone(),
             file_path: relative_path.to_string(),
             name,
-            description: None,
+            description: frontmatter.description.clone(),
             schema_version: "v1".to_string(),
-            provider_ref: default_provider_ref.to_string(),
-            model_override: None,
-            parameters: None,
-            test_data_path: None,
-            evaluation_config: None,
-            tags: None,
+            provider_ref: frontmatter.provider_ref.clone().unwrap_or_else(|| default_provider_ref.to_string()),
+            model_override: frontmatter.model.clone(),
+            parameters: frontmatter.parameters.as_ref().and_then(|p| serde_json::to_string(p).ok()),
+            test_data_path: frontmatter.test_data.clone(),
+            evaluation_config: frontmatter.evaluation.as_ref().and_then(|e| serde_json::to_string(e).ok()),
+            tags: frontmatter.tags.as_ref().and_then(|t| serde_json::to_string(t).ok()),
             variables: Some(variables_json),
             file_hash,
             file_size,
@@ -126,6 +131,7 @@ impl FileTracker {
     fn extract_name_from_markdown(&self, content: &str) -> Option<String> {
         use pulldown_cmark::{Parser, Event, Tag, HeadingLevel};
         
+        let (_, content) = split_frontmatter(content);
         let parser = Parser::new(content);
         let mut in_h1 = false;
         let mut h1_text = String::new();
@@ -0,0 +1,316 @@
+use crate::models::prompt::{Message, PromptRuntime};
+use crate::models::variables::VariableType;
+use crate::services::database::ProjectDatabase;
+use crate::services::{partials, template};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// file_dependencies.dependency_type for files loaded into prompts
+pub const FILE_VARIABLE_DEPENDENCY: &str = "file_variable";
+
+pub const MAX_FILE_VARIABLE_BYTES: u64 = 256 * 1024;
+
+const ALLOWED_EXTENSIONS: &[&str] = &["txt", "md", "json", "csv"];
+
+/// `file:path` with an optional line range: `#L10-L20`, `#10-20`, `#L5` or `#10-`
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileReference {
+    pub path: String,
+    /// First and last line (1-based, inclusive); no last line means through the end
+    pub lines: Option<(usize, Option<usize>)>,
+}
+
+/// File loaded for an execution, as recorded in execution_history.input_files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedFile {
+    /// Variable that received the content: the `file:` reference or a declared file variable
+    pub variable: String,
+    /// Workspace-relative path
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lines: Option<String>,
+    /// SHA-256 of the content passed to the prompt
+    pub sha256: String,
+    pub bytes: usize,
+}
+
+pub fn parse_reference(reference: &str) -> Result<FileReference, String> {
+    let reference = reference.strip_prefix("file:").unwrap_or(reference).trim();
+
+    let (path, range) = match reference.rsplit_once('#') {
+        Some((path, range)) => (path, Some(range)),
+        None => (reference, None),
+    };
+    if path.is_empty() {
+        return Err("File variable needs a path".to_string());
+    }
+    if path.contains("://") {
+        return Err(format!("{} is a URL: only workspace files can be loaded, save it in the workspace first", path));
+    }
+
+    let lines = match range {
+        None => None,
+        Some(range) => {
+            let invalid = || format!("Invalid line range '#{}' (use #L10-L20)", range);
+            let number = |s: &str| s.trim().trim_start_matches(['L', 'l']).parse::<usize>().map_err(|_| invalid());
+
+            let (start, end) = match range.split_once('-') {
+                Some((start, "")) => (number(start)?, None),
+                Some((start, end)) => (number(start)?, Some(number(end)?)),
+                None => {
+                    let line = number(range)?;
+                    (line, Some(line))
+                }
+            };
+            if start == 0 || end.is_some_and(|end| end < start) {
+                return Err(invalid());
+            }
+            Some((start, end))
+        }
+    };
+
+    Ok(FileReference { path: path.to_string(), lines })
+}
+
+fn describe_lines(lines: (usize, Option<usize>)) -> String {
+    match lines {
+        (start, Some(end)) if end == start => start.to_string(),
+        (start, Some(end)) => format!("{}-{}", start, end),
+        (start, None) => format!("{}-", start),
+    }
+}
+
+/// Path of a referenced file; it must stay inside the workspace
+fn resolve_path(workspace: &Path, path: &str) -> Result<PathBuf, String> {
+    let full_path = workspace.join(path);
+    let canonical = full_path
+        .canonicalize()
+        .map_err(|_| format!("File not found: {}", path))?;
+    let root = workspace
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve workspace: {}", e))?;
+
+    if !canonical.starts_with(&root) {
+        return Err(format!("File {} is outside the workspace", path));
+    }
+    Ok(full_path)
+}
+
+/// Read a referenced file as text, applying its line range
+pub fn read_reference(workspace: &Path, reference: &FileReference) -> Result<String, String> {
+    let path = resolve_path(workspace, &reference.path)?;
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+    if !ALLOWED_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(format!(
+            "Unsupported file type for {}: use .txt, .md, .json or .csv",
+            reference.path
+        ));
+    }
+
+    let size = fs::metadata(&path)
+        .map_err(|e| format!("Failed to read {}: {}", reference.path, e))?
+        .len();
+    if size > MAX_FILE_VARIABLE_BYTES {
+        return Err(format!(
+            "{} is too large ({} KB, limit {} KB)",
+            reference.path,
+            size / 1024,
+            MAX_FILE_VARIABLE_BYTES / 1024
+        ));
+    }
+
+    let bytes = fs::read(&path).map_err(|e| format!("Failed to read {}: {}", reference.path, e))?;
+    let text = String::from_utf8(bytes).map_err(|_| format!("{} is not UTF-8 text", reference.path))?;
+    let text = text.trim_start_matches('\u{feff}').replace("\r\n", "\n");
+
+    match reference.lines {
+        Some((start, end)) => {
+            let lines: Vec<&str> = text.lines().collect();
+            if start > lines.len() {
+                return Err(format!(
+                    "Line range {} is outside {} ({} lines)",
+                    describe_lines((start, end)),
+                    reference.path,
+                    lines.len()
+                ));
+            }
+            let end = end.unwrap_or(lines.len()).min(lines.len());
+            Ok(lines[start - 1..end].join("\n"))
+        }
+        // Whole JSON documents are validated, and passed as written
+        None if extension == "json" => {
+            serde_json::from_str::<serde_json::Value>(&text)
+                .map_err(|e| format!("{} is not valid JSON: {}", reference.path, e))?;
+            Ok(text)
+        }
+        None => Ok(text),
+    }
+}
+
+fn load(workspace: &Path, variable: &str, reference: &str) -> Result<(String, LoadedFile), String> {
+    let reference = parse_reference(reference)?;
+    let content = read_reference(workspace, &reference)?;
+
+    let loaded = LoadedFile {
+        variable: variable.to_string(),
+        path: reference.path.clone(),
+        lines: reference.lines.map(describe_lines),
+        sha256: format!("{:x}", Sha256::digest(content.as_bytes())),
+        bytes: content.len(),
+    };
+    Ok((content, loaded))
+}
+
+/// `file:` references in the messages (messages with template errors are skipped)
+pub fn file_references(messages: &[Message]) -> Vec<String> {
+    let mut references = Vec::new();
+    for message in messages {
+        for reference in template::extract_file_references(&message.content).unwrap_or_default() {
+            if !references.contains(&reference) {
+                references.push(reference);
+            }
+        }
+    }
+    references
+}
+
+/// Load the files a prompt needs into `variables`:
+/// `{{file:...}}` references, and declared `file` variables (whose value is a path)
+pub fn load_file_variables(
+    prompt: &PromptRuntime,
+    workspace: Option<&Path>,
+    variables: &mut HashMap<String, String>,
+) -> Result<Vec<LoadedFile>, String> {
+    let mut requests: Vec<(String, String)> = file_references(&prompt.messages)
+        .into_iter()
+        .map(|reference| (reference.clone(), reference))
+        .collect();
+
+    for declaration in prompt.variables.iter().filter(|d| d.var_type == VariableType::File) {
+        let path = variables
+            .get(&declaration.name)
+            .filter(|v| !v.is_empty())
+            .cloned()
+            .or_else(|| declaration.default_text());
+        if let Some(path) = path {
+            requests.push((declaration.name.clone(), path));
+        }
+    }
+
+    if requests.is_empty() {
+        return Ok(Vec::new());
+    }
+    let workspace = workspace.ok_or("File variables need an open workspace")?;
+
+    let mut loaded = Vec::new();
+    for (variable, reference) in requests {
+        let (content, file) = load(workspace, &variable, &reference)?;
+        variables.insert(variable, content);
+        loaded.push(file);
+    }
+    Ok(loaded)
+}
+
+/// Record the files a prompt loads in file_dependencies (replacing the previous list),
+/// with workspace-relative paths
+pub fn record_files(db: &ProjectDatabase, file_path: &str, workspace: &Path, paths: &[String]) -> Result<(), String> {
+    let prompt_file_id = db.ensure_prompt_file(file_path)
+        .map_err(|e| format!("Failed to ensure file record: {}", e))?;
+
+    let mut targets: Vec<String> = Vec::new();
+    for path in paths {
+        let target = partials::workspace_relative(workspace, Path::new(path));
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+
+    db.replace_file_dependencies(&prompt_file_id, FILE_VARIABLE_DEPENDENCY, &targets)
+        .map_err(|e| format!("Failed to record dependencies: {}", e))
+}
+
+/// Paths of the `file:` references of a prompt (invalid references are skipped)
+pub fn referenced_paths(messages: &[Message]) -> Vec<String> {
+    file_references(messages)
+        .iter()
+        .filter_map(|reference| parse_reference(reference).ok())
+        .map(|reference| reference.path)
+        .collect()
+}
+
+/// Re-scan every .vibe.md file in the workspace and record the files it references
+pub fn index_workspace(db: &ProjectDatabase, workspace_path: &str) -> Result<usize, String> {
+    let workspace = Path::new(workspace_path);
+    let files = partials::markdown_prompts(workspace);
+
+    for file in &files {
+        let messages = match fs::read_to_string(file)
+            .map_err(|e| e.to_string())
+            .and_then(|content| crate::models::prompt::parse_markdown_prompt(&content))
+        {
+            Ok(messages) => messages,
+            Err(_) => continue,
+        };
+        record_files(db, &file.display().to_string(), workspace, &referenced_paths(&messages))?;
+    }
+
+    Ok(files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reference() {
+        assert_eq!(
+            parse_reference("file:docs/spec.md#L10-L20").unwrap(),
+            FileReference { path: "docs/spec.md".to_string(), lines: Some((10, Some(20))) }
+        );
+        assert_eq!(parse_reference("file:a.txt#5").unwrap().lines, Some((5, Some(5))));
+        assert_eq!(parse_reference("file:a.txt#5-").unwrap().lines, Some((5, None)));
+        assert_eq!(parse_reference("file:a.txt").unwrap().lines, None);
+        assert!(parse_reference("file:a.txt#20-10").is_err());
+        assert!(parse_reference("file:https://example.com/spec.md").unwrap_err().contains("is a URL"));
+    }
+
+    #[test]
+    fn test_load_file_variables() {
+        let workspace = std::env::temp_dir().join(format!("vibebase-files-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(workspace.join("docs")).unwrap();
+        fs::write(workspace.join("docs/spec.md"), "one\r\ntwo\r\nthree\r\n").unwrap();
+        fs::write(workspace.join("docs/data.json"), r#"{"b": 1, "a": 2}"#).unwrap();
+        fs::write(workspace.join("docs/image.png"), "png").unwrap();
+
+        let prompt: PromptRuntime = serde_yaml::from_str(
+            "schema: v1\nname: t\nconfig: {provider: openai, model: gpt-4o}\nmessages:\n  - role: user\n    content: \"{{file:docs/spec.md#L2-L3}} {{file:docs/data.json}}\"\n",
+        )
+        .unwrap();
+        let mut variables = HashMap::new();
+        let loaded = load_file_variables(&prompt, Some(&workspace), &mut variables).unwrap();
+
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(variables["file:docs/spec.md#L2-L3"], "two\nthree");
+        assert_eq!(variables["file:docs/data.json"], r#"{"b": 1, "a": 2}"#);
+        assert_eq!(loaded[0].lines.as_deref(), Some("2-3"));
+        assert_eq!(loaded[0].sha256.len(), 64);
+
+        let rendered = template::replace_variables(&prompt.messages[0].content, &variables).unwrap();
+        assert!(rendered.starts_with("two\nthree {"));
+
+        let png = FileReference { path: "docs/image.png".to_string(), lines: None };
+        assert!(read_reference(&workspace, &png).unwrap_err().contains("Unsupported file type"));
+        let outside = FileReference { path: "../".to_string() + "x.txt", lines: None };
+        assert!(read_reference(&workspace, &outside).is_err());
+
+        fs::remove_dir_all(&workspace).ok();
+    }
+}
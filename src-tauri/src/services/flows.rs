@@ -0,0 +1,379 @@
+//! Prompt chains defined in `.vibe.flow.yaml` files
+//!
+//! ```yaml
+//! name: support
+//! inputs: [ticket]
+//! steps:
+//!   - id: classify
+//!     prompt: prompts/classify.vibe.md
+//!     outputs:
+//!       category: { json: $.category }
+//!     next:
+//!       - when: { var: classify.category, equals: refund }
+//!         goto: refund
+//!       - goto: respond
+//!   - id: refund
+//!     prompt: ./refund.vibe.md
+//!     inputs:
+//!       reason: "{{classify.output}}"
+//!     next: [{ goto: end }]
+//!   - id: respond
+//!     prompt: prompts/respond.vibe.md
+//!     provider: anthropic_default
+//!     outputs:
+//!       reply: text
+//!       ticket_id: { regex: "#(\\d+)" }
+//! ```
+//!
+//! Each executed step is added to the flow variables under its id as a JSON object holding
+//! `output` (the raw text) and its mapped outputs, so later steps can use `{{classify.category}}`.
+
+use crate::services::json_output::{json_path, parse_json_output};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+pub const FLOW_EXTENSION: &str = ".vibe.flow.yaml";
+
+/// `goto` target that stops the flow
+pub const END: &str = "end";
+
+/// Steps executed by one run at most (guards against goto loops)
+pub const MAX_EXECUTED_STEPS: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Flow {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Variables the flow must be given
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    pub steps: Vec<FlowStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlowStep {
+    pub id: String,
+    /// `.vibe.md` file: `./` and `../` paths are relative to the flow file, others to the workspace
+    pub prompt: String,
+    /// provider_ref overriding the prompt's own
+    #[serde(default)]
+    pub provider: Option<String>,
+    /// Step variables rendered from the flow variables, added to them for this step
+    #[serde(default)]
+    pub inputs: HashMap<String, String>,
+    #[serde(default)]
+    pub outputs: HashMap<String, OutputMapping>,
+    /// The step is skipped unless the condition holds
+    #[serde(default)]
+    pub when: Option<Condition>,
+    /// First matching branch wins; without one the flow continues with the following step
+    #[serde(default)]
+    pub next: Vec<Branch>,
+}
+
+/// How a named output is taken from a step's raw output
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum OutputMapping {
+    /// `{ json: $.items[0].name }`: the output parsed as JSON (code fences allowed)
+    Json { json: String },
+    /// `{ regex: "...", group: 1 }`: a capture group, the first one by default
+    Regex {
+        regex: String,
+        #[serde(default)]
+        group: Option<usize>,
+    },
+    /// `text`: the whole output
+    Text(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Branch {
+    #[serde(default)]
+    pub when: Option<Condition>,
+    /// Step id, or `end`
+    pub goto: String,
+}
+
+/// Test on a flow variable (`step.output_name` paths allowed); every given check must pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Condition {
+    pub var: String,
+    pub equals: Option<Value>,
+    pub not_equals: Option<Value>,
+    pub contains: Option<String>,
+    /// Regex
+    pub matches: Option<String>,
+    #[serde(rename = "in")]
+    pub one_of: Option<Vec<Value>>,
+    pub exists: Option<bool>,
+}
+
+pub fn is_flow_file(path: &str) -> bool {
+    path.ends_with(FLOW_EXTENSION)
+}
+
+/// Parse and check a flow file
+pub fn parse_flow(content: &str) -> Result<Flow, String> {
+    let flow: Flow = serde_yaml::from_str(content).map_err(|e| format!("Invalid flow: {}", e))?;
+    validate(&flow)?;
+    Ok(flow)
+}
+
+fn validate(flow: &Flow) -> Result<(), String> {
+    if flow.steps.is_empty() {
+        return Err("A flow needs at least one step".to_string());
+    }
+
+    let mut ids: Vec<&str> = Vec::new();
+    for step in &flow.steps {
+        if step.id.is_empty() || step.id == END || step.id.contains('.') {
+            return Err(format!("Invalid step id '{}'", step.id));
+        }
+        if ids.contains(&step.id.as_str()) {
+            return Err(format!("Duplicate step id '{}'", step.id));
+        }
+        if flow.inputs.contains(&step.id) {
+            return Err(format!("Step id '{}' is also a flow input", step.id));
+        }
+        ids.push(&step.id);
+    }
+
+    for step in &flow.steps {
+        for branch in &step.next {
+            if branch.goto != END && !ids.contains(&branch.goto.as_str()) {
+                return Err(format!("Step '{}' goes to unknown step '{}'", step.id, branch.goto));
+            }
+        }
+        for (name, mapping) in &step.outputs {
+            if name == "output" {
+                return Err(format!("Step '{}': 'output' is reserved for the raw output", step.id));
+            }
+            match mapping {
+                OutputMapping::Regex { regex, .. } => {
+                    Regex::new(regex).map_err(|e| format!("Step '{}', output '{}': {}", step.id, name, e))?;
+                }
+                OutputMapping::Text(keyword) if keyword != "text" => {
+                    return Err(format!(
+                        "Step '{}', output '{}': expected 'text', {{ json: ... }} or {{ regex: ... }}",
+                        step.id, name
+                    ));
+                }
+                _ => {}
+            }
+        }
+        let conditions = step.when.iter().chain(step.next.iter().filter_map(|b| b.when.as_ref()));
+        for condition in conditions {
+            if let Some(pattern) = &condition.matches {
+                Regex::new(pattern).map_err(|e| format!("Step '{}', condition on '{}': {}", step.id, condition.var, e))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Apply an output mapping to a step's raw output
+pub fn extract_output(output: &str, mapping: &OutputMapping) -> Result<Value, String> {
+    match mapping {
+        OutputMapping::Text(_) => Ok(Value::String(output.to_string())),
+        OutputMapping::Json { json } => {
+            let value = parse_json_output(output)?;
+            json_path(&value, json)?
+                .cloned()
+                .ok_or_else(|| format!("JSON path '{}' not found in output", json))
+        }
+        OutputMapping::Regex { regex, group } => {
+            let regex = Regex::new(regex).map_err(|e| format!("Invalid regex: {}", e))?;
+            let captures = regex
+                .captures(output)
+                .ok_or_else(|| format!("Regex '{}' does not match the output", regex))?;
+            let group = group.unwrap_or(if regex.captures_len() > 1 { 1 } else { 0 });
+            captures
+                .get(group)
+                .map(|m| Value::String(m.as_str().to_string()))
+                .ok_or_else(|| format!("Regex '{}' has no group {}", regex, group))
+        }
+    }
+}
+
+/// The flow variable of a finished step: `{ "output": raw, <name>: value, ... }`
+pub fn step_value(output: &str, outputs: &serde_json::Map<String, Value>) -> String {
+    let mut value = outputs.clone();
+    value.insert("output".to_string(), Value::String(output.to_string()));
+    Value::Object(value).to_string()
+}
+
+/// Look up a flow variable; `step.name` paths navigate step values
+fn lookup(variables: &HashMap<String, String>, path: &str) -> Option<Value> {
+    let (name, rest) = match path.split_once('.') {
+        Some((name, rest)) => (name, Some(rest)),
+        None => (path, None),
+    };
+    let raw = variables.get(name.trim())?;
+    match rest {
+        None => Some(Value::String(raw.clone())),
+        Some(rest) => {
+            let value: Value = serde_json::from_str(raw).ok()?;
+            json_path(&value, rest).ok().flatten().cloned()
+        }
+    }
+}
+
+/// Values compare as text so `"3"` equals `3`
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl Condition {
+    pub fn holds(&self, variables: &HashMap<String, String>) -> bool {
+        let value = lookup(variables, &self.var).filter(|v| !v.is_null());
+
+        if let Some(exists) = self.exists {
+            if value.is_some() != exists {
+                return false;
+            }
+        }
+        let value_text = value.as_ref().map(text);
+
+        if let Some(expected) = &self.equals {
+            if value_text.as_deref() != Some(text(expected).as_str()) {
+                return false;
+            }
+        }
+        if let Some(unexpected) = &self.not_equals {
+            if value_text.as_deref() == Some(text(unexpected).as_str()) {
+                return false;
+            }
+        }
+        if let Some(needle) = &self.contains {
+            let found = match &value {
+                Some(Value::Array(items)) => items.iter().any(|item| text(item) == *needle),
+                Some(other) => text(other).contains(needle.as_str()),
+                None => false,
+            };
+            if !found {
+                return false;
+            }
+        }
+        if let Some(pattern) = &self.matches {
+            let matched = match (Regex::new(pattern), &value_text) {
+                (Ok(regex), Some(value_text)) => regex.is_match(value_text),
+                _ => false,
+            };
+            if !matched {
+                return false;
+            }
+        }
+        if let Some(options) = &self.one_of {
+            if !options.iter().any(|option| value_text.as_deref() == Some(text(option).as_str())) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Index of the step to run after `index`, or None when the flow ends
+pub fn next_step(flow: &Flow, index: usize, variables: &HashMap<String, String>) -> Option<usize> {
+    let step = &flow.steps[index];
+    let branch = step
+        .next
+        .iter()
+        .find(|branch| branch.when.as_ref().is_none_or(|condition| condition.holds(variables)));
+
+    match branch {
+        Some(branch) if branch.goto == END => None,
+        Some(branch) => flow.steps.iter().position(|s| s.id == branch.goto),
+        None if index + 1 < flow.steps.len() => Some(index + 1),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLOW: &str = r##"
+name: support
+inputs: [ticket]
+steps:
+  - id: classify
+    prompt: classify.vibe.md
+    outputs:
+      category: { json: "$.result.category" }
+    next:
+      - when: { var: classify.category, in: [refund, billing] }
+        goto: refund
+      - goto: respond
+  - id: refund
+    prompt: ./refund.vibe.md
+    next: [{ goto: end }]
+  - id: respond
+    prompt: respond.vibe.md
+    when: { var: ticket, matches: "\\S" }
+    outputs:
+      reply: text
+      ticket_id: { regex: "#(\\d+)" }
+"##;
+
+    #[test]
+    fn test_parse_and_validate_flow() {
+        let flow = parse_flow(FLOW).unwrap();
+        assert_eq!(flow.steps.len(), 3);
+        assert!(matches!(flow.steps[2].outputs["reply"], OutputMapping::Text(_)));
+        assert!(matches!(flow.steps[2].outputs["ticket_id"], OutputMapping::Regex { group: None, .. }));
+
+        let unknown = FLOW.replace("goto: respond", "goto: reply");
+        assert!(parse_flow(&unknown).unwrap_err().contains("unknown step 'reply'"));
+        let duplicate = FLOW.replace("id: refund", "id: classify");
+        assert!(parse_flow(&duplicate).unwrap_err().contains("Duplicate"));
+    }
+
+    #[test]
+    fn test_extract_output() {
+        let output = "```json\n{\"result\": {\"category\": \"refund\", \"tags\": [\"a\", \"b\"]}}\n```";
+        let json = |path: &str| extract_output(output, &OutputMapping::Json { json: path.to_string() });
+        assert_eq!(json("$.result.category").unwrap(), Value::String("refund".to_string()));
+        assert_eq!(json("result.tags[1]").unwrap(), Value::String("b".to_string()));
+        assert!(json("$.missing").is_err());
+
+        let regex = |pattern: &str, group| {
+            extract_output("Ticket #42 closed", &OutputMapping::Regex { regex: pattern.to_string(), group })
+        };
+        assert_eq!(regex(r"#(\d+)", None).unwrap(), Value::String("42".to_string()));
+        assert_eq!(regex(r"#\d+", None).unwrap(), Value::String("#42".to_string()));
+        assert!(regex(r"(\d+)", Some(2)).is_err());
+    }
+
+    #[test]
+    fn test_branches_and_conditions() {
+        let flow = parse_flow(FLOW).unwrap();
+        let mut variables = HashMap::from([("ticket".to_string(), "My order #42".to_string())]);
+
+        let outputs = serde_json::Map::from_iter([("category".to_string(), Value::String("billing".to_string()))]);
+        variables.insert("classify".to_string(), step_value("{}", &outputs));
+        assert_eq!(next_step(&flow, 0, &variables), Some(1));
+        assert_eq!(next_step(&flow, 1, &variables), None);
+
+        let outputs = serde_json::Map::from_iter([("category".to_string(), Value::String("question".to_string()))]);
+        variables.insert("classify".to_string(), step_value("{}", &outputs));
+        assert_eq!(next_step(&flow, 0, &variables), Some(2));
+        assert_eq!(next_step(&flow, 2, &variables), None);
+
+        let condition = |yaml: &str| serde_yaml::from_str::<Condition>(yaml).unwrap().holds(&variables);
+        assert!(condition("{ var: classify.output, equals: '{}' }"));
+        assert!(condition("{ var: ticket, contains: '#42' }"));
+        assert!(condition("{ var: classify.score, exists: false }"));
+        assert!(!condition("{ var: classify.category, not_equals: question }"));
+    }
+}
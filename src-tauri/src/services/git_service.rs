@@ -193,6 +193,23 @@ impl GitService {
         })
     }
 
+    // Get current commit and branch (both None outside a repository)
+    pub fn get_head_info(&self) -> GitHeadInfo {
+        let repo = match self.init_repository() {
+            Ok(repo) => repo,
+            Err(_) => return GitHeadInfo::default(),
+        };
+
+        let info = match repo.head() {
+            Ok(head) => GitHeadInfo {
+                commit: head.target().map(|oid| oid.to_string()),
+                branch: head.shorthand().map(String::from),
+            },
+            Err(_) => GitHeadInfo::default(), // Unborn branch (no commits yet)
+        };
+        info
+    }
+
     // Get ahead/behind counts
     fn get_ahead_behind(&self, repo: &Repository) -> Result<(usize, usize)> {
         let head = repo.head()?;
@@ -0,0 +1,109 @@
+use serde_json::Value;
+
+/// Output text without a surrounding Markdown code fence
+pub fn strip_code_fence(output: &str) -> &str {
+    let trimmed = output.trim();
+    if !trimmed.starts_with("```") || !trimmed.ends_with("```") || trimmed.len() < 6 {
+        return trimmed;
+    }
+    let inner = &trimmed[3..trimmed.len() - 3];
+    // Drop the info string (```json)
+    match inner.find('\n') {
+        Some(newline) => inner[newline + 1..].trim(),
+        None => inner.trim(),
+    }
+}
+
+/// Parse the output as JSON; a single ``` code block around it is accepted
+pub fn parse_json_output(output: &str) -> Result<Value, String> {
+    serde_json::from_str(strip_code_fence(output)).map_err(|e| format!("Output is not valid JSON: {}", e))
+}
+
+/// Follow a path into a JSON value: `$.items[0].name`, `$["a key"]` or `items.0.name`
+/// Ok(None) when the value has nothing at that path, Err when the path itself is invalid
+pub fn json_path<'a>(json: &'a Value, path: &str) -> Result<Option<&'a Value>, String> {
+    let invalid = || format!("Invalid JSON path '{}'", path);
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err(invalid());
+    }
+    let mut rest = trimmed.strip_prefix('$').unwrap_or(trimmed);
+    // Without `$` the path starts with a key (`items.0`)
+    let mut leading_key = !trimmed.starts_with('$');
+    let mut current = json;
+
+    while !rest.is_empty() {
+        let next = if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(invalid)?;
+            let segment = after[..end].trim();
+            rest = &after[end + 1..];
+            match segment.parse::<usize>() {
+                Ok(index) => current.get(index),
+                Err(_) => {
+                    let key = segment
+                        .strip_prefix('"')
+                        .and_then(|s| s.strip_suffix('"'))
+                        .or_else(|| segment.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')))
+                        .ok_or_else(invalid)?;
+                    current.get(key)
+                }
+            }
+        } else {
+            let after = match rest.strip_prefix('.') {
+                Some(after) => after,
+                None if leading_key => rest,
+                None => return Err(invalid()),
+            };
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return Err(invalid());
+            }
+            let key = &after[..end];
+            rest = &after[end..];
+            match current {
+                Value::Array(items) => key.parse::<usize>().ok().and_then(|index| items.get(index)),
+                _ => current.get(key),
+            }
+        };
+        leading_key = false;
+
+        match next {
+            Some(value) => current = value,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(current))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_json_output() {
+        assert_eq!(parse_json_output("```json\n{\"a\": 1}\n```").unwrap(), json!({"a": 1}));
+        assert_eq!(parse_json_output("```{\"a\": 1}```").unwrap(), json!({"a": 1}));
+        assert_eq!(parse_json_output("  [1, 2]\n").unwrap(), json!([1, 2]));
+        assert!(parse_json_output("```\n```").is_err());
+        assert!(parse_json_output("{a: 1}").unwrap_err().starts_with("Output is not valid JSON"));
+    }
+
+    #[test]
+    fn test_json_path() {
+        let value = json!({"items": [{"name": "a"}, {"name": "b"}], "a key": true});
+        let path = |path: &str| json_path(&value, path).map(|found| found.cloned());
+
+        assert_eq!(path("$.items[1].name").unwrap(), Some(json!("b")));
+        assert_eq!(path("items.1.name").unwrap(), Some(json!("b")));
+        assert_eq!(path("items[0]").unwrap(), Some(json!({"name": "a"})));
+        assert_eq!(path("$[\"a key\"]").unwrap(), Some(json!(true)));
+        assert_eq!(path("$").unwrap(), Some(value.clone()));
+        assert_eq!(path("$.items[2].name").unwrap(), None);
+        assert_eq!(path("$.missing").unwrap(), None);
+
+        for invalid in ["", "$items", "$..items", "$.items[0", "$[key]"] {
+            assert!(path(invalid).unwrap_err().starts_with("Invalid JSON path"), "{}", invalid);
+        }
+    }
+}
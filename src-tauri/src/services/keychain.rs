@@ -91,5 +91,38 @@ impl KeychainService {
             .map_err(|e| format!("Git token not found: {}", e))
     }
 
-}
+    // Secret variables (global, project and profile), keyed by variable id
+    pub fn save_variable_secret(variable_id: &str, value: &str) -> Result<(), String> {
+        let key = format!("variable:{}", variable_id);
+        let entry = Entry::new(SERVICE_NAME, &key)
+            .map_err(|e| format!("Keychain error: {}", e))?;
+        
+        entry
+            .set_password(value)
+            .map_err(|e| format!("Failed to save secret variable: {}", e))?;
+        
+        Ok(())
+    }
+
+    pub fn get_variable_secret(variable_id: &str) -> Result<String, String> {
+        let key = format!("variable:{}", variable_id);
+        let entry = Entry::new(SERVICE_NAME, &key)
+            .map_err(|e| format!("Keychain error: {}", e))?;
+        
+        entry
+            .get_password()
+            .map_err(|e| format!("Secret variable not found: {}", e))
+    }
 
+    pub fn delete_variable_secret(variable_id: &str) -> Result<(), String> {
+        let key = format!("variable:{}", variable_id);
+        let entry = Entry::new(SERVICE_NAME, &key)
+            .map_err(|e| format!("Keychain error: {}", e))?;
+        
+        entry
+            .delete_password()
+            .map_err(|e| format!("Failed to delete secret variable: {}", e))?;
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,692 @@
+use crate::models::frontmatter::split_frontmatter;
+use crate::models::prompt::{heading_role, parse_markdown_prompt, Message, MessageRole};
+use crate::models::variables::{declared_variables, VariableDeclaration};
+use crate::services::database::{LintRuleSetting, ProjectDatabase};
+use crate::services::partials;
+use crate::services::template::{self, VariableUsage};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Off => "off",
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Severity> {
+        match name {
+            "off" => Some(Severity::Off),
+            "info" => Some(Severity::Info),
+            "warning" => Some(Severity::Warning),
+            "error" => Some(Severity::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Replace `start..end` (byte offsets in the file) with `replacement`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintFix {
+    pub description: String,
+    pub edits: Vec<TextEdit>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintFinding {
+    pub rule_id: String,
+    pub severity: Severity,
+    pub message: String,
+    /// 1-based line in the file
+    pub line: Option<usize>,
+    /// What to do about it, for findings without an autofix
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    pub fix: Option<LintFix>,
+}
+
+/// A rule as listed in the settings, with the workspace configuration applied
+#[derive(Debug, Clone, Serialize)]
+pub struct LintRuleInfo {
+    pub id: &'static str,
+    pub description: &'static str,
+    pub default_severity: Severity,
+    pub severity: Severity,
+    pub default_options: serde_json::Value,
+    pub options: serde_json::Value,
+    pub fixable: bool,
+}
+
+/// Problem reported by a rule, before severity and line are attached
+struct Hit {
+    message: String,
+    /// Byte offset in the file, used for the line number
+    offset: Option<usize>,
+    suggestion: Option<String>,
+    fix: Option<LintFix>,
+}
+
+impl Hit {
+    fn new(message: String, offset: Option<usize>) -> Self {
+        Self { message, offset, suggestion: None, fix: None }
+    }
+
+    fn with_suggestion(mut self, suggestion: String) -> Self {
+        self.suggestion = Some(suggestion);
+        self
+    }
+
+    fn with_fix(mut self, description: String, edits: Vec<TextEdit>) -> Self {
+        self.fix = Some(LintFix { description, edits });
+        self
+    }
+}
+
+struct Rule {
+    id: &'static str,
+    description: &'static str,
+    severity: Severity,
+    fixable: bool,
+    options: fn() -> serde_json::Value,
+    check: fn(&LintContext, &serde_json::Value) -> Vec<Hit>,
+}
+
+fn no_options() -> serde_json::Value {
+    json!({})
+}
+
+/// Rule ids are stable: they are stored in workspace settings and reported as warning types
+const RULES: &[Rule] = &[
+    Rule {
+        id: "no_system_message",
+        description: "The prompt has no System Message section",
+        severity: Severity::Warning,
+        fixable: false,
+        options: no_options,
+        check: check_no_system_message,
+    },
+    Rule {
+        id: "no_user_message",
+        description: "The prompt has no User Message section",
+        severity: Severity::Warning,
+        fixable: false,
+        options: no_options,
+        check: check_no_user_message,
+    },
+    Rule {
+        id: "short_content",
+        description: "A message is shorter than min_chars",
+        severity: Severity::Warning,
+        fixable: false,
+        options: || json!({ "min_chars": 10 }),
+        check: check_short_content,
+    },
+    Rule {
+        id: "duplicate_role_section",
+        description: "Two sections in a row have the same role, or there is more than one System Message",
+        severity: Severity::Warning,
+        fixable: true,
+        options: no_options,
+        check: check_duplicate_role_section,
+    },
+    Rule {
+        id: "unknown_heading",
+        description: "An H2 heading is not a message role, so its content is never sent",
+        severity: Severity::Warning,
+        fixable: false,
+        options: no_options,
+        check: check_unknown_heading,
+    },
+    Rule {
+        id: "undeclared_variable",
+        description: "A variable is used but not declared (only when the prompt declares variables)",
+        severity: Severity::Warning,
+        fixable: false,
+        options: no_options,
+        check: check_undeclared_variable,
+    },
+    Rule {
+        id: "unused_variable",
+        description: "A declared variable is never used",
+        severity: Severity::Warning,
+        fixable: false,
+        options: no_options,
+        check: check_unused_variable,
+    },
+    Rule {
+        id: "variable_whitespace",
+        description: "Whitespace inside the braces of a variable (outside frontmatter and code blocks)",
+        severity: Severity::Info,
+        fixable: true,
+        options: no_options,
+        check: check_variable_whitespace,
+    },
+    Rule {
+        id: "token_budget",
+        description: "The estimated prompt length (about 4 characters per token) is over max_tokens",
+        severity: Severity::Warning,
+        fixable: false,
+        options: || json!({ "max_tokens": 4000 }),
+        check: check_token_budget,
+    },
+    Rule {
+        id: "contradictory_instructions",
+        description: "The prompt contains phrases from both sides of a pair of opposite instructions",
+        severity: Severity::Warning,
+        fixable: false,
+        options: || {
+            json!({
+                "pairs": [
+                    [["be concise", "be brief", "keep it short"], ["be detailed", "in detail", "be thorough", "be comprehensive"]],
+                    [["respond in json", "output json", "return json"], ["do not use json", "don't use json", "plain text only"]],
+                    [["use markdown"], ["do not use markdown", "don't use markdown", "no markdown"]],
+                    [["ask clarifying questions"], ["do not ask questions", "don't ask questions", "never ask questions"]],
+                ]
+            })
+        },
+        check: check_contradictory_instructions,
+    },
+];
+
+/// An H1/H2 heading of the file
+struct Section {
+    level: usize,
+    text: String,
+    role: Option<MessageRole>,
+    /// Offset of the heading line
+    start: usize,
+    /// Offset of the line after the heading
+    content_start: usize,
+    /// Offset of the next H1/H2 heading (or the end of the file)
+    end: usize,
+}
+
+/// What the rules look at, computed once per file
+pub struct LintContext<'a> {
+    content: &'a str,
+    sections: Vec<Section>,
+    /// Messages with partials expanded (empty when the file doesn't parse)
+    messages: Vec<Message>,
+    declarations: Vec<VariableDeclaration>,
+    usages: Vec<VariableUsage>,
+}
+
+impl<'a> LintContext<'a> {
+    pub fn new(content: &'a str, file_path: Option<&Path>, workspace: Option<&Path>) -> Self {
+        let expanded = match file_path {
+            Some(file_path) => partials::expand_includes(content, file_path, workspace)
+                .map(|e| e.content)
+                .unwrap_or_else(|_| content.to_string()),
+            None => content.to_string(),
+        };
+        let messages = parse_markdown_prompt(&expanded).unwrap_or_default();
+        let usages = template::extract_message_variables(&messages).unwrap_or_default();
+
+        Self {
+            content,
+            sections: find_sections(content),
+            messages,
+            declarations: declared_variables(content).unwrap_or_default(),
+            usages,
+        }
+    }
+
+    fn line(&self, offset: usize) -> usize {
+        self.content[..offset.min(self.content.len())].matches('\n').count() + 1
+    }
+}
+
+fn find_sections(content: &str) -> Vec<Section> {
+    use pulldown_cmark::{Event, HeadingLevel, Parser, Tag};
+
+    let (_, body) = split_frontmatter(content);
+    let body_offset = content.len() - body.len();
+
+    let mut sections: Vec<Section> = Vec::new();
+    let mut current: Option<(usize, usize, String)> = None;
+    for (event, range) in Parser::new(body).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading(level, _, _))
+                if matches!(level, HeadingLevel::H1 | HeadingLevel::H2)
+                    && body[range.clone()].trim_start().starts_with('#') =>
+            {
+                let level = if level == HeadingLevel::H1 { 1 } else { 2 };
+                current = Some((level, body_offset + range.start, String::new()));
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, _, heading)) = current.as_mut() {
+                    heading.push_str(&text);
+                }
+            }
+            Event::End(Tag::Heading(..)) => {
+                if let Some((level, start, text)) = current.take() {
+                    let content_start = content[start..]
+                        .find('\n')
+                        .map(|i| start + i + 1)
+                        .unwrap_or(content.len());
+                    if let Some(previous) = sections.last_mut() {
+                        previous.end = start;
+                    }
+                    sections.push(Section {
+                        level,
+                        role: if level == 2 { heading_role(&text) } else { None },
+                        text,
+                        start,
+                        content_start,
+                        end: content.len(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    sections
+}
+
+fn role_title(role: &MessageRole) -> &'static str {
+    match role {
+        MessageRole::System => "System",
+        MessageRole::User => "User",
+        MessageRole::Assistant => "Assistant",
+    }
+}
+
+fn check_no_system_message(ctx: &LintContext, _: &serde_json::Value) -> Vec<Hit> {
+    if ctx.messages.is_empty() || ctx.messages.iter().any(|m| m.role == MessageRole::System) {
+        return Vec::new();
+    }
+    vec![Hit::new("No System Message found. Consider adding one for better results.".to_string(), None)
+        .with_suggestion("Add a ## System Message section".to_string())]
+}
+
+fn check_no_user_message(ctx: &LintContext, _: &serde_json::Value) -> Vec<Hit> {
+    if ctx.messages.is_empty() || ctx.messages.iter().any(|m| m.role == MessageRole::User) {
+        return Vec::new();
+    }
+    vec![Hit::new("No User Message found. Most prompts should have a user message.".to_string(), None)
+        .with_suggestion("Add a ## User Message section".to_string())]
+}
+
+/// Messages are measured after partials are expanded
+fn check_short_content(ctx: &LintContext, options: &serde_json::Value) -> Vec<Hit> {
+    let min_chars = options.get("min_chars").and_then(|v| v.as_u64()).unwrap_or(10) as usize;
+
+    // Message sections of the file, to point at the message when they line up
+    let sections: Vec<&Section> = ctx.sections.iter().filter(|s| s.role.is_some()).collect();
+    let aligned = sections.len() == ctx.messages.len();
+
+    ctx.messages
+        .iter()
+        .enumerate()
+        .filter(|(_, message)| message.content.trim().len() < min_chars)
+        .map(|(index, message)| {
+            Hit::new(
+                format!("Very short {} message (< {} chars)", role_title(&message.role), min_chars),
+                aligned.then(|| sections[index].start),
+            )
+            .with_suggestion("Consider adding more context".to_string())
+        })
+        .collect()
+}
+
+fn check_duplicate_role_section(ctx: &LintContext, _: &serde_json::Value) -> Vec<Hit> {
+    let mut hits = Vec::new();
+    let mut seen_system = false;
+    let mut previous: Option<&Section> = None;
+
+    for section in &ctx.sections {
+        if let Some(role) = &section.role {
+            let repeated = previous.and_then(|p| p.role.as_ref()) == Some(role);
+            let second_system = *role == MessageRole::System && seen_system;
+            if repeated || second_system {
+                let mut hit = Hit::new(
+                    format!("Duplicate {} Message section '{}'", role_title(role), section.text.trim()),
+                    Some(section.start),
+                );
+                // Removing the heading merges the section into the one right above it
+                if repeated {
+                    hit = hit.with_fix(
+                        format!("Merge into the previous {} Message", role_title(role)),
+                        vec![TextEdit {
+                            start: section.start,
+                            end: section.content_start,
+                            replacement: String::new(),
+                        }],
+                    );
+                }
+                hits.push(hit);
+            }
+            seen_system |= *role == MessageRole::System;
+        }
+        previous = Some(section);
+    }
+    hits
+}
+
+/// No autofix: whether the content belongs to the previous message or should go is the author's call
+fn check_unknown_heading(ctx: &LintContext, _: &serde_json::Value) -> Vec<Hit> {
+    ctx.sections
+        .iter()
+        .filter(|s| s.level == 2 && s.role.is_none() && !s.text.trim().eq_ignore_ascii_case("variables"))
+        .map(|section| {
+            Hit::new(
+                format!("Heading '{}' is not a message role: its content is ignored", section.text.trim()),
+                Some(section.start),
+            )
+            .with_suggestion("Rename it to a message role, or use ### to keep the content in the previous message".to_string())
+        })
+        .collect()
+}
+
+fn check_undeclared_variable(ctx: &LintContext, _: &serde_json::Value) -> Vec<Hit> {
+    if ctx.declarations.is_empty() {
+        return Vec::new();
+    }
+    ctx.usages
+        .iter()
+        .filter(|u| !ctx.declarations.iter().any(|d| d.name == u.name))
+        .map(|usage| {
+            let offset = ctx.content.find(&format!("{{{{{}", usage.name));
+            Hit::new(format!("Variable '{}' is used but not declared", usage.name), offset)
+                .with_suggestion(format!("Declare '{}' under variables", usage.name))
+        })
+        .collect()
+}
+
+fn check_unused_variable(ctx: &LintContext, _: &serde_json::Value) -> Vec<Hit> {
+    if ctx.messages.is_empty() {
+        return Vec::new();
+    }
+    ctx.declarations
+        .iter()
+        .filter(|d| !ctx.usages.iter().any(|u| u.name == d.name))
+        .map(|declaration| {
+            Hit::new(format!("Variable '{}' is declared but never used", declaration.name), None)
+                .with_suggestion(format!("Use {{{{{}}}}} or remove the declaration", declaration.name))
+        })
+        .collect()
+}
+
+fn check_variable_whitespace(ctx: &LintContext, _: &serde_json::Value) -> Vec<Hit> {
+    let variable = Regex::new(r"\{\{(\s*)([A-Za-z_$][\w.:$-]*)(\s*)\}\}").unwrap();
+
+    // Only the body is a template: frontmatter and code samples are left alone
+    let (_, body) = split_frontmatter(ctx.content);
+    let body_offset = ctx.content.len() - body.len();
+    let fences: Vec<(usize, usize)> = partials::fenced_ranges(body)
+        .into_iter()
+        .map(|(start, end)| (body_offset + start, body_offset + end))
+        .collect();
+
+    let mut hits = Vec::new();
+    for captures in variable.captures_iter(ctx.content) {
+        let whole = captures.get(0).unwrap();
+        let escaped = ctx.content[..whole.start()].ends_with('\\');
+        let in_code = fences.iter().any(|(start, end)| (*start..*end).contains(&whole.start()));
+        if whole.start() < body_offset || in_code || escaped || (captures[1].is_empty() && captures[3].is_empty()) {
+            continue;
+        }
+        let name = &captures[2];
+        hits.push(
+            Hit::new(format!("Whitespace inside {{{{{}}}}}", name), Some(whole.start())).with_fix(
+                format!("Write {{{{{}}}}}", name),
+                vec![TextEdit {
+                    start: whole.start(),
+                    end: whole.end(),
+                    replacement: format!("{{{{{}}}}}", name),
+                }],
+            ),
+        );
+    }
+    hits
+}
+
+/// Rough token count: about 4 characters per token, plus a few tokens per message
+pub fn estimate_tokens(messages: &[Message]) -> usize {
+    messages.iter().map(|m| m.content.chars().count().div_ceil(4) + 4).sum()
+}
+
+fn check_token_budget(ctx: &LintContext, options: &serde_json::Value) -> Vec<Hit> {
+    let max_tokens = options.get("max_tokens").and_then(|v| v.as_u64()).unwrap_or(4000) as usize;
+    let estimate = estimate_tokens(&ctx.messages);
+    if estimate <= max_tokens {
+        return Vec::new();
+    }
+    vec![Hit::new(
+        format!("Prompt is about {} tokens, over the budget of {}", estimate, max_tokens),
+        None,
+    )]
+}
+
+fn check_contradictory_instructions(ctx: &LintContext, options: &serde_json::Value) -> Vec<Hit> {
+    let pairs: Vec<(Vec<String>, Vec<String>)> = options
+        .get("pairs")
+        .and_then(|p| serde_json::from_value(p.clone()).ok())
+        .unwrap_or_default();
+    let text = ctx
+        .messages
+        .iter()
+        .map(|m| m.content.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let content = ctx.content.to_lowercase();
+
+    let find = |phrases: &[String]| phrases.iter().find(|p| !p.is_empty() && text.contains(&p.to_lowercase())).cloned();
+    pairs
+        .iter()
+        .filter_map(|(first, second)| {
+            let a = find(first)?;
+            let b = find(second)?;
+            Some(Hit::new(
+                format!("Contradictory instructions: '{}' and '{}'", a, b),
+                content.find(&b.to_lowercase()),
+            ))
+        })
+        .collect()
+}
+
+/// Workspace settings keyed by rule id
+pub fn load_settings(db: &ProjectDatabase) -> HashMap<String, LintRuleSetting> {
+    db.get_lint_rule_settings()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|setting| (setting.rule_id.clone(), setting))
+        .collect()
+}
+
+fn configured(rule: &Rule, settings: &HashMap<String, LintRuleSetting>) -> (Severity, serde_json::Value) {
+    let mut options = (rule.options)();
+    let setting = match settings.get(rule.id) {
+        Some(setting) => setting,
+        None => return (rule.severity, options),
+    };
+
+    // Configured options override the defaults key by key
+    let overrides = setting.options.as_deref().and_then(|o| serde_json::from_str::<serde_json::Value>(o).ok());
+    if let (Some(options), Some(serde_json::Value::Object(overrides))) = (options.as_object_mut(), overrides) {
+        options.extend(overrides);
+    }
+    (Severity::from_name(&setting.severity).unwrap_or(rule.severity), options)
+}
+
+pub fn rule_infos(settings: &HashMap<String, LintRuleSetting>) -> Vec<LintRuleInfo> {
+    RULES
+        .iter()
+        .map(|rule| {
+            let (severity, options) = configured(rule, settings);
+            LintRuleInfo {
+                id: rule.id,
+                description: rule.description,
+                default_severity: rule.severity,
+                severity,
+                default_options: (rule.options)(),
+                options,
+                fixable: rule.fixable,
+            }
+        })
+        .collect()
+}
+
+pub fn is_rule(rule_id: &str) -> bool {
+    RULES.iter().any(|rule| rule.id == rule_id)
+}
+
+/// Run every enabled rule
+pub fn lint(ctx: &LintContext, settings: &HashMap<String, LintRuleSetting>) -> Vec<LintFinding> {
+    let mut findings = Vec::new();
+    for rule in RULES {
+        let (severity, options) = configured(rule, settings);
+        if severity == Severity::Off {
+            continue;
+        }
+        for hit in (rule.check)(ctx, &options) {
+            findings.push(LintFinding {
+                rule_id: rule.id.to_string(),
+                severity,
+                message: hit.message,
+                line: hit.offset.map(|offset| ctx.line(offset)),
+                suggestion: hit.suggestion,
+                fix: hit.fix,
+            });
+        }
+    }
+    findings
+}
+
+/// Apply the fixes of the findings (overlapping edits after the first are skipped)
+/// Returns the new content and the number of fixes applied
+pub fn apply_fixes(content: &str, findings: &[LintFinding]) -> (String, usize) {
+    let mut accepted: Vec<&TextEdit> = Vec::new();
+    let mut applied = 0;
+    for fix in findings.iter().filter_map(|f| f.fix.as_ref()) {
+        let overlaps = fix
+            .edits
+            .iter()
+            .any(|e| accepted.iter().any(|a| e.start < a.end && a.start < e.end));
+        if !overlaps {
+            accepted.extend(&fix.edits);
+            applied += 1;
+        }
+    }
+
+    accepted.sort_by_key(|edit| std::cmp::Reverse(edit.start));
+    let mut result = content.to_string();
+    for edit in accepted {
+        result.replace_range(edit.start..edit.end, &edit.replacement);
+    }
+    (result, applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOC: &str = "---\nvariables:\n  topic: string\n  tone: string\n---\n# Demo\n\n## System Message\nBe concise.\n\n## System Message\nExplain {{ topic }} in detail.\n\n## Notes\nnever sent\n\n## User Message\nHi {{name}}\n";
+
+    fn rule_ids(findings: &[LintFinding]) -> Vec<&str> {
+        findings.iter().map(|f| f.rule_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_lint_rules() {
+        let ctx = LintContext::new(DOC, None, None);
+        let findings = lint(&ctx, &HashMap::new());
+
+        assert_eq!(
+            rule_ids(&findings),
+            vec![
+                "duplicate_role_section",
+                "unknown_heading",
+                "undeclared_variable",
+                "unused_variable",
+                "variable_whitespace",
+                "contradictory_instructions",
+            ]
+        );
+        assert_eq!(findings[0].line, Some(11));
+        assert_eq!(findings[1].line, Some(14));
+        assert_eq!(findings[5].message, "Contradictory instructions: 'be concise' and 'in detail'");
+    }
+
+    #[test]
+    fn test_settings_and_fixes() {
+        let mut settings = HashMap::new();
+        settings.insert(
+            "token_budget".to_string(),
+            LintRuleSetting {
+                rule_id: "token_budget".to_string(),
+                severity: "error".to_string(),
+                options: Some(r#"{"max_tokens": 5}"#.to_string()),
+                updated_at: 0,
+            },
+        );
+        settings.insert(
+            "contradictory_instructions".to_string(),
+            LintRuleSetting {
+                rule_id: "contradictory_instructions".to_string(),
+                severity: "off".to_string(),
+                options: None,
+                updated_at: 0,
+            },
+        );
+
+        let ctx = LintContext::new(DOC, None, None);
+        let findings = lint(&ctx, &settings);
+        let budget = findings.iter().find(|f| f.rule_id == "token_budget").unwrap();
+        assert_eq!(budget.severity, Severity::Error);
+        assert!(!rule_ids(&findings).contains(&"contradictory_instructions"));
+
+        let (fixed, applied) = apply_fixes(DOC, &findings);
+        assert_eq!(applied, 2);
+        assert!(fixed.contains("## System Message\nBe concise.\n\nExplain {{topic}} in detail.\n\n## Notes\n"));
+
+        let fixed_findings = lint(&LintContext::new(&fixed, None, None), &settings);
+        assert!(fixed_findings.iter().all(|f| f.fix.is_none()));
+    }
+
+    #[test]
+    fn test_messages_and_suggestions() {
+        let doc = "---\ndescription: \"{{ not a variable }}\"\n---\n## User Message\nHi\n\n```\n{{ example }}\n```\n";
+        let findings = lint(&LintContext::new(doc, None, None), &HashMap::new());
+
+        assert_eq!(rule_ids(&findings), vec!["no_system_message"]);
+        assert_eq!(findings[0].suggestion.as_deref(), Some("Add a ## System Message section"));
+
+        // Measured on the expanded message: the include makes it long enough
+        let workspace = std::env::temp_dir().join(format!("vibebase-lint-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::write(workspace.join("persona.vibe.md"), "You are a patient support agent.").unwrap();
+        let doc = "## System Message\n{{> persona}}\n\n## User Message\nHi\n";
+        let ctx = LintContext::new(doc, Some(&workspace.join("main.vibe.md")), Some(&workspace));
+        let findings = lint(&ctx, &HashMap::new());
+
+        assert_eq!(rule_ids(&findings), vec!["short_content"]);
+        assert_eq!(findings[0].message, "Very short User message (< 10 chars)");
+        assert_eq!(findings[0].line, Some(4));
+        assert_eq!(findings[0].suggestion.as_deref(), Some("Consider adding more context"));
+
+        std::fs::remove_dir_all(&workspace).ok();
+    }
+}
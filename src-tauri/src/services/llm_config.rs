@@ -2,7 +2,6 @@ use crate::models::config::Environment;
 use crate::models::prompt::{Provider, ModelParameters};
 use crate::services::database::{AppDatabase, LLMProviderConfig};
 use crate::services::keychain::KeychainService;
-use std::sync::Arc;
 
 /// Resolved LLM configuration ready for execution
 #[allow(dead_code)]
@@ -18,13 +17,13 @@ pub struct ResolvedLLMConfig {
 /// LLM Configuration Resolver
 /// Resolves provider_ref from project config to actual LLM configuration
 #[allow(dead_code)]
-pub struct LLMConfigResolver {
-    app_db: Arc<AppDatabase>,
+pub struct LLMConfigResolver<'a> {
+    app_db: &'a AppDatabase,
 }
 
 #[allow(dead_code)]
-impl LLMConfigResolver {
-    pub fn new(app_db: Arc<AppDatabase>) -> Self {
+impl<'a> LLMConfigResolver<'a> {
+    pub fn new(app_db: &'a AppDatabase) -> Self {
         Self { app_db }
     }
 
@@ -82,6 +81,20 @@ impl LLMConfigResolver {
         })
     }
 
+    /// Resolve a global provider configuration by name, without environment overrides
+    pub fn resolve_provider_ref(&self, provider_ref: &str) -> Result<ResolvedLLMConfig, String> {
+        let global_config = self.app_db.get_llm_provider(provider_ref)
+            .map_err(|e| format!("Provider '{}' not found in global config: {}", provider_ref, e))?;
+
+        Ok(ResolvedLLMConfig {
+            provider: self.parse_provider(&global_config.provider)?,
+            model: global_config.model.clone(),
+            base_url: global_config.base_url.clone(),
+            api_key: self.get_api_key(&global_config)?,
+            parameters: self.merge_parameters(&global_config.parameters, &None)?,
+        })
+    }
+
     /// Resolve using legacy direct configuration (backward compatibility)
     fn resolve_from_legacy(
         &self,
@@ -113,9 +126,10 @@ impl LLMConfigResolver {
         })
     }
 
-    /// Get API key from keychain or environment variable
+    /// Get API key stored directly, from keychain or environment variable
     fn get_api_key(&self, config: &LLMProviderConfig) -> Result<String, String> {
         match config.api_key_source.as_str() {
+            "direct" => Ok(config.api_key.clone().unwrap_or_default()),  // May be empty (e.g. Ollama)
             "keychain" => {
                 let key_ref = config.api_key_ref.as_ref()
                     .ok_or("API key reference not specified")?;
@@ -236,8 +250,8 @@ mod tests {
 
     #[test]
     fn test_parse_provider() {
-        let app_db = Arc::new(AppDatabase::new().unwrap());
-        let resolver = LLMConfigResolver::new(app_db);
+        let app_db = AppDatabase::new().unwrap();
+        let resolver = LLMConfigResolver::new(&app_db);
         
         assert!(matches!(resolver.parse_provider("openai").unwrap(), Provider::OpenAI));
         assert!(matches!(resolver.parse_provider("anthropic").unwrap(), Provider::Anthropic));
@@ -0,0 +1,175 @@
+use crate::services::database::TestResult;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+pub const DEFAULT_CONCURRENCY: usize = 4;
+pub const MAX_CONCURRENCY: usize = 16;
+
+/// Separates the provider name from the model id in `list_enabled_models` ids
+const MODEL_REF_SEPARATOR: &str = "::";
+
+/// A model of the matrix: provider config name and model id
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MatrixModel {
+    pub provider_name: String,
+    pub model: String,
+}
+
+impl MatrixModel {
+    /// Parse an enabled model id, e.g. `OpenRouter::anthropic/claude-3.7-sonnet`
+    pub fn parse(model_ref: &str) -> Result<Self, String> {
+        match model_ref.split_once(MODEL_REF_SEPARATOR) {
+            Some((provider_name, model)) if !provider_name.trim().is_empty() && !model.trim().is_empty() => Ok(Self {
+                provider_name: provider_name.to_string(),
+                model: model.to_string(),
+            }),
+            _ => Err(format!("Invalid model '{}', expected provider::model", model_ref)),
+        }
+    }
+
+    pub fn model_ref(&self) -> String {
+        format!("{}{}{}", self.provider_name, MODEL_REF_SEPARATOR, self.model)
+    }
+}
+
+/// A dataset row executed on a model
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixCell {
+    pub row: usize,
+    pub model: MatrixModel,
+}
+
+/// Identifies a cell across restarts: (row, provider name, model)
+pub type CellKey = (usize, String, String);
+
+impl MatrixCell {
+    pub fn key(&self) -> CellKey {
+        (self.row, self.model.provider_name.clone(), self.model.model.clone())
+    }
+}
+
+/// Cell a saved test result belongs to
+pub fn result_key(result: &TestResult) -> CellKey {
+    (
+        result.test_case_index as usize,
+        result.llm_provider_name.clone().unwrap_or_default(),
+        result.model.clone(),
+    )
+}
+
+/// Cells whose latest attempt succeeded, and cells whose latest attempt failed
+/// A cell retried on resume may still have its failed attempt; only the latest result counts
+pub fn cell_status(results: &[TestResult]) -> (HashSet<CellKey>, HashSet<CellKey>) {
+    let mut latest: HashMap<CellKey, &TestResult> = HashMap::new();
+    for result in results {
+        let key = result_key(result);
+        if latest.get(&key).is_none_or(|previous| previous.timestamp <= result.timestamp) {
+            latest.insert(key, result);
+        }
+    }
+
+    let done: HashSet<CellKey> = latest
+        .iter()
+        .filter(|(_, result)| result.error.is_none())
+        .map(|(key, _)| key.clone())
+        .collect();
+    let failed = latest.into_keys().filter(|key| !done.contains(key)).collect();
+    (done, failed)
+}
+
+/// Concurrency to run with: the requested value clamped to 1..=MAX_CONCURRENCY
+pub fn concurrency(requested: Option<usize>) -> usize {
+    requested.unwrap_or(DEFAULT_CONCURRENCY).clamp(1, MAX_CONCURRENCY)
+}
+
+/// Row indices to run: the requested subset (deduplicated, in order) or every row
+pub fn select_rows(requested: Option<&[usize]>, row_count: usize) -> Result<Vec<usize>, String> {
+    let rows = match requested {
+        None => return Ok((0..row_count).collect()),
+        Some(rows) => rows,
+    };
+    if let Some(row) = rows.iter().find(|row| **row >= row_count) {
+        return Err(format!("Row {} is outside the dataset ({} rows)", row, row_count));
+    }
+
+    let mut seen = HashSet::new();
+    Ok(rows.iter().copied().filter(|row| seen.insert(*row)).collect())
+}
+
+/// Cells still to run, row by row, skipping those already done
+pub fn pending_cells(rows: &[usize], models: &[MatrixModel], done: &HashSet<CellKey>) -> Vec<MatrixCell> {
+    rows.iter()
+        .flat_map(|row| {
+            models.iter().map(move |model| MatrixCell { row: *row, model: model.clone() })
+        })
+        .filter(|cell| !done.contains(&cell.key()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_model_ref() {
+        let model = MatrixModel::parse("OpenRouter::anthropic/claude-3.7-sonnet:thinking").unwrap();
+        assert_eq!(model.provider_name, "OpenRouter");
+        assert_eq!(model.model, "anthropic/claude-3.7-sonnet:thinking");
+        assert_eq!(model.model_ref(), "OpenRouter::anthropic/claude-3.7-sonnet:thinking");
+        assert!(MatrixModel::parse("gpt-4o").is_err());
+        assert!(MatrixModel::parse("::gpt-4o").is_err());
+    }
+
+    #[test]
+    fn test_plan_cells() {
+        assert_eq!(select_rows(None, 3).unwrap(), vec![0, 1, 2]);
+        assert_eq!(select_rows(Some(&[2, 0, 2]), 3).unwrap(), vec![2, 0]);
+        assert!(select_rows(Some(&[3]), 3).is_err());
+        assert_eq!(concurrency(Some(0)), 1);
+        assert_eq!(concurrency(Some(100)), MAX_CONCURRENCY);
+
+        let models = vec![MatrixModel::parse("OpenAI::gpt-4o").unwrap(), MatrixModel::parse("OpenAI::gpt-4o-mini").unwrap()];
+        let done = HashSet::from([(0, "OpenAI".to_string(), "gpt-4o-mini".to_string())]);
+        let cells = pending_cells(&[0, 1], &models, &done);
+
+        let summary: Vec<(usize, &str)> = cells.iter().map(|c| (c.row, c.model.model.as_str())).collect();
+        assert_eq!(summary, vec![(0, "gpt-4o"), (1, "gpt-4o"), (1, "gpt-4o-mini")]);
+    }
+
+    #[test]
+    fn test_resume_after_retry() {
+        let result = |row: i64, model: &str, timestamp: i64, error: Option<&str>| TestResult {
+            id: uuid::Uuid::new_v4().to_string(),
+            prompt_file_id: "file-1".to_string(),
+            dataset_id: "dataset-1".to_string(),
+            test_case_index: row,
+            input_variables: "{}".to_string(),
+            output: String::new(),
+            model: model.to_string(),
+            provider: "openai".to_string(),
+            latency_ms: 0,
+            tokens_input: 0,
+            tokens_output: 0,
+            cost_usd: 0.0,
+            timestamp,
+            matrix_run_id: Some("run-1".to_string()),
+            llm_provider_name: Some("OpenAI".to_string()),
+            error: error.map(String::from),
+        };
+        let models = vec![MatrixModel::parse("OpenAI::gpt-4o").unwrap()];
+
+        // First run: row 1 failed
+        let mut results = vec![result(0, "gpt-4o", 1, None), result(1, "gpt-4o", 1, Some("timeout"))];
+        let (done, failed) = cell_status(&results);
+        assert_eq!(failed, HashSet::from([(1, "OpenAI".to_string(), "gpt-4o".to_string())]));
+        let cells = pending_cells(&[0, 1], &models, &done);
+        assert_eq!(cells.iter().map(|c| c.row).collect::<Vec<_>>(), vec![1]);
+
+        // Resumed: the retry succeeded, so nothing is failed or left to run
+        results.push(result(1, "gpt-4o", 2, None));
+        let (done, failed) = cell_status(&results);
+        assert_eq!(done.len(), 2);
+        assert!(failed.is_empty());
+        assert!(pending_cells(&[0, 1], &models, &done).is_empty());
+    }
+}
@@ -7,4 +7,22 @@ pub mod file_tracker;
 pub mod llm_config;
 pub mod validator;
 pub mod git_service;
-pub mod commit_message_generator;
\ No newline at end of file
+pub mod commit_message_generator;
+pub mod budget;
+pub mod replay;
+pub mod prompt_config;
+pub mod partials;
+pub mod file_variables;
+pub mod converters;
+pub mod yaml_migration;
+pub mod lint;
+pub mod flows;
+pub mod variable_resolution;
+pub mod secret_variables;
+pub mod variable_io;
+pub mod dynamic_variables;
+pub mod datasets;
+pub mod matrix;
+pub mod json_output;
+pub mod assertions;
+pub mod evaluation;
@@ -0,0 +1,438 @@
+use crate::models::frontmatter::split_frontmatter;
+use crate::services::database::ProjectDatabase;
+use regex::Regex;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// file_dependencies.dependency_type for prompt partials
+pub const INCLUDE_DEPENDENCY: &str = "include";
+
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// `{{> path}}` or `{{> path#Section}}` found in a prompt
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncludeRef {
+    pub path: String,
+    pub section: Option<String>,
+}
+
+/// Prompt content with every include replaced by the partial's content
+#[derive(Debug, Clone)]
+pub struct ExpandedPrompt {
+    pub content: String,
+    /// All files included, directly or through other partials
+    pub includes: Vec<PathBuf>,
+}
+
+/// Status of a direct include, as reported by the validator
+#[derive(Debug, Clone)]
+pub struct IncludeCheck {
+    pub target: PathBuf,
+    pub exists: bool,
+    /// Include chain leading back to the including file, if any
+    pub cycle: Option<Vec<PathBuf>>,
+}
+
+fn include_regex() -> Regex {
+    Regex::new(r"\{\{>\s*([^}#\s]+)(?:#([^}]+?))?\s*\}\}").unwrap()
+}
+
+/// Fence marker opening or closing a code block on this line: its character and length
+fn fence_marker(line: &str) -> Option<(char, usize)> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = trimmed.chars().take_while(|c| *c == marker).count();
+    (length >= 3).then_some((marker, length))
+}
+
+/// Byte ranges of the fenced code blocks (``` or ~~~) of `content` (an unclosed fence runs to the end)
+pub(crate) fn fenced_ranges(content: &str) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    // Start offset and marker of the open fence
+    let mut open: Option<(usize, char, usize)> = None;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        if let Some((marker, length)) = fence_marker(line) {
+            match open {
+                // Closed by a bare fence of the same character, at least as long
+                Some((start, open_marker, open_length)) => {
+                    let bare = line.trim().chars().all(|c| c == marker);
+                    if marker == open_marker && length >= open_length && bare {
+                        ranges.push((start, offset + line.len()));
+                        open = None;
+                    }
+                }
+                None => open = Some((offset, marker, length)),
+            }
+        }
+        offset += line.len();
+    }
+    if let Some((start, _, _)) = open {
+        ranges.push((start, content.len()));
+    }
+    ranges
+}
+
+/// Include tags outside fenced code blocks: includes in code samples stay literal
+fn include_captures<'a>(regex: &'a Regex, content: &'a str) -> impl Iterator<Item = regex::Captures<'a>> {
+    let fences = fenced_ranges(content);
+    regex.captures_iter(content).filter(move |cap| {
+        let start = cap.get(0).unwrap().start();
+        !fences.iter().any(|(from, to)| (*from..*to).contains(&start))
+    })
+}
+
+/// Find the includes of a prompt, in order of appearance
+pub fn find_includes(content: &str) -> Vec<IncludeRef> {
+    let regex = include_regex();
+    include_captures(&regex, content)
+        .map(|cap| IncludeRef {
+            path: cap[1].to_string(),
+            section: cap.get(2).map(|s| s.as_str().trim().to_string()),
+        })
+        .collect()
+}
+
+/// Resolve an include path
+/// `./` and `../` paths are relative to the including file, others to the workspace root
+/// The `.vibe.md` extension may be omitted
+pub fn resolve_include_path(include: &str, including_file: &Path, workspace: Option<&Path>) -> PathBuf {
+    let file_dir = including_file.parent().unwrap_or_else(|| Path::new(""));
+    let base = if include.starts_with("./") || include.starts_with("../") {
+        file_dir
+    } else {
+        workspace.unwrap_or(file_dir)
+    };
+
+    let path = normalize(&base.join(include));
+    if !path.exists() && !include.ends_with(".md") {
+        let with_extension = normalize(&base.join(format!("{}.vibe.md", include)));
+        if with_extension.exists() {
+            return with_extension;
+        }
+    }
+    path
+}
+
+/// Workspace-relative form of `path`, as stored in prompt_files and file_dependencies
+/// Paths outside the workspace are kept as given
+pub fn workspace_relative(workspace: &Path, path: &Path) -> String {
+    let full = normalize(&workspace.join(path));
+    match full.strip_prefix(normalize(workspace)) {
+        Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+        Err(_) => path.to_string_lossy().replace('\\', "/"),
+    }
+}
+
+/// Lexically remove `.` and `..` components (the file may not exist)
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+/// Content of the `## Section` heading whose text starts with `section` (case-insensitive)
+fn extract_section(body: &str, section: &str) -> Option<String> {
+    let wanted = section.to_lowercase();
+    let mut in_fence = false;
+    let mut capturing = false;
+    let mut found = false;
+    let mut lines = Vec::new();
+
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_fence = !in_fence;
+        }
+
+        let is_heading = !in_fence && (trimmed.starts_with("## ") || trimmed.starts_with("# "));
+        if is_heading {
+            if capturing {
+                break;
+            }
+            let title = trimmed.trim_start_matches('#').trim().to_lowercase();
+            if trimmed.starts_with("## ") && title.starts_with(&wanted) {
+                capturing = true;
+                found = true;
+            }
+            continue;
+        }
+
+        if capturing {
+            lines.push(line);
+        }
+    }
+
+    if found {
+        Some(lines.join("\n").trim().to_string())
+    } else {
+        None
+    }
+}
+
+fn display_chain(chain: &[PathBuf]) -> String {
+    chain
+        .iter()
+        .map(|p| p.display().to_string())
+        .collect::<Vec<_>>()
+        .join(" -> ")
+}
+
+/// Replace every include in `content` (the content of `file_path`), recursively
+pub fn expand_includes(content: &str, file_path: &Path, workspace: Option<&Path>) -> Result<ExpandedPrompt, String> {
+    let mut stack = vec![normalize(file_path)];
+    let mut includes = Vec::new();
+    let content = expand(content, file_path, workspace, &mut stack, &mut includes)?;
+    Ok(ExpandedPrompt { content, includes })
+}
+
+fn expand(
+    content: &str,
+    file_path: &Path,
+    workspace: Option<&Path>,
+    stack: &mut Vec<PathBuf>,
+    includes: &mut Vec<PathBuf>,
+) -> Result<String, String> {
+    let regex = include_regex();
+    let mut output = String::with_capacity(content.len());
+    let mut last = 0;
+
+    for cap in include_captures(&regex, content) {
+        let whole = cap.get(0).unwrap();
+        output.push_str(&content[last..whole.start()]);
+        last = whole.end();
+
+        let target = resolve_include_path(&cap[1], file_path, workspace);
+        if stack.contains(&target) {
+            let mut chain = stack.clone();
+            chain.push(target);
+            return Err(format!("Include cycle: {}", display_chain(&chain)));
+        }
+        if stack.len() > MAX_INCLUDE_DEPTH {
+            return Err(format!("Includes nested more than {} levels deep", MAX_INCLUDE_DEPTH));
+        }
+
+        let partial = fs::read_to_string(&target)
+            .map_err(|_| format!("Included file not found: {}", target.display()))?;
+        let (_, body) = split_frontmatter(&partial);
+        let text = match cap.get(2) {
+            Some(section) => extract_section(body, section.as_str().trim()).ok_or_else(|| {
+                format!("Section '{}' not found in {}", section.as_str().trim(), target.display())
+            })?,
+            None => body.trim().to_string(),
+        };
+
+        if !includes.contains(&target) {
+            includes.push(target.clone());
+        }
+
+        stack.push(target.clone());
+        let expanded = expand(&text, &target, workspace, stack, includes)?;
+        stack.pop();
+
+        output.push_str(&expanded);
+    }
+
+    output.push_str(&content[last..]);
+    Ok(output)
+}
+
+/// Check the direct includes of a prompt: whether they exist and whether they lead back to it
+pub fn check_includes(content: &str, file_path: &Path, workspace: Option<&Path>) -> Vec<IncludeCheck> {
+    let origin = normalize(file_path);
+
+    find_includes(content)
+        .into_iter()
+        .map(|include| {
+            let target = resolve_include_path(&include.path, file_path, workspace);
+            let exists = target.exists();
+            let cycle = if exists {
+                let mut chain = vec![origin.clone()];
+                find_path_to(&target, &origin, workspace, &mut chain).then_some(chain)
+            } else {
+                None
+            };
+            IncludeCheck { target, exists, cycle }
+        })
+        .collect()
+}
+
+// Depth-first search through includes from `current` to `origin`, leaving the path in `chain`
+fn find_path_to(current: &Path, origin: &Path, workspace: Option<&Path>, chain: &mut Vec<PathBuf>) -> bool {
+    chain.push(current.to_path_buf());
+    if current == origin {
+        return true;
+    }
+    if chain.len() <= MAX_INCLUDE_DEPTH + 1 && !chain[..chain.len() - 1].contains(&current.to_path_buf()) {
+        if let Ok(content) = fs::read_to_string(current) {
+            for include in find_includes(&content) {
+                let next = resolve_include_path(&include.path, current, workspace);
+                if find_path_to(&next, origin, workspace, chain) {
+                    return true;
+                }
+            }
+        }
+    }
+    chain.pop();
+    false
+}
+
+/// Record the includes of a prompt in file_dependencies, with workspace-relative paths
+pub fn record_includes(db: &ProjectDatabase, workspace: &Path, file_path: &str, includes: &[PathBuf]) -> Result<(), String> {
+    let prompt_file_id = db.ensure_prompt_file(file_path)
+        .map_err(|e| format!("Failed to ensure file record: {}", e))?;
+    let targets: Vec<String> = includes.iter().map(|p| workspace_relative(workspace, p)).collect();
+
+    db.replace_file_dependencies(&prompt_file_id, INCLUDE_DEPENDENCY, &targets)
+        .map_err(|e| format!("Failed to record dependencies: {}", e))
+}
+
+/// Re-scan every .vibe.md file in the workspace and record its includes
+/// Returns the number of prompts scanned
+pub fn index_workspace(db: &ProjectDatabase, workspace_path: &str) -> Result<usize, String> {
+    let workspace = Path::new(workspace_path);
+    let files = markdown_prompts(workspace);
+
+    for file in &files {
+        let content = match fs::read_to_string(file) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        // Broken includes are reported by the validator; record what can be resolved
+        let includes: Vec<PathBuf> = find_includes(&content)
+            .iter()
+            .map(|include| resolve_include_path(&include.path, file, Some(workspace)))
+            .collect();
+        record_includes(db, workspace, &file.display().to_string(), &includes)?;
+    }
+
+    Ok(files.len())
+}
+
+/// Every .vibe.md file under `dir`, skipping hidden entries
+pub fn markdown_prompts(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    collect_markdown_prompts(dir, &mut files);
+    files
+}
+
+fn collect_markdown_prompts(dir: &Path, files: &mut Vec<PathBuf>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                collect_markdown_prompts(&path, files);
+            } else if name.ends_with(".vibe.md") {
+                files.push(path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("vibebase-partials-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(dir.join("shared")).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_find_includes() {
+        let includes = find_includes("{{> shared/persona.vibe.md}}\n{{> shared/rules#System}}");
+        assert_eq!(includes[0], IncludeRef { path: "shared/persona.vibe.md".to_string(), section: None });
+        assert_eq!(includes[1].section.as_deref(), Some("System"));
+    }
+
+    #[test]
+    fn test_expand_includes_with_section() {
+        let ws = workspace();
+        fs::write(ws.join("shared/persona.vibe.md"), "---\ntags: [shared]\n---\nYou are Vibe.").unwrap();
+        fs::write(ws.join("shared/rules.vibe.md"), "## System Message\nBe safe.\n\n## User Message\nignored").unwrap();
+        let prompt = ws.join("main.vibe.md");
+        let content = "## System Message\n{{> shared/persona.vibe.md}}\n{{> shared/rules#System}}\n";
+
+        let expanded = expand_includes(content, &prompt, Some(&ws)).unwrap();
+        assert_eq!(expanded.content, "## System Message\nYou are Vibe.\nBe safe.\n");
+        assert_eq!(expanded.includes.len(), 2);
+
+        fs::remove_dir_all(&ws).ok();
+    }
+
+    #[test]
+    fn test_include_cycle() {
+        let ws = workspace();
+        fs::write(ws.join("shared/a.vibe.md"), "{{> shared/b}}").unwrap();
+        fs::write(ws.join("shared/b.vibe.md"), "{{> shared/a}}").unwrap();
+        let prompt = ws.join("main.vibe.md");
+        let content = "## User Message\n{{> shared/a}}";
+
+        let err = expand_includes(content, &prompt, Some(&ws)).unwrap_err();
+        assert!(err.starts_with("Include cycle"));
+
+        let a = ws.join("shared/a.vibe.md");
+        let checks = check_includes("{{> shared/b}}", &a, Some(&ws));
+        assert!(checks[0].exists);
+        assert_eq!(checks[0].cycle.as_ref().map(|c| c.len()), Some(3));
+
+        let missing = check_includes("{{> shared/missing}}", &prompt, Some(&ws));
+        assert!(!missing[0].exists);
+
+        fs::remove_dir_all(&ws).ok();
+    }
+
+    #[test]
+    fn test_includes_in_code_fences_stay_literal() {
+        let ws = workspace();
+        fs::write(ws.join("shared/persona.vibe.md"), "You are Vibe.").unwrap();
+        let prompt = ws.join("main.vibe.md");
+        let content = "## User Message\n{{> shared/persona}}\n```\n{{> shared/missing}}\n```\n";
+
+        assert_eq!(find_includes(content).len(), 1);
+        let expanded = expand_includes(content, &prompt, Some(&ws)).unwrap();
+        assert_eq!(expanded.content, "## User Message\nYou are Vibe.\n```\n{{> shared/missing}}\n```\n");
+
+        // Tilde fences, and fences only closed by a matching marker
+        assert!(find_includes("~~~\n{{> shared/missing}}\n~~~\n").is_empty());
+        assert!(find_includes("````\n```\n{{> shared/missing}}\n````\n").is_empty());
+        assert!(find_includes("~~~\n```\n{{> shared/missing}}\n").is_empty());
+        assert_eq!(find_includes("~~~\n~~~\n{{> shared/persona}}").len(), 1);
+
+        fs::remove_dir_all(&ws).ok();
+    }
+
+    #[test]
+    fn test_index_workspace_records_relative_paths() {
+        let ws = workspace();
+        fs::write(ws.join("shared/persona.vibe.md"), "You are Vibe.").unwrap();
+        fs::write(ws.join("main.vibe.md"), "## User Message\n{{> shared/persona}}").unwrap();
+
+        let db = ProjectDatabase::new(&ws).unwrap();
+        index_workspace(&db, &ws.display().to_string()).unwrap();
+
+        let dependents = db.get_dependent_files("shared/persona.vibe.md", INCLUDE_DEPENDENCY).unwrap();
+        assert_eq!(dependents, vec!["main.vibe.md".to_string()]);
+        // Absolute and relative paths name the same record
+        let file_id = db.find_prompt_file_id("main.vibe.md").unwrap();
+        assert!(file_id.is_some());
+        assert_eq!(db.find_prompt_file_id(&ws.join("main.vibe.md").display().to_string()).unwrap(), file_id);
+        assert_eq!(db.ensure_prompt_file(&ws.join("./main.vibe.md").display().to_string()).unwrap(), file_id.unwrap());
+
+        fs::remove_dir_all(&ws).ok();
+    }
+}
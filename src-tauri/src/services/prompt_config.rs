@@ -0,0 +1,136 @@
+use crate::models::frontmatter::{parse_frontmatter, write_frontmatter, PromptFrontmatter};
+use crate::models::prompt::{ModelConfig, ModelParameters, Provider};
+use crate::services::database::{AppDatabase, ProjectDatabase, PromptFileMetadata};
+use std::fs;
+use std::path::Path;
+
+// Used when neither the prompt nor any provider config says otherwise
+const FALLBACK_MODEL: &str = "gpt-4o-mini";
+
+fn is_markdown_prompt(file_path: &str) -> bool {
+    file_path.ends_with(".vibe.md")
+}
+
+/// Read the frontmatter of a .vibe.md file (None for other files or when there is none)
+pub fn read_file_frontmatter(workspace_path: &str, file_path: &str) -> Result<Option<PromptFrontmatter>, String> {
+    if !is_markdown_prompt(file_path) {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(Path::new(workspace_path).join(file_path))
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let (frontmatter, _) = parse_frontmatter(&content)?;
+    Ok(frontmatter)
+}
+
+/// Copy the file's frontmatter into prompt_files (the file wins on conflict)
+/// Returns the frontmatter, or None if the file has none (the database is left as-is)
+pub fn sync_file_to_db(
+    db: &ProjectDatabase,
+    workspace_path: &str,
+    file_path: &str,
+) -> Result<Option<PromptFrontmatter>, String> {
+    let frontmatter = read_file_frontmatter(workspace_path, file_path)?;
+
+    if let Some(frontmatter) = &frontmatter {
+        db.apply_prompt_frontmatter(file_path, frontmatter)
+            .map_err(|e| format!("Failed to sync frontmatter: {}", e))?;
+    }
+
+    Ok(frontmatter)
+}
+
+/// Write prompt config into the file's frontmatter, keeping the body and any other keys
+/// Files without frontmatter only get one when there is something to record
+pub fn write_config_to_file(
+    workspace_path: &str,
+    file_path: &str,
+    metadata: &PromptFileMetadata,
+) -> Result<(), String> {
+    if !is_markdown_prompt(file_path) {
+        return Ok(());
+    }
+
+    let path = Path::new(workspace_path).join(file_path);
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let (existing, _) = parse_frontmatter(&content)?;
+
+    let has_frontmatter = existing.is_some();
+    let mut frontmatter = existing.unwrap_or_default();
+    frontmatter.provider_ref = Some(metadata.provider_ref.clone()).filter(|p| !p.is_empty() && p != "default");
+    frontmatter.model = metadata.model_override.clone().filter(|m| !m.is_empty());
+    frontmatter.parameters = metadata.parameters.as_deref().and_then(|json| serde_json::from_str(json).ok());
+    frontmatter.tags = metadata.tags.as_deref()
+        .and_then(|json| serde_json::from_str::<Vec<String>>(json).ok())
+        .filter(|tags| !tags.is_empty());
+    frontmatter.test_data = metadata.test_data_path.clone().filter(|t| !t.is_empty());
+
+    if !has_frontmatter && frontmatter.is_empty() {
+        return Ok(());
+    }
+
+    let updated = write_frontmatter(&content, &frontmatter)?;
+    if updated != content {
+        fs::write(&path, updated).map_err(|e| format!("Failed to write file: {}", e))?;
+        println!("📝 Updated frontmatter of {}", file_path);
+    }
+
+    Ok(())
+}
+
+/// Prompt config as stored in prompt_files, in frontmatter form
+pub fn frontmatter_from_metadata(metadata: &PromptFileMetadata) -> PromptFrontmatter {
+    PromptFrontmatter {
+        provider_ref: Some(metadata.provider_ref.clone()).filter(|p| !p.is_empty() && p != "default"),
+        model: metadata.model_override.clone(),
+        parameters: metadata.parameters.as_deref().and_then(|json| serde_json::from_str(json).ok()),
+        tags: metadata.tags.as_deref().and_then(|json| serde_json::from_str(json).ok()),
+        description: metadata.description.clone(),
+        test_data: metadata.test_data_path.clone(),
+        evaluation: metadata.evaluation_config.as_deref().and_then(|json| serde_json::from_str(json).ok()),
+        assertions: Vec::new(),
+        judge: None,
+        variables: Vec::new(),
+        extra: Default::default(),
+    }
+}
+
+/// Resolve provider type, model and parameters for a prompt
+/// provider_ref names a global LLM provider config; without one the default provider is used
+pub fn resolve_model_config(frontmatter: &PromptFrontmatter, app_db: Option<&AppDatabase>) -> ModelConfig {
+    let provider_config = app_db.and_then(|db| match frontmatter.provider_ref.as_deref() {
+        Some(name) => db.get_llm_provider(name).ok(),
+        None => db.list_llm_providers().ok()?.into_iter().find(|p| p.is_default),
+    });
+
+    let provider = provider_config
+        .as_ref()
+        .and_then(|config| Provider::from_name(&config.provider))
+        .unwrap_or(Provider::OpenAI);
+    let model = frontmatter
+        .model
+        .clone()
+        .or_else(|| provider_config.as_ref().map(|config| config.model.clone()).filter(|m| !m.is_empty()))
+        .unwrap_or_else(|| FALLBACK_MODEL.to_string());
+    let parameters = frontmatter
+        .parameters
+        .clone()
+        .or_else(|| {
+            provider_config
+                .as_ref()
+                .and_then(|config| config.parameters.as_deref())
+                .and_then(|json| serde_json::from_str(json).ok())
+        })
+        .or(Some(ModelParameters {
+            temperature: Some(0.7),
+            top_p: None,
+            max_tokens: None,
+        }));
+
+    ModelConfig {
+        provider,
+        model,
+        parameters,
+    }
+}
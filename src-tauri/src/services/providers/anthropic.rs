@@ -31,6 +31,10 @@ struct AnthropicContent {
 struct AnthropicUsage {
     input_tokens: u32,
     output_tokens: u32,
+    #[serde(default)]
+    cache_read_input_tokens: u32,
+    #[serde(default)]
+    cache_creation_input_tokens: u32,
 }
 
 pub async fn execute(
@@ -90,10 +94,19 @@ pub async fn execute(
         .map_err(|e| format!("Failed to parse response: {}", e))?;
 
     let output = api_response.content[0].text.clone();
+    // Anthropic reports cached prompt tokens separately from input_tokens
+    let usage_data = &api_response.usage;
+    let prompt_tokens = usage_data.input_tokens
+        + usage_data.cache_read_input_tokens
+        + usage_data.cache_creation_input_tokens;
     let usage = OpenAIUsage {
-        prompt_tokens: api_response.usage.input_tokens,
-        completion_tokens: api_response.usage.output_tokens,
-        total_tokens: api_response.usage.input_tokens + api_response.usage.output_tokens,
+        prompt_tokens,
+        completion_tokens: usage_data.output_tokens,
+        total_tokens: prompt_tokens + usage_data.output_tokens,
+        prompt_tokens_details: Some(PromptTokensDetails {
+            cached_tokens: usage_data.cache_read_input_tokens,
+        }),
+        cache_write_tokens: usage_data.cache_creation_input_tokens,
     };
 
     Ok((output, usage))
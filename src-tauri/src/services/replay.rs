@@ -0,0 +1,181 @@
+use crate::models::assertions::Assertion;
+use crate::models::frontmatter::parse_frontmatter;
+use crate::models::prompt::{parse_markdown_prompt, Message, ModelParameters, PromptRuntime};
+use crate::services::database::ExecutionRecord;
+use crate::services::partials;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Changes applied when replaying executions (unset fields keep the original value)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplayOverrides {
+    pub provider_name: Option<String>,
+    pub model: Option<String>,
+    pub parameters: Option<ModelParameters>,
+}
+
+/// Differences between a replay and the execution it re-ran
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayDiff {
+    pub source_model: String,
+    pub replay_model: String,
+    pub output_identical: bool,
+    pub lines_added: usize,
+    pub lines_removed: usize,
+    /// Share of output lines in common (1.0 = identical)
+    pub similarity: f64,
+    pub tokens_input_delta: i64,
+    pub tokens_output_delta: i64,
+    pub latency_ms_delta: i64,
+    pub cost_usd_delta: f64,
+}
+
+/// Parse the messages of a stored prompt version (.vibe.md or legacy YAML)
+/// Partials are expanded from their current content: only for executions without stored prompt_messages
+pub fn snapshot_messages(file_path: &str, content: &str, workspace: Option<&Path>) -> Result<Vec<Message>, String> {
+    if file_path.ends_with(".vibe.md") || file_path.ends_with(".md") {
+        let expanded = partials::expand_includes(content, Path::new(file_path), workspace)?;
+        // Versions from before a .vibe.yaml migration keep their YAML content
+        parse_markdown_prompt(&expanded.content).or_else(|e| {
+            serde_yaml::from_str::<PromptRuntime>(content)
+                .map(|prompt| prompt.messages)
+                .map_err(|_| e)
+        })
+    } else {
+        serde_yaml::from_str::<PromptRuntime>(content)
+            .map(|prompt| prompt.messages)
+            .map_err(|e| format!("YAML parse error: {}", e))
+    }
+}
+
+/// Assertions of a stored prompt version, checked against the replay like a normal run
+pub fn snapshot_assertions(file_path: &str, content: &str) -> Vec<Assertion> {
+    let from_yaml = || serde_yaml::from_str::<PromptRuntime>(content).map(|prompt| prompt.assertions).ok();
+    let assertions = if file_path.ends_with(".vibe.md") || file_path.ends_with(".md") {
+        // Versions from before a .vibe.yaml migration keep their YAML content
+        match parse_frontmatter(content) {
+            Ok((Some(frontmatter), _)) => Some(frontmatter.assertions),
+            _ => from_yaml(),
+        }
+    } else {
+        from_yaml()
+    };
+    assertions.unwrap_or_default()
+}
+
+/// Apply parameter overrides on top of the parameters the execution ran with
+pub fn merge_parameters(
+    original: Option<ModelParameters>,
+    overrides: Option<&ModelParameters>,
+) -> Option<ModelParameters> {
+    let overrides = match overrides {
+        Some(o) => o,
+        None => return original,
+    };
+
+    let mut params = original.unwrap_or(ModelParameters {
+        temperature: None,
+        top_p: None,
+        max_tokens: None,
+    });
+    if overrides.temperature.is_some() {
+        params.temperature = overrides.temperature;
+    }
+    if overrides.top_p.is_some() {
+        params.top_p = overrides.top_p;
+    }
+    if overrides.max_tokens.is_some() {
+        params.max_tokens = overrides.max_tokens;
+    }
+    Some(params)
+}
+
+pub fn diff_summary(source: &ExecutionRecord, replay: &ExecutionRecord) -> ReplayDiff {
+    let (lines_added, lines_removed, common) = line_diff(&source.output, &replay.output);
+    let total_lines = source.output.lines().count() + replay.output.lines().count();
+
+    ReplayDiff {
+        source_model: source.model.clone(),
+        replay_model: replay.model.clone(),
+        output_identical: source.output == replay.output,
+        lines_added,
+        lines_removed,
+        similarity: if total_lines == 0 { 1.0 } else { (2 * common) as f64 / total_lines as f64 },
+        tokens_input_delta: replay.tokens_input - source.tokens_input,
+        tokens_output_delta: replay.tokens_output - source.tokens_output,
+        latency_ms_delta: replay.latency_ms - source.latency_ms,
+        cost_usd_delta: replay.cost_usd - source.cost_usd,
+    }
+}
+
+// Largest outputs compared line by line (LCS is O(n*m))
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// Returns (lines added, lines removed, lines in common) going from `old` to `new`
+fn line_diff(old: &str, new: &str) -> (usize, usize, usize) {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    let common = if a.len() * b.len() > MAX_LCS_CELLS {
+        // Too large: count shared lines regardless of order
+        let mut remaining = b.clone();
+        a.iter()
+            .filter(|line| match remaining.iter().position(|l| l == *line) {
+                Some(i) => {
+                    remaining.swap_remove(i);
+                    true
+                }
+                None => false,
+            })
+            .count()
+    } else {
+        let mut prev = vec![0usize; b.len() + 1];
+        let mut curr = vec![0usize; b.len() + 1];
+        for line_a in &a {
+            for (j, line_b) in b.iter().enumerate() {
+                curr[j + 1] = if line_a == line_b {
+                    prev[j] + 1
+                } else {
+                    prev[j + 1].max(curr[j])
+                };
+            }
+            std::mem::swap(&mut prev, &mut curr);
+        }
+        prev[b.len()]
+    };
+
+    (b.len() - common, a.len() - common, common)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        assert_eq!(line_diff("a\nb\nc", "a\nb\nc"), (0, 0, 3));
+        assert_eq!(line_diff("a\nb\nc", "a\nx\nc\nd"), (2, 1, 2));
+        assert_eq!(line_diff("", "a"), (1, 0, 0));
+    }
+
+    #[test]
+    fn test_snapshot_assertions() {
+        let markdown = "---\nassertions:\n  - type: contains\n    value: refund\n---\n## User Message\nHi";
+        assert_eq!(
+            snapshot_assertions("prompts/support.vibe.md", markdown),
+            vec![Assertion::Contains { value: "refund".to_string() }]
+        );
+        assert!(snapshot_assertions("prompts/support.vibe.md", "## User Message\nHi").is_empty());
+    }
+
+    #[test]
+    fn test_merge_parameters() {
+        let original = Some(ModelParameters { temperature: Some(0.7), top_p: None, max_tokens: Some(100) });
+        let overrides = ModelParameters { temperature: Some(0.0), top_p: None, max_tokens: None };
+
+        let merged = merge_parameters(original, Some(&overrides)).unwrap();
+        assert_eq!(merged.temperature, Some(0.0));
+        assert_eq!(merged.max_tokens, Some(100));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::models::execution::OpenAIMessage;
+use crate::services::database::StoredVariable;
+use crate::services::keychain::KeychainService;
+use crate::services::variable_resolution::VariableLayers;
+use std::collections::HashMap;
+
+/// Shown instead of secret values; passing it back keeps (or substitutes) the stored secret
+pub const SECRET_MASK: &str = "••••••••";
+
+/// Shorter values would mask ordinary words wherever they appear in outputs
+pub const MIN_SECRET_LENGTH: usize = 4;
+
+/// Prepare a variable for saving: secret values go to the keychain and the database keeps an empty value
+/// `previous` is the saved variable with the same id, if any
+pub fn store(variable: &mut StoredVariable, previous: Option<&StoredVariable>) -> Result<(), String> {
+    if variable.id.is_empty() {
+        variable.id = uuid::Uuid::new_v4().to_string();
+    }
+    let was_secret = previous.is_some_and(|p| p.secret);
+
+    if variable.secret {
+        if variable.value != SECRET_MASK {
+            check_length(&variable.key, &variable.value)?;
+            KeychainService::save_variable_secret(&variable.id, &variable.value)?;
+        } else if !was_secret {
+            // Marked secret without retyping the value: move the plaintext value to the keychain
+            let value = previous.map(|p| p.value.as_str()).ok_or_else(|| format!("Missing value for secret '{}'", variable.key))?;
+            check_length(&variable.key, value)?;
+            KeychainService::save_variable_secret(&variable.id, value)?;
+        }
+        variable.value = String::new();
+    } else if was_secret {
+        if variable.value == SECRET_MASK {
+            variable.value = KeychainService::get_variable_secret(&variable.id)?;
+        }
+        KeychainService::delete_variable_secret(&variable.id).ok();
+    }
+
+    Ok(())
+}
+
+fn check_length(key: &str, value: &str) -> Result<(), String> {
+    if value.chars().count() < MIN_SECRET_LENGTH {
+        return Err(format!("Secret '{}' must be at least {} characters long", key, MIN_SECRET_LENGTH));
+    }
+    Ok(())
+}
+
+/// Delete the keychain entries of secret variables that are no longer saved
+pub fn forget_removed(previous: &[StoredVariable], current: &[StoredVariable]) {
+    for variable in previous.iter().filter(|p| p.secret) {
+        if !current.iter().any(|c| c.id == variable.id && c.secret) {
+            KeychainService::delete_variable_secret(&variable.id).ok();
+        }
+    }
+}
+
+/// Keychain value of a secret variable
+pub fn reveal(variable: &StoredVariable) -> Result<String, String> {
+    KeychainService::get_variable_secret(&variable.id)
+        .map_err(|e| format!("Secret '{}': {}", variable.key, e))
+}
+
+/// Hide secret values before returning variables
+pub fn mask(variables: &mut [StoredVariable]) {
+    for variable in variables.iter_mut().filter(|v| v.secret) {
+        variable.value = SECRET_MASK.to_string();
+    }
+}
+
+/// Substitute secret variables given as the mask (or left empty) with their keychain values
+/// A masked value without a secret to fill it is an error: the mask would be sent to the model
+/// Returns the substituted values so they can be redacted from what gets stored
+pub fn fill_secrets(layers: &VariableLayers, variables: &mut HashMap<String, String>) -> Result<Vec<String>, String> {
+    let mut secrets = Vec::new();
+    for (name, value) in variables.iter_mut() {
+        if !value.is_empty() && value != SECRET_MASK {
+            continue;
+        }
+        match layers.secret_id(name) {
+            Some(id) => {
+                *value = KeychainService::get_variable_secret(id)?;
+                secrets.push(value.clone());
+            }
+            None if value == SECRET_MASK => {
+                return Err(format!(
+                    "Secret '{}' has no value in the global, project or active profile variables",
+                    name
+                ));
+            }
+            None => {}
+        }
+    }
+    Ok(secrets)
+}
+
+/// Replace every occurrence of the secrets with the mask, including inside longer tokens
+pub fn redact(text: &str, secrets: &[String]) -> String {
+    let mut secrets: Vec<&String> = secrets.iter().filter(|s| !s.is_empty()).collect();
+    // Longest first, so a secret containing another one is fully masked
+    secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+
+    let mut redacted = text.to_string();
+    for secret in secrets {
+        redacted = redacted.replace(secret.as_str(), SECRET_MASK);
+    }
+    redacted
+}
+
+/// Rendered messages with the secrets masked
+pub fn redact_messages(messages: &[OpenAIMessage], secrets: &[String]) -> Vec<OpenAIMessage> {
+    messages
+        .iter()
+        .map(|message| OpenAIMessage {
+            role: message.role.clone(),
+            content: redact(&message.content, secrets),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() {
+        let secrets = vec!["https://intra.example".to_string(), "intra".to_string(), String::new()];
+        assert_eq!(
+            redact("See https://intra.example/docs and intra wiki", &secrets),
+            format!("See {}/docs and {} wiki", SECRET_MASK, SECRET_MASK)
+        );
+        assert_eq!(redact("nothing here", &secrets), "nothing here");
+
+        // Occurrences inside longer tokens are masked too
+        let secrets = vec!["sk-123".to_string(), "token".to_string()];
+        assert_eq!(
+            redact("sk-123: sk-1234, Bearer_token \"token\"", &secrets),
+            format!("{m}: {m}4, Bearer_{m} \"{m}\"", m = SECRET_MASK)
+        );
+
+        assert!(check_length("PIN", "123").is_err());
+        assert!(check_length("API_KEY", "sk-1").is_ok());
+    }
+}
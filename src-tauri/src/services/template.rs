@@ -1,31 +1,851 @@
-use regex::Regex;
+use crate::models::prompt::Message;
+use crate::services::dynamic_variables::{DynamicVariable, DYNAMIC_PREFIX};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
+use std::fmt;
 
+// Prompt template language
+//
+//   {{name}}  {{user.name}}                  variable (JSON values can be navigated with dots)
+//   {{name | upper}}                         filters: upper, lower, trim, json, truncate: N,
+//                                            indent: N, default: "value"
+//   {{#if name}}...{{else}}...{{/if}}        conditional (also {{#unless}})
+//   {{#each items}}...{{/each}}              loop over a JSON array: {{this}}, {{this.field}},
+//                                            {{@index}}, {{@first}}, {{@last}}, optional {{else}}
+//   {{file:docs/spec.md#L10-L20}}            content of a workspace file (see services::file_variables)
+//                                            (there are no URL sources: `{{url:...}}` is an error)
+//   {{$now}}  {{$date:%Y-%m-%d}}             reserved variables resolved at execution time
+//                                            (see services::dynamic_variables); left as is when not given
+//   {{! comment }}                           removed from the output
+//   \{{                                      literal {{
+//
+// Any other `{{ ... }}` (code samples, other template languages, an unclosed `{{`) is left as is.
+// Block tags alone on their line don't leave an empty line behind.
+// `{{> path}}` includes are expanded beforehand (see services::partials) and left untouched.
+
+/// Template syntax error, positioned in the template (1-based)
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl TemplateError {
+    fn at(source: &str, offset: usize, message: impl Into<String>) -> Self {
+        let (line, column) = line_column(source, offset);
+        Self {
+            message: message.into(),
+            line,
+            column,
+        }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// A variable used by a template
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VariableUsage {
+    pub name: String,
+    /// Rendering succeeds without it (it has a default, is only used as a condition or
+    /// loop, or only inside `{{#if name}}`)
+    pub optional: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// Position of the first use
+    pub line: usize,
+    pub column: usize,
+}
+
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+    (line, column)
+}
+
+// ---------- Lexer ----------
+
+enum Token {
+    Text(String),
+    Tag { body: String, offset: usize },
+}
+
+fn is_block_tag(body: &str) -> bool {
+    body.starts_with('#') || body.starts_with('/') || body.starts_with('!') || body == "else"
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Whether a tag body is meant for this template language: a block tag, or a variable path
+/// optionally followed by filters. Other tags are literal text
+fn is_template_tag(body: &str) -> bool {
+    if is_block_tag(body) {
+        return true;
+    }
+
+    let path = split_unquoted(body, '|')[0].trim();
+    if path.starts_with(FILE_PREFIX) || path.starts_with(URL_PREFIX) {
+        return true;
+    }
+    if let Some(name) = path.strip_prefix(DYNAMIC_PREFIX) {
+        return name.starts_with(|c: char| c.is_ascii_alphabetic());
+    }
+
+    let mut segments = path.split('.');
+    let root = segments.next().unwrap_or("");
+    (is_identifier(root) || matches!(root, "@index" | "@first" | "@last"))
+        && segments.all(|field| is_identifier(field) || field.parse::<usize>().is_ok())
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, TemplateError> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    let mut i = 0;
+
+    while i < source.len() {
+        let rest = &source[i..];
+
+        if rest.starts_with("\\{{") {
+            text.push_str("{{");
+            i += 3;
+            continue;
+        }
+        if rest.starts_with("{{>") {
+            text.push_str("{{>");
+            i += 3;
+            continue;
+        }
+        if let Some(inner) = rest.strip_prefix("{{") {
+            let tag = if inner.starts_with("!--") {
+                let end = rest.find("--}}")
+                    .ok_or_else(|| TemplateError::at(source, i, "Unclosed comment, expected '--}}'"))?;
+                Some(("!", end + 4))
+            } else {
+                inner
+                    .find("}}")
+                    .map(|end| (inner[..end].trim(), end + 4))
+                    .filter(|(body, _)| is_template_tag(body))
+            };
+
+            // Not a tag: keep the braces and scan on, a real tag may follow inside
+            let Some((body, length)) = tag else {
+                text.push_str("{{");
+                i += 2;
+                continue;
+            };
+
+            if !text.is_empty() {
+                tokens.push(Token::Text(std::mem::take(&mut text)));
+            }
+            tokens.push(Token::Tag { body: body.to_string(), offset: i });
+            i += length;
+            continue;
+        }
+
+        let ch = rest.chars().next().unwrap();
+        text.push(ch);
+        i += ch.len_utf8();
+    }
+
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+
+    strip_standalone_lines(&mut tokens);
+    Ok(tokens)
+}
+
+/// Remove the line of block tags that stand alone on it (only whitespace around)
+fn strip_standalone_lines(tokens: &mut [Token]) {
+    let text = |token: Option<&Token>| match token {
+        Some(Token::Text(t)) => Some(t.clone()),
+        _ => None,
+    };
+
+    // (cut start of text through its first newline, cut end of text after its last newline)
+    let mut cuts = vec![(false, false); tokens.len()];
+
+    for k in 0..tokens.len() {
+        match &tokens[k] {
+            Token::Tag { body, .. } if is_block_tag(body) => {}
+            _ => continue,
+        }
+
+        let prev = if k == 0 { None } else { Some(&tokens[k - 1]) };
+        let left_ok = match (k, text(prev)) {
+            (0, _) => true,
+            (_, Some(t)) => match t.rfind('\n') {
+                Some(pos) => t[pos + 1..].trim().is_empty(),
+                None => k == 1 && t.trim().is_empty(),
+            },
+            _ => false,
+        };
+
+        let next = tokens.get(k + 1);
+        let right_ok = match (next, text(next)) {
+            (None, _) => true,
+            (_, Some(t)) => match t.find('\n') {
+                Some(pos) => t[..pos].trim().is_empty(),
+                None => k + 2 == tokens.len() && t.trim().is_empty(),
+            },
+            _ => false,
+        };
+
+        if left_ok && right_ok {
+            if k > 0 {
+                cuts[k - 1].1 = true;
+            }
+            if k + 1 < tokens.len() {
+                cuts[k + 1].0 = true;
+            }
+        }
+    }
+
+    for (token, (cut_start, cut_end)) in tokens.iter_mut().zip(cuts) {
+        if let Token::Text(t) = token {
+            let start = if cut_start { t.find('\n').map(|p| p + 1).unwrap_or(t.len()) } else { 0 };
+            let end = if cut_end { t.rfind('\n').map(|p| p + 1).unwrap_or(0) } else { t.len() };
+            *t = t[start..end.max(start)].to_string();
+        }
+    }
+}
+
+// ---------- Parser ----------
+
+const FILE_PREFIX: &str = "file:";
+/// Reserved so URL sources are rejected rather than rendered as text
+const URL_PREFIX: &str = "url:";
+
+#[derive(Debug, Clone)]
+struct VarPath {
+    root: String,
+    fields: Vec<String>,
+}
+
+impl VarPath {
+    fn is_loop_local(&self) -> bool {
+        self.root == "this" || self.root.starts_with('@')
+    }
+
+    /// `file:` sources are loaded by the caller and passed in as variables named after the reference
+    fn is_file(&self) -> bool {
+        self.root.starts_with(FILE_PREFIX)
+    }
+
+    /// `$` variables are resolved by the caller too, and are never reported missing
+    fn is_dynamic(&self) -> bool {
+        self.root.starts_with(DYNAMIC_PREFIX)
+    }
+}
+
+impl fmt::Display for VarPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.root)?;
+        for field in &self.fields {
+            write!(f, ".{}", field)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Filter {
+    Upper,
+    Lower,
+    Trim,
+    Json,
+    Truncate(usize),
+    Indent(usize),
+    Default(String),
+}
+
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Value {
+        path: VarPath,
+        filters: Vec<Filter>,
+        offset: usize,
+    },
+    If {
+        condition: VarPath,
+        negate: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+        offset: usize,
+    },
+    Each {
+        list: VarPath,
+        body: Vec<Node>,
+        otherwise: Vec<Node>,
+        offset: usize,
+    },
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+    loop_depth: usize,
+}
+
+/// How a block's content ended
+enum BlockEnd {
+    Eof,
+    Else(usize),
+    Close(String, usize),
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, offset: usize, message: impl Into<String>) -> TemplateError {
+        TemplateError::at(self.source, offset, message)
+    }
+
+    fn parse_template(&mut self) -> Result<Vec<Node>, TemplateError> {
+        let (nodes, end) = self.parse_nodes()?;
+        match end {
+            BlockEnd::Eof => Ok(nodes),
+            BlockEnd::Else(offset) => Err(self.error(offset, "{{else}} outside of {{#if}} or {{#each}}")),
+            BlockEnd::Close(name, offset) => Err(self.error(offset, format!("Unexpected {{{{/{}}}}}", name))),
+        }
+    }
+
+    fn parse_nodes(&mut self) -> Result<(Vec<Node>, BlockEnd), TemplateError> {
+        let mut nodes = Vec::new();
+
+        while self.pos < self.tokens.len() {
+            let (body, offset) = match &self.tokens[self.pos] {
+                Token::Text(text) => {
+                    if !text.is_empty() {
+                        nodes.push(Node::Text(text.clone()));
+                    }
+                    self.pos += 1;
+                    continue;
+                }
+                Token::Tag { body, offset } => (body.clone(), *offset),
+            };
+            self.pos += 1;
+
+            if body.starts_with('!') {
+                continue;
+            }
+            if body == "else" {
+                return Ok((nodes, BlockEnd::Else(offset)));
+            }
+            if let Some(name) = body.strip_prefix('/') {
+                return Ok((nodes, BlockEnd::Close(name.trim().to_string(), offset)));
+            }
+            if let Some(block) = body.strip_prefix('#') {
+                nodes.push(self.parse_block(block, offset)?);
+                continue;
+            }
+
+            nodes.push(self.parse_value(&body, offset)?);
+        }
+
+        Ok((nodes, BlockEnd::Eof))
+    }
+
+    fn parse_block(&mut self, block: &str, offset: usize) -> Result<Node, TemplateError> {
+        let (keyword, argument) = match block.split_once(char::is_whitespace) {
+            Some((keyword, argument)) => (keyword, argument.trim()),
+            None => (block, ""),
+        };
+        if !matches!(keyword, "if" | "unless" | "each") {
+            return Err(self.error(offset, format!("Unknown block '#{}'", keyword)));
+        }
+        if argument.is_empty() {
+            return Err(self.error(offset, format!("{{{{#{}}}}} needs a variable", keyword)));
+        }
+        let path = self.parse_path(argument, offset)?;
+
+        if keyword == "each" {
+            self.loop_depth += 1;
+        }
+        let (first, end) = self.parse_nodes()?;
+        if keyword == "each" {
+            self.loop_depth -= 1;
+        }
+
+        let second = match end {
+            BlockEnd::Else(_) => {
+                let (second, end) = self.parse_nodes()?;
+                self.expect_close(keyword, offset, end)?;
+                second
+            }
+            end => {
+                self.expect_close(keyword, offset, end)?;
+                Vec::new()
+            }
+        };
+
+        Ok(match keyword {
+            "each" => Node::Each { list: path, body: first, otherwise: second, offset },
+            _ => Node::If { condition: path, negate: keyword == "unless", then: first, otherwise: second, offset },
+        })
+    }
+
+    fn expect_close(&self, keyword: &str, open_offset: usize, end: BlockEnd) -> Result<(), TemplateError> {
+        match end {
+            BlockEnd::Close(name, _) if name == keyword => Ok(()),
+            BlockEnd::Close(name, offset) => Err(self.error(
+                offset,
+                format!("Expected {{{{/{}}}}}, found {{{{/{}}}}}", keyword, name),
+            )),
+            BlockEnd::Else(offset) => Err(self.error(offset, format!("Duplicate {{{{else}}}} in {{{{#{}}}}}", keyword))),
+            BlockEnd::Eof => Err(self.error(open_offset, format!("Unclosed {{{{#{}}}}}", keyword))),
+        }
+    }
+
+    fn parse_value(&self, body: &str, offset: usize) -> Result<Node, TemplateError> {
+        let mut parts = split_unquoted(body, '|').into_iter();
+        let path = self.parse_path(parts.next().unwrap_or("").trim(), offset)?;
+        let filters = parts
+            .map(|part| self.parse_filter(part.trim(), offset))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Node::Value { path, filters, offset })
+    }
+
+    fn parse_path(&self, text: &str, offset: usize) -> Result<VarPath, TemplateError> {
+        if let Some(path) = text.strip_prefix(FILE_PREFIX) {
+            if path.trim().is_empty() {
+                return Err(self.error(offset, "File variable needs a path, e.g. {{file:docs/spec.md}}"));
+            }
+            return Ok(VarPath { root: format!("{}{}", FILE_PREFIX, path.trim()), fields: Vec::new() });
+        }
+        if text.starts_with(URL_PREFIX) {
+            return Err(self.error(offset, "URL variables are not supported: save the document in the workspace and use {{file:path}}"));
+        }
+        if text.starts_with(DYNAMIC_PREFIX) {
+            DynamicVariable::parse(text).map_err(|e| self.error(offset, e))?;
+            return Ok(VarPath { root: text.to_string(), fields: Vec::new() });
+        }
+
+        let mut segments = text.split('.');
+        let root = segments.next().unwrap_or("").to_string();
+        let fields: Vec<String> = segments.map(str::to_string).collect();
+
+        let loop_variable = matches!(root.as_str(), "this" | "@index" | "@first" | "@last");
+        if !loop_variable && !is_identifier(&root) {
+            return Err(self.error(offset, format!("Invalid variable name '{}'", text)));
+        }
+        if loop_variable && self.loop_depth == 0 {
+            return Err(self.error(offset, format!("'{}' can only be used inside {{{{#each}}}}", root)));
+        }
+        if root.starts_with('@') && !fields.is_empty() {
+            return Err(self.error(offset, format!("Invalid variable name '{}'", text)));
+        }
+        if let Some(field) = fields.iter().find(|f| !is_identifier(f) && f.parse::<usize>().is_err()) {
+            return Err(self.error(offset, format!("Invalid field '{}' in '{}'", field, text)));
+        }
+
+        Ok(VarPath { root, fields })
+    }
+
+    fn parse_filter(&self, text: &str, offset: usize) -> Result<Filter, TemplateError> {
+        let (name, argument) = match text.split_once(':') {
+            Some((name, argument)) => (name.trim(), Some(argument.trim())),
+            None => (text, None),
+        };
+
+        let number = |argument: Option<&str>| {
+            argument
+                .and_then(|a| a.parse::<usize>().ok())
+                .ok_or_else(|| self.error(offset, format!("Filter '{}' needs a number, e.g. {}: 2", name, name)))
+        };
+
+        match name {
+            "upper" => Ok(Filter::Upper),
+            "lower" => Ok(Filter::Lower),
+            "trim" => Ok(Filter::Trim),
+            "json" => Ok(Filter::Json),
+            "truncate" => Ok(Filter::Truncate(number(argument)?)),
+            "indent" => Ok(Filter::Indent(number(argument)?)),
+            "default" => {
+                let value = argument
+                    .and_then(unquote)
+                    .ok_or_else(|| self.error(offset, "Filter 'default' needs a value, e.g. default: \"friendly\""))?;
+                Ok(Filter::Default(value))
+            }
+            _ => Err(self.error(offset, format!("Unknown filter '{}'", name))),
+        }
+    }
+}
+
+/// Split on `separator` outside of quotes
+fn split_unquoted(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == separator => {
+                parts.push(&text[start..i]);
+                start = i + c.len_utf8();
+            }
+            None => {}
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// A quoted string or a bare number
+fn unquote(text: &str) -> Option<String> {
+    for quote in ['"', '\''] {
+        if let Some(inner) = text.strip_prefix(quote).and_then(|t| t.strip_suffix(quote)) {
+            return Some(inner.to_string());
+        }
+    }
+    text.parse::<f64>().ok().map(|_| text.to_string())
+}
+
+fn parse(template: &str) -> Result<Vec<Node>, TemplateError> {
+    let mut parser = Parser {
+        source: template,
+        tokens: tokenize(template)?,
+        pos: 0,
+        loop_depth: 0,
+    };
+    parser.parse_template()
+}
+
+// ---------- Rendering ----------
+
+struct LoopFrame {
+    item: Value,
+    index: usize,
+    last: bool,
+}
+
+struct Renderer<'a> {
+    source: &'a str,
+    variables: &'a HashMap<String, String>,
+    frames: Vec<LoopFrame>,
+    /// Missing variables with the offset of their first use
+    missing: Vec<(String, usize)>,
+}
+
+/// Variable values are strings; JSON arrays and objects can be navigated
+/// Only used where structure matters: plain `{{name}}` outputs the string as given
+fn variable_value(value: &str) -> Value {
+    let trimmed = value.trim_start();
+    if trimmed.starts_with('[') || trimmed.starts_with('{') {
+        if let Ok(json) = serde_json::from_str(value) {
+            return json;
+        }
+    }
+    Value::String(value.to_string())
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn is_truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(b)) => *b,
+        Some(Value::Number(n)) => n.as_f64().map(|n| n != 0.0).unwrap_or(true),
+        Some(Value::String(s)) => !matches!(s.trim(), "" | "false" | "0" | "null"),
+        Some(Value::Array(a)) => !a.is_empty(),
+        Some(Value::Object(o)) => !o.is_empty(),
+    }
+}
+
+fn apply_filter(filter: &Filter, value: Option<Value>) -> Option<Value> {
+    if let Filter::Default(default) = filter {
+        return match value {
+            Some(v) if !display(&v).is_empty() => Some(v),
+            _ => Some(Value::String(default.clone())),
+        };
+    }
+
+    let value = value?;
+    let text = match filter {
+        Filter::Upper => display(&value).to_uppercase(),
+        Filter::Lower => display(&value).to_lowercase(),
+        Filter::Trim => display(&value).trim().to_string(),
+        Filter::Json => serde_json::to_string(&value).unwrap_or_default(),
+        Filter::Truncate(length) => {
+            let text = display(&value);
+            if text.chars().count() > *length {
+                format!("{}...", text.chars().take(*length).collect::<String>())
+            } else {
+                text
+            }
+        }
+        Filter::Indent(width) => {
+            // The first line stays where the tag is
+            let padding = " ".repeat(*width);
+            display(&value)
+                .split('\n')
+                .enumerate()
+                .map(|(i, line)| if i == 0 || line.is_empty() { line.to_string() } else { format!("{}{}", padding, line) })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        Filter::Default(_) => unreachable!(),
+    };
+    Some(Value::String(text))
+}
+
+impl<'a> Renderer<'a> {
+    /// `structured`: parse JSON values even without a field path (loops, conditions, `json` filter)
+    fn resolve(&self, path: &VarPath, structured: bool) -> Option<Value> {
+        let frame = self.frames.last();
+        let root = match path.root.as_str() {
+            "this" => frame?.item.clone(),
+            "@index" => Value::from(frame?.index),
+            "@first" => Value::Bool(frame?.index == 0),
+            "@last" => Value::Bool(frame?.last),
+            name => {
+                let value = self.variables.get(name)?;
+                if structured || !path.fields.is_empty() {
+                    variable_value(value)
+                } else {
+                    Value::String(value.clone())
+                }
+            }
+        };
+
+        path.fields.iter().try_fold(root, |value, field| match value {
+            Value::Object(mut map) => map.remove(field),
+            Value::Array(mut items) => {
+                let index = field.parse::<usize>().ok()?;
+                (index < items.len()).then(|| items.swap_remove(index))
+            }
+            _ => None,
+        })
+    }
+
+    fn render(&mut self, nodes: &[Node], output: &mut String) -> Result<(), TemplateError> {
+        for node in nodes {
+            match node {
+                Node::Text(text) => output.push_str(text),
+                Node::Value { path, filters, offset } => {
+                    let structured = filters.iter().any(|f| matches!(f, Filter::Json));
+                    let value = filters.iter().fold(self.resolve(path, structured), |value, filter| apply_filter(filter, value));
+                    match value {
+                        Some(value) => output.push_str(&display(&value)),
+                        None if path.is_dynamic() => output.push_str(&format!("{{{{{}}}}}", path)),
+                        None => {
+                            let name = path.to_string();
+                            if !self.missing.iter().any(|(n, _)| *n == name) {
+                                self.missing.push((name, *offset));
+                            }
+                        }
+                    }
+                }
+                Node::If { condition, negate, then, otherwise, .. } => {
+                    let truthy = is_truthy(self.resolve(condition, true).as_ref());
+                    let branch = if truthy != *negate { then } else { otherwise };
+                    self.render(branch, output)?;
+                }
+                Node::Each { list, body, otherwise, offset } => {
+                    let items = match self.resolve(list, true) {
+                        None | Some(Value::Null) => Vec::new(),
+                        Some(Value::Array(items)) => items,
+                        Some(Value::String(s)) if s.trim().is_empty() => Vec::new(),
+                        Some(_) => {
+                            return Err(TemplateError::at(
+                                self.source,
+                                *offset,
+                                format!("'{}' is not a JSON array", list),
+                            ))
+                        }
+                    };
+
+                    if items.is_empty() {
+                        self.render(otherwise, output)?;
+                        continue;
+                    }
+
+                    let count = items.len();
+                    for (index, item) in items.into_iter().enumerate() {
+                        self.frames.push(LoopFrame { item, index, last: index + 1 == count });
+                        let result = self.render(body, output);
+                        self.frames.pop();
+                        result?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Render a template
+/// Fails on syntax errors and on variables that are missing and have no default
 pub fn replace_variables(template: &str, variables: &HashMap<String, String>) -> Result<String, String> {
-    let regex = Regex::new(r"\{\{([a-zA-Z_][a-zA-Z0-9_]*)\}\}").unwrap();
-    let mut result = template.to_string();
-    let mut missing_vars = Vec::new();
+    let nodes = parse(template).map_err(|e| format!("Template error at {}", e))?;
+
+    let mut renderer = Renderer {
+        source: template,
+        variables,
+        frames: Vec::new(),
+        missing: Vec::new(),
+    };
+    let mut output = String::with_capacity(template.len());
+    renderer
+        .render(&nodes, &mut output)
+        .map_err(|e| format!("Template error at {}", e))?;
 
-    for cap in regex.captures_iter(template) {
-        let var_name = &cap[1];
-        if let Some(value) = variables.get(var_name) {
-            result = result.replace(&format!("{{{{{}}}}}", var_name), value);
-        } else {
-            missing_vars.push(var_name.to_string());
+    if !renderer.missing.is_empty() {
+        let missing: Vec<String> = renderer
+            .missing
+            .iter()
+            .map(|(name, offset)| {
+                let (line, column) = line_column(template, *offset);
+                format!("{} (line {}, column {})", name, line, column)
+            })
+            .collect();
+        return Err(format!("Missing variables: {}", missing.join(", ")));
+    }
+
+    Ok(output)
+}
+
+/// Check a template's syntax without rendering it
+pub fn validate_template(template: &str) -> Result<(), TemplateError> {
+    parse(template).map(|_| ())
+}
+
+/// Variables used by a template, in order of first use
+pub fn extract_variables(template: &str) -> Result<Vec<VariableUsage>, TemplateError> {
+    let nodes = parse(template)?;
+    let mut usages = Vec::new();
+    collect_usage(template, &nodes, &mut Vec::new(), &mut usages);
+    Ok(usages)
+}
+
+/// `file:` references of a template (e.g. `file:docs/spec.md#L1-L20`), in order of first use
+pub fn extract_file_references(template: &str) -> Result<Vec<String>, TemplateError> {
+    Ok(collect_references(&parse(template)?, VarPath::is_file))
+}
+
+/// `$` variables of a template (e.g. `$date:%Y-%m-%d`), in order of first use
+pub fn extract_dynamic_references(template: &str) -> Result<Vec<String>, TemplateError> {
+    Ok(collect_references(&parse(template)?, VarPath::is_dynamic))
+}
+
+fn collect_references(nodes: &[Node], matches: fn(&VarPath) -> bool) -> Vec<String> {
+    fn collect(nodes: &[Node], matches: fn(&VarPath) -> bool, references: &mut Vec<String>) {
+        for node in nodes {
+            let (path, children): (&VarPath, Vec<&[Node]>) = match node {
+                Node::Text(_) => continue,
+                Node::Value { path, .. } => (path, Vec::new()),
+                Node::If { condition, then, otherwise, .. } => (condition, vec![then, otherwise]),
+                Node::Each { list, body, otherwise, .. } => (list, vec![body, otherwise]),
+            };
+            if matches(path) && !references.contains(&path.root) {
+                references.push(path.root.clone());
+            }
+            for child in children {
+                collect(child, matches, references);
+            }
         }
     }
 
-    if !missing_vars.is_empty() {
-        return Err(format!("Missing variables: {}", missing_vars.join(", ")));
+    let mut references = Vec::new();
+    collect(nodes, matches, &mut references);
+    references
+}
+
+/// Variables used across the messages of a prompt, in order of first use
+pub fn extract_message_variables(messages: &[Message]) -> Result<Vec<VariableUsage>, String> {
+    let mut usages: Vec<VariableUsage> = Vec::new();
+    for message in messages {
+        let found = extract_variables(&message.content)
+            .map_err(|e| format!("Template error in {:?} message at {}", message.role, e))?;
+        for usage in found {
+            merge_usage(&mut usages, usage);
+        }
     }
+    Ok(usages)
+}
 
-    Ok(result)
+fn merge_usage(usages: &mut Vec<VariableUsage>, usage: VariableUsage) {
+    match usages.iter_mut().find(|u| u.name == usage.name) {
+        Some(existing) => {
+            existing.optional &= usage.optional;
+            if existing.default.is_none() {
+                existing.default = usage.default;
+            }
+        }
+        None => usages.push(usage),
+    }
+}
+
+fn collect_usage(source: &str, nodes: &[Node], guards: &mut Vec<String>, usages: &mut Vec<VariableUsage>) {
+    let add = |path: &VarPath, offset: usize, optional: bool, default: Option<String>, usages: &mut Vec<VariableUsage>| {
+        if path.is_loop_local() || path.is_file() || path.is_dynamic() {
+            return;
+        }
+        let (line, column) = line_column(source, offset);
+        merge_usage(usages, VariableUsage { name: path.root.clone(), optional, default, line, column });
+    };
+
+    for node in nodes {
+        match node {
+            Node::Text(_) => {}
+            Node::Value { path, filters, offset } => {
+                let default = filters.iter().find_map(|f| match f {
+                    Filter::Default(value) => Some(value.clone()),
+                    _ => None,
+                });
+                let optional = default.is_some() || guards.contains(&path.root);
+                add(path, *offset, optional, default, usages);
+            }
+            Node::If { condition, negate, then, otherwise, offset } => {
+                add(condition, *offset, true, None, usages);
+
+                let guarded = !*negate && condition.fields.is_empty() && !condition.is_loop_local();
+                if guarded {
+                    guards.push(condition.root.clone());
+                }
+                collect_usage(source, then, guards, usages);
+                if guarded {
+                    guards.pop();
+                }
+                collect_usage(source, otherwise, guards, usages);
+            }
+            Node::Each { list, body, otherwise, offset } => {
+                add(list, *offset, true, None, usages);
+                collect_usage(source, body, guards, usages);
+                collect_usage(source, otherwise, guards, usages);
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
     #[test]
     fn test_replace_variables() {
         let template = "Hello {{name}}! Your order {{order_id}} is ready.";
@@ -46,10 +866,87 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("Missing variables: name"));
     }
-}
 
+    #[test]
+    fn test_conditionals_and_loops() {
+        let template = "{{#if context}}\nContext: {{context}}\n{{else}}\nNo context.\n{{/if}}\n{{#each items}}\n{{@index}}. {{this.title | upper}}\n{{/each}}\n";
+        let variables = vars(&[("items", r#"[{"title": "a"}, {"title": "b"}]"#)]);
+
+        let result = replace_variables(template, &variables).unwrap();
+        assert_eq!(result, "No context.\n0. A\n1. B\n");
+    }
+
+    #[test]
+    fn test_filters_defaults_and_escape() {
+        let template = "{{tone | default: \"friendly\"}} {{text | truncate: 5}} {{data | json}}\n  {{body | indent: 2}} \\{{literal}}";
+        let variables = vars(&[("text", "abcdefgh"), ("data", r#"{"a": 1}"#), ("body", "x\ny")]);
+
+        let result = replace_variables(template, &variables).unwrap();
+        assert_eq!(result, "friendly abcde... {\"a\":1}\n  x\n  y {{literal}}");
+    }
 
+    #[test]
+    fn test_errors_have_positions() {
+        let err = replace_variables("Hi\n  {{#if a}}x", &HashMap::new()).unwrap_err();
+        assert!(err.contains("line 2, column 3"), "{}", err);
 
+        let err = replace_variables("{{name | shout}}", &HashMap::new()).unwrap_err();
+        assert!(err.contains("Unknown filter 'shout'"));
 
+        let err = replace_variables("a\n{{b}} {{c}}", &HashMap::new()).unwrap_err();
+        assert_eq!(err, "Missing variables: b (line 2, column 1), c (line 2, column 7)");
+    }
 
+    #[test]
+    fn test_dynamic_variables() {
+        let template = "{{$date:%d.%m.%Y}} {{$uuid | upper}} {{name}}";
+        assert_eq!(replace_variables(template, &vars(&[("name", "x")])).unwrap(), "{{$date:%d.%m.%Y}} {{$uuid}} x");
+
+        let variables = vars(&[("name", "x"), ("$date:%d.%m.%Y", "01.02.2026"), ("$uuid", "ab")]);
+        assert_eq!(replace_variables(template, &variables).unwrap(), "01.02.2026 AB x");
+
+        assert_eq!(extract_dynamic_references(template).unwrap(), vec!["$date:%d.%m.%Y", "$uuid"]);
+        assert_eq!(extract_variables(template).unwrap().len(), 1);
+        assert!(validate_template("{{$today}}").unwrap_err().message.contains("Unknown variable '$today'"));
+    }
 
+    #[test]
+    fn test_extract_variables() {
+        let template = "{{name}} {{tone | default: \"calm\"}}\n{{#if notes}}{{notes}}{{/if}}\n{{#each items}}{{this}}{{/each}}";
+        let usages = extract_variables(template).unwrap();
+
+        let summary: Vec<(&str, bool)> = usages.iter().map(|u| (u.name.as_str(), u.optional)).collect();
+        assert_eq!(summary, vec![("name", false), ("tone", true), ("notes", true), ("items", true)]);
+        assert_eq!(usages[1].default.as_deref(), Some("calm"));
+        assert_eq!((usages[1].line, usages[1].column), (1, 10));
+    }
+
+    #[test]
+    fn test_plain_values_are_output_verbatim() {
+        let variables = vars(&[("data", "{\"b\":1, \"a\":2}"), ("list", "[1,  2]")]);
+
+        assert_eq!(replace_variables("{{data}}", &variables).unwrap(), "{\"b\":1, \"a\":2}");
+        assert_eq!(replace_variables("{{list}} {{list.1}}", &variables).unwrap(), "[1,  2] 2");
+        assert_eq!(replace_variables("{{list | json}}", &variables).unwrap(), "[1,2]");
+    }
+
+    #[test]
+    fn test_literal_braces() {
+        let variables = vars(&[("name", "Ada")]);
+        let cases = [
+            ("const tpl = `{{ some-thing }}`;", "const tpl = `{{ some-thing }}`;"),
+            ("Jinja: {{ items[0] }} {% if x %}", "Jinja: {{ items[0] }} {% if x %}"),
+            ("call {{ render(user) }} for {{name}}", "call {{ render(user) }} for Ada"),
+            ("unclosed {{ here, {{name}}", "unclosed {{ here, Ada"),
+            ("dangling {{", "dangling {{"),
+            ("{{}} and {{ }}", "{{}} and {{ }}"),
+        ];
+        for (template, expected) in cases {
+            assert_eq!(replace_variables(template, &variables).unwrap(), expected, "{}", template);
+        }
+
+        assert!(validate_template("{{#if a}}no close").is_err());
+        assert!(validate_template("{{#if some-thing}}x{{/if}}").is_err());
+        assert!(validate_template("{{url:https://example.com}}").unwrap_err().message.contains("URL variables"));
+    }
+}
@@ -1,5 +1,8 @@
 use crate::models::prompt::parse_markdown_prompt;
+use crate::models::variables::declared_variables;
 use crate::services::database::ProjectDatabase;
+use crate::services::lint::{self, LintContext, LintFinding, Severity};
+use crate::services::{file_variables, partials, template};
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
 use std::fs;
@@ -31,6 +34,9 @@ pub struct DependencyInfo {
     pub target_file: String,
     pub dependency_type: String,
     pub exists: bool,
+    /// Include chain leading back to the validated file (includes only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +45,9 @@ pub struct ValidationResult {
     pub errors: Vec<ValidationError>,
     pub warnings: Vec<ValidationWarning>,
     pub dependencies: Vec<DependencyInfo>,
+    /// Lint rule results, also listed in errors/warnings by severity
+    #[serde(default)]
+    pub findings: Vec<LintFinding>,
 }
 
 pub struct FileValidator {
@@ -61,6 +70,7 @@ impl FileValidator {
             errors: Vec::new(),
             warnings: Vec::new(),
             dependencies: Vec::new(),
+            findings: Vec::new(),
         };
 
         // 1. File existence check
@@ -99,7 +109,7 @@ impl FileValidator {
         };
 
         match self.validate_schema(&content) {
-            Ok(warnings) => result.warnings.extend(warnings),
+            Ok(()) => self.run_lint_rules(file_path, &content, &mut result),
             Err(e) => {
                 result.errors.push(ValidationError {
                     error_type: "schema_invalid".to_string(),
@@ -110,6 +120,21 @@ impl FileValidator {
             }
         }
 
+        // Template syntax ({{#if}}, filters, ...), positioned within each message
+        if let Ok(messages) = parse_markdown_prompt(&content) {
+            if let Err(e) = template::extract_message_variables(&messages) {
+                result.errors.push(ValidationError {
+                    error_type: "template_invalid".to_string(),
+                    message: e,
+                    file_path: Some(file_path.to_str().unwrap_or("").to_string()),
+                });
+                result.status = ValidationStatus::Invalid;
+            }
+        }
+
+        // Invalid variable declarations (undeclared and unused variables are lint rules)
+        self.check_variables(file_path, &content, &mut result);
+
         // 4. Dependency check
         if let Ok(deps) = self.check_dependencies(file_path) {
             result.dependencies = deps.clone();
@@ -122,6 +147,14 @@ impl FileValidator {
                     });
                     result.status = ValidationStatus::Invalid;
                 }
+                if let Some(cycle) = &dep.cycle {
+                    result.errors.push(ValidationError {
+                        error_type: "dependency_cycle".to_string(),
+                        message: format!("Include cycle: {}", cycle.join(" -> ")),
+                        file_path: Some(file_path.to_str().unwrap_or("").to_string()),
+                    });
+                    result.status = ValidationStatus::Invalid;
+                }
             }
         }
 
@@ -161,6 +194,7 @@ impl FileValidator {
                         }],
                         warnings: Vec::new(),
                         dependencies: Vec::new(),
+                        findings: Vec::new(),
                     };
                     results.push((file_meta.file_path, error_result));
                 }
@@ -200,71 +234,114 @@ impl FileValidator {
     }
 
     /// Validate Markdown schema
-    fn validate_schema(&self, content: &str) -> Result<Vec<ValidationWarning>, String> {
-        let mut warnings = Vec::new();
-
-        // Try to parse Markdown
+    fn validate_schema(&self, content: &str) -> Result<(), String> {
         match parse_markdown_prompt(content) {
-            Ok(messages) => {
-                // Check if at least one message exists
-                if messages.is_empty() {
-                    return Err("No valid messages found. Use ## System Message, ## User Message, or ## Assistant headings.".to_string());
-                }
+            Ok(messages) if messages.is_empty() => {
+                Err("No valid messages found. Use ## System Message, ## User Message, or ## Assistant headings.".to_string())
+            }
+            Ok(_) => Ok(()),
+            Err(e) => Err(format!("Markdown parsing failed: {}", e)),
+        }
+    }
 
-                // Warn if no System Message
-                if !messages.iter().any(|m| matches!(m.role, crate::models::prompt::MessageRole::System)) {
-                    warnings.push(ValidationWarning {
-                        warning_type: "no_system_message".to_string(),
-                        message: "No System Message found. Consider adding one for better results.".to_string(),
-                        suggestion: Some("Add a ## System Message section".to_string()),
-                    });
-                }
+    /// Run the workspace's lint rules (see services::lint)
+    fn run_lint_rules(&self, file_path: &Path, content: &str, result: &mut ValidationResult) {
+        let workspace = Path::new(&self.workspace_path);
+        let ctx = LintContext::new(content, Some(file_path), Some(workspace));
+        let findings = lint::lint(&ctx, &lint::load_settings(&self.project_db));
+
+        for finding in &findings {
+            let message = match finding.line {
+                Some(line) => format!("Line {}: {}", line, finding.message),
+                None => finding.message.clone(),
+            };
+            if finding.severity == Severity::Error {
+                result.errors.push(ValidationError {
+                    error_type: finding.rule_id.clone(),
+                    message,
+                    file_path: Some(file_path.to_str().unwrap_or("").to_string()),
+                });
+                result.status = ValidationStatus::Invalid;
+            } else {
+                result.warnings.push(ValidationWarning {
+                    warning_type: finding.rule_id.clone(),
+                    message,
+                    suggestion: finding
+                        .suggestion
+                        .clone()
+                        .or_else(|| finding.fix.as_ref().map(|fix| fix.description.clone())),
+                });
+            }
+        }
+        result.findings = findings;
+    }
 
-                // Warn if no User Message
-                if !messages.iter().any(|m| matches!(m.role, crate::models::prompt::MessageRole::User)) {
-                    warnings.push(ValidationWarning {
-                        warning_type: "no_user_message".to_string(),
-                        message: "No User Message found. Most prompts should have a user message.".to_string(),
-                        suggestion: Some("Add a ## User Message section".to_string()),
-                    });
-                }
+    /// Check variable declarations
+    fn check_variables(&self, file_path: &Path, content: &str, result: &mut ValidationResult) {
+        let file = Some(file_path.to_str().unwrap_or("").to_string());
 
-                // Check for very short content
-                for message in &messages {
-                    if message.content.trim().len() < 10 {
-                        warnings.push(ValidationWarning {
-                            warning_type: "short_content".to_string(),
-                            message: format!("Very short {} message (< 10 chars)", 
-                                match message.role {
-                                    crate::models::prompt::MessageRole::System => "System",
-                                    crate::models::prompt::MessageRole::User => "User",
-                                    crate::models::prompt::MessageRole::Assistant => "Assistant",
-                                }),
-                            suggestion: Some("Consider adding more context".to_string()),
-                        });
-                    }
-                }
-            }
+        let declarations = match declared_variables(content) {
+            Ok(declarations) => declarations,
             Err(e) => {
-                return Err(format!("Markdown parsing failed: {}", e));
+                result.errors.push(ValidationError {
+                    error_type: "variable_declaration_invalid".to_string(),
+                    message: e,
+                    file_path: file,
+                });
+                result.status = ValidationStatus::Invalid;
+                return;
             }
-        }
+        };
 
-        Ok(warnings)
+        for problem in declarations.iter().flat_map(|d| d.check()) {
+            result.errors.push(ValidationError {
+                error_type: "variable_declaration_invalid".to_string(),
+                message: problem,
+                file_path: file.clone(),
+            });
+            result.status = ValidationStatus::Invalid;
+        }
     }
 
     /// Check file dependencies
     fn check_dependencies(&self, file_path: &Path) -> Result<Vec<DependencyInfo>, String> {
         let mut dependencies = Vec::new();
 
-        let relative_path = file_path.strip_prefix(&self.workspace_path)
-            .map_err(|_| "Invalid file path")?
-            .to_str()
-            .ok_or("Invalid path encoding")?;
+        // Check includes ({{> path}}), which don't need database metadata
+        if let Ok(content) = fs::read_to_string(file_path) {
+            let workspace = Path::new(&self.workspace_path);
+            for include in partials::check_includes(&content, file_path, Some(workspace)) {
+                dependencies.push(DependencyInfo {
+                    target_file: include.target.display().to_string(),
+                    dependency_type: partials::INCLUDE_DEPENDENCY.to_string(),
+                    exists: include.exists,
+                    cycle: include.cycle.map(|chain| chain.iter().map(|p| p.display().to_string()).collect()),
+                });
+            }
 
-        // Get metadata
-        let metadata = self.project_db.get_prompt_metadata(relative_path)
-            .map_err(|e| format!("Failed to get metadata: {}", e))?;
+            // Files loaded by {{file:...}} variables
+            if let Ok(messages) = parse_markdown_prompt(&content) {
+                for path in file_variables::referenced_paths(&messages) {
+                    dependencies.push(DependencyInfo {
+                        exists: workspace.join(&path).is_file(),
+                        target_file: path,
+                        dependency_type: file_variables::FILE_VARIABLE_DEPENDENCY.to_string(),
+                        cycle: None,
+                    });
+                }
+            }
+        }
+
+        let relative_path = match file_path.strip_prefix(&self.workspace_path).ok().and_then(|p| p.to_str()) {
+            Some(path) => path,
+            None => return Ok(dependencies),
+        };
+
+        // Get metadata (files not tracked yet only have includes)
+        let metadata = match self.project_db.get_prompt_metadata(relative_path) {
+            Ok(metadata) => metadata,
+            Err(_) => return Ok(dependencies),
+        };
 
         // Check test_data_path
         if let Some(test_data) = metadata.test_data_path {
@@ -273,6 +350,7 @@ impl FileValidator {
                 target_file: test_data,
                 dependency_type: "test_data".to_string(),
                 exists: test_data_path.exists(),
+                cycle: None,
             });
         }
 
@@ -286,6 +364,7 @@ impl FileValidator {
                             target_file: ref_path.to_string(),
                             dependency_type: "evaluation".to_string(),
                             exists: eval_file_path.exists(),
+                            cycle: None,
                         });
                     }
                 }
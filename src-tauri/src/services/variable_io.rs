@@ -0,0 +1,378 @@
+use crate::services::database::StoredVariable;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableFileFormat {
+    Env,
+    Json,
+    Csv,
+}
+
+impl VariableFileFormat {
+    /// `.env`, `.env.local`, `vars.env` → Env; `.json` → Json; `.csv` → Csv
+    pub fn detect(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_lowercase();
+        if name.ends_with(".json") {
+            Some(VariableFileFormat::Json)
+        } else if name.ends_with(".csv") {
+            Some(VariableFileFormat::Csv)
+        } else if name.starts_with(".env") || name.ends_with(".env") {
+            Some(VariableFileFormat::Env)
+        } else {
+            None
+        }
+    }
+}
+
+/// What to do with an imported key that already exists
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Keep the existing value
+    #[default]
+    Skip,
+    Overwrite,
+    /// Import under a free name: `key_2`, `key_3`, ...
+    Rename,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MergeSummary {
+    pub added: Vec<String>,
+    pub overwritten: Vec<String>,
+    /// (imported key, key it was saved as)
+    pub renamed: Vec<(String, String)>,
+    pub skipped: Vec<String>,
+}
+
+/// Key/value pairs of a variables file, in file order
+pub fn parse(content: &str, format: VariableFileFormat) -> Result<Vec<(String, String)>, String> {
+    let pairs = match format {
+        VariableFileFormat::Env => parse_env(content)?,
+        VariableFileFormat::Json => parse_json(content)?,
+        VariableFileFormat::Csv => parse_csv(content)?,
+    };
+    if let Some((key, _)) = pairs.iter().find(|(key, _)| !valid_key(key)) {
+        return Err(format!("Invalid variable name '{}'", key));
+    }
+    Ok(pairs)
+}
+
+fn valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+fn parse_env(content: &str) -> Result<Vec<(String, String)>, String> {
+    let mut pairs = Vec::new();
+    let mut lines = content.lines().enumerate();
+    while let Some((number, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, raw) = line
+            .split_once('=')
+            .ok_or_else(|| format!("Line {}: expected KEY=value", number + 1))?;
+        let raw = raw.trim();
+
+        let value = if let Some(rest) = raw.strip_prefix('"') {
+            // Double quoted: escapes, may span lines
+            let mut quoted = rest.to_string();
+            while !closes_double_quote(&quoted) {
+                let (_, next) = lines
+                    .next()
+                    .ok_or_else(|| format!("Line {}: unterminated quoted value", number + 1))?;
+                quoted.push('\n');
+                quoted.push_str(next);
+            }
+            unescape_double_quoted(&quoted[..quoted.rfind('"').unwrap_or(quoted.len())])
+        } else if let Some(rest) = raw.strip_prefix('\'') {
+            rest.rfind('\'')
+                .map(|end| rest[..end].to_string())
+                .ok_or_else(|| format!("Line {}: unterminated quoted value", number + 1))?
+        } else {
+            // Unquoted: ` #` starts a comment
+            match raw.find(" #") {
+                Some(comment) => raw[..comment].trim_end().to_string(),
+                None => raw.to_string(),
+            }
+        };
+        pairs.push((key.trim().to_string(), value));
+    }
+    Ok(pairs)
+}
+
+/// Whether a double quoted value (opening quote removed) has its closing quote
+fn closes_double_quote(text: &str) -> bool {
+    let mut escaped = false;
+    for c in text.chars() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return true,
+            _ => escaped = false,
+        }
+    }
+    false
+}
+
+fn unescape_double_quoted(text: &str) -> String {
+    let mut value = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => value.push('\n'),
+            Some('t') => value.push('\t'),
+            Some('r') => value.push('\r'),
+            Some(other) => value.push(other),
+            None => value.push('\\'),
+        }
+    }
+    value
+}
+
+/// A flat JSON object; non-string values are kept as JSON text
+fn parse_json(content: &str) -> Result<Vec<(String, String)>, String> {
+    let value: serde_json::Value = serde_json::from_str(content).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let object = value.as_object().ok_or("Expected a JSON object of variable names to values")?;
+    Ok(object
+        .iter()
+        .map(|(key, value)| {
+            let text = match value {
+                serde_json::Value::String(s) => s.clone(),
+                serde_json::Value::Null => String::new(),
+                other => other.to_string(),
+            };
+            (key.clone(), text)
+        })
+        .collect())
+}
+
+/// RFC 4180 records (quoted fields may contain commas, quotes and newlines)
+pub(crate) fn csv_records(content: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                other => field.push(other),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            other => field.push(other),
+        }
+    }
+    if quoted {
+        return Err("Unterminated quoted CSV field".to_string());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Two columns: name, value; a `key,value` / `name,value` header row is skipped
+fn parse_csv(content: &str) -> Result<Vec<(String, String)>, String> {
+    let mut pairs = Vec::new();
+    for (index, record) in csv_records(content)?.into_iter().enumerate() {
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        if record.len() != 2 {
+            return Err(format!("Row {}: expected 2 columns, found {}", index + 1, record.len()));
+        }
+        let key = record[0].trim().to_string();
+        let is_header = index == 0
+            && matches!(key.to_lowercase().as_str(), "key" | "name" | "variable")
+            && record[1].trim().eq_ignore_ascii_case("value");
+        if !is_header {
+            pairs.push((key, record[1].clone()));
+        }
+    }
+    Ok(pairs)
+}
+
+/// Write key/value pairs in a format
+pub fn serialize(pairs: &[(String, String)], format: VariableFileFormat) -> Result<String, String> {
+    match format {
+        VariableFileFormat::Env => Ok(pairs
+            .iter()
+            .map(|(key, value)| format!("{}={}\n", key, env_value(value)))
+            .collect()),
+        VariableFileFormat::Json => {
+            let object: serde_json::Map<String, serde_json::Value> = pairs
+                .iter()
+                .map(|(key, value)| (key.clone(), serde_json::Value::String(value.clone())))
+                .collect();
+            serde_json::to_string_pretty(&object)
+                .map(|json| json + "\n")
+                .map_err(|e| format!("Failed to serialize variables: {}", e))
+        }
+        VariableFileFormat::Csv => {
+            let mut csv = String::from("key,value\n");
+            for (key, value) in pairs {
+                csv.push_str(&format!("{},{}\n", csv_field(key), csv_field(value)));
+            }
+            Ok(csv)
+        }
+    }
+}
+
+fn env_value(value: &str) -> String {
+    let plain = !value.is_empty()
+        && !value.starts_with(['"', '\''])
+        && !value.contains(|c: char| c.is_whitespace() || c == '#' || c == '\\');
+    if plain {
+        return value.to_string();
+    }
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t");
+    format!("\"{}\"", escaped)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) || value.trim() != value {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Merge imported pairs into the existing variables
+/// Overwritten variables keep their id and secret flag
+pub fn merge(
+    existing: &mut Vec<StoredVariable>,
+    imported: Vec<(String, String)>,
+    strategy: ConflictStrategy,
+) -> MergeSummary {
+    let mut summary = MergeSummary::default();
+    for (key, value) in imported {
+        let position = existing.iter().position(|v| v.key == key);
+        match (position, strategy) {
+            (None, _) => {
+                summary.added.push(key.clone());
+                existing.push(StoredVariable { id: String::new(), key, value, secret: false });
+            }
+            (Some(_), ConflictStrategy::Skip) => summary.skipped.push(key),
+            (Some(index), ConflictStrategy::Overwrite) => {
+                existing[index].value = value;
+                summary.overwritten.push(key);
+            }
+            (Some(_), ConflictStrategy::Rename) => {
+                let mut n = 2;
+                let mut renamed = format!("{}_{}", key, n);
+                while existing.iter().any(|v| v.key == renamed) {
+                    n += 1;
+                    renamed = format!("{}_{}", key, n);
+                }
+                existing.push(StoredVariable { id: String::new(), key: renamed.clone(), value, secret: false });
+                summary.renamed.push((key, renamed));
+            }
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+        items.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_parse_formats() {
+        let env = "# comment\nexport API_URL=https://x.dev # prod\nGREETING=\"Hello\\n\\\"you\\\"\"\nRAW='a # b'\nMULTI=\"line 1\nline 2\"\n";
+        assert_eq!(
+            parse(env, VariableFileFormat::Env).unwrap(),
+            pairs(&[
+                ("API_URL", "https://x.dev"),
+                ("GREETING", "Hello\n\"you\""),
+                ("RAW", "a # b"),
+                ("MULTI", "line 1\nline 2"),
+            ])
+        );
+
+        let json = r#"{"company": "Acme", "limit": 3, "tags": ["a"]}"#;
+        assert_eq!(
+            parse(json, VariableFileFormat::Json).unwrap(),
+            pairs(&[("company", "Acme"), ("limit", "3"), ("tags", "[\"a\"]")])
+        );
+
+        let csv = "key,value\ncompany,\"Acme, Inc.\"\nquote,\"say \"\"hi\"\"\nnow\"\n";
+        assert_eq!(
+            parse(csv, VariableFileFormat::Csv).unwrap(),
+            pairs(&[("company", "Acme, Inc."), ("quote", "say \"hi\"\nnow")])
+        );
+        assert!(parse("a,b,c\n", VariableFileFormat::Csv).is_err());
+        assert!(parse("1BAD=x\n", VariableFileFormat::Env).is_err());
+    }
+
+    #[test]
+    fn test_serialize_round_trip() {
+        let values = pairs(&[("plain", "value"), ("spaced", " a \"b\" #c\nd"), ("empty", "")]);
+        for format in [VariableFileFormat::Env, VariableFileFormat::Json, VariableFileFormat::Csv] {
+            let text = serialize(&values, format).unwrap();
+            let mut parsed = parse(&text, format).unwrap();
+            let mut expected = values.clone();
+            parsed.sort();
+            expected.sort();
+            assert_eq!(parsed, expected, "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_merge_strategies() {
+        let existing = vec![
+            StoredVariable { id: "1".to_string(), key: "company".to_string(), value: "Acme".to_string(), secret: true },
+            StoredVariable { id: "2".to_string(), key: "company_2".to_string(), value: "Other".to_string(), secret: false },
+        ];
+        let imported = pairs(&[("company", "Globex"), ("region", "eu")]);
+
+        let mut skip = existing.clone();
+        let summary = merge(&mut skip, imported.clone(), ConflictStrategy::Skip);
+        assert_eq!((summary.added.len(), summary.skipped.len()), (1, 1));
+        assert_eq!(skip[0].value, "Acme");
+
+        let mut overwrite = existing.clone();
+        merge(&mut overwrite, imported.clone(), ConflictStrategy::Overwrite);
+        assert_eq!(overwrite[0].value, "Globex");
+        assert!(overwrite[0].secret);
+
+        let mut rename = existing.clone();
+        let summary = merge(&mut rename, imported, ConflictStrategy::Rename);
+        assert_eq!(summary.renamed, vec![("company".to_string(), "company_3".to_string())]);
+        assert_eq!(rename.len(), 4);
+    }
+}
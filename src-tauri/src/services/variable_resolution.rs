@@ -0,0 +1,202 @@
+use crate::services::database::{AppDatabase, ProjectDatabase, StoredVariable};
+use crate::services::secret_variables::SECRET_MASK;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// Where a variable value comes from, highest priority first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableSource {
+    Manual,
+    Profile,
+    Project,
+    Global,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolvedVariable {
+    pub name: String,
+    /// None when no source defines the variable
+    pub value: Option<String>,
+    pub source: Option<VariableSource>,
+    /// The value is masked; it is substituted at execution time
+    pub secret: bool,
+    /// Lower priority sources that define the variable too
+    pub overridden: Vec<VariableSource>,
+}
+
+/// Variable values of every source, for one workspace and profile
+/// Secret variables hold the mask; their keychain ids are kept in `secrets`
+#[derive(Debug, Default)]
+pub struct VariableLayers {
+    pub manual: HashMap<String, String>,
+    pub profile: HashMap<String, String>,
+    pub project: HashMap<String, String>,
+    pub global: HashMap<String, String>,
+    pub secrets: HashMap<(VariableSource, String), String>,
+}
+
+impl VariableLayers {
+    /// Load global, project and profile variables; empty or masked manual inputs count as not given
+    pub fn load(
+        app_db: &AppDatabase,
+        project_db: Option<&ProjectDatabase>,
+        profile: Option<&str>,
+        manual: HashMap<String, String>,
+    ) -> Result<Self, String> {
+        let global = app_db
+            .get_global_variables()
+            .map_err(|e| format!("Failed to load global variables: {}", e))?;
+
+        let (project, profile) = match project_db {
+            Some(db) => {
+                let project = db
+                    .get_project_variables()
+                    .map_err(|e| format!("Failed to load project variables: {}", e))?;
+                let profile = match profile {
+                    Some(name) => db
+                        .get_profile_variables(name)
+                        .map_err(|e| format!("Failed to load profile '{}': {}", name, e))?,
+                    None => Vec::new(),
+                };
+                (project, profile)
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+
+        let mut layers = VariableLayers {
+            manual: manual
+                .into_iter()
+                .filter(|(_, value)| !value.is_empty() && value != SECRET_MASK)
+                .collect(),
+            ..Default::default()
+        };
+        layers.add(VariableSource::Profile, profile);
+        layers.add(VariableSource::Project, project);
+        layers.add(VariableSource::Global, global);
+        Ok(layers)
+    }
+
+    fn add(&mut self, source: VariableSource, variables: Vec<StoredVariable>) {
+        for variable in variables {
+            let value = if variable.secret {
+                self.secrets.insert((source, variable.key.clone()), variable.id);
+                SECRET_MASK.to_string()
+            } else {
+                variable.value
+            };
+            let values = match source {
+                VariableSource::Manual => &mut self.manual,
+                VariableSource::Profile => &mut self.profile,
+                VariableSource::Project => &mut self.project,
+                VariableSource::Global => &mut self.global,
+            };
+            values.insert(variable.key, value);
+        }
+    }
+
+    /// Keychain id of a variable whose resolved value is a secret
+    pub fn secret_id(&self, name: &str) -> Option<&str> {
+        let source = self.resolve(name).source?;
+        self.secrets.get(&(source, name.to_string())).map(|id| id.as_str())
+    }
+
+    fn layers(&self) -> [(VariableSource, &HashMap<String, String>); 4] {
+        [
+            (VariableSource::Manual, &self.manual),
+            (VariableSource::Profile, &self.profile),
+            (VariableSource::Project, &self.project),
+            (VariableSource::Global, &self.global),
+        ]
+    }
+
+    pub fn resolve(&self, name: &str) -> ResolvedVariable {
+        let mut resolved = ResolvedVariable {
+            name: name.to_string(),
+            value: None,
+            source: None,
+            secret: false,
+            overridden: Vec::new(),
+        };
+        for (source, values) in self.layers() {
+            if let Some(value) = values.get(name) {
+                if resolved.source.is_none() {
+                    resolved.value = Some(value.clone());
+                    resolved.source = Some(source);
+                    resolved.secret = self.secrets.contains_key(&(source, name.to_string()));
+                } else {
+                    resolved.overridden.push(source);
+                }
+            }
+        }
+        resolved
+    }
+
+    /// Resolve the given names, or every variable defined by a source
+    pub fn resolve_all(&self, names: Option<&[String]>) -> Vec<ResolvedVariable> {
+        let names: BTreeSet<&str> = match names {
+            Some(names) => names.iter().map(|n| n.as_str()).collect(),
+            None => self.layers().iter().flat_map(|(_, values)| values.keys().map(|k| k.as_str())).collect(),
+        };
+        names.into_iter().map(|name| self.resolve(name)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_resolution_order() {
+        let layers = VariableLayers {
+            manual: values(&[("tone", "formal")]),
+            profile: values(&[("tone", "casual"), ("customer", "Acme")]),
+            project: values(&[("customer", "Internal"), ("product", "Vibe")]),
+            global: values(&[("product", "Base"), ("language", "en")]),
+            secrets: HashMap::new(),
+        };
+
+        let tone = layers.resolve("tone");
+        assert_eq!(tone.value.as_deref(), Some("formal"));
+        assert_eq!(tone.source, Some(VariableSource::Manual));
+        assert_eq!(tone.overridden, vec![VariableSource::Profile]);
+
+        let resolved = layers.resolve_all(None);
+        let names: Vec<&str> = resolved.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["customer", "language", "product", "tone"]);
+        assert_eq!(resolved[0].source, Some(VariableSource::Profile));
+        assert_eq!(resolved[2].value.as_deref(), Some("Vibe"));
+        assert_eq!(resolved[2].overridden, vec![VariableSource::Global]);
+
+        let missing = layers.resolve("unknown");
+        assert!(missing.value.is_none() && missing.source.is_none());
+    }
+
+    #[test]
+    fn test_secret_layers() {
+        let variable = |key: &str, value: &str, secret| StoredVariable {
+            id: format!("id-{}", key),
+            key: key.to_string(),
+            value: value.to_string(),
+            secret,
+        };
+        let mut layers = VariableLayers {
+            manual: values(&[("customer", "Manual")]),
+            ..Default::default()
+        };
+        layers.add(VariableSource::Project, vec![variable("token", "", true), variable("customer", "", true)]);
+        layers.add(VariableSource::Global, vec![variable("token", "plain", false)]);
+
+        let token = layers.resolve("token");
+        assert_eq!(token.value.as_deref(), Some(SECRET_MASK));
+        assert!(token.secret);
+        assert_eq!(layers.secret_id("token"), Some("id-token"));
+        // A manual value overrides the secret
+        assert!(!layers.resolve("customer").secret);
+        assert_eq!(layers.secret_id("customer"), None);
+    }
+}
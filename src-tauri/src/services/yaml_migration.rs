@@ -0,0 +1,409 @@
+use crate::models::frontmatter::parse_frontmatter;
+use crate::models::prompt::{parse_markdown_prompt, PromptRuntime};
+use crate::services::converters::{to_vibe_markdown, Conversion};
+use crate::services::database::{LLMProviderConfig, ProjectDatabase, PromptFileConfig, PromptMigration};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LEGACY_EXTENSIONS: &[&str] = &[".vibe.yaml", ".vibe.yml"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MigrationStatus {
+    /// Dry run: would be migrated
+    Ready,
+    Migrated,
+    /// The .vibe.md file (or, when undoing, the .vibe.yaml file) already exists
+    Conflict,
+    Failed,
+    Restored,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationItem {
+    pub source_path: String,
+    pub target_path: String,
+    pub status: MigrationStatus,
+    /// What the Markdown file can't express
+    pub lossy: Vec<String>,
+    pub error: Option<String>,
+    /// Converted content (dry run only)
+    pub preview: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    /// Id to pass to undo; None for a dry run or when nothing was migrated
+    pub batch_id: Option<String>,
+    pub dry_run: bool,
+    pub items: Vec<MigrationItem>,
+}
+
+/// Every .vibe.yaml / .vibe.yml file under `dir`, skipping hidden entries
+pub fn legacy_prompts(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    collect_legacy_prompts(dir, &mut files);
+    files.sort();
+    files
+}
+
+fn collect_legacy_prompts(dir: &Path, files: &mut Vec<PathBuf>) {
+    if let Ok(entries) = fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            if path.is_dir() {
+                collect_legacy_prompts(&path, files);
+            } else if LEGACY_EXTENSIONS.iter().any(|ext| name.ends_with(ext)) {
+                files.push(path);
+            }
+        }
+    }
+}
+
+/// `prompts/support.vibe.yaml` → `prompts/support.vibe.md`
+pub fn target_path(source: &Path) -> PathBuf {
+    let name = source.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+    let stem = LEGACY_EXTENSIONS
+        .iter()
+        .find_map(|ext| name.strip_suffix(ext))
+        .unwrap_or(name);
+    source.with_file_name(format!("{}.vibe.md", stem))
+}
+
+/// Convert a legacy YAML prompt to .vibe.md
+/// `config.provider` becomes a provider_ref to a provider config of that type, when there is one
+pub fn convert(content: &str, providers: &[LLMProviderConfig]) -> Result<Conversion<String>, String> {
+    let prompt: PromptRuntime = serde_yaml::from_str(content).map_err(|e| format!("YAML parse error: {}", e))?;
+    let mut markdown = to_vibe_markdown(&prompt, providers)?;
+    if prompt.schema != "v1" {
+        markdown.lossy.push(format!("Schema '{}' is not recorded in Markdown", prompt.schema));
+    }
+
+    // The Markdown must give back the same messages
+    let messages = parse_markdown_prompt(&markdown.value)?;
+    let unchanged = messages.len() == prompt.messages.len()
+        && messages.iter().zip(&prompt.messages).all(|(a, b)| a.role == b.role);
+    if !unchanged {
+        return Err("Converted messages differ from the original".to_string());
+    }
+
+    Ok(markdown)
+}
+
+fn now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64
+}
+
+/// Convert every legacy YAML prompt of a workspace to .vibe.md
+/// The YAML file is removed; its history, metadata and executions move to the new file
+/// Existing .vibe.md files are never overwritten
+pub fn migrate_workspace(
+    db: &ProjectDatabase,
+    workspace: &Path,
+    providers: &[LLMProviderConfig],
+    dry_run: bool,
+) -> Result<MigrationReport, String> {
+    let batch_id = uuid::Uuid::new_v4().to_string();
+    let mut items = Vec::new();
+
+    for source in legacy_prompts(workspace) {
+        let target = target_path(&source);
+        let source_path = source.display().to_string();
+        let target_path = target.display().to_string();
+        let mut item = MigrationItem {
+            source_path: source_path.clone(),
+            target_path: target_path.clone(),
+            status: MigrationStatus::Ready,
+            lossy: Vec::new(),
+            error: None,
+            preview: None,
+        };
+
+        if target.exists() {
+            item.status = MigrationStatus::Conflict;
+            item.error = Some(format!("{} already exists", target_path));
+            items.push(item);
+            continue;
+        }
+
+        let source_content = match fs::read_to_string(&source) {
+            Ok(content) => content,
+            Err(e) => {
+                item.status = MigrationStatus::Failed;
+                item.error = Some(format!("Failed to read file: {}", e));
+                items.push(item);
+                continue;
+            }
+        };
+        let markdown = match convert(&source_content, providers) {
+            Ok(markdown) => markdown,
+            Err(e) => {
+                item.status = MigrationStatus::Failed;
+                item.error = Some(e);
+                items.push(item);
+                continue;
+            }
+        };
+        item.lossy = markdown.lossy;
+
+        if dry_run {
+            item.preview = Some(markdown.value);
+            items.push(item);
+            continue;
+        }
+
+        if let Err(e) = fs::write(&target, &markdown.value) {
+            item.status = MigrationStatus::Failed;
+            item.error = Some(format!("Failed to write {}: {}", target_path, e));
+            items.push(item);
+            continue;
+        }
+
+        // The records move in one transaction; the written file goes away if they can't
+        let recorded = db.in_transaction(|| {
+            let source_config = db
+                .get_prompt_file_config(&source_path)
+                .map_err(|e| format!("Failed to read file record: {}", e))?
+                .unwrap_or_default();
+
+            // Keep the last YAML version in the history that moves with the file
+            db.save_file_history(&source_path, &source_content)
+                .map_err(|e| format!("Failed to save history: {}", e))?;
+            db.move_file_records(&source_path, &target_path)
+                .map_err(|e| format!("Failed to move file records: {}", e))?;
+            let (history_id, _) = db
+                .snapshot_file_history(&target_path, &markdown.value)
+                .map_err(|e| format!("Failed to save history: {}", e))?;
+
+            // Config goes to prompt_files, as for any file with frontmatter
+            if let (Some(frontmatter), _) = parse_frontmatter(&markdown.value)? {
+                db.ensure_prompt_file(&target_path)
+                    .map_err(|e| format!("Failed to ensure file record: {}", e))?;
+                db.apply_prompt_frontmatter(&target_path, &frontmatter)
+                    .map_err(|e| format!("Failed to sync frontmatter: {}", e))?;
+            }
+
+            db.save_prompt_migration(&PromptMigration {
+                id: uuid::Uuid::new_v4().to_string(),
+                batch_id: batch_id.clone(),
+                source_path: source_path.clone(),
+                target_path: target_path.clone(),
+                source_content: source_content.clone(),
+                target_content: markdown.value.clone(),
+                target_history_id: Some(history_id),
+                source_config: serde_json::to_string(&source_config).ok(),
+                created_at: now(),
+                undone_at: None,
+            })
+            .map_err(|e| format!("Failed to record migration: {}", e))
+        });
+        if let Err(e) = recorded {
+            fs::remove_file(&target).ok();
+            item.status = MigrationStatus::Failed;
+            item.error = Some(e);
+            items.push(item);
+            continue;
+        }
+
+        // The YAML content is kept in prompt_migrations, so undo works even if this fails
+        match fs::remove_file(&source) {
+            Ok(()) => item.status = MigrationStatus::Migrated,
+            Err(e) => {
+                item.status = MigrationStatus::Failed;
+                item.error = Some(format!("Migrated, but failed to remove {}: {}", source_path, e));
+            }
+        }
+        items.push(item);
+    }
+
+    // Also counts files recorded as migrated whose YAML could not be removed
+    let migrated = !db
+        .get_prompt_migrations(Some(&batch_id))
+        .map_err(|e| format!("Failed to load migrations: {}", e))?
+        .is_empty();
+    println!(
+        "🔄 {} {} legacy prompts",
+        if dry_run { "Checked" } else { "Migrated" },
+        items.iter().filter(|i| matches!(i.status, MigrationStatus::Ready | MigrationStatus::Migrated)).count()
+    );
+    Ok(MigrationReport {
+        batch_id: migrated.then_some(batch_id),
+        dry_run,
+        items,
+    })
+}
+
+/// Restore the YAML files of a migration batch (the latest one when none is given)
+/// Files edited since the migration are left alone
+pub fn undo_migration(db: &ProjectDatabase, batch_id: Option<&str>) -> Result<MigrationReport, String> {
+    let migrations = db
+        .get_prompt_migrations(batch_id)
+        .map_err(|e| format!("Failed to load migrations: {}", e))?;
+    if migrations.is_empty() {
+        return Err("No migration to undo".to_string());
+    }
+
+    let mut items = Vec::new();
+    for migration in &migrations {
+        let mut item = MigrationItem {
+            source_path: migration.source_path.clone(),
+            target_path: migration.target_path.clone(),
+            status: MigrationStatus::Restored,
+            lossy: Vec::new(),
+            error: None,
+            preview: None,
+        };
+
+        let target = Path::new(&migration.target_path);
+        let source = Path::new(&migration.source_path);
+        if source.exists() {
+            item.status = MigrationStatus::Conflict;
+            item.error = Some(format!("{} already exists", migration.source_path));
+        } else if fs::read_to_string(target).is_ok_and(|content| content != migration.target_content) {
+            item.status = MigrationStatus::Failed;
+            item.error = Some(format!("{} was edited after the migration", migration.target_path));
+        }
+        if item.status != MigrationStatus::Restored {
+            items.push(item);
+            continue;
+        }
+
+        if let Err(e) = fs::write(source, &migration.source_content) {
+            item.status = MigrationStatus::Failed;
+            item.error = Some(format!("Failed to write {}: {}", migration.source_path, e));
+            items.push(item);
+            continue;
+        }
+
+        let restored = db.in_transaction(|| {
+            if let Some(history_id) = &migration.target_history_id {
+                db.delete_history_entry(history_id)
+                    .map_err(|e| format!("Failed to update history: {}", e))?;
+            }
+            db.move_file_records(&migration.target_path, &migration.source_path)
+                .map_err(|e| format!("Failed to move file records: {}", e))?;
+
+            // Put back the config the frontmatter sync replaced
+            if let Some(config) = migration.source_config.as_deref() {
+                let config: PromptFileConfig = serde_json::from_str(config)
+                    .map_err(|e| format!("Invalid saved config: {}", e))?;
+                db.restore_prompt_file_config(&migration.source_path, &config)
+                    .map_err(|e| format!("Failed to restore file record: {}", e))?;
+            }
+
+            db.mark_prompt_migration_undone(&migration.id)
+                .map_err(|e| format!("Failed to record undo: {}", e))
+        });
+        if let Err(e) = restored {
+            fs::remove_file(source).ok();
+            item.status = MigrationStatus::Failed;
+            item.error = Some(e);
+            items.push(item);
+            continue;
+        }
+
+        if target.exists() {
+            if let Err(e) = fs::remove_file(target) {
+                item.error = Some(format!("Restored, but failed to remove {}: {}", migration.target_path, e));
+            }
+        }
+        items.push(item);
+    }
+
+    println!("↩️  Restored {} legacy prompts", items.iter().filter(|i| i.status == MigrationStatus::Restored).count());
+    Ok(MigrationReport {
+        batch_id: Some(migrations[0].batch_id.clone()),
+        dry_run: false,
+        items,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = "schema: v1\nname: Support\ndescription: Replies\nconfig:\n  provider: anthropic\n  model: claude-3-5-sonnet\n  parameters:\n    temperature: 0.3\nmessages:\n  - role: system\n    content: You are helpful.\n  - role: user\n    content: \"{{question}}\"\n";
+
+    fn provider(name: &str, provider: &str, is_default: bool) -> LLMProviderConfig {
+        LLMProviderConfig {
+            id: name.to_string(),
+            name: name.to_string(),
+            provider: provider.to_string(),
+            model: String::new(),
+            base_url: None,
+            api_key: None,
+            api_key_source: "direct".to_string(),
+            api_key_ref: None,
+            parameters: None,
+            enabled: true,
+            enabled_models: None,
+            is_default,
+        }
+    }
+
+    #[test]
+    fn test_convert() {
+        let providers = vec![provider("openai_default", "openai", true), provider("claude", "anthropic", false)];
+        let markdown = convert(YAML, &providers).unwrap();
+
+        assert!(markdown.lossy.is_empty(), "{:?}", markdown.lossy);
+        assert!(markdown.value.contains("provider_ref: claude\n"));
+        assert!(markdown.value.contains("model: claude-3-5-sonnet\n"));
+        assert!(markdown.value.contains("# Support\n\n## System Message\n\nYou are helpful.\n"));
+
+        let markdown = convert(YAML, &[]).unwrap();
+        assert_eq!(markdown.lossy.len(), 1);
+    }
+
+    #[test]
+    fn test_migrate_and_undo() {
+        let workspace = std::env::temp_dir().join(format!("vibebase-migration-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(workspace.join("prompts")).unwrap();
+        let source = workspace.join("prompts/support.vibe.yaml");
+        let source_path = source.display().to_string();
+        fs::write(&source, YAML).unwrap();
+        fs::write(workspace.join("prompts/taken.vibe.yaml"), YAML).unwrap();
+        fs::write(workspace.join("prompts/taken.vibe.md"), "## User Message\nkeep").unwrap();
+
+        let db = ProjectDatabase::new(&workspace).unwrap();
+        db.save_file_history(&source_path, "schema: v1\nold").unwrap();
+        let file_id = db.ensure_prompt_file(&source_path).unwrap();
+        let config = PromptFileConfig { tags: Some(r#"["support"]"#.to_string()), ..Default::default() };
+        db.restore_prompt_file_config(&source_path, &config).unwrap();
+
+        let dry_run = migrate_workspace(&db, &workspace, &[], true).unwrap();
+        assert_eq!(dry_run.batch_id, None);
+        assert_eq!(dry_run.items[0].status, MigrationStatus::Ready);
+        assert_eq!(dry_run.items[1].status, MigrationStatus::Conflict);
+        assert!(source.exists());
+
+        let report = migrate_workspace(&db, &workspace, &[], false).unwrap();
+        let target = workspace.join("prompts/support.vibe.md");
+        let target_path = target.display().to_string();
+        assert_eq!(report.items[0].status, MigrationStatus::Migrated);
+        assert!(!source.exists() && target.exists());
+        assert_eq!(db.get_file_history(&target_path, 10).unwrap().len(), 3);
+        assert_eq!(db.find_prompt_file_id(&target_path).unwrap(), Some(file_id.clone()));
+        assert_eq!(db.get_prompt_metadata(&target_path).unwrap().model_override.as_deref(), Some("claude-3-5-sonnet"));
+
+        let undo = undo_migration(&db, None).unwrap();
+        assert_eq!(undo.batch_id, report.batch_id);
+        assert_eq!(undo.items[0].status, MigrationStatus::Restored);
+        assert_eq!(fs::read_to_string(&source).unwrap(), YAML);
+        assert!(!target.exists());
+        assert_eq!(db.get_file_history(&source_path, 10).unwrap().len(), 2);
+        assert_eq!(db.find_prompt_file_id(&source_path).unwrap(), Some(file_id));
+        let restored = db.get_prompt_file_config(&source_path).unwrap().unwrap();
+        assert_eq!(restored.model_override, None);
+        assert_eq!(restored.tags, config.tags);
+        assert!(undo_migration(&db, None).is_err());
+
+        fs::remove_dir_all(&workspace).ok();
+    }
+}
@@ -59,6 +59,53 @@ CREATE TABLE IF NOT EXISTS global_variables (
 
 CREATE INDEX IF NOT EXISTS idx_global_variables_key ON global_variables(key);
 
+-- Model Pricing (USD per 1M tokens)
+-- model = '*' acts as a provider-wide fallback (e.g. local providers)
+CREATE TABLE IF NOT EXISTS model_pricing (
+    id TEXT PRIMARY KEY,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    input_price REAL NOT NULL,
+    output_price REAL NOT NULL,
+    cache_read_price REAL,
+    cache_write_price REAL,
+    source TEXT NOT NULL,              -- 'default' | 'user' | 'openrouter'
+    version INTEGER NOT NULL DEFAULT 1, -- Bumped on every price change
+    updated_at INTEGER NOT NULL,
+    UNIQUE(provider, model)
+);
+
+CREATE INDEX IF NOT EXISTS idx_model_pricing_lookup ON model_pricing(provider, model);
+
+-- Spending Budgets
+-- scope_ref is the workspace path (scope = 'workspace') or provider config name (scope = 'provider')
+CREATE TABLE IF NOT EXISTS spending_budgets (
+    id TEXT PRIMARY KEY,
+    scope TEXT NOT NULL,               -- 'workspace' | 'provider'
+    scope_ref TEXT NOT NULL,
+    period TEXT NOT NULL,              -- 'daily' | 'monthly'
+    soft_limit_usd REAL,               -- Warn when reached
+    hard_limit_usd REAL,               -- Refuse execution when reached
+    enabled INTEGER DEFAULT 1,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    UNIQUE(scope, scope_ref, period)
+);
+
+-- Spending Ledger (cost of every execution, across all workspaces)
+CREATE TABLE IF NOT EXISTS spending_ledger (
+    id TEXT PRIMARY KEY,               -- Execution ID
+    workspace_path TEXT,
+    provider_name TEXT,
+    provider TEXT NOT NULL,
+    model TEXT NOT NULL,
+    cost_usd REAL NOT NULL,
+    timestamp INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_spending_ledger_workspace ON spending_ledger(workspace_path, timestamp);
+CREATE INDEX IF NOT EXISTS idx_spending_ledger_provider ON spending_ledger(provider_name, timestamp);
+
 -- Schema Version
 CREATE TABLE IF NOT EXISTS schema_version (
     version TEXT PRIMARY KEY,
@@ -59,6 +59,18 @@ CREATE TABLE IF NOT EXISTS execution_history (
     tokens_input INTEGER NOT NULL,
     tokens_output INTEGER NOT NULL,
     cost_usd REAL NOT NULL,
+    pricing_version INTEGER,
+    pricing_id TEXT,                   -- model_pricing entry, at pricing_version (v1.19.0)
+    
+    -- Rendered request (v1.5.0)
+    rendered_messages TEXT,            -- JSON array: [{"role": "...", "content": "..."}, ...]
+    parameters TEXT,                   -- JSON: {"temperature": ..., ...}
+    run_mode TEXT DEFAULT 'single',    -- 'single' | 'arena'
+    file_history_id TEXT,              -- Prompt version executed (v1.6.0)
+    input_files TEXT,                  -- JSON: [{"path": "...", "lines": ..., "sha256": "...", "bytes": ...}] (v1.8.0)
+    dynamic_variables TEXT,            -- JSON: {"$now": "...", ...} values resolved at execution time (v1.14.0)
+    prompt_messages TEXT,              -- JSON: [{"role": "...", "content": "..."}] before rendering, partials expanded (v1.20.0)
+    variable_profile TEXT,             -- Variable profile that supplied secret values (v1.21.0)
     
     -- Context
     timestamp INTEGER NOT NULL,
@@ -70,6 +82,24 @@ CREATE TABLE IF NOT EXISTS execution_history (
 
 CREATE INDEX IF NOT EXISTS idx_execution_prompt ON execution_history(prompt_file_id);
 CREATE INDEX IF NOT EXISTS idx_execution_timestamp ON execution_history(timestamp DESC);
+CREATE INDEX IF NOT EXISTS idx_execution_model ON execution_history(model);
+CREATE INDEX IF NOT EXISTS idx_execution_branch ON execution_history(git_branch);
+
+-- Execution Replays (historical execution re-run, paired with its source)
+CREATE TABLE IF NOT EXISTS execution_replays (
+    id TEXT PRIMARY KEY,
+    batch_id TEXT NOT NULL,
+    source_execution_id TEXT NOT NULL,
+    replay_execution_id TEXT NOT NULL,
+    diff_summary TEXT NOT NULL,        -- JSON: output/tokens/latency/cost differences
+    created_at INTEGER NOT NULL,
+    
+    FOREIGN KEY (source_execution_id) REFERENCES execution_history(id) ON DELETE CASCADE,
+    FOREIGN KEY (replay_execution_id) REFERENCES execution_history(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_execution_replays_batch ON execution_replays(batch_id);
+CREATE INDEX IF NOT EXISTS idx_execution_replays_source ON execution_replays(source_execution_id);
 
 -- Evaluation Results
 CREATE TABLE IF NOT EXISTS evaluation_results (
@@ -82,6 +112,14 @@ CREATE TABLE IF NOT EXISTS evaluation_results (
     reasoning TEXT,
     passed INTEGER,
     timestamp INTEGER NOT NULL,
+    evaluator_type TEXT,               -- 'assertion' | 'rule' | 'judge' | 'aggregate' (v1.17.0)
+    prompt_file_id TEXT,               -- Prompt, model and matrix run evaluated, for pass rates (v1.17.0)
+    model TEXT,
+    matrix_run_id TEXT,                -- Set when execution_id is a test_results cell (v1.17.0)
+    judge_model TEXT,                  -- Judge call of LLM evaluators, not counted in the execution's cost (v1.18.0)
+    judge_tokens_input INTEGER,
+    judge_tokens_output INTEGER,
+    judge_cost_usd REAL,
     
     FOREIGN KEY (execution_id) REFERENCES execution_history(id) ON DELETE CASCADE
 );
@@ -109,7 +147,8 @@ CREATE TABLE IF NOT EXISTS test_datasets (
     file_path TEXT NOT NULL,
     format TEXT NOT NULL,
     row_count INTEGER,
-    columns TEXT,
+    columns TEXT,                      -- JSON array of column names
+    content_hash TEXT,                 -- SHA-256 of the indexed file content (v1.15.0)
     created_at INTEGER NOT NULL,
     updated_at INTEGER NOT NULL
 );
@@ -131,6 +170,9 @@ CREATE TABLE IF NOT EXISTS test_results (
     tokens_output INTEGER NOT NULL,
     cost_usd REAL NOT NULL,
     timestamp INTEGER NOT NULL,
+    matrix_run_id TEXT,                -- Matrix run that produced the cell (v1.16.0)
+    llm_provider_name TEXT,            -- Provider config the model ran on (v1.16.0)
+    error TEXT,                        -- Set when the cell failed; retried on resume (v1.16.0)
     
     FOREIGN KEY (prompt_file_id) REFERENCES prompt_files(id) ON DELETE CASCADE,
     FOREIGN KEY (dataset_id) REFERENCES test_datasets(id) ON DELETE CASCADE
@@ -152,6 +194,7 @@ CREATE TABLE IF NOT EXISTS arena_battles (
     winner_model TEXT,                 -- User voted winner (optional)
     votes TEXT,                        -- JSON: {"model1": 2, "model2": 5, ...} (optional)
     timestamp INTEGER NOT NULL,
+    file_history_id TEXT,              -- Prompt version the battle ran against (v1.6.0)
     
     FOREIGN KEY (prompt_file_id) REFERENCES prompt_files(id) ON DELETE SET NULL
 );
@@ -185,6 +228,127 @@ CREATE INDEX IF NOT EXISTS idx_file_history_path ON file_history(file_path);
 CREATE INDEX IF NOT EXISTS idx_file_history_time ON file_history(created_at DESC);
 CREATE INDEX IF NOT EXISTS idx_file_history_hash ON file_history(file_path, content_hash);
 
+-- Legacy .vibe.yaml files converted to .vibe.md (v1.9.0)
+-- Keeps the original content so a migration batch can be undone
+CREATE TABLE IF NOT EXISTS prompt_migrations (
+    id TEXT PRIMARY KEY,
+    batch_id TEXT NOT NULL,
+    source_path TEXT NOT NULL,         -- Original .vibe.yaml file
+    target_path TEXT NOT NULL,         -- Created .vibe.md file
+    source_content TEXT NOT NULL,
+    target_content TEXT NOT NULL,
+    target_history_id TEXT,            -- file_history entry of the converted content
+    source_config TEXT,                -- JSON: prompt_files config of the source before the frontmatter sync
+    created_at INTEGER NOT NULL,
+    undone_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_prompt_migrations_batch ON prompt_migrations(batch_id);
+
+-- Prompt Lint Rule Settings (v1.10.0)
+-- Rules without a row use their default severity and options (see services::lint)
+CREATE TABLE IF NOT EXISTS lint_rule_settings (
+    rule_id TEXT PRIMARY KEY,
+    severity TEXT NOT NULL,            -- 'off' | 'info' | 'warning' | 'error'
+    options TEXT,                      -- JSON, merged over the rule's default options
+    updated_at INTEGER NOT NULL
+);
+
+-- Prompt Flow Runs (v1.11.0)
+-- One row per run of a .vibe.flow.yaml file; each executed step points at its execution_history row
+CREATE TABLE IF NOT EXISTS flow_runs (
+    id TEXT PRIMARY KEY,
+    flow_path TEXT NOT NULL,
+    flow_name TEXT,
+    inputs TEXT,                       -- JSON: flow input variables
+    status TEXT NOT NULL,              -- 'completed' | 'failed'
+    error TEXT,
+    total_tokens_input INTEGER NOT NULL DEFAULT 0,
+    total_tokens_output INTEGER NOT NULL DEFAULT 0,
+    total_cost_usd REAL NOT NULL DEFAULT 0,
+    total_latency_ms INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_flow_runs_path ON flow_runs(flow_path, created_at DESC);
+
+CREATE TABLE IF NOT EXISTS flow_run_steps (
+    id TEXT PRIMARY KEY,
+    flow_run_id TEXT NOT NULL,
+    position INTEGER NOT NULL,         -- Order of execution within the run
+    step_id TEXT NOT NULL,
+    prompt_path TEXT NOT NULL,
+    status TEXT NOT NULL,              -- 'completed' | 'skipped' | 'failed'
+    execution_id TEXT,                 -- execution_history row (None when skipped or failed before the call)
+    variables TEXT,                    -- JSON: variables given to the prompt
+    outputs TEXT,                      -- JSON: mapped outputs
+    error TEXT,
+
+    FOREIGN KEY (flow_run_id) REFERENCES flow_runs(id) ON DELETE CASCADE,
+    FOREIGN KEY (execution_id) REFERENCES execution_history(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_flow_run_steps_run ON flow_run_steps(flow_run_id, position);
+CREATE INDEX IF NOT EXISTS idx_flow_run_steps_execution ON flow_run_steps(execution_id);
+
+-- Project Variables and Profiles (v1.12.0)
+-- Resolution order: manual input > active profile > project > global (app.db)
+CREATE TABLE IF NOT EXISTS project_variables (
+    id TEXT PRIMARY KEY,
+    key TEXT NOT NULL UNIQUE,
+    value TEXT NOT NULL,
+    secret INTEGER NOT NULL DEFAULT 0, -- value kept in the keychain, not here (v1.13.0)
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS variable_profiles (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,         -- e.g. 'dev', 'staging', 'customer-acme'
+    description TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS profile_variables (
+    id TEXT PRIMARY KEY,
+    profile_id TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    secret INTEGER NOT NULL DEFAULT 0, -- value kept in the keychain, not here (v1.13.0)
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+
+    FOREIGN KEY (profile_id) REFERENCES variable_profiles(id) ON DELETE CASCADE,
+    UNIQUE(profile_id, key)
+);
+
+-- Matrix Runs (v1.16.0)
+-- A prompt run over dataset rows × models; each cell is a test_results row
+CREATE TABLE IF NOT EXISTS matrix_runs (
+    id TEXT PRIMARY KEY,
+    prompt_path TEXT NOT NULL,
+    prompt_file_id TEXT NOT NULL,
+    dataset_id TEXT NOT NULL,
+    row_indices TEXT,                  -- JSON array of dataset rows (NULL: every row)
+    models TEXT NOT NULL,              -- JSON array of "provider::model" ids
+    prompt_content TEXT NOT NULL,      -- Prompt file content when the run was created; resumed cells run it too
+    prompt_runtime TEXT NOT NULL,      -- JSON: the prompt loaded from that content (partials expanded)
+    concurrency INTEGER NOT NULL,
+    status TEXT NOT NULL,              -- 'running' | 'paused' | 'completed'
+    total_cells INTEGER NOT NULL,
+    completed_cells INTEGER NOT NULL DEFAULT 0,
+    failed_cells INTEGER NOT NULL DEFAULT 0,
+    total_cost_usd REAL NOT NULL DEFAULT 0,
+    error TEXT,                        -- Why the run paused by itself (e.g. a budget hard limit)
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+
+    FOREIGN KEY (dataset_id) REFERENCES test_datasets(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_matrix_runs_created ON matrix_runs(created_at DESC);
+
 -- Schema Migrations
 CREATE TABLE IF NOT EXISTS schema_migrations (
     version TEXT PRIMARY KEY,
@@ -248,4 +412,53 @@ VALUES ('1.3.0', strftime('%s', 'now'), 'Add git_config table for Git integratio
 INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
 VALUES ('1.4.0', strftime('%s', 'now'), 'Add commit_message generation settings to git_config');
 
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.5.0', strftime('%s', 'now'), 'Persist rendered messages, parameters and pricing version in execution_history');
 
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.6.0', strftime('%s', 'now'), 'Link executions and arena battles to file_history snapshots');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.7.0', strftime('%s', 'now'), 'Add execution_replays table');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.8.0', strftime('%s', 'now'), 'Record files loaded by file variables in execution_history');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.9.0', strftime('%s', 'now'), 'Add prompt_migrations table for .vibe.yaml to .vibe.md migration');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.10.0', strftime('%s', 'now'), 'Add lint_rule_settings table for configurable prompt lint rules');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.11.0', strftime('%s', 'now'), 'Add flow_runs and flow_run_steps tables for prompt flows');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.12.0', strftime('%s', 'now'), 'Add project_variables, variable_profiles and profile_variables tables');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.13.0', strftime('%s', 'now'), 'Add secret flag to project and profile variables (values kept in the keychain)');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.14.0', strftime('%s', 'now'), 'Record resolved dynamic variables in execution_history');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.15.0', strftime('%s', 'now'), 'Add content_hash to test_datasets for re-indexing changed dataset files');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.16.0', strftime('%s', 'now'), 'Add matrix_runs table and link test_results to matrix runs');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.17.0', strftime('%s', 'now'), 'Record assertion results in evaluation_results with prompt, model and matrix run');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.18.0', strftime('%s', 'now'), 'Track judge model, tokens and cost of LLM evaluators in evaluation_results');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.19.0', strftime('%s', 'now'), 'Record the model_pricing entry used by each execution');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.20.0', strftime('%s', 'now'), 'Store prompt messages with partials expanded for exact replays');
+
+INSERT OR IGNORE INTO schema_migrations (version, applied_at, description)
+VALUES ('1.21.0', strftime('%s', 'now'), 'Record the variable profile of each execution so replays fill the same secrets');
@@ -23,8 +23,17 @@ interface ExecutionResult {
     cost_usd: number;
     timestamp: number;
   };
+  budget_warnings?: string[];
 }
 
+// execute_prompt rejects with { kind, message } (e.g. kind = "budget_exceeded")
+const formatExecutionError = (err: unknown): string => {
+  if (err && typeof err === "object" && "message" in err) {
+    return String((err as { message: unknown }).message);
+  }
+  return String(err);
+};
+
 interface EnabledModel {
   id: string;
   model_id: string;
@@ -67,6 +76,7 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
 
   // Read passed data from localStorage
   const [variables, setVariables] = useState<string[]>([]);
+  const [optionalVariables, setOptionalVariables] = useState<string[]>([]);
   const [variableValues, setVariableValues] = useState<Record<string, string>>({});
   const [filePath, setFilePath] = useState<string>("");
   const [promptContent, setPromptContent] = useState("");
@@ -103,6 +113,13 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
     loadArenaData();
   }, []);
 
+  // The content the runtime is loaded from, snapshotted with the execution
+  const readExecutedPrompt = async () => {
+    const content = await invoke<string>("read_prompt", { filePath });
+    setPromptContent(content);
+    return content;
+  };
+
   const loadArenaData = async () => {
     try {
       // Read Arena context from localStorage
@@ -110,12 +127,25 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
       if (arenaContext) {
         const context = JSON.parse(arenaContext);
         setVariables(context.variables || []);
+        setOptionalVariables(context.optionalVariables || []);
         setVariableValues(context.variableValues || {});
         setFilePath(context.filePath || "");
         setFileName(context.fileName);
         setWorkspacePath(context.workspacePath);
         setSelectedModels(new Set(context.selectedModels || []));
 
+        // Secrets are substituted with this window's profile: use the one picked in the main window
+        if (context.workspacePath) {
+          try {
+            await invoke("set_active_profile", {
+              workspacePath: context.workspacePath,
+              profile: context.variableProfile ?? null,
+            });
+          } catch (err) {
+            console.error("Failed to set variable profile:", err);
+          }
+        }
+
         // Read file content for preview
         if (context.filePath) {
           try {
@@ -219,9 +249,12 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
 
               console.log(`[Arena] Final API key length: ${apiKey.length}`);
 
+              const content = await readExecutedPrompt();
+
               // Load runtime and replace model
               const runtime = await invoke<PromptRuntime>("load_prompt_runtime", {
                 filePath: filePath,
+                workspacePath: workspacePath || null,
               });
 
               console.log(`[Arena] Runtime loaded, original provider: ${runtime.config.provider}`);
@@ -244,6 +277,11 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
                 variables: variableValues,
                 apiKey: apiKey,
                 baseUrl: baseUrl,
+                workspacePath: workspacePath || null,
+                providerName: model.provider_name,
+                filePath: filePath || null,
+                runMode: "arena",
+                promptContent: content,
               });
 
               console.log(`[Arena] Execution success for ${model.model_name}`);
@@ -274,7 +312,7 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
               console.error(`[Arena] Error details:`, err);
 
               // Save error information
-              const errorMessage = String(err);
+              const errorMessage = formatExecutionError(err);
               setModelErrors(prev => {
                 const newMap = new Map(prev);
                 newMap.set(modelId, errorMessage);
@@ -324,9 +362,12 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
 
             console.log(`[Arena Serial] Final API key length: ${apiKey.length}`);
 
+            const content = await readExecutedPrompt();
+
             // 加载 runtime 并替换模型
             const runtime = await invoke<PromptRuntime>("load_prompt_runtime", {
               filePath: filePath,
+              workspacePath: workspacePath || null,
             });
 
             console.log(`[Arena Serial] Runtime loaded, original provider: ${runtime.config.provider}`);
@@ -349,6 +390,11 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
               variables: variableValues,
               apiKey: apiKey,
               baseUrl: baseUrl,
+              workspacePath: workspacePath || null,
+              providerName: model.provider_name,
+              filePath: filePath || null,
+              runMode: "arena",
+              promptContent: content,
             });
 
             console.log(`[Arena Serial] Execution success for ${model.model_name}`);
@@ -377,7 +423,7 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
             console.error(`[Arena Serial] Error details:`, err);
 
             // 保存错误信息
-            const errorMessage = String(err);
+            const errorMessage = formatExecutionError(err);
             setModelErrors(prev => {
               const newMap = new Map(prev);
               newMap.set(modelId, errorMessage);
@@ -475,6 +521,7 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
         inputVariables: JSON.stringify(variableValues),
         models: JSON.stringify(modelsArray),
         outputs: JSON.stringify(outputsArray),
+        filePath: filePath || null,
       });
 
       setBattleId(id);
@@ -577,9 +624,12 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
         apiKey = fullProvider.api_key;
       }
 
+      const content = await readExecutedPrompt();
+
       // 加载 runtime 并替换模型
       const runtime = await invoke<PromptRuntime>("load_prompt_runtime", {
         filePath: filePath,
+        workspacePath: workspacePath || null,
       });
 
       runtime.config.model = model.model_id;
@@ -595,6 +645,11 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
         variables: variableValues,
         apiKey: apiKey,
         baseUrl: baseUrl,
+        workspacePath: workspacePath || null,
+        providerName: model.provider_name,
+        filePath: filePath || null,
+        runMode: "arena",
+        promptContent: content,
       });
 
       console.log(`[Arena Retry] Success for ${model.model_name}`);
@@ -619,7 +674,7 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
       console.error(`[Arena Retry] Failed to execute ${model.model_name}:`, err);
 
       // 保存错误信息
-      const errorMessage = String(err);
+      const errorMessage = formatExecutionError(err);
       setModelErrors(prev => {
         const newMap = new Map(prev);
         newMap.set(modelId, errorMessage);
@@ -723,7 +778,7 @@ export default function ArenaWindow({ onClose, isStandaloneWindow = false }: Are
           <div className="p-4 border-t border-border">
             <button
               onClick={handleExecute}
-              disabled={selectedModels.size === 0 || isExecuting || variables.some(v => !variableValues[v])}
+              disabled={selectedModels.size === 0 || isExecuting || variables.some(v => !variableValues[v] && !optionalVariables.includes(v))}
               className="w-full flex items-center justify-center gap-2 px-4 py-2.5 bg-primary text-primary-foreground rounded-md hover:bg-primary/90 disabled:opacity-50 disabled:cursor-not-allowed transition-colors"
             >
               {isExecuting ? (
@@ -1,4 +1,4 @@
-import { useState, useEffect } from "react";
+import { useState, useEffect, useRef } from "react";
 import { useTranslation } from "react-i18next";
 import { invoke } from "@tauri-apps/api/core";
 import { Loader2, AlertCircle, ChevronDown, Check, Trophy } from "lucide-react";
@@ -15,8 +15,23 @@ interface ArenaSettings {
   card_density: string;
 }
 
+// Input field for a prompt variable (see get_variable_fields)
+export interface VariableField {
+  name: string;
+  type: "string" | "number" | "enum" | "json" | "multiline" | "file";
+  description?: string;
+  default?: unknown;
+  values?: string[];
+  min?: number;
+  max?: number;
+  examples?: unknown[];
+  declared: boolean;
+  optional: boolean;
+}
+
 interface ExecutionPanelProps {
   variables: string[];
+  variableFields?: VariableField[];
   promptContent: string;
   filePath: string;
 }
@@ -35,10 +50,21 @@ interface ExecutionResult {
   };
 }
 
-interface GlobalVariable {
+type VariableSource = "manual" | "profile" | "project" | "global";
+
+// See resolve_variables: secret values are masked and substituted at execution time
+interface ResolvedVariable {
+  name: string;
+  value?: string | null;
+  source?: VariableSource | null;
+  secret: boolean;
+  overridden: VariableSource[];
+}
+
+interface VariableProfile {
   id: string;
-  key: string;
-  value: string;
+  name: string;
+  description?: string | null;
 }
 
 interface LLMProvider {
@@ -66,6 +92,7 @@ interface EnabledModel {
 
 export default function ExecutionPanel({
   variables,
+  variableFields = [],
   promptContent: _promptContent,
   filePath,
 }: ExecutionPanelProps) {
@@ -73,7 +100,11 @@ export default function ExecutionPanel({
   const { workspace } = useWorkspaceStore();
   const { currentFile } = useEditorStore();
   const [variableValues, setVariableValues] = useState<Record<string, string>>({});
-  const [globalVariableKeys, setGlobalVariableKeys] = useState<Set<string>>(new Set());
+  const [resolvedVariables, setResolvedVariables] = useState<Record<string, ResolvedVariable>>({});
+  // Values filled from resolve_variables, replaced when the profile changes unless edited
+  const autoFilled = useRef<Record<string, string>>({});
+  const [profiles, setProfiles] = useState<VariableProfile[]>([]);
+  const [activeProfile, setActiveProfile] = useState("");
   const [_providers, setProviders] = useState<LLMProvider[]>([]);
   const [enabledModels, setEnabledModels] = useState<EnabledModel[]>([]);
   const [selectedModels, setSelectedModels] = useState<Set<string>>(new Set());
@@ -148,36 +179,89 @@ export default function ExecutionPanel({
     loadData();
   }, [arenaSettings.remember_last_selection]);
 
-  // Load global variables and auto-fill
+  // Load the workspace's variable profiles and the one this window uses
   useEffect(() => {
-    const loadGlobalVariables = async () => {
+    const loadProfiles = async () => {
+      if (!workspace) {
+        setProfiles([]);
+        setActiveProfile("");
+        return;
+      }
+
       try {
-        const globalVars = await invoke<GlobalVariable[]>("list_global_variables");
-        const newValues: Record<string, string> = { ...variableValues };
-        const globalKeys = new Set<string>();
-
-        // Auto-fill default values for each variable that exists in global variables
-        variables.forEach((varName) => {
-          const globalVar = globalVars.find((v) => v.key === varName);
-          if (globalVar) {
-            globalKeys.add(varName);
-            if (!variableValues[varName]) {
-              newValues[varName] = globalVar.value;
-            }
+        const list = await invoke<VariableProfile[]>("list_variable_profiles", {
+          workspacePath: workspace.path,
+        });
+        const active = await invoke<string | null>("get_active_profile", {
+          workspacePath: workspace.path,
+        });
+        setProfiles(list);
+        setActiveProfile(active ?? "");
+      } catch (error) {
+        console.error("Failed to load variable profiles:", error);
+      }
+    };
+
+    loadProfiles();
+  }, [workspace?.path]);
+
+  // Resolve variables (profile > project > global) and auto-fill them
+  useEffect(() => {
+    const resolveVariables = async () => {
+      try {
+        const resolved = await invoke<ResolvedVariable[]>("resolve_variables", {
+          workspacePath: workspace?.path ?? null,
+          names: variables,
+        });
+        const byName: Record<string, ResolvedVariable> = {};
+        resolved.forEach((variable) => {
+          if (variable.source && variable.value != null) {
+            byName[variable.name] = variable;
           }
         });
 
-        setGlobalVariableKeys(globalKeys);
-        setVariableValues(newValues);
+        setResolvedVariables(byName);
+        setVariableValues((prev) => {
+          const next = { ...prev };
+          variables.forEach((name) => {
+            // Values typed by the user are kept
+            if (prev[name] && prev[name] !== autoFilled.current[name]) return;
+
+            const value = byName[name]?.value;
+            if (value != null) {
+              next[name] = value;
+              autoFilled.current[name] = value;
+            } else {
+              delete next[name];
+              delete autoFilled.current[name];
+            }
+          });
+          return next;
+        });
       } catch (error) {
-        console.error("Failed to load global variables:", error);
+        console.error("Failed to resolve variables:", error);
       }
     };
 
     if (variables.length > 0) {
-      loadGlobalVariables();
+      resolveVariables();
+    }
+  }, [variables, workspace?.path, activeProfile]);
+
+  const handleProfileChange = async (profile: string) => {
+    if (!workspace) return;
+
+    try {
+      await invoke("set_active_profile", {
+        workspacePath: workspace.path,
+        profile: profile || null,
+      });
+      setActiveProfile(profile);
+    } catch (error) {
+      console.error("Failed to switch variable profile:", error);
+      setError(String(error));
     }
-  }, [variables]);
+  };
 
   // Load tags when file changes
   useEffect(() => {
@@ -270,10 +354,12 @@ export default function ExecutionPanel({
     // Save Arena context to localStorage
     const arenaContext = {
       variables,
+      optionalVariables,
       variableValues,
       filePath: currentFile,
       fileName: currentFile.split('/').pop(),
       workspacePath: workspace.path,
+      variableProfile: activeProfile || null,
       selectedModels: Array.from(selectedModels),
     };
     localStorage.setItem("arena_context", JSON.stringify(arenaContext));
@@ -294,8 +380,74 @@ export default function ExecutionPanel({
     }));
   };
 
+  const renderVariableInput = (variable: string, field?: VariableField) => {
+    const value = variableValues[variable] || "";
+    const className =
+      "w-full px-2 py-1.5 text-sm bg-background border border-input rounded-md focus:outline-none focus:ring-2 focus:ring-ring";
+    const fallback = field?.default ?? field?.examples?.[0];
+    const placeholder =
+      fallback !== undefined
+        ? typeof fallback === "string"
+          ? fallback
+          : JSON.stringify(fallback)
+        : t("execution.enter_variable", { variable });
+
+    switch (field?.type) {
+      case "enum":
+        return (
+          <select
+            value={value}
+            onChange={(e) => handleVariableChange(variable, e.target.value)}
+            className={className}
+          >
+            <option value="">{placeholder}</option>
+            {(field.values || []).map((option) => (
+              <option key={option} value={option}>
+                {option}
+              </option>
+            ))}
+          </select>
+        );
+      case "number":
+        return (
+          <input
+            type="number"
+            value={value}
+            min={field.min}
+            max={field.max}
+            onChange={(e) => handleVariableChange(variable, e.target.value)}
+            placeholder={placeholder}
+            className={className}
+          />
+        );
+      case "multiline":
+      case "json":
+        return (
+          <textarea
+            value={value}
+            rows={field.type === "json" ? 4 : 3}
+            onChange={(e) => handleVariableChange(variable, e.target.value)}
+            placeholder={placeholder}
+            className={`${className} font-mono resize-y`}
+          />
+        );
+      default:
+        return (
+          <input
+            type="text"
+            value={value}
+            onChange={(e) => handleVariableChange(variable, e.target.value)}
+            placeholder={placeholder}
+            className={className}
+          />
+        );
+    }
+  };
+
+  const optionalVariables = variableFields.filter((f) => f.optional).map((f) => f.name);
+
   const canExecute =
-    variables.every((v) => variableValues[v]) &&
+    variables.every((v) => variableValues[v] || optionalVariables.includes(v)) &&
     selectedModels.size > 0;
 
   return (
@@ -312,27 +464,60 @@ export default function ExecutionPanel({
         {/* Variables Section */}
         {variables.length > 0 && (
           <div>
-            <h3 className="text-sm font-semibold mb-2">{t("execution.variables")}</h3>
+            <div className="flex items-center justify-between gap-2 mb-2">
+              <h3 className="text-sm font-semibold">{t("execution.variables")}</h3>
+              {workspace && profiles.length > 0 && (
+                <select
+                  value={activeProfile}
+                  onChange={(e) => handleProfileChange(e.target.value)}
+                  title={t("execution.profile")}
+                  className="px-2 py-1 text-xs bg-background border border-input rounded-md focus:outline-none focus:ring-2 focus:ring-ring"
+                >
+                  <option value="">{t("execution.no_profile")}</option>
+                  {profiles.map((profile) => (
+                    <option key={profile.id} value={profile.name} title={profile.description ?? undefined}>
+                      {profile.name}
+                    </option>
+                  ))}
+                </select>
+              )}
+            </div>
             <div className="space-y-2">
               {variables.map((variable) => {
-                const isGlobalVariable = globalVariableKeys.has(variable);
+                const resolved = resolvedVariables[variable];
+                const value = variableValues[variable];
+                // Where the value comes from: a variable source, or typed in
+                const source: VariableSource | null =
+                  resolved && value === resolved.value ? resolved.source ?? null : value ? "manual" : null;
+                const field = variableFields.find((f) => f.name === variable);
                 return (
-                  <div key={variable}>
+                  <div key={variable} title={field?.description}>
                     <label className="text-xs font-medium text-muted-foreground mb-1 flex items-center gap-1">
                       {variable}
-                      {isGlobalVariable && (
-                        <span className="text-xs px-1 py-0.5 bg-primary/10 text-primary rounded">
-                          {t("execution.global")}
+                      {field?.optional && (
+                        <span className="text-xs text-muted-foreground/70">({t("execution.optional")})</span>
+                      )}
+                      {source && (
+                        <span
+                          className="text-xs px-1 py-0.5 bg-primary/10 text-primary rounded"
+                          title={
+                            resolved && resolved.overridden.length > 0
+                              ? t("execution.overrides", {
+                                  sources: resolved.overridden.map((s) => t(`execution.source_${s}`)).join(", "),
+                                })
+                              : undefined
+                          }
+                        >
+                          {source === "profile"
+                            ? t("execution.source_profile_named", { profile: activeProfile })
+                            : t(`execution.source_${source}`)}
                         </span>
                       )}
+                      {resolved?.secret && source !== "manual" && (
+                        <span className="text-xs text-muted-foreground/70">({t("execution.secret")})</span>
+                      )}
                     </label>
-                    <input
-                      type="text"
-                      value={variableValues[variable] || ""}
-                      onChange={(e) => handleVariableChange(variable, e.target.value)}
-                      placeholder={t("execution.enter_variable", { variable })}
-                      className="w-full px-2 py-1.5 text-sm bg-background border border-input rounded-md focus:outline-none focus:ring-2 focus:ring-ring"
-                    />
+                    {renderVariableInput(variable, field)}
                   </div>
                 );
               })}
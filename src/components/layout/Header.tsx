@@ -148,6 +148,12 @@ export default function Header() {
 
   const openVariablesWindow = async () => {
     try {
+      // Project and profile variables are edited for the current workspace
+      if (workspace) {
+        localStorage.setItem("variables_workspace", workspace.path);
+      } else {
+        localStorage.removeItem("variables_workspace");
+      }
       await invoke("open_variables_window");
     } catch (error) {
       console.error("Failed to open variables window:", error);
@@ -2,7 +2,7 @@ import { useTranslation } from "react-i18next";
 import { useEditorStore } from "../../stores/editorStore";
 import { invoke } from "@tauri-apps/api/core";
 import { useEffect, useState } from "react";
-import ExecutionPanel from "../execution/ExecutionPanel";
+import ExecutionPanel, { VariableField } from "../execution/ExecutionPanel";
 import HistoryPanel from "../history/HistoryPanel";
 
 type TabType = "execution" | "history";
@@ -11,6 +11,7 @@ export default function Inspector() {
   const { t } = useTranslation();
   const { content, currentFile } = useEditorStore();
   const [variables, setVariables] = useState<string[]>([]);
+  const [variableFields, setVariableFields] = useState<VariableField[]>([]);
   const [activeTab, setActiveTab] = useState<TabType>("execution");
 
   useEffect(() => {
@@ -18,26 +19,22 @@ export default function Inspector() {
       extractVariables();
     } else {
       setVariables([]);
+      setVariableFields([]);
     }
   }, [content, currentFile]);
 
   const extractVariables = async () => {
     try {
-      // Check if it's a Markdown file
-      if (currentFile?.endsWith(".vibe.md")) {
-        const vars = await invoke<string[]>("extract_variables_from_markdown", {
-          content: content,
-        });
-        setVariables(vars);
-      } else {
-        const vars = await invoke<string[]>("extract_variables", {
-          content: content,
-        });
-        setVariables(vars);
-      }
+      const fields = await invoke<VariableField[]>("get_variable_fields", {
+        content: content,
+        filePath: currentFile,
+      });
+      setVariables(fields.map((field) => field.name));
+      setVariableFields(fields);
     } catch (error) {
       console.error("Failed to extract variables:", error);
       setVariables([]);
+      setVariableFields([]);
     }
   };
 
@@ -72,7 +69,12 @@ export default function Inspector() {
         {currentFile ? (
           <>
             {activeTab === "execution" && (
-              <ExecutionPanel variables={variables} promptContent={content} filePath={currentFile} />
+              <ExecutionPanel
+                variables={variables}
+                variableFields={variableFields}
+                promptContent={content}
+                filePath={currentFile}
+              />
             )}
             {activeTab === "history" && (
               <HistoryPanel filePath={currentFile} />
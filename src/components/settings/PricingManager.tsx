@@ -0,0 +1,239 @@
+import { useState, useEffect } from "react";
+import { useTranslation } from "react-i18next";
+import { invoke } from "@tauri-apps/api/core";
+import { Plus, Pencil, Trash2, Check, X } from "lucide-react";
+
+interface ModelPricing {
+  id: string;
+  provider: string;
+  model: string;
+  input_price: number;
+  output_price: number;
+  cache_read_price: number | null;
+  cache_write_price: number | null;
+  source: string;   // "default" | "user" | "openrouter"
+  version: number;
+  updated_at: number;
+}
+
+interface PricingForm {
+  provider: string;
+  model: string;
+  input_price: string;
+  output_price: string;
+  cache_read_price: string;
+  cache_write_price: string;
+}
+
+interface PricingManagerProps {
+  onSaveStatusChange?: (status: "saving" | "saved") => void;
+}
+
+const EMPTY_FORM: PricingForm = {
+  provider: "",
+  model: "",
+  input_price: "",
+  output_price: "",
+  cache_read_price: "",
+  cache_write_price: "",
+};
+
+const optionalPrice = (value: string) => (value.trim() === "" ? null : parseFloat(value));
+
+export default function PricingManager({ onSaveStatusChange }: PricingManagerProps) {
+  const { t } = useTranslation();
+  const [entries, setEntries] = useState<ModelPricing[]>([]);
+  const [form, setForm] = useState<PricingForm | null>(null);
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    loadPricing();
+  }, []);
+
+  const loadPricing = async () => {
+    try {
+      setEntries(await invoke<ModelPricing[]>("list_model_pricing"));
+    } catch (error) {
+      console.error("Failed to load model pricing:", error);
+    }
+  };
+
+  const editEntry = (entry: ModelPricing) => {
+    setError(null);
+    setForm({
+      provider: entry.provider,
+      model: entry.model,
+      input_price: String(entry.input_price),
+      output_price: String(entry.output_price),
+      cache_read_price: entry.cache_read_price?.toString() ?? "",
+      cache_write_price: entry.cache_write_price?.toString() ?? "",
+    });
+  };
+
+  const handleSave = async () => {
+    if (!form) return;
+    onSaveStatusChange?.("saving");
+    try {
+      await invoke("save_model_pricing", {
+        input: {
+          provider: form.provider,
+          model: form.model,
+          input_price: parseFloat(form.input_price) || 0,
+          output_price: parseFloat(form.output_price) || 0,
+          cache_read_price: optionalPrice(form.cache_read_price),
+          cache_write_price: optionalPrice(form.cache_write_price),
+        },
+      });
+      setForm(null);
+      setError(null);
+      await loadPricing();
+      onSaveStatusChange?.("saved");
+    } catch (error) {
+      setError(String(error));
+    }
+  };
+
+  const handleDelete = async (entry: ModelPricing) => {
+    if (!confirm(t("pricing.deleteConfirm", { provider: entry.provider, model: entry.model }))) return;
+    try {
+      await invoke("delete_model_pricing", { provider: entry.provider, model: entry.model });
+      await loadPricing();
+    } catch (error) {
+      console.error("Failed to delete model pricing:", error);
+    }
+  };
+
+  const formatPrice = (price: number | null) => (price === null ? "-" : `$${price}`);
+
+  const priceField = (key: keyof PricingForm, label: string) => (
+    <label className="flex flex-col gap-1 text-xs text-muted-foreground">
+      {label}
+      <input
+        type="number"
+        step="0.01"
+        min="0"
+        value={form?.[key] ?? ""}
+        onChange={(e) => form && setForm({ ...form, [key]: e.target.value })}
+        className="w-28 px-3 py-2 bg-secondary rounded-lg border border-border text-sm text-foreground"
+      />
+    </label>
+  );
+
+  return (
+    <div className="flex-1 overflow-auto p-8 max-w-5xl mx-auto w-full">
+      <div className="flex items-start justify-between mb-6">
+        <div>
+          <h3 className="text-xl font-semibold mb-2">{t("pricing.title")}</h3>
+          <p className="text-sm text-muted-foreground">{t("pricing.description")}</p>
+        </div>
+        <button
+          onClick={() => {
+            setError(null);
+            setForm({ ...EMPTY_FORM });
+          }}
+          className="flex items-center gap-2 px-3 py-2 bg-primary text-primary-foreground rounded-lg text-sm hover:bg-primary/90 transition-colors"
+        >
+          <Plus className="w-4 h-4" />
+          {t("pricing.add")}
+        </button>
+      </div>
+
+      {form && (
+        <div className="mb-6 p-4 border border-border rounded-lg space-y-4">
+          <div className="flex flex-wrap gap-3">
+            <label className="flex flex-col gap-1 text-xs text-muted-foreground">
+              {t("pricing.provider")}
+              <input
+                value={form.provider}
+                onChange={(e) => setForm({ ...form, provider: e.target.value })}
+                placeholder="openai"
+                className="w-36 px-3 py-2 bg-secondary rounded-lg border border-border text-sm text-foreground"
+              />
+            </label>
+            <label className="flex flex-col gap-1 text-xs text-muted-foreground">
+              {t("pricing.model")}
+              <input
+                value={form.model}
+                onChange={(e) => setForm({ ...form, model: e.target.value })}
+                placeholder="gpt-4o"
+                title={t("pricing.modelHint")}
+                className="w-48 px-3 py-2 bg-secondary rounded-lg border border-border text-sm text-foreground"
+              />
+            </label>
+            {priceField("input_price", t("pricing.input"))}
+            {priceField("output_price", t("pricing.output"))}
+            {priceField("cache_read_price", t("pricing.cacheRead"))}
+            {priceField("cache_write_price", t("pricing.cacheWrite"))}
+          </div>
+          {error && <p className="text-sm text-destructive">{error}</p>}
+          <div className="flex gap-2">
+            <button
+              onClick={handleSave}
+              className="flex items-center gap-1 px-3 py-1.5 bg-primary text-primary-foreground rounded text-sm hover:bg-primary/90"
+            >
+              <Check className="w-4 h-4" />
+              {t("pricing.save")}
+            </button>
+            <button
+              onClick={() => setForm(null)}
+              className="flex items-center gap-1 px-3 py-1.5 bg-secondary rounded text-sm hover:bg-secondary/80"
+            >
+              <X className="w-4 h-4" />
+              {t("pricing.cancel")}
+            </button>
+          </div>
+        </div>
+      )}
+
+      {entries.length === 0 ? (
+        <p className="text-sm text-muted-foreground">{t("pricing.empty")}</p>
+      ) : (
+        <table className="w-full text-sm">
+          <thead>
+            <tr className="text-left text-muted-foreground border-b border-border">
+              <th className="py-2 font-medium">{t("pricing.provider")}</th>
+              <th className="py-2 font-medium">{t("pricing.model")}</th>
+              <th className="py-2 font-medium text-right">{t("pricing.input")}</th>
+              <th className="py-2 font-medium text-right">{t("pricing.output")}</th>
+              <th className="py-2 font-medium text-right">{t("pricing.cacheRead")}</th>
+              <th className="py-2 font-medium text-right">{t("pricing.cacheWrite")}</th>
+              <th className="py-2 font-medium pl-4">{t("pricing.source")}</th>
+              <th className="py-2 font-medium text-right">{t("pricing.version")}</th>
+              <th className="py-2" />
+            </tr>
+          </thead>
+          <tbody>
+            {entries.map((entry) => (
+              <tr key={entry.id} className="border-b border-border/50 hover:bg-accent/30">
+                <td className="py-2">{entry.provider}</td>
+                <td className="py-2 font-mono text-xs">{entry.model}</td>
+                <td className="py-2 text-right">{formatPrice(entry.input_price)}</td>
+                <td className="py-2 text-right">{formatPrice(entry.output_price)}</td>
+                <td className="py-2 text-right">{formatPrice(entry.cache_read_price)}</td>
+                <td className="py-2 text-right">{formatPrice(entry.cache_write_price)}</td>
+                <td className="py-2 pl-4 text-muted-foreground">{entry.source}</td>
+                <td className="py-2 text-right text-muted-foreground">v{entry.version}</td>
+                <td className="py-2 text-right whitespace-nowrap">
+                  <button
+                    onClick={() => editEntry(entry)}
+                    className="p-1.5 hover:bg-accent rounded"
+                    title={t("pricing.edit")}
+                  >
+                    <Pencil className="w-3.5 h-3.5" />
+                  </button>
+                  <button
+                    onClick={() => handleDelete(entry)}
+                    className="p-1.5 hover:bg-destructive/10 text-destructive rounded"
+                    title={t("pricing.delete")}
+                  >
+                    <Trash2 className="w-3.5 h-3.5" />
+                  </button>
+                </td>
+              </tr>
+            ))}
+          </tbody>
+        </table>
+      )}
+    </div>
+  );
+}
@@ -1,10 +1,11 @@
 import { useState, useEffect } from "react";
 import { X, Moon, Sun, Languages, Download, Upload, RotateCcw, Monitor, Zap, GitBranch } from "lucide-react";
-import { Settings, Package, Plug, FolderOpen, Keyboard, Info } from "lucide-react";
+import { Settings, Package, Plug, FolderOpen, Keyboard, Info, DollarSign } from "lucide-react";
 import LLMProviderManager from "./LLMProviderManager";
 import WorkspaceManager from "./WorkspaceManager";
 import AboutPanel from "./AboutPanel";
 import GitSettingsPanel from "./GitSettingsPanel";
+import PricingManager from "./PricingManager";
 import AutoSaveIndicator from "../ui/AutoSaveIndicator";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { useTranslation } from "react-i18next";
@@ -29,6 +30,7 @@ interface SettingsPanelProps {
 type SettingsTab =
   | "general"
   | "providers"
+  | "pricing"
   | "arena"
   | "git"
   | "mcpservers"
@@ -151,6 +153,7 @@ export default function SettingsPanel({ onClose, isStandaloneWindow = false }: S
   const menuItems: { id: SettingsTab; label: string; icon: any }[] = [
     { id: "general", label: t("settingsTabs.general"), icon: Settings },
     { id: "providers", label: t("settingsTabs.providers"), icon: Package },
+    { id: "pricing", label: t("settingsTabs.pricing"), icon: DollarSign },
     { id: "arena", label: t("settingsTabs.arena"), icon: Zap },
     { id: "git", label: t("git.title"), icon: GitBranch },
     { id: "mcpservers", label: t("settingsTabs.mcpservers"), icon: Plug },
@@ -210,6 +213,7 @@ export default function SettingsPanel({ onClose, isStandaloneWindow = false }: S
           {/* Main Content Area */}
           <div className="flex-1 flex flex-col overflow-hidden">
             {activeTab === "providers" && <LLMProviderManager onSaveStatusChange={updateSaveStatus} />}
+            {activeTab === "pricing" && <PricingManager onSaveStatusChange={updateSaveStatus} />}
             {activeTab === "git" && <GitSettingsPanel onSaveStatusChange={updateSaveStatus} />}
             {activeTab === "arena" && (
               <div className="flex-1 overflow-auto p-8 max-w-3xl mx-auto w-full">
@@ -458,7 +462,7 @@ export default function SettingsPanel({ onClose, isStandaloneWindow = false }: S
                 </div>
               </div>
             )}
-            {activeTab !== "providers" && activeTab !== "arena" && activeTab !== "general" && activeTab !== "git" && activeTab !== "workspace" && activeTab !== "about" && (
+            {activeTab !== "providers" && activeTab !== "pricing" && activeTab !== "arena" && activeTab !== "general" && activeTab !== "git" && activeTab !== "workspace" && activeTab !== "about" && (
               <div className="flex-1 flex items-center justify-center">
                 <p className="text-muted-foreground">{menuItems.find(m => m.id === activeTab)?.label} settings coming soon...</p>
               </div>
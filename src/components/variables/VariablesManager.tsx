@@ -1,14 +1,25 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { useTranslation } from "react-i18next";
-import { X, Plus, Trash2, HelpCircle, Search } from "lucide-react";
+import { X, Plus, Trash2, HelpCircle, Search, Lock, Unlock } from "lucide-react";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import WindowControls from "../ui/WindowControls";
 
-interface GlobalVariable {
+interface StoredVariable {
   id: string;
   key: string;
   value: string;
+  // Value kept in the OS keychain; listed masked
+  secret: boolean;
+}
+
+// Global variables apply everywhere; project and profile ones to the workspace the window was opened from
+type VariableScope = "global" | "project" | "profile";
+
+interface VariableProfile {
+  id: string;
+  name: string;
+  description?: string | null;
 }
 
 interface VariablesManagerProps {
@@ -18,20 +29,50 @@ interface VariablesManagerProps {
 
 export default function VariablesManager({ onClose, isStandaloneWindow = false }: VariablesManagerProps) {
   const { t } = useTranslation();
-  const [variables, setVariables] = useState<GlobalVariable[]>([]);
+  const [variables, setVariables] = useState<StoredVariable[]>([]);
   const [loading, setLoading] = useState(true);
   const [saving, setSaving] = useState(false);
   const [showHelp, setShowHelp] = useState(false);
   const [searchQuery, setSearchQuery] = useState("");
+  const [workspacePath] = useState(() => localStorage.getItem("variables_workspace"));
+  const [scope, setScope] = useState<VariableScope>("global");
+  const [profiles, setProfiles] = useState<VariableProfile[]>([]);
+  const [selectedProfile, setSelectedProfile] = useState("");
+
+  useEffect(() => {
+    if (workspacePath) {
+      loadProfiles();
+    }
+  }, [workspacePath]);
 
   useEffect(() => {
     loadVariables();
-  }, []);
+  }, [scope, selectedProfile]);
+
+  const loadProfiles = async (select?: string) => {
+    try {
+      const list = await invoke<VariableProfile[]>("list_variable_profiles", { workspacePath });
+      setProfiles(list);
+      setSelectedProfile(select ?? list[0]?.name ?? "");
+    } catch (error) {
+      console.error("Failed to load profiles:", error);
+    }
+  };
 
   const loadVariables = async () => {
     try {
       setLoading(true);
-      const data = await invoke<GlobalVariable[]>("list_global_variables");
+      let data: StoredVariable[] = [];
+      if (scope === "global") {
+        data = await invoke<StoredVariable[]>("list_global_variables");
+      } else if (scope === "project") {
+        data = await invoke<StoredVariable[]>("list_project_variables", { workspacePath });
+      } else if (selectedProfile && profiles.some((p) => p.name === selectedProfile && p.id)) {
+        data = await invoke<StoredVariable[]>("get_profile_variables", {
+          workspacePath,
+          profile: selectedProfile,
+        });
+      }
       setVariables(data);
     } catch (error) {
       console.error("Failed to load variables:", error);
@@ -42,16 +83,52 @@ export default function VariablesManager({ onClose, isStandaloneWindow = false }
     }
   };
 
+  const handleNewProfile = () => {
+    const name = window.prompt(t("variables.newProfilePrompt"))?.trim();
+    if (!name) return;
+    if (profiles.some((p) => p.name === name)) {
+      setSelectedProfile(name);
+      return;
+    }
+
+    // Created when its variables are saved
+    setProfiles([...profiles, { id: "", name }]);
+    setSelectedProfile(name);
+  };
+
+  const handleDeleteProfile = async () => {
+    if (!selectedProfile || !confirm(t("variables.deleteProfileConfirm", { profile: selectedProfile }))) {
+      return;
+    }
+
+    try {
+      if (profiles.some((p) => p.name === selectedProfile && p.id)) {
+        await invoke("delete_variable_profile", { workspacePath, name: selectedProfile });
+      }
+      await loadProfiles();
+    } catch (error) {
+      console.error("Failed to delete profile:", error);
+      alert(t("errors.saveFailed") + ": " + error);
+    }
+  };
+
   const handleAddVariable = () => {
-    const newVar: GlobalVariable = {
+    const newVar: StoredVariable = {
       id: Date.now().toString(),
       key: "",
       value: "",
+      secret: false,
     };
     setVariables([...variables, newVar]);
   };
 
-  const handleUpdateVariable = (id: string, field: keyof GlobalVariable, value: string) => {
+  const handleToggleSecret = (id: string) => {
+    setVariables(
+      variables.map((v) => (v.id === id ? { ...v, secret: !v.secret } : v))
+    );
+  };
+
+  const handleUpdateVariable = (id: string, field: "key" | "value", value: string) => {
     setVariables(
       variables.map((v) => (v.id === id ? { ...v, [field]: value } : v))
     );
@@ -83,7 +160,24 @@ export default function VariablesManager({ onClose, isStandaloneWindow = false }
         return;
       }
 
-      await invoke("save_global_variables", { variables });
+      if (scope === "global") {
+        await invoke("save_global_variables", { variables });
+      } else if (scope === "project") {
+        await invoke("save_project_variables", { workspacePath, variables });
+      } else {
+        const profile = profiles.find((p) => p.name === selectedProfile);
+        if (!profile) {
+          alert(t("variables.noProfileSelected"));
+          setSaving(false);
+          return;
+        }
+        await invoke("save_variable_profile", {
+          workspacePath,
+          name: profile.name,
+          description: profile.description ?? null,
+          variables,
+        });
+      }
 
       // Close window after successful save
       if (isStandaloneWindow) {
@@ -157,6 +251,56 @@ export default function VariablesManager({ onClose, isStandaloneWindow = false }
             </div>
           ) : (
             <div className="space-y-4">
+              {/* Scope */}
+              {workspacePath && (
+                <div className="flex items-center gap-2 flex-wrap">
+                  {(["global", "project", "profile"] as VariableScope[]).map((value) => (
+                    <button
+                      key={value}
+                      onClick={() => setScope(value)}
+                      className={`px-3 py-1.5 text-sm rounded-lg transition-colors ${scope === value
+                        ? "bg-primary text-primary-foreground"
+                        : "bg-secondary text-foreground hover:bg-secondary/80"
+                        }`}
+                    >
+                      {t(`variables.scope_${value}`)}
+                    </button>
+                  ))}
+                  {scope === "profile" && (
+                    <>
+                      <select
+                        value={selectedProfile}
+                        onChange={(e) => setSelectedProfile(e.target.value)}
+                        className="px-2 py-1.5 text-sm bg-background border border-input rounded-lg focus:outline-none focus:ring-2 focus:ring-ring"
+                      >
+                        {profiles.length === 0 && <option value="">{t("variables.noProfiles")}</option>}
+                        {profiles.map((profile) => (
+                          <option key={profile.name} value={profile.name}>
+                            {profile.name}
+                          </option>
+                        ))}
+                      </select>
+                      <button
+                        onClick={handleNewProfile}
+                        className="p-1.5 hover:bg-accent rounded transition-colors"
+                        title={t("variables.newProfile")}
+                      >
+                        <Plus className="w-4 h-4" />
+                      </button>
+                      {selectedProfile && (
+                        <button
+                          onClick={handleDeleteProfile}
+                          className="p-1.5 hover:bg-destructive/10 rounded transition-colors"
+                          title={t("variables.deleteProfile")}
+                        >
+                          <Trash2 className="w-4 h-4 text-destructive" />
+                        </button>
+                      )}
+                    </>
+                  )}
+                </div>
+              )}
+
               <div className="flex items-center justify-between mb-2 gap-4">
                 {/* 搜索框 */}
                 <div className="flex-1 max-w-md relative">
@@ -180,7 +324,8 @@ export default function VariablesManager({ onClose, isStandaloneWindow = false }
                 </div>
                 <button
                   onClick={handleAddVariable}
-                  className="flex items-center gap-2 px-4 py-2 text-sm font-medium text-white bg-primary rounded-lg hover:bg-primary/90 shadow-sm flex-shrink-0"
+                  disabled={scope === "profile" && !selectedProfile}
+                  className="flex items-center gap-2 px-4 py-2 text-sm font-medium text-white bg-primary rounded-lg hover:bg-primary/90 disabled:opacity-50 shadow-sm flex-shrink-0"
                   title={t("variables.addVariable")}
                 >
                   <Plus className="w-4 h-4" />
@@ -189,10 +334,11 @@ export default function VariablesManager({ onClose, isStandaloneWindow = false }
               </div>
 
               {/* 列表表头 */}
-              <div className="grid grid-cols-[1fr_1fr_40px] gap-3 px-3 py-2 text-xs font-medium text-muted-foreground border-b border-border">
+              <div className="grid grid-cols-[1fr_1fr_40px_40px] gap-3 px-3 py-2 text-xs font-medium text-muted-foreground border-b border-border">
                 <div>{t("variables.variableKey")} *</div>
                 <div>{t("variables.defaultValue")} *</div>
                 <div></div>
+                <div></div>
               </div>
 
               {/* 变量列表 */}
@@ -202,7 +348,7 @@ export default function VariablesManager({ onClose, isStandaloneWindow = false }
                   return (
                     <div
                       key={variable.id}
-                      className={`grid grid-cols-[1fr_1fr_40px] gap-3 px-3 py-2 rounded transition-colors group ${matchIndices
+                      className={`grid grid-cols-[1fr_1fr_40px_40px] gap-3 px-3 py-2 rounded transition-colors group ${matchIndices
                         ? "bg-amber-500/10 hover:bg-amber-500/20 border border-amber-500/30"
                         : "hover:bg-accent/50"
                         }`}
@@ -228,7 +374,7 @@ export default function VariablesManager({ onClose, isStandaloneWindow = false }
                         />
                       </div>
                       <input
-                        type="text"
+                        type={variable.secret ? "password" : "text"}
                         value={variable.value}
                         onChange={(e) =>
                           handleUpdateVariable(variable.id, "value", e.target.value)
@@ -236,6 +382,17 @@ export default function VariablesManager({ onClose, isStandaloneWindow = false }
                         className="w-full px-2 py-1.5 text-sm bg-background border border-input rounded focus:outline-none focus:ring-1 focus:ring-ring"
                         placeholder={t("variables.defaultValue")}
                       />
+                      <button
+                        onClick={() => handleToggleSecret(variable.id)}
+                        className="p-1.5 hover:bg-accent rounded transition-colors"
+                        title={t("variables.secret")}
+                      >
+                        {variable.secret ? (
+                          <Lock className="w-4 h-4 text-primary" />
+                        ) : (
+                          <Unlock className="w-4 h-4 text-muted-foreground" />
+                        )}
+                      </button>
                       <button
                         onClick={() => handleDeleteVariable(variable.id)}
                         className="p-1.5 hover:bg-destructive/10 rounded transition-colors opacity-0 group-hover:opacity-100"
@@ -247,7 +404,7 @@ export default function VariablesManager({ onClose, isStandaloneWindow = false }
                   );
                 })}
 
-                {variables.length === 0 && (
+                {variables.length === 0 && !(scope === "profile" && !selectedProfile) && (
                   <div className="text-center py-20">
                     <h3 className="text-lg font-semibold text-foreground mb-2">
                       {t("variables.noVariablesYet")}
@@ -294,7 +451,8 @@ export default function VariablesManager({ onClose, isStandaloneWindow = false }
                 <span className="text-xs text-muted-foreground">
                   {searchQuery
                     ? `${filteredVariables.length} / ${variables.length}`
-                    : `${variables.length}`} • {t("variables.stored")} ~/.vibebase/app.db
+                    : `${variables.length}`} • {t("variables.stored")}{" "}
+                  {scope === "global" ? "~/.vibebase/app.db" : `${workspacePath}/.vibebase/project.db`}
                 </span>
               </div>
             <button
@@ -316,7 +474,7 @@ export default function VariablesManager({ onClose, isStandaloneWindow = false }
             </button>
             <button
               onClick={handleSave}
-              disabled={saving}
+              disabled={saving || (scope === "profile" && !selectedProfile)}
               className="px-5 py-2 text-sm font-medium text-white bg-primary rounded-lg hover:bg-primary/90 disabled:opacity-50 shadow-sm transition-colors"
               title={saving ? t("variables.saving") : t("variables.saveVariables")}
             >